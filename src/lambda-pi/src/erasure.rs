@@ -0,0 +1,130 @@
+//! Type erasure into the untyped lambda calculus. Annotations leave their terms, a `let` stays
+//! a `let` without its type, and integers become Church numerals, but types are terms like any
+//! other here and an application does not say whether its argument is one, so `*` and products
+//! stay as the identity instead of being dropped. Terms that only differ by their types then
+//! still erase to terms that agree.
+
+use crate::parser::location::Range;
+use crate::parser::parsetree::{Abs, Anno, Appl, Checkable, Expr, Int, Let, Prod, Star, Var};
+use untyped_lambda_calculus::encodings;
+use untyped_lambda_calculus::parser::{location as untyped_location, parsetree as untyped};
+
+fn range(range: Range) -> untyped_location::Range {
+    let start = untyped_location::Pos::new(range.start.index);
+    let end = untyped_location::Pos::new(range.end.index);
+
+    untyped_location::Range::new(start, end)
+}
+
+/// Erases the types of a checkable term.
+pub fn erase(term: &Checkable) -> untyped::Expr {
+    match term {
+        Checkable::Abs(Abs { param, body, range: span, .. }) => {
+            encodings::abs(&param.name, erase(body), range(*span))
+        }
+        Checkable::Inf(expr) => erase_expr(expr),
+    }
+}
+
+/// Erases the types of an inferable term.
+pub fn erase_expr(expr: &Expr) -> untyped::Expr {
+    let range = range(expr.range());
+
+    match expr {
+        Expr::Int(Int { value, .. }) => encodings::numeral(*value, range),
+        Expr::Var(Var { value, .. }) => encodings::var(&value.name, range),
+        Expr::Star(Star { .. }) | Expr::Prod(Prod { .. }) => encodings::unit(),
+        Expr::Anno(Anno { expr, .. }) => erase(expr),
+        Expr::Appl(Appl { lambda, argm, .. }) => {
+            encodings::app(erase_expr(lambda), erase(argm), range)
+        }
+        Expr::Let(Let { name, value, body, .. }) => untyped::Expr::Let(untyped::Let {
+            name: name.name.clone(),
+            value: Box::new(erase(value)),
+            body: Box::new(erase(body)),
+            range,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::erase;
+    use crate::{parser, prelude};
+    use untyped_lambda_calculus::encodings::{evaluate, Sort};
+    use untyped_lambda_calculus::reduction::Norm;
+
+    #[test]
+    fn test_erase_dependent_types() {
+        let expr = parser::from_string(r"λA: *. λB: Πa: A. *. λa: A. B a").unwrap();
+
+        assert_eq!(erase(&expr).to_string(), "(λA. (λB. (λa. (B a))))")
+    }
+
+    #[test]
+    fn test_erase_runs() {
+        // An abstraction at the head of an application needs its type, the annotation goes
+        // with the erasure.
+        let input = r"
+        (λA: *. λf: Πx: A. A. λx: A. f x : ΠA: *. Πf: Πx: A. A. Πx: A. A)
+            * (λn: *. n : Πn: *. *) 3
+        ";
+        let expr = erase(&parser::from_string(input).unwrap());
+
+        for strategy in [Norm::NOR, Norm::APP, Norm::CBN, Norm::CBV] {
+            let value = evaluate(strategy, expr.clone(), Sort::Integer);
+            assert_eq!(value.to_string(), "3", "{:?}", strategy);
+        }
+    }
+
+    #[test]
+    fn test_erase_program() {
+        let input = r"
+        def id: Πa: *. Πx: a. a = λa: *. λx: a. x
+        def T: * = *
+        def idT: Πx: T. T = id T
+        main = idT 3
+        ";
+        let expr = erase(&parser::from_string(input).unwrap());
+
+        for strategy in [Norm::NOR, Norm::APP, Norm::CBN, Norm::CBV] {
+            let value = evaluate(strategy, expr.clone(), Sort::Integer);
+            assert_eq!(value.to_string(), "3", "{:?}", strategy);
+        }
+    }
+
+    #[test]
+    fn test_erase_prelude() {
+        // Encodings take the type they are eliminated at first, so `*` stands in for it before
+        // the results are read back.
+        let inputs = [
+            (
+                r"sum (cons Nat (succ zero) (cons Nat (mul (succ (succ zero)) (succ (succ zero)))
+                    (nil Nat))) *",
+                "5",
+                Sort::Integer,
+            ),
+            (
+                r"snd Bool Nat (pair Bool Nat tru (add (succ zero) (succ zero))) *",
+                "2",
+                Sort::Integer,
+            ),
+            (
+                r"compose Nat Nat Nat (add (succ zero)) (mul (succ (succ zero))) zero *",
+                "1",
+                Sort::Integer,
+            ),
+            (r"and (not fls) (iszero zero) *", "true", Sort::Boolean),
+            (r"or fls (iszero (succ zero)) *", "false", Sort::Boolean),
+        ];
+
+        for (input, value, sort) in inputs {
+            let expr = erase(&prelude::load(input).unwrap());
+
+            for strategy in [Norm::NOR, Norm::APP, Norm::CBN, Norm::CBV] {
+                let erased = evaluate(strategy, expr.clone(), sort).to_string();
+                assert_eq!(erased, value, "{} {:?}", input, strategy);
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod checker;
+pub mod erasure;
 pub mod parser;
+pub mod prelude;
 // pub mod reduction;
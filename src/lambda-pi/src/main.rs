@@ -1,9 +1,16 @@
-use lambda_pi::parser;
+use lambda_pi::{erasure, prelude};
+use untyped_lambda_calculus::encodings::{evaluate, Sort};
+use untyped_lambda_calculus::reduction::Norm;
 
 fn main() {
-    let input = r"λA: *. λB: Πa: A. *. λa: A. B a";
+    let input = r"
+    def two: Nat = succ (succ zero)
+    def double: Πn: Nat. Nat = λn: Nat. add n n
+    main = sum (cons Nat two (cons Nat (double two) (nil Nat))) *
+    ";
 
-    let expr_parsed = parser::from_string(input).unwrap();
+    let expr_parsed = prelude::load(input).unwrap();
+    let expr_reduced = evaluate(Norm::NOR, erasure::erase(&expr_parsed), Sort::Integer);
 
-    println!("{}", expr_parsed)
+    println!("{}", expr_reduced)
 }
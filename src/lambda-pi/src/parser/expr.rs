@@ -2,7 +2,7 @@ use super::{
     error::ParserError,
     lexer::tokens::Token,
     macros::{consume, match_token},
-    parsetree::{Abs, Anno, Appl, Checkable, Expr, Int, Prod, Star, Var},
+    parsetree::{Abs, Anno, Appl, Checkable, Expr, Int, Let, Prod, Star, Var},
     state::Parser,
     symbol::Symbol,
 };
@@ -16,7 +16,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_number_expr(&mut self) -> Result<Expr, ParserError> {
-        let (token, range) = consume!(self, Token::Number(num) => num.clone())?;
+        let (token, range) = consume!(self, Token::Number(num) => *num)?;
 
         Ok(Expr::Int(Int { value: token, range }))
     }
@@ -26,18 +26,45 @@ impl<'a> Parser<'a> {
             Token::LParen => self.parse_parens_expr(),
             Token::Variable(_) => self.parse_variable_expr(),
             Token::Number(_) => self.parse_number_expr(),
+            Token::Star => self.parse_kind(),
             _ => self.fail(),
         }
     }
 
-    pub fn parse_parens_expr(&mut self) -> Result<Expr, ParserError> {
-        consume!(self, Token::LParen)?;
-        let expr = self.parse_expr()?;
+    // `(e)` or `(e : T)`. An abstraction only infers a type under an annotation, so it is left
+    // to the callers that take a checkable term.
+    pub fn parse_parens(&mut self) -> Result<Checkable, ParserError> {
+        let (_, lpos) = consume!(self, Token::LParen)?;
+        let expr = self.parse_checkable()?;
+
+        if let Token::Colon = self.get() {
+            consume!(self, Token::Colon)?;
+            let anno = self.parse_checkable()?;
+            let (_, rpos) = consume!(self, Token::RParen)?;
+            let range = lpos.mix(rpos);
+
+            return Ok(Checkable::Inf(Box::new(Expr::Anno(Anno { expr, anno, range }))));
+        }
+
         consume!(self, Token::RParen)?;
 
         Ok(expr)
     }
 
+    pub fn parse_parens_expr(&mut self) -> Result<Expr, ParserError> {
+        match self.parse_parens()? {
+            Checkable::Inf(expr) => Ok(*expr),
+            Checkable::Abs(_) => self.fail(),
+        }
+    }
+
+    pub fn parse_argument(&mut self) -> Result<Checkable, ParserError> {
+        match self.get() {
+            Token::LParen => self.parse_parens(),
+            _ => Ok(Checkable::Inf(Box::new(self.parse_atom()?))),
+        }
+    }
+
     pub fn parse_abs(&mut self) -> Result<Checkable, ParserError> {
         let (_, range) = consume!(self, Token::Lambda)?;
         let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
@@ -61,7 +88,7 @@ impl<'a> Parser<'a> {
         let func = self.parse_atom()?;
         let mut args = Vec::new();
 
-        while let Some(arg) = self.try_single(|state| state.parse_checkable())? {
+        while let Some(arg) = self.try_single(|state| state.parse_argument())? {
             args.push(arg);
         }
 
@@ -86,16 +113,6 @@ impl<'a> Parser<'a> {
         Ok(head)
     }
 
-    pub fn parse_annot(&mut self) -> Result<Expr, ParserError> {
-        let (_, lpos) = consume!(self, Token::LParen)?;
-        let expr = self.parse_checkable()?;
-        consume!(self, Token::Colon)?;
-        let anno = self.parse_checkable()?;
-        let (_, rpos) = consume!(self, Token::RParen)?;
-
-        Ok(Expr::Anno(Anno { expr, anno, range: lpos.mix(rpos) }))
-    }
-
     pub fn parse_pi(&mut self) -> Result<Expr, ParserError> {
         let (_, range) = consume!(self, Token::Pi)?;
         let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
@@ -115,9 +132,56 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    // `let x: T = e in b`. A top-level `def x: T = e` binds over the declarations after it,
+    // down to the `main = e` closing the program.
+    pub fn parse_let(&mut self) -> Result<Expr, ParserError> {
+        let declaration = matches!(self.get(), Token::Def);
+        let (_, range) = consume!(self, Token::Let | Token::Def)?;
+        let (name, _) = consume!(self, Token::Variable(var) => var.clone())?;
+
+        consume!(self, Token::Colon)?;
+        let ty = self.parse_checkable()?;
+
+        consume!(self, Token::Equal)?;
+        let value = self.parse_checkable()?;
+
+        let body = match self.get() {
+            Token::Def | Token::Main if declaration => self.parse_program()?,
+            _ if declaration => self.fail()?,
+            _ => {
+                consume!(self, Token::In)?;
+                self.parse_checkable()?
+            }
+        };
+
+        Ok(Expr::Let(Let {
+            name: Symbol::new(name),
+            ty: Box::new(ty),
+            value: Box::new(value),
+            range: range.mix(body.range()),
+            body: Box::new(body),
+        }))
+    }
+
+    pub fn parse_main(&mut self) -> Result<Checkable, ParserError> {
+        consume!(self, Token::Main)?;
+        consume!(self, Token::Equal)?;
+
+        self.parse_checkable()
+    }
+
+    // A program is a term, or declarations closed by `main = e`; `main` is accepted only
+    // here, where a declaration could start.
+    pub fn parse_program(&mut self) -> Result<Checkable, ParserError> {
+        match self.get() {
+            Token::Main => self.parse_main(),
+            _ => self.parse_checkable(),
+        }
+    }
+
     pub fn parse_expr(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
-            Token::LParen => self.parse_annot(),
+            Token::Let | Token::Def => self.parse_let(),
             Token::Pi => self.parse_pi(),
             Token::Star => self.parse_kind(),
             _ => self.parse_appl(),
@@ -147,3 +211,51 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::parser;
+
+    #[test]
+    fn test_parse_program() {
+        let input = r"
+        def A: * = *
+        def id: Πx: A. A = λx: A. x
+        main = id A
+        ";
+
+        assert_eq!(
+            parser::from_string(input).unwrap().to_string(),
+            "(let A: * = * in (let id: Πx: A. A = (λx: A. x) in (id A)))"
+        )
+    }
+
+    #[test]
+    fn test_parse_let() {
+        let input = r"let a: * = * in let f: Πx: a. a = λx: a. x in f";
+
+        assert_eq!(
+            parser::from_string(input).unwrap().to_string(),
+            "(let a: * = * in (let f: Πx: a. a = (λx: a. x) in f))"
+        )
+    }
+
+    #[test]
+    fn test_parse_application() {
+        let input = r"(λA: *. λx: A. x : ΠA: *. Πx: A. A) * (f (g x) y)";
+
+        assert_eq!(
+            parser::from_string(input).unwrap().to_string(),
+            "(((λA: *. (λx: A. x)):ΠA: *. Πx: A. A *) ((f (g x)) y))"
+        )
+    }
+
+    #[test]
+    fn test_parse_main_in_expression() {
+        let inputs = [r"λx: *. main = x", r"def a: * = (main = *) main = a", r"def a: * = *"];
+
+        for input in inputs {
+            assert!(parser::from_string(input).is_err())
+        }
+    }
+}
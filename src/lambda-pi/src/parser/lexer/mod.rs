@@ -9,7 +9,7 @@ fn is_whitespace(c: char) -> bool {
 }
 
 fn is_digit(c: char) -> bool {
-    ('0'..='9').contains(&c)
+    c.is_ascii_digit()
 }
 
 fn is_reserved(c: char) -> bool {
@@ -40,6 +40,10 @@ impl<'a> Lexer<'a> {
     fn to_keyword(buf: &str) -> Token {
         match buf {
             "lambda" => Token::Lambda,
+            "let" => Token::Let,
+            "in" => Token::In,
+            "def" => Token::Def,
+            "main" => Token::Main,
             _ => Token::Variable(buf.to_string()),
         }
     }
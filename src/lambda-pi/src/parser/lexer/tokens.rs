@@ -3,6 +3,10 @@ pub enum Token {
     Number(usize),
     Variable(String),
     Lambda,
+    Let,
+    In,
+    Def,
+    Main,
 
     TInt,
     Star,
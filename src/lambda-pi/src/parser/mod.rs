@@ -13,5 +13,5 @@ pub fn from_string(str: &str) -> Result<parsetree::Checkable, error::ParserError
     let mut string = str.to_string();
     let mut parser = state::Parser::init(&mut string)?;
 
-    return parser.parse_checkable();
+    parser.parse_program()
 }
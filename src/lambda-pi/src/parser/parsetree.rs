@@ -55,6 +55,16 @@ pub struct Appl {
     pub range: Range,
 }
 
+/// `let x: T = e in b`, which a top-level `def x: T = e` stands for as well.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Let {
+    pub name: Symbol,
+    pub ty: Box<Checkable>,
+    pub value: Box<Checkable>,
+    pub body: Box<Checkable>,
+    pub range: Range,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Checkable {
     Abs(Abs),
@@ -69,12 +79,13 @@ pub enum Expr {
     Anno(Anno),
     Prod(Prod),
     Appl(Appl),
+    Let(Let),
 }
 
 impl Checkable {
     pub fn range(&self) -> Range {
         match self {
-            Checkable::Abs(Abs { range, .. }) => range.clone(),
+            Checkable::Abs(Abs { range, .. }) => *range,
             Checkable::Inf(expr) => (*expr.clone()).range(),
         }
     }
@@ -83,12 +94,13 @@ impl Checkable {
 impl Expr {
     pub fn range(&self) -> Range {
         match self {
-            Expr::Int(Int { range, .. }) => range.clone(),
-            Expr::Var(Var { range, .. }) => range.clone(),
-            Expr::Appl(Appl { range, .. }) => range.clone(),
-            Expr::Anno(Anno { range, .. }) => range.clone(),
-            Expr::Prod(Prod { range, .. }) => range.clone(),
-            Expr::Star(Star { range, .. }) => range.clone(),
+            Expr::Int(Int { range, .. }) => *range,
+            Expr::Var(Var { range, .. }) => *range,
+            Expr::Appl(Appl { range, .. }) => *range,
+            Expr::Anno(Anno { range, .. }) => *range,
+            Expr::Prod(Prod { range, .. }) => *range,
+            Expr::Star(Star { range, .. }) => *range,
+            Expr::Let(Let { range, .. }) => *range,
         }
     }
 }
@@ -135,6 +147,12 @@ impl fmt::Display for Anno {
     }
 }
 
+impl fmt::Display for Let {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(let {}: {} = {} in {})", self.name, self.ty, self.value, self.body)
+    }
+}
+
 impl fmt::Display for Checkable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -153,6 +171,7 @@ impl fmt::Display for Expr {
             Expr::Prod(prod) => write!(f, "{}", prod),
             Expr::Appl(appl) => write!(f, "{}", appl),
             Expr::Anno(anno) => write!(f, "{}", anno),
+            Expr::Let(def) => write!(f, "{}", def),
         }
     }
 }
@@ -0,0 +1,33 @@
+use crate::parser::{
+    self,
+    error::ParserError,
+    parsetree::{Checkable, Expr, Let},
+};
+
+/// Definitions every session starts with: `def` declarations still waiting for their `main`.
+pub const PRELUDE: &str = include_str!("../prelude.lp");
+
+// `input` takes the place of the body of the last definition.
+fn splice(prelude: Checkable, input: Checkable) -> Checkable {
+    match prelude {
+        Checkable::Inf(expr) => match *expr {
+            Expr::Let(Let { name, ty, value, body, range }) => {
+                let body = Box::new(splice(*body, input));
+
+                Checkable::Inf(Box::new(Expr::Let(Let { name, ty, value, body, range })))
+            }
+            _ => input,
+        },
+        Checkable::Abs(_) => input,
+    }
+}
+
+/// Parses `input` in the scope of the prelude definitions, the later definitions seeing the
+/// earlier ones. The two are parsed apart, so the ranges of `input` are the ones of its own
+/// text.
+pub fn load(input: &str) -> Result<Checkable, ParserError> {
+    // The `main` holds the place of the input until it is spliced in.
+    let prelude = parser::from_string(&format!("{}main = 0", PRELUDE))?;
+
+    Ok(splice(prelude, parser::from_string(input)?))
+}
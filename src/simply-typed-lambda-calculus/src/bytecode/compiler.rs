@@ -0,0 +1,121 @@
+use super::vm::Error;
+use super::{Instr, Program};
+use crate::parser::parsetree::{Abs, App, BinOp, Bool, Expr, Fix, If, Int, Unit, Var};
+use crate::reduction::variables::free_variables;
+
+// A function being compiled, with the names of its locals.
+struct Function {
+    code: Vec<Instr>,
+    scope: Vec<String>,
+}
+
+impl Function {
+    fn new(scope: Vec<String>) -> Function {
+        Function { code: Vec::new(), scope }
+    }
+
+    fn slot(&self, name: &str) -> Result<usize, Error> {
+        let slot = self.scope.iter().rposition(|local| local == name);
+        slot.ok_or_else(|| Error::Unbound(name.to_string()))
+    }
+
+    // Emits a jump whose target is set once it is known.
+    fn jump(&mut self, instr: fn(usize) -> Instr) -> usize {
+        self.code.push(instr(0));
+        self.code.len() - 1
+    }
+
+    fn land(&mut self, jump: usize) {
+        let target = self.code.len();
+
+        match &mut self.code[jump] {
+            Instr::Jump(to) | Instr::JumpUnless(to) => *to = target,
+            instr => unreachable!("{} is not a jump", instr),
+        }
+    }
+}
+
+// The abstraction becomes a function of the program whose locals are the variables it uses,
+// in the order of their names, then its parameter.
+fn closure(functions: &mut Vec<Vec<Instr>>, outer: &Function, abs: &Abs) -> Result<Instr, Error> {
+    let mut names: Vec<String> = free_variables(Expr::Abs(abs.clone())).into_iter().collect();
+    names.sort();
+
+    let captures = names.iter().map(|name| outer.slot(name)).collect::<Result<_, _>>()?;
+    names.push(abs.param.clone());
+
+    let index = functions.len();
+    functions.push(Vec::new());
+
+    let mut function = Function::new(names);
+    expression(functions, &mut function, &abs.body, true)?;
+    function.code.push(Instr::Return);
+    functions[index] = function.code;
+
+    Ok(Instr::Closure { function: index, captures })
+}
+
+// Compiles `expr` so that it leaves its value on the stack. A call in tail position replaces
+// the running function instead.
+fn expression(
+    functions: &mut Vec<Vec<Instr>>,
+    function: &mut Function,
+    expr: &Expr,
+    tail: bool,
+) -> Result<(), Error> {
+    let apply = if tail { Instr::TailApply } else { Instr::Apply };
+
+    match expr {
+        Expr::Int(Int { value, .. }) => function.code.push(Instr::Int(*value)),
+        Expr::Bool(Bool { value, .. }) => function.code.push(Instr::Bool(*value)),
+        Expr::Unit(Unit { .. }) => function.code.push(Instr::Unit),
+        Expr::Var(Var { value, .. }) => {
+            let slot = function.slot(value)?;
+            function.code.push(Instr::Load(slot))
+        }
+        Expr::Abs(abs) => {
+            let closure = closure(functions, function, abs)?;
+            function.code.push(closure)
+        }
+        Expr::App(App { lambda, argm, .. }) => {
+            expression(functions, function, lambda, false)?;
+            expression(functions, function, argm, false)?;
+            function.code.push(apply)
+        }
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            expression(functions, function, cond, false)?;
+            let otherwise = function.jump(Instr::JumpUnless);
+
+            expression(functions, function, then_branch, tail)?;
+            let end = function.jump(Instr::Jump);
+
+            function.land(otherwise);
+            expression(functions, function, else_branch, tail)?;
+            function.land(end)
+        }
+        Expr::BinOp(BinOp { op, left, right, .. }) => {
+            expression(functions, function, left, false)?;
+            expression(functions, function, right, false)?;
+            function.code.push(Instr::BinOp(*op))
+        }
+        Expr::Fix(Fix { value, .. }) => {
+            expression(functions, function, value, false)?;
+            function.code.push(Instr::Fix);
+            function.code.push(apply)
+        }
+    }
+
+    Ok(())
+}
+
+/// Compiles a well-typed term, erasing its types.
+pub fn compile(expr: &Expr) -> Result<Program, Error> {
+    let mut functions = vec![Vec::new()];
+    let mut main = Function::new(Vec::new());
+
+    expression(&mut functions, &mut main, expr, true)?;
+    main.code.push(Instr::Return);
+    functions[0] = main.code;
+
+    Ok(Program { functions })
+}
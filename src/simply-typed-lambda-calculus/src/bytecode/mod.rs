@@ -0,0 +1,182 @@
+//! A compiler from well-typed terms to the bytecode of a stack machine, and the machine that
+//! runs it by call-by-value. Types are erased, and every abstraction becomes a function of the
+//! program whose closures copy the variables it uses, so nothing is substituted at run time.
+
+use crate::parser::parsetree::{Expr, Operator};
+use std::fmt;
+
+pub mod compiler;
+pub mod vm;
+
+use vm::{Error, Value, Vm};
+
+/// An instruction of the machine, which works on a stack of values and on the locals of the
+/// function it is running: the variables its closure captured, then its parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instr {
+    Int(usize),
+    Bool(bool),
+    Unit,
+    /// Pushes a local.
+    Load(usize),
+    /// Pushes a closure of a function, capturing the given locals.
+    Closure { function: usize, captures: Vec<usize> },
+    /// Pushes the fixed point of the function on top of the stack above it, ready to be
+    /// applied to it.
+    Fix,
+    /// Pops an argument and a function, and calls the function.
+    Apply,
+    /// Calls like `Apply` in place of the running function, which has nothing left to do.
+    TailApply,
+    /// Returns the top of the stack to the caller.
+    Return,
+    Jump(usize),
+    /// Pops a boolean and jumps when it is false.
+    JumpUnless(usize),
+    BinOp(Operator),
+}
+
+/// A compiled program: its functions, the body of the program being the first one.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub functions: Vec<Vec<Instr>>,
+}
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instr::Int(value) => write!(f, "int {}", value),
+            Instr::Bool(value) => write!(f, "bool {}", value),
+            Instr::Unit => write!(f, "unit"),
+            Instr::Load(slot) => write!(f, "load {}", slot),
+            Instr::Closure { function, captures } => {
+                write!(f, "closure {} {:?}", function, captures)
+            }
+            Instr::Fix => write!(f, "fix"),
+            Instr::Apply => write!(f, "apply"),
+            Instr::TailApply => write!(f, "tailapply"),
+            Instr::Return => write!(f, "return"),
+            Instr::Jump(target) => write!(f, "jump {}", target),
+            Instr::JumpUnless(target) => write!(f, "jumpunless {}", target),
+            Instr::BinOp(op) => write!(f, "binop {}", op),
+        }
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, function) in self.functions.iter().enumerate() {
+            writeln!(f, "{}:", index)?;
+
+            for (pc, instr) in function.iter().enumerate() {
+                writeln!(f, "  {:>3}  {}", pc, instr)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compiles a well-typed term and runs it for at most `limit` instructions.
+pub fn run(expr: &Expr, limit: Option<usize>) -> Result<Value, Error> {
+    let program = compiler::compile(expr)?;
+    Vm::new(&program).run(limit)
+}
+
+#[cfg(test)]
+mod test {
+    use super::vm::{Error, Vm};
+    use super::{compiler::compile, run};
+    use crate::reduction::{reduce, Norm};
+    use crate::{checker, parser, prelude};
+
+    #[test]
+    fn test_compile_closure() {
+        let expr = parser::from_string(r"λx: int. λy: int. x + y").unwrap();
+        let program = compile(&expr).unwrap().to_string();
+        let expected = "\
+0:
+    0  closure 1 []
+    1  return
+1:
+    0  closure 2 [0]
+    1  return
+2:
+    0  load 0
+    1  load 1
+    2  binop +
+    3  return
+";
+
+        assert_eq!(program, expected)
+    }
+
+    #[test]
+    fn test_run_agrees_with_normal_order() {
+        let inputs = [
+            r"(λb: bool. if b then 1 else 2) false",
+            r"(λx: int. if x * 2 + 1 < 10 - 2 then x / 2 else 0) 3",
+            r"fact 5 + pow 2 (pred 4)",
+            r"compose succ (twice succ) (fact 3 + toint (cmul (csucc zero) (cadd zero zero)))",
+            r"toint (cmul (csucc (csucc zero)) (cadd (csucc zero) (csucc zero)))",
+            r"fix (λfib: int -> int. λn: int. if n < 2 then n else fib (n - 1) + fib (n - 2)) 10",
+            r"and (iszero (pred 1)) (not false)",
+        ];
+
+        for input in inputs {
+            let expr = prelude::load(input).unwrap();
+            checker::type_of(&expr).unwrap();
+
+            let value = run(&expr, None).unwrap().to_expr(expr.range()).unwrap();
+            let normal = reduce(Norm::NOR, expr, None);
+
+            assert_eq!(value.to_string(), normal.to_string(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_run_tail_calls() {
+        let input = r"
+        def rec count: int -> int -> int =
+            λn: int. λacc: int. if n == 0 then acc else count (n - 1) (acc + 2)
+        main = count 100000 0
+        ";
+        let expr = parser::from_string(input).unwrap();
+        let program = compile(&expr).unwrap();
+        let mut vm = Vm::new(&program);
+
+        assert_eq!(vm.run(None).unwrap().to_string(), "200000");
+        assert!(vm.depth < 5);
+    }
+
+    #[test]
+    fn test_run_errors() {
+        let expr = parser::from_string(r"1 / 0 == 0").unwrap();
+        assert!(matches!(run(&expr, None), Err(Error::DivisionByZero)));
+
+        let expr = parser::from_string(r"fix (λf: int -> int. λn: int. f n) 0").unwrap();
+        assert!(matches!(run(&expr, Some(1000)), Err(Error::Limit)));
+
+        let expr = parser::from_string(r"100000 * 100000 * 100000 * 100000").unwrap();
+        assert!(matches!(run(&expr, None), Err(Error::Overflow)));
+
+        let expr = parser::from_string(r"λx: int. x + y").unwrap();
+        assert!(matches!(run(&expr, None), Err(Error::Unbound(name)) if name == "y"));
+    }
+
+    #[test]
+    fn test_run_fixed_point_of_value() {
+        // Well-typed, but only the fixed point of a function can be unfolded.
+        let inputs = [
+            r"fix (λx: int. x + 1)",
+            r"if fix (λb: bool. b) then 1 else 2",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            checker::type_of(&expr).unwrap();
+
+            assert!(matches!(run(&expr, None), Err(Error::FixedPoint)), "{}", input);
+        }
+    }
+}
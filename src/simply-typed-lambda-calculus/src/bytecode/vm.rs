@@ -0,0 +1,226 @@
+use super::{Instr, Program};
+use crate::parser::location::Range;
+use crate::parser::parsetree::{Bool, Expr, Int, Operator, Unit};
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(usize),
+    Bool(bool),
+    Unit,
+    Closure(Rc<Closure>),
+    /// The fixed point of a function, unfolded when it is applied.
+    Fix(Rc<Value>),
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    function: usize,
+    captured: Vec<Value>,
+}
+
+impl Value {
+    /// The term of a value which is not a function.
+    pub fn to_expr(&self, range: Range) -> Option<Expr> {
+        match self {
+            Value::Int(value) => Some(Expr::Int(Int { value: *value, range })),
+            Value::Bool(value) => Some(Expr::Bool(Bool { value: *value, range })),
+            Value::Unit => Some(Expr::Unit(Unit { range })),
+            Value::Closure(_) | Value::Fix(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Unit => write!(f, "()"),
+            Value::Closure(closure) => write!(f, "<closure {}>", closure.function),
+            Value::Fix(value) => write!(f, "(fix {})", value),
+        }
+    }
+}
+
+/// Why a term has no value on the machine.
+#[derive(Debug)]
+pub enum Error {
+    /// The term has a variable no binder binds, so it was not type checked.
+    Unbound(String),
+    DivisionByZero,
+    /// An addition or a multiplication does not fit an integer.
+    Overflow,
+    /// A fixed point was used where a value which is not a function was needed. Like the
+    /// strategies, the machine only unfolds a fixed point by applying it.
+    FixedPoint,
+    /// The program ran for as many instructions as it was allowed to.
+    Limit,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unbound(name) => write!(f, "{} is not bound", name),
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::Overflow => write!(f, "integer overflow"),
+            Error::FixedPoint => write!(f, "the fixed point of a value which is not a function"),
+            Error::Limit => write!(f, "the program ran out of steps"),
+        }
+    }
+}
+
+struct Frame {
+    function: usize,
+    pc: usize,
+    locals: Vec<Value>,
+    // The arguments the value this call returns is applied to next, the first one last.
+    pending: Vec<Value>,
+}
+
+/// The machine, which runs a well-typed program by call-by-value. Like the CBV strategy, it
+/// does not terminate on arguments that do not, even those normal order would drop.
+pub struct Vm<'p> {
+    program: &'p Program,
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    /// The instructions run so far.
+    pub steps: usize,
+    /// The most calls that were running at once.
+    pub depth: usize,
+}
+
+// Subtraction is truncated at zero since literals are naturals.
+fn binop(op: Operator, left: usize, right: usize) -> Result<Value, Error> {
+    let value = match op {
+        Operator::Add => Value::Int(left.checked_add(right).ok_or(Error::Overflow)?),
+        Operator::Sub => Value::Int(left.saturating_sub(right)),
+        Operator::Mul => Value::Int(left.checked_mul(right).ok_or(Error::Overflow)?),
+        Operator::Div if right == 0 => return Err(Error::DivisionByZero),
+        Operator::Div => Value::Int(left / right),
+        Operator::Eq => Value::Bool(left == right),
+        Operator::Lt => Value::Bool(left < right),
+    };
+
+    Ok(value)
+}
+
+// The error of a value which is not the one an instruction needs. In a well-typed program only
+// a fixed point is.
+fn mismatch(value: Value, expected: &str) -> Error {
+    match value {
+        Value::Fix(_) => Error::FixedPoint,
+        value => unreachable!("{} is not {}", value, expected),
+    }
+}
+
+impl<'p> Vm<'p> {
+    pub fn new(program: &'p Program) -> Vm<'p> {
+        let main = Frame { function: 0, pc: 0, locals: Vec::new(), pending: Vec::new() };
+        Vm { program, stack: Vec::new(), frames: vec![main], steps: 0, depth: 1 }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("the stack holds the operands of every instruction")
+    }
+
+    fn pop_int(&mut self) -> Result<usize, Error> {
+        match self.pop() {
+            Value::Int(value) => Ok(value),
+            value => Err(mismatch(value, "an integer")),
+        }
+    }
+
+    fn call(&mut self, lambda: Value, argm: Value, mut pending: Vec<Value>) {
+        match lambda {
+            Value::Closure(closure) => {
+                let mut locals = closure.captured.clone();
+                locals.push(argm);
+
+                let frame = Frame { function: closure.function, pc: 0, locals, pending };
+                self.frames.push(frame);
+                self.depth = self.depth.max(self.frames.len());
+            }
+            Value::Fix(ref functional) => {
+                pending.push(argm);
+                let functional = Value::clone(functional);
+                self.call(functional, lambda, pending)
+            }
+            value => unreachable!("{} is not a function", value),
+        }
+    }
+
+    /// Runs the program for at most `limit` instructions.
+    pub fn run(&mut self, limit: Option<usize>) -> Result<Value, Error> {
+        let program = self.program;
+
+        loop {
+            if limit.is_some_and(|limit| self.steps >= limit) {
+                return Err(Error::Limit);
+            }
+            self.steps += 1;
+
+            let frame = self.frames.last_mut().expect("a program returns from its last call");
+            let instr = &program.functions[frame.function][frame.pc];
+            frame.pc += 1;
+
+            match instr {
+                Instr::Int(value) => self.stack.push(Value::Int(*value)),
+                Instr::Bool(value) => self.stack.push(Value::Bool(*value)),
+                Instr::Unit => self.stack.push(Value::Unit),
+                Instr::Load(slot) => self.stack.push(frame.locals[*slot].clone()),
+                Instr::Closure { function, captures } => {
+                    let captured = captures.iter().map(|slot| frame.locals[*slot].clone());
+                    let closure = Closure { function: *function, captured: captured.collect() };
+
+                    self.stack.push(Value::Closure(Rc::new(closure)))
+                }
+                Instr::Fix => {
+                    let functional = self.pop();
+                    self.stack.push(functional.clone());
+                    self.stack.push(Value::Fix(Rc::new(functional)))
+                }
+                Instr::Apply => {
+                    let argm = self.pop();
+                    let lambda = self.pop();
+                    self.call(lambda, argm, Vec::new())
+                }
+                Instr::TailApply => {
+                    let argm = self.pop();
+                    let lambda = self.pop();
+                    let frame = self.frames.pop().expect("the running function has a frame");
+                    self.call(lambda, argm, frame.pending)
+                }
+                Instr::Return => {
+                    let mut frame = self.frames.pop().expect("the running function has a frame");
+                    let value = self.pop();
+
+                    match frame.pending.pop() {
+                        Some(argm) => self.call(value, argm, frame.pending),
+                        None if self.frames.is_empty() => return Ok(value),
+                        None => self.stack.push(value),
+                    }
+                }
+                Instr::Jump(target) => frame.pc = *target,
+                Instr::JumpUnless(target) => match self.pop() {
+                    Value::Bool(true) => (),
+                    Value::Bool(false) => self.jump(*target),
+                    value => return Err(mismatch(value, "a boolean")),
+                },
+                Instr::BinOp(op) => {
+                    let right = self.pop_int()?;
+                    let left = self.pop_int()?;
+                    let value = binop(*op, left, right)?;
+
+                    self.stack.push(value)
+                }
+            }
+        }
+    }
+
+    fn jump(&mut self, target: usize) {
+        let frame = self.frames.last_mut().expect("the running function has a frame");
+        frame.pc = target;
+    }
+}
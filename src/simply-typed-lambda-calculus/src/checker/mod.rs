@@ -3,7 +3,10 @@ pub mod error;
 use std::collections::HashMap;
 
 use self::error::TypeError;
-use crate::parser::parsetree::{Abs, App, Arrow, Expr, Int, TInt, Type, Var};
+use crate::parser::parsetree::{
+    Abs, App, Arrow, BinOp, Bool, Expr, Fix, If, Int, Operator, TBool, TInt, TUnit, Type, Unit,
+    Var,
+};
 
 fn equal_type(ty1: &Type, ty2: &Type) -> bool {
     match (ty1, ty2) {
@@ -11,24 +14,40 @@ fn equal_type(ty1: &Type, ty2: &Type) -> bool {
             equal_type(left, l0) && equal_type(right, r0)
         }
         (Type::TInt(_), Type::TInt(_)) => true,
-        (Type::Arrow(_), Type::TInt(_)) => false,
-        (Type::TInt(_), Type::Arrow(_)) => false,
+        (Type::TBool(_), Type::TBool(_)) => true,
+        (Type::TUnit(_), Type::TUnit(_)) => true,
+        (_, _) => false,
     }
 }
 
-type TypeContext = HashMap<String, Type>;
+pub(crate) fn operator_type(op: Operator) -> Type {
+    match op {
+        Operator::Add | Operator::Sub | Operator::Mul | Operator::Div => Type::TInt(TInt {}),
+        Operator::Eq | Operator::Lt => Type::TBool(TBool {}),
+    }
+}
+
+pub(crate) type TypeContext = HashMap<String, Type>;
 
-fn infer_type(context: &mut TypeContext, expr: &Expr) -> Result<Type, TypeError> {
+pub(crate) fn infer_type(context: &mut TypeContext, expr: &Expr) -> Result<Type, TypeError> {
     match expr {
         Expr::Int(Int { .. }) => Ok(Type::TInt(TInt {})),
+        Expr::Bool(Bool { .. }) => Ok(Type::TBool(TBool {})),
+        Expr::Unit(Unit { .. }) => Ok(Type::TUnit(TUnit {})),
         Expr::Var(Var { value, .. }) => match context.get(value) {
             None => Err(TypeError::UndefinedVariable(value.clone())),
             Some(ty) => Ok(ty.clone()),
         },
         Expr::Abs(Abs { param, param_ty, body, .. }) => {
-            context.insert(param.clone(), param_ty.clone());
+            let shadowed = context.insert(param.clone(), param_ty.clone());
+            let body_ty = infer_type(context, body);
 
-            let body_ty = infer_type(context, body)?;
+            match shadowed {
+                Some(ty) => context.insert(param.clone(), ty),
+                None => context.remove(param),
+            };
+
+            let body_ty = body_ty?;
 
             Ok(Type::Arrow(Arrow {
                 left: Box::new(param_ty.clone()),
@@ -49,9 +68,86 @@ fn infer_type(context: &mut TypeContext, expr: &Expr) -> Result<Type, TypeError>
                 Err(TypeError::UnexpectedType(lambda_ty.clone()))
             }
         }
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            let cond_ty = infer_type(context, cond)?;
+
+            if !equal_type(&cond_ty, &Type::TBool(TBool {})) {
+                return Err(TypeError::Mismatch(Type::TBool(TBool {}), cond_ty));
+            }
+
+            let then_ty = infer_type(context, then_branch)?;
+            let else_ty = infer_type(context, else_branch)?;
+
+            if equal_type(&then_ty, &else_ty) {
+                Ok(then_ty)
+            } else {
+                Err(TypeError::Mismatch(then_ty, else_ty))
+            }
+        }
+        Expr::BinOp(BinOp { op, left, right, .. }) => {
+            for operand in [left, right] {
+                let operand_ty = infer_type(context, operand)?;
+
+                if !equal_type(&operand_ty, &Type::TInt(TInt {})) {
+                    return Err(TypeError::Mismatch(Type::TInt(TInt {}), operand_ty));
+                }
+            }
+
+            Ok(operator_type(*op))
+        }
+        Expr::Fix(Fix { value, .. }) => {
+            let value_ty = infer_type(context, value)?;
+
+            match value_ty {
+                Type::Arrow(Arrow { left, right }) if equal_type(&left, &right) => Ok(*left),
+                Type::Arrow(Arrow { left, right }) => Err(TypeError::Mismatch(*left, *right)),
+                _ => Err(TypeError::UnexpectedType(value_ty)),
+            }
+        }
     }
 }
 
 pub fn type_of(expr: &Expr) -> Result<Type, TypeError> {
     infer_type(&mut HashMap::new(), expr)
 }
+
+#[cfg(test)]
+mod test {
+    use super::type_of;
+    use crate::parser;
+
+    #[test]
+    fn test_check_if_branches() {
+        let expr = parser::from_string(r"if true then 1 else false").unwrap();
+
+        assert!(type_of(&expr).is_err())
+    }
+
+    #[test]
+    fn test_check_fix_mismatch() {
+        let expr = parser::from_string(r"fix (λn: int. true)").unwrap();
+
+        assert!(type_of(&expr).is_err())
+    }
+
+    #[test]
+    fn test_check_program_order() {
+        let input = r"
+        def twice: int -> int = λn: int. double (double n)
+        def double: int -> int = λn: int. n + n
+        main = twice 1
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(&expr).is_err())
+    }
+
+    #[test]
+    fn test_check_shadowed_parameter() {
+        // The inner `x` goes out of scope after its abstraction.
+        let input = r"(λx: int. if (λx: bool. x) true then x else 0) 1";
+        let expr = parser::from_string(input).unwrap();
+
+        assert_eq!(type_of(&expr).unwrap().to_string(), "int")
+    }
+}
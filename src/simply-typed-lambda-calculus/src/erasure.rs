@@ -0,0 +1,135 @@
+//! Type erasure into the untyped lambda calculus. A simply typed term is an untyped one with
+//! annotated binders, so the annotations go and only the primitives need encoding: integers and
+//! booleans become Church numerals and booleans, the unit the identity, and `fix` the fixed
+//! point combinator of call-by-value, which applicative order unfolds forever.
+
+use crate::parser::location::Range;
+use crate::parser::parsetree::{
+    Abs, App, BinOp, Bool, Expr, Fix, If, Int, Operator, Type, Unit, Var,
+};
+use untyped_lambda_calculus::encodings::{self, Primitive, Sort};
+use untyped_lambda_calculus::parser::{location as untyped_location, parsetree as untyped};
+
+fn range(range: Range) -> untyped_location::Range {
+    let start = untyped_location::Pos::new(range.start.index);
+    let end = untyped_location::Pos::new(range.end.index);
+
+    untyped_location::Range::new(start, end)
+}
+
+fn primitive(op: Operator) -> Primitive {
+    match op {
+        Operator::Add => Primitive::Add,
+        Operator::Sub => Primitive::Sub,
+        Operator::Mul => Primitive::Mul,
+        Operator::Div => Primitive::Div,
+        Operator::Eq => Primitive::Eq,
+        Operator::Lt => Primitive::Lt,
+    }
+}
+
+/// How the erasure of a term of type `ty` reads back.
+pub fn sort(ty: &Type) -> Sort {
+    match ty {
+        Type::TInt(_) => Sort::Integer,
+        Type::TBool(_) => Sort::Boolean,
+        _ => Sort::Other,
+    }
+}
+
+/// The untyped term of a simply typed one, which should type check.
+pub fn erase(expr: &Expr) -> untyped::Expr {
+    let range = range(expr.range());
+
+    match expr {
+        Expr::Int(Int { value, .. }) => encodings::numeral(*value, range),
+        Expr::Bool(Bool { value, .. }) => encodings::boolean(*value),
+        Expr::Unit(Unit { .. }) => encodings::unit(),
+        Expr::Var(Var { value, .. }) => encodings::var(value, range),
+        Expr::Abs(Abs { param, body, .. }) => encodings::abs(param, erase(body), range),
+        Expr::App(App { lambda, argm, .. }) => encodings::app(erase(lambda), erase(argm), range),
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            encodings::branch(erase(cond), erase(then_branch), erase(else_branch), range)
+        }
+        Expr::BinOp(BinOp { op, left, right, .. }) => {
+            encodings::primitive(primitive(*op), erase(left), erase(right), range)
+        }
+        Expr::Fix(Fix { value, .. }) => encodings::fix(erase(value), range),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{erase, sort};
+    use crate::reduction::{reduce, Norm};
+    use crate::{checker, parser, prelude};
+    use untyped_lambda_calculus::encodings::evaluate;
+    use untyped_lambda_calculus::reduction as untyped;
+
+    // The strategies of both calculi that have the same name.
+    const STRATEGIES: [(Norm, untyped::Norm); 4] = [
+        (Norm::NOR, untyped::Norm::NOR),
+        (Norm::APP, untyped::Norm::APP),
+        (Norm::CBN, untyped::Norm::CBN),
+        (Norm::CBV, untyped::Norm::CBV),
+    ];
+
+    #[test]
+    fn test_erase_term() {
+        let expr = parser::from_string(r"λf: int -> bool. λx: int. if f x then x else ()").unwrap();
+
+        assert_eq!(
+            erase(&expr).to_string(),
+            "(λf. (λx. ((((λc. (λt. (λe. (((c t) e) (λx. x))))) (f x)) (λd. x)) (λd. (λx. x)))))"
+        )
+    }
+
+    #[test]
+    fn test_erase_keeps_strategies() {
+        let inputs = [
+            r"(λb: bool. if b then 1 else 2) false",
+            r"(λx: int. if x * 2 + 1 < 10 - 3 then x + 4 else 0) 2",
+            r"(λp: int -> int -> int. p (p 1 2) 3) (λx: int. λy: int. x * y - 1)",
+            r"(λb: bool. if b then 5 / 2 else 0) (2 == 1 + 1)",
+            r"(λx: int. x < 3) 4",
+            r"(λf: int -> bool. f 2) (λx: int. x == 1 + 1)",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            let sort = sort(&checker::type_of(&expr).unwrap());
+
+            for (typed, strategy) in STRATEGIES {
+                let value = reduce(typed, expr.clone(), None).to_string();
+                let erased = evaluate(strategy, erase(&expr), sort).to_string();
+                assert_eq!(erased, value, "{}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_erase_recursion() {
+        // Applicative order unfolds the erased `fix` before it is applied, which the typed
+        // strategies never do.
+        let inputs = [
+            r"fact 2 + 1",
+            r"pow 2 2",
+            r"compose succ (twice succ) (toint (cmul (csucc (csucc zero)) (cadd zero zero)))",
+            r"if and (iszero (pred 1)) (not false) then 1 else 0",
+            r"fix (λfib: int -> int. λn: int. if n < 2 then n else fib (n - 1) + fib (n - 2)) 3",
+            r"and (iszero (pred 1)) (not true)",
+        ];
+
+        for input in inputs {
+            let expr = prelude::load(input).unwrap();
+            let sort = sort(&checker::type_of(&expr).unwrap());
+            let strategies = STRATEGIES.into_iter().filter(|&(typed, _)| typed != Norm::APP);
+
+            for (typed, strategy) in strategies {
+                let value = reduce(typed, expr.clone(), None).to_string();
+                let erased = evaluate(strategy, erase(&expr), sort).to_string();
+                assert_eq!(erased, value, "{}", input);
+            }
+        }
+    }
+}
@@ -1,3 +1,7 @@
+pub mod bytecode;
 pub mod checker;
+pub mod erasure;
 pub mod parser;
+pub mod prelude;
 pub mod reduction;
+pub mod transform;
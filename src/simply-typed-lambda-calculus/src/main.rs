@@ -1,11 +1,14 @@
 use simply_typed_lambda_calculus::checker;
-use simply_typed_lambda_calculus::parser;
+use simply_typed_lambda_calculus::prelude;
 use simply_typed_lambda_calculus::reduction;
 
 fn main() {
-    let input = r"(λf: int -> int. f) (λx: int. x)";
+    let input = r"
+    def rec fib: int -> int = λn: int. if n < 2 then n else fib (n - 1) + fib (n - 2)
+    main = if iszero (pred 1) then fib 10 else pow 2 10
+    ";
 
-    let expr_parsed = parser::from_string(input).unwrap();
+    let expr_parsed = prelude::load(input).unwrap();
     let expr_typed = checker::type_of(&expr_parsed).unwrap();
     let expr_reduced = reduction::reduce(reduction::Norm::NOR, expr_parsed, None);
 
@@ -2,10 +2,24 @@ use super::{
     error::ParserError,
     lexer::tokens::Token,
     macros::{consume, match_token},
-    parsetree::{Abs, App, Arrow, Expr, Int, TInt, Type, Var},
+    parsetree::{
+        Abs, App, Arrow, BinOp, Bool, Expr, Fix, If, Int, Operator, TBool, TInt, TUnit, Type, Unit,
+        Var,
+    },
     state::Parser,
 };
 
+fn make_binop(op: Operator, left: Expr, right: Expr) -> Expr {
+    let range = left.range().mix(right.range());
+
+    Expr::BinOp(BinOp {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+        range,
+    })
+}
+
 impl<'a> Parser<'a> {
     pub fn parse_variable_expr(&mut self) -> Result<Expr, ParserError> {
         let (token, range) = consume!(self, Token::Variable(var) => var.clone())?;
@@ -14,20 +28,95 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_number_expr(&mut self) -> Result<Expr, ParserError> {
-        let (token, range) = consume!(self, Token::Number(num) => num.clone())?;
+        let (token, range) = consume!(self, Token::Number(num) => *num)?;
 
         Ok(Expr::Int(Int { value: token, range }))
     }
 
+    pub fn parse_bool_expr(&mut self) -> Result<Expr, ParserError> {
+        let value = matches!(self.get(), Token::True);
+        let (_, range) = consume!(self, Token::True | Token::False)?;
+
+        Ok(Expr::Bool(Bool { value, range }))
+    }
+
     pub fn parse_atom(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
             Token::LParen => self.parse_parens_expr(),
             Token::Variable(_) => self.parse_variable_expr(),
             Token::Number(_) => self.parse_number_expr(),
+            Token::True | Token::False => self.parse_bool_expr(),
+            Token::Fix => self.parse_fix(),
             _ => self.fail(),
         }
     }
 
+    pub fn parse_fix(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Fix)?;
+        let value = self.parse_atom()?;
+        let endr = value.range();
+
+        Ok(Expr::Fix(Fix { value: Box::new(value), range: range.mix(endr) }))
+    }
+
+    // `let x: T = e in b` is sugar for `(λx: T. b) e` and `let rec f: T = e in b`
+    // for `(λf: T. b) (fix (λf: T. e))`. A top-level `def x: T = e` binds the same way
+    // over the declarations after it, down to the `main = e` closing the program.
+    pub fn parse_let(&mut self) -> Result<Expr, ParserError> {
+        let declaration = matches!(self.get(), Token::Def);
+        let (_, range) = consume!(self, Token::Let | Token::Def)?;
+        let recursive = matches!(self.get(), Token::Rec);
+
+        if recursive {
+            consume!(self, Token::Rec)?;
+        }
+
+        let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+
+        consume!(self, Token::Colon)?;
+        let param_ty = self.parse_type()?;
+
+        consume!(self, Token::Equal)?;
+        let value = self.parse_expr()?;
+
+        let body = match self.get() {
+            Token::Def | Token::Main if declaration => self.parse_program()?,
+            _ if declaration => self.fail()?,
+            _ => {
+                consume!(self, Token::In)?;
+                self.parse_expr()?
+            }
+        };
+        let endr = body.range();
+
+        let value = if recursive {
+            let value_range = value.range();
+            let functional = Expr::Abs(Abs {
+                param: param.clone(),
+                param_ty: param_ty.clone(),
+                body: Box::new(value),
+                range: value_range,
+            });
+
+            Expr::Fix(Fix { value: Box::new(functional), range: value_range })
+        } else {
+            value
+        };
+
+        let lambda = Expr::Abs(Abs {
+            param,
+            param_ty,
+            body: Box::new(body),
+            range: range.mix(endr),
+        });
+
+        Ok(Expr::App(App {
+            lambda: Box::new(lambda),
+            argm: Box::new(value),
+            range: range.mix(endr),
+        }))
+    }
+
     pub fn parse_abs(&mut self) -> Result<Expr, ParserError> {
         let (_, range) = consume!(self, Token::Lambda)?;
         let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
@@ -77,18 +166,108 @@ impl<'a> Parser<'a> {
         Ok(head)
     }
 
+    pub fn parse_multiplicative(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_application()?;
+
+        loop {
+            let op = match self.get() {
+                Token::Times => Operator::Mul,
+                Token::Slash => Operator::Div,
+                _ => return Ok(left),
+            };
+
+            self.advance()?;
+            let right = self.parse_application()?;
+            left = make_binop(op, left, right);
+        }
+    }
+
+    pub fn parse_additive(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_multiplicative()?;
+
+        loop {
+            let op = match self.get() {
+                Token::Plus => Operator::Add,
+                Token::Minus => Operator::Sub,
+                _ => return Ok(left),
+            };
+
+            self.advance()?;
+            let right = self.parse_multiplicative()?;
+            left = make_binop(op, left, right);
+        }
+    }
+
+    pub fn parse_comparison(&mut self) -> Result<Expr, ParserError> {
+        let left = self.parse_additive()?;
+
+        let op = match self.get() {
+            Token::EqualEqual => Operator::Eq,
+            Token::Less => Operator::Lt,
+            _ => return Ok(left),
+        };
+
+        self.advance()?;
+        let right = self.parse_additive()?;
+
+        Ok(make_binop(op, left, right))
+    }
+
     pub fn parse_parens_expr(&mut self) -> Result<Expr, ParserError> {
-        consume!(self, Token::LParen)?;
+        let (_, range_l) = consume!(self, Token::LParen)?;
+
+        if let Token::RParen = self.get() {
+            let (_, range_r) = consume!(self, Token::RParen)?;
+            return Ok(Expr::Unit(Unit { range: range_l.mix(range_r) }));
+        }
+
         let expr = self.parse_expr()?;
         consume!(self, Token::RParen)?;
 
         Ok(expr)
     }
 
+    pub fn parse_if(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::If)?;
+        let cond = self.parse_expr()?;
+
+        consume!(self, Token::Then)?;
+        let then_branch = self.parse_expr()?;
+
+        consume!(self, Token::Else)?;
+        let else_branch = self.parse_expr()?;
+        let endr = else_branch.range();
+
+        Ok(Expr::If(If {
+            cond: Box::new(cond),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_main(&mut self) -> Result<Expr, ParserError> {
+        consume!(self, Token::Main)?;
+        consume!(self, Token::Equal)?;
+
+        self.parse_expr()
+    }
+
+    // A program is an expression, or declarations closed by `main = e`; `main` is accepted
+    // only here, where a declaration could start.
+    pub fn parse_program(&mut self) -> Result<Expr, ParserError> {
+        match self.get() {
+            Token::Main => self.parse_main(),
+            _ => self.parse_expr(),
+        }
+    }
+
     pub fn parse_expr(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
             Token::Lambda => self.parse_abs(),
-            _ => self.parse_application(),
+            Token::If => self.parse_if(),
+            Token::Let | Token::Def => self.parse_let(),
+            _ => self.parse_comparison(),
         }
     }
 
@@ -98,6 +277,14 @@ impl<'a> Parser<'a> {
                 consume!(self, Token::TInt)?;
                 Ok(Type::TInt(TInt {}))
             }
+            Token::TBool => {
+                consume!(self, Token::TBool)?;
+                Ok(Type::TBool(TBool {}))
+            }
+            Token::TUnit => {
+                consume!(self, Token::TUnit)?;
+                Ok(Type::TUnit(TUnit {}))
+            }
             Token::LParen => {
                 consume!(self, Token::LParen)?;
                 let ty = self.parse_type()?;
@@ -135,3 +322,24 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::parser;
+
+    #[test]
+    fn test_parse_program_without_main() {
+        let input = r"def one: int = 1 one";
+
+        assert!(parser::from_string(input).is_err())
+    }
+
+    #[test]
+    fn test_parse_main_in_expression() {
+        let inputs = [r"(λx: int. main = x + 1) 1", r"if true then main = 1 else 2"];
+
+        for input in inputs {
+            assert!(parser::from_string(input).is_err())
+        }
+    }
+}
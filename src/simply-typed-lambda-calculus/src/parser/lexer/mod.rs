@@ -5,15 +5,15 @@ pub mod state;
 pub mod tokens;
 
 fn is_whitespace(c: char) -> bool {
-    matches!(c, ' ' | '\t' | '\r')
+    matches!(c, ' ' | '\t' | '\r' | '\n')
 }
 
 fn is_digit(c: char) -> bool {
-    ('0'..='9').contains(&c)
+    c.is_ascii_digit()
 }
 
 fn is_reserved(c: char) -> bool {
-    matches!(c, '(' | ')' | '.' | ':' | 'λ')
+    matches!(c, '(' | ')' | '.' | ':' | 'λ' | '+' | '-' | '*' | '/' | '=' | '<')
 }
 
 fn is_valid_char(c: char) -> bool {
@@ -26,10 +26,38 @@ impl<'a> Lexer<'a> {
         self.make_token(token, start)
     }
 
+    pub fn double_token(
+        &mut self,
+        second: char,
+        token: Token,
+        single: Token,
+        start: usize,
+    ) -> (Token, Range) {
+        self.next_char();
+
+        match self.peekable.peek() {
+            Some(chr) if *chr == second => self.single_token(token, start),
+            _ => self.make_token(single, start),
+        }
+    }
+
     fn to_keyword(buf: &str) -> Token {
         match buf {
             "lambda" => Token::Lambda,
+            "true" => Token::True,
+            "false" => Token::False,
+            "if" => Token::If,
+            "then" => Token::Then,
+            "else" => Token::Else,
+            "fix" => Token::Fix,
+            "let" => Token::Let,
+            "def" => Token::Def,
+            "main" => Token::Main,
+            "rec" => Token::Rec,
+            "in" => Token::In,
             "int" => Token::TInt,
+            "bool" => Token::TBool,
+            "unit" => Token::TUnit,
             "->" => Token::Arrow,
             _ => Token::Variable(buf.to_string()),
         }
@@ -55,6 +83,12 @@ impl<'a> Lexer<'a> {
                 ')' => self.single_token(Token::RParen, start),
                 ':' => self.single_token(Token::Colon, start),
                 '.' => self.single_token(Token::Dot, start),
+                '+' => self.single_token(Token::Plus, start),
+                '*' => self.single_token(Token::Times, start),
+                '/' => self.single_token(Token::Slash, start),
+                '<' => self.single_token(Token::Less, start),
+                '-' => self.double_token('>', Token::Arrow, Token::Minus, start),
+                '=' => self.double_token('=', Token::EqualEqual, Token::Equal, start),
                 chr if is_digit(*chr) => {
                     let num = self.accu_while(is_digit);
                     let num = num.parse::<usize>().unwrap();
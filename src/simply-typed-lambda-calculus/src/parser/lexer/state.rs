@@ -11,7 +11,7 @@ pub struct Lexer<'a> {
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(input: &str) -> Lexer {
+    pub fn new(input: &str) -> Lexer<'_> {
         Lexer {
             input,
             peekable: input.chars().peekable(),
@@ -49,7 +49,7 @@ impl<'a> Lexer<'a> {
     pub fn accu_while(&mut self, pred: fn(char) -> bool) -> &'a str {
         let start = self.current_pos;
 
-        while let Some(x) = self.peekable.peek().map(|c| *c) {
+        while let Some(x) = self.peekable.peek().copied() {
             if !pred(x) {
                 break;
             }
@@ -3,12 +3,32 @@ pub enum Token {
     Variable(String),
     Number(usize),
     Lambda,
+    True,
+    False,
+    If,
+    Then,
+    Else,
+    Fix,
+    Let,
+    Def,
+    Main,
+    Rec,
+    In,
     Dot,
     LParen,
     RParen,
     Colon,
     TInt,
+    TBool,
+    TUnit,
     Arrow,
+    Plus,
+    Minus,
+    Times,
+    Slash,
+    Equal,
+    EqualEqual,
+    Less,
     Error,
     Eof,
 }
@@ -13,6 +13,17 @@ pub struct Int {
     pub range: Range,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bool {
+    pub value: bool,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub range: Range,
+}
+
 #[derive(Debug, Clone)]
 pub struct Abs {
     pub param: String,
@@ -28,9 +39,47 @@ pub struct App {
     pub range: Range,
 }
 
+#[derive(Debug, Clone)]
+pub struct If {
+    pub cond: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub value: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Lt,
+}
+
+#[derive(Debug, Clone)]
+pub struct BinOp {
+    pub op: Operator,
+    pub left: Box<Expr>,
+    pub right: Box<Expr>,
+    pub range: Range,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TInt {}
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct TBool {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TUnit {}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Arrow {
     pub left: Box<Type>,
@@ -40,24 +89,36 @@ pub struct Arrow {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     TInt(TInt),
+    TBool(TBool),
+    TUnit(TUnit),
     Arrow(Arrow),
 }
 
 #[derive(Debug, Clone)]
 pub enum Expr {
     Int(Int),
+    Bool(Bool),
+    Unit(Unit),
     Var(Var),
     Abs(Abs),
     App(App),
+    If(If),
+    BinOp(BinOp),
+    Fix(Fix),
 }
 
 impl Expr {
     pub fn range(&self) -> Range {
         match self {
-            Expr::Int(Int { range, .. }) => range.clone(),
-            Expr::Var(Var { range, .. }) => range.clone(),
-            Expr::Abs(Abs { range, .. }) => range.clone(),
-            Expr::App(App { range, .. }) => range.clone(),
+            Expr::Int(Int { range, .. }) => *range,
+            Expr::Bool(Bool { range, .. }) => *range,
+            Expr::Unit(Unit { range }) => *range,
+            Expr::Var(Var { range, .. }) => *range,
+            Expr::Abs(Abs { range, .. }) => *range,
+            Expr::App(App { range, .. }) => *range,
+            Expr::If(If { range, .. }) => *range,
+            Expr::BinOp(BinOp { range, .. }) => *range,
+            Expr::Fix(Fix { range, .. }) => *range,
         }
     }
 }
@@ -68,6 +129,18 @@ impl fmt::Display for Int {
     }
 }
 
+impl fmt::Display for Bool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "()")
+    }
+}
+
 impl fmt::Display for Var {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.value)
@@ -86,6 +159,41 @@ impl fmt::Display for App {
     }
 }
 
+impl fmt::Display for If {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(if {} then {} else {})",
+            self.cond, self.then_branch, self.else_branch
+        )
+    }
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(fix {})", self.value)
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Sub => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+            Operator::Eq => write!(f, "=="),
+            Operator::Lt => write!(f, "<"),
+        }
+    }
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} {} {})", self.left, self.op, self.right)
+    }
+}
+
 impl fmt::Display for Arrow {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self.left {
@@ -99,6 +207,8 @@ impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Type::TInt(_) => write!(f, "int"),
+            Type::TBool(_) => write!(f, "bool"),
+            Type::TUnit(_) => write!(f, "unit"),
             Type::Arrow(arrow) => write!(f, "{}", arrow),
         }
     }
@@ -108,9 +218,14 @@ impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Expr::Int(int) => write!(f, "{}", int),
+            Expr::Bool(bool) => write!(f, "{}", bool),
+            Expr::Unit(unit) => write!(f, "{}", unit),
             Expr::Var(var) => write!(f, "{}", var),
             Expr::Abs(abs) => write!(f, "{}", abs),
             Expr::App(app) => write!(f, "{}", app),
+            Expr::If(cond) => write!(f, "{}", cond),
+            Expr::BinOp(binop) => write!(f, "{}", binop),
+            Expr::Fix(fix) => write!(f, "{}", fix),
         }
     }
 }
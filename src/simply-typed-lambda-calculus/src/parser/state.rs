@@ -59,7 +59,7 @@ impl<'a> Parser<'a> {
         match expect(self.get()) {
             None => self.fail(),
             Some(res) => {
-                let range = self.current_token.1.clone();
+                let range = self.current_token.1;
                 self.advance()?;
                 Ok((res, range))
             }
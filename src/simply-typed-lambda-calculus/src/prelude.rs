@@ -0,0 +1,47 @@
+use crate::parser::{
+    self,
+    error::ParserError,
+    parsetree::{Abs, App, Expr},
+};
+
+/// Definitions every session starts with: a chain of `let`s still waiting for its body.
+pub const PRELUDE: &str = include_str!("../prelude.stlc");
+
+// A `let` is an abstraction applied to its value, and `input` takes the place of the last body.
+fn splice(prelude: Expr, input: Expr) -> Expr {
+    match prelude {
+        Expr::App(App { lambda, argm, range }) => match *lambda {
+            Expr::Abs(abs) => {
+                let body = Box::new(splice(*abs.body, input));
+                let lambda = Box::new(Expr::Abs(Abs { body, ..abs }));
+
+                Expr::App(App { lambda, argm, range })
+            }
+            _ => unreachable!("the prelude is a chain of lets"),
+        },
+        _ => input,
+    }
+}
+
+/// Parses `input` in the scope of the prelude definitions. The two are parsed apart, so the
+/// ranges of `input` are the ones of its own text.
+pub fn load(input: &str) -> Result<Expr, ParserError> {
+    // The `0` holds the place of the input until it is spliced in.
+    let prelude = parser::from_string(&format!("{}0", PRELUDE))?;
+
+    Ok(splice(prelude, parser::from_string(input)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::load;
+    use crate::parser::error::ParserError;
+
+    #[test]
+    fn test_prelude_error_range() {
+        // The input is parsed apart from the prelude, so its ranges start at its own text.
+        let error = load(r"(1 in").unwrap_err();
+
+        assert!(matches!(error, ParserError::UnexpectedToken(_, range) if range.start.index == 3))
+    }
+}
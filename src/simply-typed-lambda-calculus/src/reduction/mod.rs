@@ -1,22 +1,139 @@
 use crate::parser::parsetree::Expr;
 
+pub mod nbe;
+pub mod primitives;
 pub mod strategies;
 pub mod variables;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Norm {
     NOR, // normal-order
     APP, // applicative-order
     CBN, // call-by-name
     CBV, // call-by-value
+    NBE, // normal forms by evaluation
 }
 
+/// The reductions evaluation takes when given no limit.
+pub const STEPS: usize = 1_000_000;
+
 pub fn reduce(strategy: Norm, expr: Expr, limit: Option<usize>) -> Expr {
-    let limit = Some(limit.unwrap_or(100));
+    // The strategies bound how deeply reductions nest and evaluation how many there are, with
+    // a bound of their own when given no limit, so a term that diverges stops under either.
+    let depth = Some(limit.unwrap_or(100));
+    let steps = Some(limit.unwrap_or(STEPS));
 
     match strategy {
-        Norm::NOR => strategies::normal_order(expr, limit),
-        Norm::APP => strategies::applicative_order(expr, limit),
-        Norm::CBN => strategies::call_by_name(expr, limit),
-        Norm::CBV => strategies::call_by_value(expr, limit),
+        Norm::NOR => strategies::normal_order(expr, depth),
+        Norm::APP => strategies::applicative_order(expr, depth),
+        Norm::CBN => strategies::call_by_name(expr, depth),
+        Norm::CBV => strategies::call_by_value(expr, depth),
+        Norm::NBE => nbe::normalize(&expr, steps),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{reduce, Norm};
+    use crate::{checker, parser, prelude};
+
+    fn eval(strategy: Norm, input: &str) -> String {
+        let expr = parser::from_string(input).unwrap();
+        checker::type_of(&expr).unwrap();
+        reduce(strategy, expr, None).to_string()
+    }
+
+    #[test]
+    fn test_reduce_if() {
+        let input = r"(λb: bool. if b then 1 else 2) false";
+
+        assert_eq!(eval(Norm::NOR, input), "2");
+        assert_eq!(eval(Norm::APP, input), "2");
+        assert_eq!(eval(Norm::CBN, input), "2");
+        assert_eq!(eval(Norm::CBV, input), "2");
+    }
+
+    #[test]
+    fn test_reduce_stuck_if() {
+        let input = r"λb: bool. if b then () else ()";
+
+        assert_eq!(eval(Norm::NOR, input), "(λb: bool. (if b then () else ()))");
+        assert_eq!(eval(Norm::CBN, input), "(λb: bool. (if b then () else ()))");
+    }
+
+    #[test]
+    fn test_reduce_arithmetic() {
+        let input = r"(λx: int. if x * 2 + 1 < 10 - 2 then x / 2 else 0) 3";
+
+        assert_eq!(eval(Norm::NOR, input), "1");
+        assert_eq!(eval(Norm::APP, input), "1");
+        assert_eq!(eval(Norm::CBN, input), "1");
+        assert_eq!(eval(Norm::CBV, input), "1");
+    }
+
+    #[test]
+    fn test_reduce_stuck_arithmetic() {
+        assert_eq!(eval(Norm::NOR, r"λx: int. x + (1 + 2)"), "(λx: int. (x + 3))");
+        assert_eq!(eval(Norm::NOR, r"1 / 0 == 0"), "((1 / 0) == 0)");
+        assert_eq!(eval(Norm::NOR, r"4294967296 * 4294967296"), "(4294967296 * 4294967296)");
+    }
+
+    #[test]
+    fn test_reduce_let_rec() {
+        let input = r"
+        let rec fact: int -> int = λn: int. if n == 0 then 1 else n * fact (n - 1) in
+        fact 5
+        ";
+
+        assert_eq!(eval(Norm::NOR, input), "120");
+        assert_eq!(eval(Norm::APP, input), "120");
+        assert_eq!(eval(Norm::CBN, input), "120");
+        assert_eq!(eval(Norm::CBV, input), "120");
+    }
+
+    #[test]
+    fn test_reduce_curried_let_rec() {
+        let input = r"
+        let rec pow: int -> int -> int =
+            λb: int. λe: int. if e == 0 then 1 else b * pow b (e - 1) in
+        pow 2 5
+        ";
+
+        assert_eq!(eval(Norm::NOR, input), "32");
+        assert_eq!(eval(Norm::CBN, input), "32");
+        assert_eq!(eval(Norm::CBV, input), "32");
+    }
+
+    #[test]
+    fn test_reduce_fix() {
+        let input = r"
+        fix (λfib: int -> int. λn: int. if n < 2 then n else fib (n - 1) + fib (n - 2)) 10
+        ";
+
+        assert_eq!(eval(Norm::NOR, input), "55");
+        assert_eq!(eval(Norm::CBV, input), "55");
+    }
+
+    #[test]
+    fn test_reduce_program() {
+        let input = r"
+        def square: int -> int = λn: int. n * n
+        def rec sum: int -> int = λn: int. if n < 1 then 0 else square n + sum (n - 1)
+        main = sum 3
+        ";
+
+        assert_eq!(eval(Norm::NOR, input), "14");
+        assert_eq!(eval(Norm::CBV, input), "14");
+    }
+
+    #[test]
+    fn test_reduce_prelude() {
+        let input = r"
+        compose succ (twice succ) (fact 3 + toint (cmul (csucc zero) (cadd zero zero)))
+        ";
+        let expr = prelude::load(input).unwrap();
+        checker::type_of(&expr).unwrap();
+
+        assert_eq!(reduce(Norm::NOR, expr, None).to_string(), "9");
     }
 }
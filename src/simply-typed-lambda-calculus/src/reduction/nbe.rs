@@ -0,0 +1,281 @@
+//! Normalisation by evaluation for the simply typed calculus, on the thunks and environments
+//! of the untyped one. Literals evaluate to values of their own that primitives compute on by
+//! the rules of `delta`, and an `if` or an operation stuck on a variable becomes a neutral
+//! value, whose branches are only evaluated when it is read back.
+//!
+//! A `fix` is unfolded only when it is applied, as the strategies do. Reading back a recursive
+//! function whose recursion a stuck `if` guards unfolds it again on every call, so its normal
+//! form is only found up to a limit.
+
+use super::primitives::delta;
+use super::variables::free_variables;
+use crate::parser::location::Range;
+use crate::parser::parsetree::{Abs, App, BinOp, Bool, Expr, Fix, If, Int, Operator, Unit, Var};
+use std::collections::HashSet;
+use std::rc::Rc;
+use untyped_lambda_calculus::reduction::nbe::{Env, Thunk};
+use untyped_lambda_calculus::reduction::variables::fresh_name;
+
+#[derive(Clone)]
+enum Value<'a> {
+    Int(usize, Range),
+    Bool(bool, Range),
+    Unit(Range),
+    /// An abstraction with the environment it was evaluated in.
+    Lam(&'a Abs, Scope<'a>),
+    /// `fix` of a function, unfolded when it is applied.
+    Fix(Rc<Argument<'a>>, Range),
+    Neutral(Rc<Neutral<'a>>),
+}
+
+/// A value that cannot be reduced further, headed by a free variable, a stuck primitive, or a
+/// redex left once the limit was reached.
+enum Neutral<'a> {
+    Var(Var),
+    App(Value<'a>, Rc<Argument<'a>>, Range),
+    If(Value<'a>, &'a If, Scope<'a>),
+    BinOp(Operator, Value<'a>, Value<'a>, Range),
+}
+
+/// A term with the environment it is evaluated in.
+struct Suspended<'a>(&'a Expr, Scope<'a>);
+
+type Argument<'a> = Thunk<Suspended<'a>, Value<'a>>;
+
+type Scope<'a> = Env<'a, Rc<Argument<'a>>>;
+
+fn neutral(neutral: Neutral) -> Value {
+    Value::Neutral(Rc::new(neutral))
+}
+
+struct Evaluator {
+    limit: Option<usize>,
+    // The names a binder must not take when read back: the free variables of the term and the
+    // binders it is read back under.
+    used: HashSet<String>,
+}
+
+impl Evaluator {
+    // Counts a reduction, or tells that the limit was reached.
+    fn tick(&mut self) -> bool {
+        match self.limit {
+            Some(0) => false,
+            Some(count) => {
+                self.limit = Some(count - 1);
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn force<'a>(&mut self, thunk: &Argument<'a>) -> Value<'a> {
+        thunk.force(|Suspended(term, env)| self.eval(term, env))
+    }
+
+    // Arguments that are variables share the thunk they are bound to.
+    fn delay<'a>(&self, term: &'a Expr, env: &Scope<'a>) -> Rc<Argument<'a>> {
+        match term {
+            Expr::Var(Var { value, .. }) => match env.lookup(value) {
+                Some(thunk) => thunk.clone(),
+                None => Thunk::new(Suspended(term, env.clone())),
+            },
+            _ => Thunk::new(Suspended(term, env.clone())),
+        }
+    }
+
+    // `fix (λf: T. e)` unfolds to `e` with `f` bound to itself.
+    fn unfold<'a>(&mut self, value: Value<'a>) -> Value<'a> {
+        let Value::Fix(function, _) = &value else {
+            return value;
+        };
+
+        match self.force(function) {
+            Value::Lam(abs, env) if self.tick() => {
+                let itself = Thunk::ready(value.clone());
+                self.eval(&abs.body, env.bind(&abs.param, itself))
+            }
+            _ => value,
+        }
+    }
+
+    fn binop<'a>(&mut self, binop: &'a BinOp, env: &Scope<'a>) -> Value<'a> {
+        let left = self.eval(&binop.left, env.clone());
+        let right = self.eval(&binop.right, env.clone());
+
+        // Literals are computed by the same rules the strategies use.
+        if let (Value::Int(left, left_range), Value::Int(right, right_range)) = (&left, &right) {
+            let left = Expr::Int(Int { value: *left, range: *left_range });
+            let right = Expr::Int(Int { value: *right, range: *right_range });
+            let (left, right) = (Box::new(left), Box::new(right));
+            let operation = BinOp { op: binop.op, left, right, range: binop.range };
+
+            match delta(operation) {
+                Expr::Int(Int { value, range }) => return Value::Int(value, range),
+                Expr::Bool(Bool { value, range }) => return Value::Bool(value, range),
+                _ => (),
+            }
+        }
+
+        neutral(Neutral::BinOp(binop.op, left, right, binop.range))
+    }
+
+    // Applications and branches in tail position loop instead of recursing, so that a long
+    // chain of reductions does not grow the stack.
+    fn eval<'a>(&mut self, mut term: &'a Expr, mut env: Scope<'a>) -> Value<'a> {
+        loop {
+            match term {
+                Expr::Int(Int { value, range }) => return Value::Int(*value, *range),
+                Expr::Bool(Bool { value, range }) => return Value::Bool(*value, *range),
+                Expr::Unit(Unit { range }) => return Value::Unit(*range),
+                Expr::Var(var) => {
+                    return match env.lookup(&var.value) {
+                        Some(thunk) => self.force(&thunk.clone()),
+                        None => neutral(Neutral::Var(var.clone())),
+                    }
+                }
+                Expr::Abs(abs) => return Value::Lam(abs, env),
+                Expr::Fix(Fix { value, range }) => {
+                    return Value::Fix(self.delay(value, &env), *range)
+                }
+                Expr::App(App { lambda, argm, range }) => {
+                    let argm = self.delay(argm, &env);
+                    let lambda = self.eval(lambda, env);
+
+                    match self.unfold(lambda) {
+                        Value::Lam(abs, closure) if self.tick() => {
+                            term = &abs.body;
+                            env = closure.bind(&abs.param, argm);
+                        }
+                        lambda => return neutral(Neutral::App(lambda, argm, *range)),
+                    }
+                }
+                Expr::If(cond) => match self.eval(&cond.cond, env.clone()) {
+                    Value::Bool(value, _) if self.tick() => {
+                        term = if value { &cond.then_branch } else { &cond.else_branch };
+                    }
+                    test => return neutral(Neutral::If(test, cond, env)),
+                },
+                Expr::BinOp(binop) => return self.binop(binop, &env),
+            }
+        }
+    }
+
+    fn quote(&mut self, value: Value) -> Expr {
+        match value {
+            Value::Int(value, range) => Expr::Int(Int { value, range }),
+            Value::Bool(value, range) => Expr::Bool(Bool { value, range }),
+            Value::Unit(range) => Expr::Unit(Unit { range }),
+            Value::Lam(abs, env) => {
+                let param = fresh_name(&abs.param, &self.used);
+                let var = Var { value: param.clone(), range: abs.range };
+                let var = Thunk::ready(neutral(Neutral::Var(var)));
+
+                let body = self.eval(&abs.body, env.bind(&abs.param, var));
+                self.used.insert(param.clone());
+                let body = self.quote(body);
+                self.used.remove(&param);
+
+                Expr::Abs(Abs { param, body: Box::new(body), ..abs.clone() })
+            }
+            Value::Fix(function, range) => {
+                let function = self.force(&function);
+                Expr::Fix(Fix { value: Box::new(self.quote(function)), range })
+            }
+            Value::Neutral(neutral) => match &*neutral {
+                Neutral::Var(var) => Expr::Var(var.clone()),
+                Neutral::App(lambda, argm, range) => {
+                    let lambda = self.quote(lambda.clone());
+                    let argm = self.force(argm);
+
+                    Expr::App(App {
+                        lambda: Box::new(lambda),
+                        argm: Box::new(self.quote(argm)),
+                        range: *range,
+                    })
+                }
+                Neutral::If(test, cond, env) => {
+                    let test = self.quote(test.clone());
+                    let then_branch = self.eval(&cond.then_branch, env.clone());
+                    let then_branch = self.quote(then_branch);
+                    let else_branch = self.eval(&cond.else_branch, env.clone());
+                    let else_branch = self.quote(else_branch);
+
+                    Expr::If(If {
+                        cond: Box::new(test),
+                        then_branch: Box::new(then_branch),
+                        else_branch: Box::new(else_branch),
+                        range: cond.range,
+                    })
+                }
+                Neutral::BinOp(op, left, right, range) => {
+                    let left = self.quote(left.clone());
+                    let right = self.quote(right.clone());
+
+                    Expr::BinOp(BinOp {
+                        op: *op,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                        range: *range,
+                    })
+                }
+            },
+        }
+    }
+}
+
+/// The normal form of `expr`, after at most `limit` reductions when there is one. The
+/// redexes left when the limit is reached are read back as they are.
+pub fn normalize(expr: &Expr, limit: Option<usize>) -> Expr {
+    let mut evaluator = Evaluator { limit, used: free_variables(expr.clone()) };
+    let value = evaluator.eval(expr, Scope::default());
+
+    evaluator.quote(value)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reduction::{reduce, Norm};
+    use crate::{checker, prelude};
+
+    fn eval(strategy: Norm, input: &str) -> String {
+        let expr = prelude::load(input).unwrap();
+        checker::type_of(&expr).unwrap();
+        reduce(strategy, expr, None).to_string()
+    }
+
+    #[test]
+    fn test_nbe_agrees_with_normal_order() {
+        let inputs = [
+            r"(λb: bool. if b then 1 else 2) false",
+            r"λb: bool. if b then () else ()",
+            r"λx: int. x + (1 + 2)",
+            r"1 / 0 == 0",
+            r"λf: int -> int. twice (compose f succ)",
+            r"fact 5 + pow 2 (pred 4)",
+            r"toint (cmul (csucc (csucc zero)) (cadd (csucc zero) (csucc zero)))",
+            r"fix (λfib: int -> int. λn: int. if n < 2 then n else fib (n - 1) + fib (n - 2)) 10",
+        ];
+
+        for input in inputs {
+            assert_eq!(eval(Norm::NBE, input), eval(Norm::NOR, input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_nbe_recursion() {
+        let input = r"
+        def rec fib: int -> int = λn: int. if n < 2 then n else fib (n - 1) + fib (n - 2)
+        main = fib 20
+        ";
+
+        assert_eq!(eval(Norm::NBE, input), "6765")
+    }
+
+    #[test]
+    fn test_nbe_stops_without_limit() {
+        let input = r"fix (λf: int -> int. λn: int. f n) 0";
+        let expected = r"((λn: int. ((fix (λf: int -> int. (λn': int. (f n')))) n)) 0)";
+
+        assert_eq!(eval(Norm::NBE, input), expected)
+    }
+}
@@ -1,5 +1,6 @@
+use super::primitives::delta;
 use super::variables::substitution;
-use crate::parser::parsetree::{Abs, App, Expr};
+use crate::parser::parsetree::{Abs, App, BinOp, Bool, Expr, Fix, If};
 
 macro_rules! break_limit {
     ($expr: ident, $limit: ident) => {
@@ -11,11 +12,23 @@ macro_rules! break_limit {
     };
 }
 
+// `fix (λf: T. e)` is treated as a value and only unfolded to
+// `e[f := fix (λf: T. e)]` once it is applied, so that the strategies which
+// reduce under binders do not unroll it forever.
+fn unfold(fix: Fix) -> Expr {
+    match *fix.value.clone() {
+        Expr::Abs(abs) => substitution(*abs.body, abs.param, Expr::Fix(fix)),
+        _ => Expr::Fix(fix),
+    }
+}
+
 pub fn normal_order(ex: Expr, limit: Option<usize>) -> Expr {
     break_limit!(ex, limit);
 
     match ex {
         Expr::Int(int) => Expr::Int(int),
+        Expr::Bool(bool) => Expr::Bool(bool),
+        Expr::Unit(unit) => Expr::Unit(unit),
         Expr::Var(var) => Expr::Var(var),
         Expr::Abs(abs) => {
             let body = normal_order(*abs.body, limit);
@@ -23,13 +36,25 @@ pub fn normal_order(ex: Expr, limit: Option<usize>) -> Expr {
             Expr::Abs(Abs { body: Box::new(body), ..abs })
         }
         Expr::App(app) => {
-            let func_expr = normal_order(*app.lambda, limit);
+            // The head is only reduced to weak head normal form so that all the
+            // pending arguments are substituted before going under its binders.
+            let func_expr = call_by_name(*app.lambda, limit);
 
             match func_expr {
                 Expr::Abs(abs) => {
                     let substituted = substitution(*abs.body, abs.param, *app.argm);
-                    normal_order(substituted, limit.and_then(|l| Some(l - 1)))
+                    normal_order(substituted, limit.map(|l| l - 1))
                 }
+                Expr::Fix(fix) => match unfold(fix) {
+                    Expr::Abs(abs) => {
+                        let substituted = substitution(*abs.body, abs.param, *app.argm);
+                        normal_order(substituted, limit.map(|l| l - 1))
+                    }
+                    expr => {
+                        let app = App { lambda: Box::new(expr), ..app };
+                        normal_order(Expr::App(app), limit.map(|l| l - 1))
+                    }
+                },
                 expr => {
                     let func = normal_order(expr, limit);
                     let argm = normal_order(*app.argm, limit);
@@ -42,6 +67,44 @@ pub fn normal_order(ex: Expr, limit: Option<usize>) -> Expr {
                 }
             }
         }
+        Expr::If(cond) => {
+            let test = normal_order(*cond.cond, limit);
+
+            match test {
+                Expr::Bool(Bool { value: true, .. }) => {
+                    normal_order(*cond.then_branch, limit.map(|l| l - 1))
+                }
+                Expr::Bool(Bool { value: false, .. }) => {
+                    normal_order(*cond.else_branch, limit.map(|l| l - 1))
+                }
+                test => {
+                    let then_branch = normal_order(*cond.then_branch, limit);
+                    let else_branch = normal_order(*cond.else_branch, limit);
+
+                    Expr::If(If {
+                        cond: Box::new(test),
+                        then_branch: Box::new(then_branch),
+                        else_branch: Box::new(else_branch),
+                        ..cond
+                    })
+                }
+            }
+        }
+        Expr::BinOp(binop) => {
+            let left = normal_order(*binop.left, limit);
+            let right = normal_order(*binop.right, limit);
+
+            delta(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
+        Expr::Fix(fix) => {
+            let value = normal_order(*fix.value, limit);
+
+            Expr::Fix(Fix { value: Box::new(value), ..fix })
+        }
     }
 }
 
@@ -50,6 +113,8 @@ pub fn applicative_order(ex: Expr, limit: Option<usize>) -> Expr {
 
     match ex {
         Expr::Int(int) => Expr::Int(int),
+        Expr::Bool(bool) => Expr::Bool(bool),
+        Expr::Unit(unit) => Expr::Unit(unit),
         Expr::Var(var) => Expr::Var(var),
         Expr::Abs(abs) => {
             let body = applicative_order(*abs.body, limit);
@@ -62,8 +127,18 @@ pub fn applicative_order(ex: Expr, limit: Option<usize>) -> Expr {
             match func_expr {
                 Expr::Abs(abs) => {
                     let substituted = substitution(*abs.body, abs.param, argm_expr);
-                    applicative_order(substituted, limit.and_then(|l| Some(l - 1)))
+                    applicative_order(substituted, limit.map(|l| l - 1))
                 }
+                Expr::Fix(fix) => match unfold(fix) {
+                    Expr::Abs(abs) => {
+                        let substituted = substitution(*abs.body, abs.param, argm_expr);
+                        applicative_order(substituted, limit.map(|l| l - 1))
+                    }
+                    expr => {
+                        let app = App { lambda: Box::new(expr), argm: Box::new(argm_expr), range };
+                        applicative_order(Expr::App(app), limit.map(|l| l - 1))
+                    }
+                },
                 expr => Expr::App(App {
                     lambda: Box::new(expr),
                     argm: Box::new(argm_expr),
@@ -71,6 +146,44 @@ pub fn applicative_order(ex: Expr, limit: Option<usize>) -> Expr {
                 }),
             }
         }
+        Expr::If(cond) => {
+            let test = applicative_order(*cond.cond, limit);
+
+            match test {
+                Expr::Bool(Bool { value: true, .. }) => {
+                    applicative_order(*cond.then_branch, limit.map(|l| l - 1))
+                }
+                Expr::Bool(Bool { value: false, .. }) => {
+                    applicative_order(*cond.else_branch, limit.map(|l| l - 1))
+                }
+                test => {
+                    let then_branch = applicative_order(*cond.then_branch, limit);
+                    let else_branch = applicative_order(*cond.else_branch, limit);
+
+                    Expr::If(If {
+                        cond: Box::new(test),
+                        then_branch: Box::new(then_branch),
+                        else_branch: Box::new(else_branch),
+                        ..cond
+                    })
+                }
+            }
+        }
+        Expr::BinOp(binop) => {
+            let left = applicative_order(*binop.left, limit);
+            let right = applicative_order(*binop.right, limit);
+
+            delta(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
+        Expr::Fix(fix) => {
+            let value = applicative_order(*fix.value, limit);
+
+            Expr::Fix(Fix { value: Box::new(value), ..fix })
+        }
     }
 }
 
@@ -79,6 +192,8 @@ pub fn call_by_name(ex: Expr, limit: Option<usize>) -> Expr {
 
     match ex {
         Expr::Int(int) => Expr::Int(int),
+        Expr::Bool(bool) => Expr::Bool(bool),
+        Expr::Unit(unit) => Expr::Unit(unit),
         Expr::Var(var) => Expr::Var(var),
         Expr::Abs(abs) => Expr::Abs(abs),
         Expr::App(App { lambda, argm, range }) => {
@@ -87,11 +202,45 @@ pub fn call_by_name(ex: Expr, limit: Option<usize>) -> Expr {
             match func_expr {
                 Expr::Abs(abs) => {
                     let substituted = substitution(*abs.body, abs.param, *argm);
-                    call_by_name(substituted, limit.and_then(|l| Some(l - 1)))
+                    call_by_name(substituted, limit.map(|l| l - 1))
                 }
+                Expr::Fix(fix) => match unfold(fix) {
+                    Expr::Abs(abs) => {
+                        let substituted = substitution(*abs.body, abs.param, *argm);
+                        call_by_name(substituted, limit.map(|l| l - 1))
+                    }
+                    expr => {
+                        let app = App { lambda: Box::new(expr), argm, range };
+                        call_by_name(Expr::App(app), limit.map(|l| l - 1))
+                    }
+                },
                 expr => Expr::App(App { lambda: Box::new(expr), argm, range }),
             }
         }
+        Expr::If(cond) => {
+            let test = call_by_name(*cond.cond, limit);
+
+            match test {
+                Expr::Bool(Bool { value: true, .. }) => {
+                    call_by_name(*cond.then_branch, limit.map(|l| l - 1))
+                }
+                Expr::Bool(Bool { value: false, .. }) => {
+                    call_by_name(*cond.else_branch, limit.map(|l| l - 1))
+                }
+                test => Expr::If(If { cond: Box::new(test), ..cond }),
+            }
+        }
+        Expr::BinOp(binop) => {
+            let left = call_by_name(*binop.left, limit);
+            let right = call_by_name(*binop.right, limit);
+
+            delta(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
+        Expr::Fix(fix) => Expr::Fix(fix),
     }
 }
 
@@ -100,6 +249,8 @@ pub fn call_by_value(ex: Expr, limit: Option<usize>) -> Expr {
 
     match ex {
         Expr::Int(int) => Expr::Int(int),
+        Expr::Bool(bool) => Expr::Bool(bool),
+        Expr::Unit(unit) => Expr::Unit(unit),
         Expr::Var(var) => Expr::Var(var),
         Expr::Abs(abs) => Expr::Abs(abs),
         Expr::App(App { lambda, argm, range }) => {
@@ -109,7 +260,22 @@ pub fn call_by_value(ex: Expr, limit: Option<usize>) -> Expr {
                 Expr::Abs(abs) => {
                     let argm_expr = call_by_value(*argm, limit);
                     let substituted = substitution(*abs.body, abs.param, argm_expr);
-                    call_by_name(substituted, limit.and_then(|l| Some(l - 1)))
+                    call_by_name(substituted, limit.map(|l| l - 1))
+                }
+                Expr::Fix(fix) => {
+                    let argm_expr = call_by_value(*argm, limit);
+
+                    match unfold(fix) {
+                        Expr::Abs(abs) => {
+                            let substituted = substitution(*abs.body, abs.param, argm_expr);
+                            call_by_value(substituted, limit.map(|l| l - 1))
+                        }
+                        expr => {
+                            let argm = Box::new(argm_expr);
+                            let app = App { lambda: Box::new(expr), argm, range };
+                            call_by_value(Expr::App(app), limit.map(|l| l - 1))
+                        }
+                    }
                 }
                 expr => {
                     let argm_expr = call_by_value(*argm, limit);
@@ -122,5 +288,29 @@ pub fn call_by_value(ex: Expr, limit: Option<usize>) -> Expr {
                 }
             }
         }
+        Expr::If(cond) => {
+            let test = call_by_value(*cond.cond, limit);
+
+            match test {
+                Expr::Bool(Bool { value: true, .. }) => {
+                    call_by_value(*cond.then_branch, limit.map(|l| l - 1))
+                }
+                Expr::Bool(Bool { value: false, .. }) => {
+                    call_by_value(*cond.else_branch, limit.map(|l| l - 1))
+                }
+                test => Expr::If(If { cond: Box::new(test), ..cond }),
+            }
+        }
+        Expr::BinOp(binop) => {
+            let left = call_by_value(*binop.left, limit);
+            let right = call_by_value(*binop.right, limit);
+
+            delta(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
+        Expr::Fix(fix) => Expr::Fix(fix),
     }
 }
@@ -1,4 +1,4 @@
-use crate::parser::parsetree::{Abs, App, Expr, Int, Var};
+use crate::parser::parsetree::{Abs, App, BinOp, Bool, Expr, Fix, If, Int, Unit, Var};
 use std::collections::HashSet;
 
 pub fn free_variables(expr: Expr) -> HashSet<String> {
@@ -6,6 +6,8 @@ pub fn free_variables(expr: Expr) -> HashSet<String> {
 
     match expr {
         Expr::Int(Int { .. }) => (),
+        Expr::Bool(Bool { .. }) => (),
+        Expr::Unit(Unit { .. }) => (),
         Expr::Var(Var { value, .. }) => {
             free.insert(value);
         }
@@ -17,6 +19,16 @@ pub fn free_variables(expr: Expr) -> HashSet<String> {
             free.extend(free_variables(*body));
             free.remove(&param);
         }
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            free.extend(free_variables(*cond));
+            free.extend(free_variables(*then_branch));
+            free.extend(free_variables(*else_branch));
+        }
+        Expr::BinOp(BinOp { left, right, .. }) => {
+            free.extend(free_variables(*left));
+            free.extend(free_variables(*right));
+        }
+        Expr::Fix(Fix { value, .. }) => free.extend(free_variables(*value)),
     }
 
     free
@@ -27,6 +39,8 @@ pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
         Expr::Var(Var { value, .. }) if value == from => to,
         Expr::Var(Var { .. }) => expr,
         Expr::Int(Int { .. }) => expr,
+        Expr::Bool(Bool { .. }) => expr,
+        Expr::Unit(Unit { .. }) => expr,
         Expr::App(app) => {
             let lambda = substitution(*app.lambda, from.clone(), to.clone());
             let argm = substitution(*app.argm, from, to);
@@ -49,5 +63,32 @@ pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
 
             Expr::Abs(abs)
         }
+        Expr::If(cond) => {
+            let test = substitution(*cond.cond, from.clone(), to.clone());
+            let then_branch = substitution(*cond.then_branch, from.clone(), to.clone());
+            let else_branch = substitution(*cond.else_branch, from, to);
+
+            Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond
+            })
+        }
+        Expr::BinOp(binop) => {
+            let left = substitution(*binop.left, from.clone(), to.clone());
+            let right = substitution(*binop.right, from, to);
+
+            Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
+        Expr::Fix(fix) => {
+            let value = substitution(*fix.value, from, to);
+
+            Expr::Fix(Fix { value: Box::new(value), ..fix })
+        }
     }
 }
@@ -0,0 +1,203 @@
+//! Conversion to A-normal form. The operands of applications and operators, the conditions and
+//! the fixed points are atoms, which are constants, variables or abstractions whose bodies are
+//! in A-normal form too, and every intermediate result is named in the order call-by-value
+//! evaluates it. The calculus has no `let`, so a name is bound by applying an abstraction to
+//! what it names, `let x: A = e in b` being `(λx: A. b) e`, and these are the only applications
+//! whose operands may be compound.
+
+use super::{abs, app, arrow, parts, under, var, Fresh};
+use crate::checker::{self, error::TypeError, operator_type, TypeContext};
+use crate::parser::parsetree::{
+    Abs, App, BinOp, Bool, Expr, Fix, If, Int, TBool, TInt, TUnit, Type, Unit, Var,
+};
+use std::collections::HashMap;
+
+// The names bound before a compound term, in order.
+type Bindings = Vec<(String, Type, Expr)>;
+
+struct Anf {
+    fresh: Fresh,
+}
+
+impl Anf {
+    // A term in A-normal form, binding its intermediate results around it, and its type.
+    fn normal(
+        &mut self,
+        context: &mut TypeContext,
+        expr: &Expr,
+    ) -> Result<(Expr, Type), TypeError> {
+        let mut bindings = Bindings::new();
+        let (body, ty) = self.compound(context, expr, &mut bindings)?;
+
+        let body = bindings.into_iter().rev().fold(body, |body, (name, ty, value)| {
+            let range = value.range();
+            app(abs(&name, ty, body, range), value, range)
+        });
+
+        Ok((body, ty))
+    }
+
+    // A compound term whose operands are atoms, pushing the names the atoms need. Its type is
+    // built from the types of its parts, so that no subterm is checked again.
+    fn compound(
+        &mut self,
+        context: &mut TypeContext,
+        expr: &Expr,
+        bindings: &mut Bindings,
+    ) -> Result<(Expr, Type), TypeError> {
+        match expr {
+            Expr::Int(Int { .. }) => Ok((expr.clone(), Type::TInt(TInt {}))),
+            Expr::Bool(Bool { .. }) => Ok((expr.clone(), Type::TBool(TBool {}))),
+            Expr::Unit(Unit { .. }) => Ok((expr.clone(), Type::TUnit(TUnit {}))),
+            Expr::Var(Var { value, .. }) => match context.get(value) {
+                Some(ty) => Ok((expr.clone(), ty.clone())),
+                None => Err(TypeError::UndefinedVariable(value.clone())),
+            },
+            Expr::Abs(Abs { param, param_ty, body, range }) => {
+                let (body, body_ty) =
+                    under(context, param, param_ty, |context| self.normal(context, body))?;
+
+                Ok((abs(param, param_ty.clone(), body, *range), arrow(param_ty.clone(), body_ty)))
+            }
+            // A binding already, whose value may stay compound.
+            Expr::App(App { lambda, argm, range }) if matches!(**lambda, Expr::Abs(_)) => {
+                let (argm, _) = self.compound(context, argm, bindings)?;
+                let (lambda, lambda_ty) = self.compound(context, lambda, bindings)?;
+
+                Ok((app(lambda, argm, *range), parts(lambda_ty)?.1))
+            }
+            Expr::App(App { lambda, argm, range }) => {
+                let (lambda, lambda_ty) = self.atom(context, lambda, bindings)?;
+                let (argm, _) = self.atom(context, argm, bindings)?;
+
+                Ok((app(lambda, argm, *range), parts(lambda_ty)?.1))
+            }
+            Expr::If(If { cond, then_branch, else_branch, range }) => {
+                let (cond, _) = self.atom(context, cond, bindings)?;
+                let (then_branch, ty) = self.normal(context, then_branch)?;
+                let (else_branch, _) = self.normal(context, else_branch)?;
+
+                let expr = Expr::If(If {
+                    cond: Box::new(cond),
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(else_branch),
+                    range: *range,
+                });
+
+                Ok((expr, ty))
+            }
+            Expr::BinOp(BinOp { op, left, right, range }) => {
+                let expr = Expr::BinOp(BinOp {
+                    op: *op,
+                    left: Box::new(self.atom(context, left, bindings)?.0),
+                    right: Box::new(self.atom(context, right, bindings)?.0),
+                    range: *range,
+                });
+
+                Ok((expr, operator_type(*op)))
+            }
+            Expr::Fix(Fix { value, range }) => {
+                let (value, value_ty) = self.atom(context, value, bindings)?;
+
+                Ok((Expr::Fix(Fix { value: Box::new(value), range: *range }), parts(value_ty)?.0))
+            }
+        }
+    }
+
+    // An atom for a term, naming it when it is compound, and its type.
+    fn atom(
+        &mut self,
+        context: &mut TypeContext,
+        expr: &Expr,
+        bindings: &mut Bindings,
+    ) -> Result<(Expr, Type), TypeError> {
+        match self.compound(context, expr, bindings)? {
+            atom @ (Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_), _) => Ok(atom),
+            atom @ (Expr::Abs(_), _) => Ok(atom),
+            (value, ty) => {
+                let name = self.fresh.name("x");
+                bindings.push((name.clone(), ty.clone(), value));
+
+                Ok((var(&name, expr.range()), ty))
+            }
+        }
+    }
+}
+
+/// Converts a closed, well-typed term to A-normal form, keeping its type.
+pub fn transform(expr: &Expr) -> Result<Expr, TypeError> {
+    checker::type_of(expr)?;
+
+    Ok(Anf { fresh: Fresh::new(expr) }.normal(&mut HashMap::new(), expr)?.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::assert_agrees;
+    use super::transform;
+    use crate::parser::parsetree::{Abs, App, BinOp, Expr, Fix, If};
+    use crate::reduction::Norm;
+    use crate::{parser, prelude};
+
+    fn is_atom(expr: &Expr) -> bool {
+        match expr {
+            Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => true,
+            Expr::Abs(Abs { body, .. }) => is_normal(body),
+            _ => false,
+        }
+    }
+
+    fn is_compound(expr: &Expr) -> bool {
+        match expr {
+            Expr::App(App { lambda, argm, .. }) => is_atom(lambda) && is_atom(argm),
+            Expr::If(If { cond, then_branch, else_branch, .. }) => {
+                is_atom(cond) && is_normal(then_branch) && is_normal(else_branch)
+            }
+            Expr::BinOp(BinOp { left, right, .. }) => is_atom(left) && is_atom(right),
+            Expr::Fix(Fix { value, .. }) => is_atom(value),
+            _ => is_atom(expr),
+        }
+    }
+
+    fn is_normal(expr: &Expr) -> bool {
+        match expr {
+            Expr::App(App { lambda, argm, .. }) => match &**lambda {
+                Expr::Abs(Abs { body, .. }) => is_normal(body) && is_compound(argm),
+                _ => is_compound(expr),
+            },
+            _ => is_compound(expr),
+        }
+    }
+
+    #[test]
+    fn test_transform_names() {
+        let expr = parser::from_string(r"λf: int -> int. f (f 1 + 2) * 3").unwrap();
+
+        assert_eq!(
+            transform(&expr).unwrap().to_string(),
+            "(λf: int -> int. ((λx0: int. ((λx1: int. ((λx2: int. (x2 * 3)) (f x1))) \
+             (x0 + 2))) (f 1)))"
+        )
+    }
+
+    #[test]
+    fn test_transform_agrees() {
+        let inputs = [
+            r"(λb: bool. if b then 1 else 2) (1 + 1 == 2)",
+            r"(λx: int. if x * 2 + 1 < 10 - 2 then x / 2 else 0) 3",
+            r"(λp: int -> int -> int. p (p 1 2) 3) (λx: int. λy: int. x * y - 1)",
+            r"fact 3 + pow 2 (pred 3)",
+            r"compose succ (twice succ) (toint (cmul (csucc (csucc zero)) (cadd zero zero)))",
+            r"fix (λfib: int -> int. λn: int. if n < 2 then n else fib (n - 1) + fib (n - 2)) 6",
+            r"and (iszero (pred 1)) (not false)",
+        ];
+
+        for input in inputs {
+            let expr = prelude::load(input).unwrap();
+            let anf = transform(&expr).unwrap();
+
+            assert!(is_normal(&anf), "{}", anf);
+            assert_agrees(&expr, &anf, &[Norm::NOR, Norm::CBN, Norm::CBV]);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Closure conversion, which makes every abstraction closed so that its code can be compiled
+//! apart from the values it captures. The calculus has no records, so the environment of a
+//! closure is passed to its code first: `λx: A. e` whose free variables are `y: B` and `z: C`
+//! becomes the code `λy: B. λz: C. λx: A. e` applied to `y` and `z`, which keeps the type
+//! `A -> T` of the abstraction. A recursive function `fix (λf: T. e)` keeps its `fix` at the end
+//! of the parameters of its code, so the codes are the chains of abstractions that only go on
+//! through their bodies and the abstraction of a `fix`.
+
+use super::{abs, app, under, var};
+use crate::checker::{self, error::TypeError, TypeContext};
+use crate::parser::location::Range;
+use crate::parser::parsetree::{Abs, App, BinOp, Expr, Fix, If};
+use crate::reduction::variables::free_variables;
+use std::collections::HashMap;
+
+// The closure of a code over the free variables of `expr`: the code takes them as parameters
+// and is applied to them.
+fn closure(
+    context: &TypeContext,
+    expr: &Expr,
+    code: Expr,
+    range: Range,
+) -> Result<Expr, TypeError> {
+    let mut free: Vec<String> = free_variables(expr.clone()).into_iter().collect();
+    free.sort();
+
+    let code = free.iter().rev().try_fold(code, |body, name| match context.get(name) {
+        Some(ty) => Ok(abs(name, ty.clone(), body, range)),
+        None => Err(TypeError::UndefinedVariable(name.clone())),
+    })?;
+
+    Ok(free.iter().fold(code, |lambda, name| app(lambda, var(name, range), range)))
+}
+
+fn convert(context: &mut TypeContext, expr: &Expr) -> Result<Expr, TypeError> {
+    match expr {
+        Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => Ok(expr.clone()),
+        Expr::Abs(Abs { param, param_ty, body, range }) => {
+            let body = under(context, param, param_ty, |context| convert(context, body))?;
+
+            closure(context, expr, abs(param, param_ty.clone(), body, *range), *range)
+        }
+        Expr::Fix(Fix { value, range }) => match &**value {
+            Expr::Abs(Abs { param, param_ty, body, range: inner }) => {
+                let body = under(context, param, param_ty, |context| convert(context, body))?;
+                let value = Box::new(abs(param, param_ty.clone(), body, *inner));
+
+                closure(context, expr, Expr::Fix(Fix { value, range: *range }), *range)
+            }
+            value => {
+                Ok(Expr::Fix(Fix { value: Box::new(convert(context, value)?), range: *range }))
+            }
+        },
+        Expr::App(App { lambda, argm, range }) => {
+            Ok(app(convert(context, lambda)?, convert(context, argm)?, *range))
+        }
+        Expr::If(If { cond, then_branch, else_branch, range }) => Ok(Expr::If(If {
+            cond: Box::new(convert(context, cond)?),
+            then_branch: Box::new(convert(context, then_branch)?),
+            else_branch: Box::new(convert(context, else_branch)?),
+            range: *range,
+        })),
+        Expr::BinOp(BinOp { op, left, right, range }) => Ok(Expr::BinOp(BinOp {
+            op: *op,
+            left: Box::new(convert(context, left)?),
+            right: Box::new(convert(context, right)?),
+            range: *range,
+        })),
+    }
+}
+
+/// Converts the abstractions of a closed, well-typed term to closures, keeping its type.
+pub fn transform(expr: &Expr) -> Result<Expr, TypeError> {
+    checker::type_of(expr)?;
+
+    convert(&mut HashMap::new(), expr)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::assert_agrees;
+    use super::transform;
+    use crate::parser::parsetree::{Abs, App, BinOp, Expr, Fix, If};
+    use crate::reduction::variables::free_variables;
+    use crate::reduction::Norm;
+    use crate::{parser, prelude};
+
+    // Whether the codes of a term are closed.
+    fn is_closed(expr: &Expr) -> bool {
+        match expr {
+            Expr::Abs(_) => free_variables(expr.clone()).is_empty() && is_closed_code(expr),
+            Expr::Fix(Fix { value, .. }) if matches!(**value, Expr::Abs(_)) => {
+                free_variables(expr.clone()).is_empty() && is_closed_code(expr)
+            }
+            _ => children(expr).into_iter().all(is_closed),
+        }
+    }
+
+    fn is_closed_code(expr: &Expr) -> bool {
+        match expr {
+            Expr::Abs(Abs { body, .. }) => is_closed_code(body),
+            Expr::Fix(Fix { value, .. }) => is_closed_code(value),
+            _ => is_closed(expr),
+        }
+    }
+
+    fn children(expr: &Expr) -> Vec<&Expr> {
+        match expr {
+            Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => vec![],
+            Expr::Abs(Abs { body, .. }) => vec![body],
+            Expr::App(App { lambda, argm, .. }) => vec![lambda, argm],
+            Expr::If(If { cond, then_branch, else_branch, .. }) => {
+                vec![cond, then_branch, else_branch]
+            }
+            Expr::BinOp(BinOp { left, right, .. }) => vec![left, right],
+            Expr::Fix(Fix { value, .. }) => vec![value],
+        }
+    }
+
+    #[test]
+    fn test_transform_closures() {
+        let expr = parser::from_string(r"λx: int. λf: int -> int. λy: int. f (x + y)").unwrap();
+
+        assert_eq!(
+            transform(&expr).unwrap().to_string(),
+            "(λx: int. ((λx: int. (λf: int -> int. (((λf: int -> int. (λx: int. (λy: int. \
+             (f (x + y))))) f) x))) x))"
+        )
+    }
+
+    #[test]
+    fn test_transform_agrees() {
+        let inputs = [
+            r"(λx: int. (λy: int. x * y) (x + 1)) 3",
+            r"(λp: int -> int -> int. p (p 1 2) 3) (λx: int. λy: int. x * y - 1)",
+            r"(λk: int. fix (λf: int -> int. λn: int. if n < 1 then k else f (n - 1) + k)) 2 3",
+            r"fact 3 + pow 2 (pred 3)",
+            r"compose succ (twice succ) (toint (cmul (csucc (csucc zero)) (cadd zero zero)))",
+            r"and (iszero (pred 1)) (not false)",
+        ];
+
+        for input in inputs {
+            let expr = prelude::load(input).unwrap();
+            let closed = transform(&expr).unwrap();
+
+            assert!(is_closed(&closed), "{}", closed);
+            assert_agrees(&expr, &closed, &[Norm::NOR, Norm::CBN, Norm::CBV]);
+        }
+    }
+}
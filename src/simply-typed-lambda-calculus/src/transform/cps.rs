@@ -0,0 +1,342 @@
+//! The call-by-value continuation-passing style translation. For an answer type `R`, a term of
+//! type `A` becomes a function of its continuation, of type `(⟦A⟧ -> R) -> R`, where the base
+//! types stay as they are and a function type `A -> B` becomes `⟦A⟧ -> (⟦B⟧ -> R) -> R`. Every
+//! call is then a tail call and the order of evaluation is explicit in the term, so it evaluates
+//! to the same answer whatever the strategy. The administrative redexes are left in place.
+
+use super::{abs, app, arrow, parts, under, var, Fresh};
+use crate::checker::{self, error::TypeError, operator_type, TypeContext};
+use crate::parser::parsetree::{
+    Abs, App, Arrow, BinOp, Bool, Expr, Fix, If, Int, TBool, TInt, TUnit, Type, Unit, Var,
+};
+use std::collections::HashMap;
+
+/// The translation `⟦A⟧` of a type for the answer type `R`.
+pub fn translate_type(ty: &Type, answer: &Type) -> Type {
+    match ty {
+        Type::Arrow(Arrow { left, right }) => {
+            let continuation = arrow(translate_type(right, answer), answer.clone());
+            let computation = arrow(continuation, answer.clone());
+
+            arrow(translate_type(left, answer), computation)
+        }
+        _ => ty.clone(),
+    }
+}
+
+// The rest of the translation, built once the type of the value it receives is known.
+type Later<'a> = Box<dyn FnOnce(&mut Cps, &mut TypeContext, &Type) -> Result<Expr, TypeError> + 'a>;
+
+// The continuation of a term: a term already, or one that needs the type of the term first.
+enum Continuation<'a> {
+    Term(Expr),
+    Later(Later<'a>),
+}
+
+impl Continuation<'_> {
+    fn resolve(
+        self,
+        cps: &mut Cps,
+        context: &mut TypeContext,
+        ty: &Type,
+    ) -> Result<Expr, TypeError> {
+        match self {
+            Continuation::Term(k) => Ok(k),
+            Continuation::Later(k) => k(cps, context, ty),
+        }
+    }
+}
+
+struct Cps {
+    answer: Type,
+    fresh: Fresh,
+}
+
+impl Cps {
+    fn translate(&self, ty: &Type) -> Type {
+        translate_type(ty, &self.answer)
+    }
+
+    // The type `⟦A⟧ -> R` of the continuations of the terms of type `A`.
+    fn continuation(&self, ty: &Type) -> Type {
+        arrow(self.translate(ty), self.answer.clone())
+    }
+
+    // A value translates to a value, the body of an abstraction taking its continuation.
+    fn value(
+        &mut self,
+        context: &mut TypeContext,
+        expr: &Expr,
+    ) -> Result<(Expr, Type), TypeError> {
+        match expr {
+            Expr::Int(Int { .. }) => Ok((expr.clone(), Type::TInt(TInt {}))),
+            Expr::Bool(Bool { .. }) => Ok((expr.clone(), Type::TBool(TBool {}))),
+            Expr::Unit(Unit { .. }) => Ok((expr.clone(), Type::TUnit(TUnit {}))),
+            Expr::Var(Var { value, .. }) => match context.get(value) {
+                Some(ty) => Ok((expr.clone(), ty.clone())),
+                None => Err(TypeError::UndefinedVariable(value.clone())),
+            },
+            Expr::Abs(Abs { param, param_ty, body, range }) => {
+                let k = self.fresh.name("k");
+                let (body, body_ty) = under(context, param, param_ty, |context| {
+                    self.term(context, body, Continuation::Term(var(&k, *range)))
+                })?;
+                let body = abs(&k, self.continuation(&body_ty), body, *range);
+                let ty = arrow(param_ty.clone(), body_ty);
+
+                Ok((abs(param, self.translate(param_ty), body, *range), ty))
+            }
+            _ => unreachable!("not a value: {}", expr),
+        }
+    }
+
+    // The translation of a term passing its value to the continuation `k`, of type `R`, and the
+    // type of the term. The types are built from the ones of the parts, which a continuation
+    // receives as soon as they are known, so no subterm is checked twice.
+    fn term(
+        &mut self,
+        context: &mut TypeContext,
+        expr: &Expr,
+        k: Continuation,
+    ) -> Result<(Expr, Type), TypeError> {
+        let range = expr.range();
+
+        match expr {
+            Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) | Expr::Abs(_) => {
+                let (value, ty) = self.value(context, expr)?;
+                let k = k.resolve(self, context, &ty)?;
+
+                Ok((app(k, value, range), ty))
+            }
+            Expr::App(App { lambda, argm, .. }) => {
+                let call = move |cps: &mut Cps, context: &mut TypeContext, lambda_ty: &Type| {
+                    let (left, right) = parts(lambda_ty.clone())?;
+                    let k = k.resolve(cps, context, &right)?;
+                    let (f, v) = (cps.fresh.name("f"), cps.fresh.name("v"));
+
+                    let call = app(app(var(&f, range), var(&v, range), range), k, range);
+                    let call = abs(&v, cps.translate(&left), call, range);
+                    let (call, _) = cps.term(context, argm, Continuation::Term(call))?;
+
+                    Ok(abs(&f, cps.translate(lambda_ty), call, range))
+                };
+                let (body, lambda_ty) =
+                    self.term(context, lambda, Continuation::Later(Box::new(call)))?;
+
+                Ok((body, parts(lambda_ty)?.1))
+            }
+            Expr::If(If { cond, then_branch, else_branch, .. }) => {
+                // Both branches continue with `k`, so it is named first unless it is a name.
+                let (name, bound) = match k {
+                    Continuation::Term(k @ Expr::Var(_)) => (k, None),
+                    k => {
+                        let name = self.fresh.name("k");
+                        (var(&name, range), Some((name, k)))
+                    }
+                };
+
+                let b = self.fresh.name("b");
+                let (then_branch, ty) =
+                    self.term(context, then_branch, Continuation::Term(name.clone()))?;
+                let (else_branch, _) = self.term(context, else_branch, Continuation::Term(name))?;
+                let branches = Expr::If(If {
+                    cond: Box::new(var(&b, range)),
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(else_branch),
+                    range,
+                });
+                let branches = abs(&b, Type::TBool(TBool {}), branches, range);
+                let (body, _) = self.term(context, cond, Continuation::Term(branches))?;
+
+                let body = match bound {
+                    None => body,
+                    Some((name, k)) => {
+                        let k = k.resolve(self, context, &ty)?;
+                        app(abs(&name, self.continuation(&ty), body, range), k, range)
+                    }
+                };
+
+                Ok((body, ty))
+            }
+            Expr::BinOp(BinOp { op, left, right, .. }) => {
+                let ty = operator_type(*op);
+                let k = k.resolve(self, context, &ty)?;
+                let (x, y) = (self.fresh.name("v"), self.fresh.name("v"));
+
+                let result = Expr::BinOp(BinOp {
+                    op: *op,
+                    left: Box::new(var(&x, range)),
+                    right: Box::new(var(&y, range)),
+                    range,
+                });
+                let result = abs(&y, Type::TInt(TInt {}), app(k, result, range), range);
+                let (right, _) = self.term(context, right, Continuation::Term(result))?;
+                let right = abs(&x, Type::TInt(TInt {}), right, range);
+                let (body, _) = self.term(context, left, Continuation::Term(right))?;
+
+                Ok((body, ty))
+            }
+            Expr::Fix(Fix { value, .. }) => {
+                let fixed = move |cps: &mut Cps, context: &mut TypeContext, value_ty: &Type| {
+                    let (ty, _) = parts(value_ty.clone())?;
+                    let f = cps.fresh.name("f");
+
+                    let fixed = match &ty {
+                        // The fixed point `g` of a function type is `λx. λk. f g (λh. h x k)`,
+                        // which only runs `f` once it is called.
+                        Type::Arrow(Arrow { left, right }) => {
+                            let k = k.resolve(cps, context, &ty)?;
+                            let g = cps.fresh.name("g");
+                            let (v, k0, h) =
+                                (cps.fresh.name("v"), cps.fresh.name("k"), cps.fresh.name("h"));
+
+                            let call = app(var(&h, range), var(&v, range), range);
+                            let call = app(call, var(&k0, range), range);
+                            let unfold = app(
+                                app(var(&f, range), var(&g, range), range),
+                                abs(&h, cps.translate(&ty), call, range),
+                                range,
+                            );
+                            let body = abs(&k0, cps.continuation(right), unfold, range);
+                            let body = abs(&v, cps.translate(left), body, range);
+                            let fixed = Expr::Fix(Fix {
+                                value: Box::new(abs(&g, cps.translate(&ty), body, range)),
+                                range,
+                            });
+
+                            app(k, fixed, range)
+                        }
+                        // A fixed point of a base type has no value to pass on, since computing
+                        // it needs it first, so the answer is the fixed point of the identity
+                        // and `k` is never called.
+                        _ => {
+                            let r = cps.fresh.name("r");
+                            let value = abs(&r, cps.answer.clone(), var(&r, range), range);
+
+                            Expr::Fix(Fix { value: Box::new(value), range })
+                        }
+                    };
+
+                    Ok(abs(&f, cps.translate(value_ty), fixed, range))
+                };
+                let (body, value_ty) =
+                    self.term(context, value, Continuation::Later(Box::new(fixed)))?;
+
+                Ok((body, parts(value_ty)?.0))
+            }
+        }
+    }
+}
+
+/// Translates a closed, well-typed term of type `A` to a term of type `(⟦A⟧ -> R) -> R`, for
+/// the answer type `R`.
+pub fn transform(expr: &Expr, answer: &Type) -> Result<Expr, TypeError> {
+    let ty = checker::type_of(expr)?;
+    let mut cps = Cps { answer: answer.clone(), fresh: Fresh::new(expr) };
+
+    let k = cps.fresh.name("k");
+    let continuation = Continuation::Term(var(&k, expr.range()));
+    let (body, _) = cps.term(&mut HashMap::new(), expr, continuation)?;
+
+    Ok(abs(&k, cps.continuation(&ty), body, expr.range()))
+}
+
+/// Translates a closed program of a base type, which is its own answer type, and runs it with
+/// the identity as its continuation, so that it has the same type and value as the program.
+pub fn program(expr: &Expr) -> Result<Expr, TypeError> {
+    let ty = checker::type_of(expr)?;
+
+    if let Type::Arrow(_) = ty {
+        return Err(TypeError::UnexpectedType(ty));
+    }
+
+    let mut cps = Cps { answer: ty.clone(), fresh: Fresh::new(expr) };
+    let v = cps.fresh.name("v");
+    let identity = abs(&v, ty, var(&v, expr.range()), expr.range());
+
+    Ok(cps.term(&mut HashMap::new(), expr, Continuation::Term(identity))?.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::arrow;
+    use super::super::test::assert_agrees;
+    use super::{program, transform, translate_type};
+    use crate::checker;
+    use crate::parser::parsetree::{TBool, TInt, Type};
+    use crate::reduction::Norm;
+    use crate::{parser, prelude};
+
+    #[test]
+    fn test_translate_type() {
+        let expr = parser::from_string(r"λf: int -> bool. λx: int. f x").unwrap();
+        let ty = checker::type_of(&expr).unwrap();
+
+        assert_eq!(
+            translate_type(&ty, &Type::TInt(TInt {})).to_string(),
+            "(int -> (bool -> int) -> int) -> ((int -> (bool -> int) -> int) -> int) -> int"
+        )
+    }
+
+    #[test]
+    fn test_transform_types() {
+        let inputs = [
+            r"λf: int -> bool. λx: int. if f x then x else 0",
+            r"(λx: int. λy: int. x * y) 3",
+            r"fix (λf: int -> int. λn: int. if n < 1 then 0 else f (n - 1))",
+            r"λb: bool. (λu: unit. b) ()",
+        ];
+        let answer = Type::TBool(TBool {});
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            let ty = checker::type_of(&expr).unwrap();
+            let cps = checker::type_of(&transform(&expr, &answer).unwrap()).unwrap();
+
+            let continuation = arrow(translate_type(&ty, &answer), answer.clone());
+            assert_eq!(cps, arrow(continuation, answer.clone()), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_program_agrees() {
+        let inputs = [
+            r"(λb: bool. if b then 1 else 2) false",
+            r"(λx: int. if x * 2 + 1 < 10 - 2 then x / 2 else 0) 3",
+            r"(λp: int -> int -> int. p (p 1 2) 3) (λx: int. λy: int. x * y - 1)",
+            r"(λf: int -> int. f (f 2) == 4) (λn: int. if n < 1 then 0 else n - 1)",
+            r"fix (λfib: int -> int. λn: int. if n < 2 then n else fib (n - 1) + fib (n - 2)) 5",
+            r"
+            let rec pow: int -> int -> int =
+                λb: int. λe: int. if e < 1 then 1 else b * pow b (e - 1) in
+            pow 2 3
+            ",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            assert_agrees(&expr, &program(&expr).unwrap(), &[Norm::NOR, Norm::CBN, Norm::CBV])
+        }
+    }
+
+    #[test]
+    fn test_program_prelude() {
+        let inputs = [
+            r"fact 3 + pow 2 2",
+            r"compose succ (twice succ) (toint (cmul (csucc (csucc zero)) (cadd zero zero)))",
+            r"and (iszero (pred 1)) (not false)",
+        ];
+
+        for input in inputs {
+            let expr = prelude::load(input).unwrap();
+            assert_agrees(&expr, &program(&expr).unwrap(), &[Norm::NOR, Norm::CBN, Norm::CBV])
+        }
+    }
+
+    #[test]
+    fn test_program_of_function() {
+        let expr = parser::from_string(r"λx: int. x").unwrap();
+
+        assert!(program(&expr).is_err())
+    }
+}
@@ -0,0 +1,212 @@
+//! Lambda lifting to top-level functions. After closure conversion every code is closed, so it
+//! is moved out of the term to a function of the program and the term refers to it by name.
+//! The codes inside a code are lifted first, so a function only refers to the ones before it.
+
+use super::{abs, app, closure, var, Fresh};
+use crate::checker::{error::TypeError, infer_type, TypeContext};
+use crate::parser::parsetree::{Abs, App, BinOp, Expr, Fix, If, Type};
+use std::fmt;
+
+/// A top-level function, named and typed like a `def`.
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub ty: Type,
+    pub body: Expr,
+}
+
+/// The top-level functions of a program, each in the scope of the ones before it, and the body
+/// of the program in the scope of all of them.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub functions: Vec<Function>,
+    pub main: Expr,
+}
+
+impl Program {
+    /// The program as a term, binding its functions in turn.
+    pub fn to_expr(&self) -> Expr {
+        self.functions.iter().rev().fold(self.main.clone(), |main, function| {
+            let range = function.body.range();
+            app(abs(&function.name, function.ty.clone(), main, range), function.body.clone(), range)
+        })
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for Function { name, ty, body } in &self.functions {
+            writeln!(f, "def {}: {} = {}", name, ty, body)?;
+        }
+
+        write!(f, "main = {}", self.main)
+    }
+}
+
+struct Lift {
+    fresh: Fresh,
+    // The types of the functions lifted so far.
+    context: TypeContext,
+    functions: Vec<Function>,
+}
+
+impl Lift {
+    fn lift(&mut self, expr: &Expr) -> Result<Expr, TypeError> {
+        match expr {
+            Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => Ok(expr.clone()),
+            Expr::Abs(_) => self.function(expr),
+            Expr::Fix(Fix { value, .. }) if matches!(**value, Expr::Abs(_)) => {
+                self.function(expr)
+            }
+            Expr::Fix(Fix { value, range }) => {
+                Ok(Expr::Fix(Fix { value: Box::new(self.lift(value)?), range: *range }))
+            }
+            Expr::App(App { lambda, argm, range }) => {
+                Ok(app(self.lift(lambda)?, self.lift(argm)?, *range))
+            }
+            Expr::If(If { cond, then_branch, else_branch, range }) => Ok(Expr::If(If {
+                cond: Box::new(self.lift(cond)?),
+                then_branch: Box::new(self.lift(then_branch)?),
+                else_branch: Box::new(self.lift(else_branch)?),
+                range: *range,
+            })),
+            Expr::BinOp(BinOp { op, left, right, range }) => Ok(Expr::BinOp(BinOp {
+                op: *op,
+                left: Box::new(self.lift(left)?),
+                right: Box::new(self.lift(right)?),
+                range: *range,
+            })),
+        }
+    }
+
+    // Lifts a code to a function, after the codes in its body.
+    fn function(&mut self, code: &Expr) -> Result<Expr, TypeError> {
+        let body = self.code(code)?;
+        let ty = infer_type(&mut self.context.clone(), &body)?;
+        let name = self.fresh.name("code");
+
+        self.context.insert(name.clone(), ty.clone());
+        self.functions.push(Function { name: name.clone(), ty, body });
+
+        Ok(var(&name, code.range()))
+    }
+
+    fn code(&mut self, code: &Expr) -> Result<Expr, TypeError> {
+        match code {
+            Expr::Abs(Abs { param, param_ty, body, range }) => {
+                Ok(abs(param, param_ty.clone(), self.code(body)?, *range))
+            }
+            Expr::Fix(Fix { value, range }) if matches!(**value, Expr::Abs(_)) => {
+                Ok(Expr::Fix(Fix { value: Box::new(self.code(value)?), range: *range }))
+            }
+            body => self.lift(body),
+        }
+    }
+}
+
+/// Converts a closed, well-typed term to closures and lifts their codes to top-level functions.
+pub fn transform(expr: &Expr) -> Result<Program, TypeError> {
+    let converted = closure::transform(expr)?;
+    let mut lift = Lift {
+        fresh: Fresh::new(&converted),
+        context: TypeContext::new(),
+        functions: Vec::new(),
+    };
+    let main = lift.lift(&converted)?;
+
+    Ok(Program { functions: lift.functions, main })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::assert_agrees;
+    use super::{transform, Function};
+    use crate::parser::parsetree::{Abs, App, BinOp, Expr, Fix, If};
+    use crate::reduction::variables::free_variables;
+    use crate::reduction::Norm;
+    use crate::{parser, prelude};
+
+    // Whether a term has no abstraction left outside of the parameters of a code.
+    fn is_flat(expr: &Expr) -> bool {
+        match expr {
+            Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => true,
+            Expr::Abs(_) => false,
+            Expr::App(App { lambda, argm, .. }) => is_flat(lambda) && is_flat(argm),
+            Expr::If(If { cond, then_branch, else_branch, .. }) => {
+                is_flat(cond) && is_flat(then_branch) && is_flat(else_branch)
+            }
+            Expr::BinOp(BinOp { left, right, .. }) => is_flat(left) && is_flat(right),
+            Expr::Fix(Fix { value, .. }) => is_flat(value),
+        }
+    }
+
+    fn is_flat_code(expr: &Expr) -> bool {
+        match expr {
+            Expr::Abs(Abs { body, .. }) => is_flat_code(body),
+            Expr::Fix(Fix { value, .. }) => is_flat_code(value),
+            _ => is_flat(expr),
+        }
+    }
+
+    #[test]
+    fn test_transform_program() {
+        let expr = parser::from_string(r"(λx: int. λy: int. x + y) 1 2").unwrap();
+
+        assert_eq!(
+            transform(&expr).unwrap().to_string(),
+            "def code0: int -> int -> int = (λx: int. (λy: int. (x + y)))\n\
+             def code1: int -> int -> int = (λx: int. (code0 x))\n\
+             main = ((code1 1) 2)"
+        )
+    }
+
+    fn assert_lifts(strategies: &[Norm], expr: Expr) {
+        let program = transform(&expr).unwrap();
+
+        let mut scope = Vec::new();
+        for Function { name, body, .. } in &program.functions {
+            assert!(is_flat_code(body), "{}", body);
+            assert!(free_variables(body.clone()).iter().all(|free| scope.contains(free)));
+            scope.push(name.clone());
+        }
+        assert!(is_flat(&program.main), "{}", program.main);
+
+        // The program reads back as the same definitions.
+        let lifted = parser::from_string(&program.to_string()).unwrap();
+        assert_eq!(lifted.to_string(), program.to_expr().to_string());
+
+        assert_agrees(&expr, &lifted, strategies);
+    }
+
+    #[test]
+    fn test_transform_agrees() {
+        let inputs = [
+            r"(λx: int. (λy: int. x * y) (x + 1)) 3",
+            r"(λp: int -> int -> int. p (p 1 2) 3) (λx: int. λy: int. x * y - 1)",
+            r"(λk: int. fix (λf: int -> int. λn: int. if n < 1 then k else f (n - 1) + k)) 2 3",
+            r"
+            let rec pow: int -> int -> int =
+                λb: int. λe: int. if e < 1 then 1 else b * pow b (e - 1) in
+            let twice: (int -> int) -> int -> int = λf: int -> int. λx: int. f (f x) in
+            twice (pow 2) 2
+            ",
+        ];
+
+        for input in inputs {
+            assert_lifts(&[Norm::NOR, Norm::CBN, Norm::CBV], parser::from_string(input).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_transform_prelude() {
+        let inputs = [
+            r"fact 3 + pow 2 (pred 3)",
+            r"compose succ (twice succ) (toint (cmul (csucc (csucc zero)) (cadd zero zero)))",
+            r"and (iszero (pred 1)) (not false)",
+        ];
+
+        for input in inputs {
+            assert_lifts(&[Norm::NOR, Norm::CBN, Norm::CBV], prelude::load(input).unwrap())
+        }
+    }
+}
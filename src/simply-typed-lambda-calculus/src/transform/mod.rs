@@ -0,0 +1,135 @@
+//! Passes that bring a typed program closer to the virtual machine: the continuation-passing
+//! style and the A-normal form spell out the order of evaluation, and closure conversion and
+//! lambda lifting take the code of each function out of the term. Each pass checks the term it
+//! is given and keeps its type, and the names it adds are fresh for the whole term, so they
+//! never capture nor get captured.
+
+pub mod anf;
+pub mod closure;
+pub mod cps;
+pub mod lift;
+
+use crate::checker::{error::TypeError, TypeContext};
+use crate::parser::location::Range;
+use crate::parser::parsetree::{Abs, App, Arrow, BinOp, Expr, Fix, If, Type, Var};
+use std::collections::HashSet;
+
+// Every name a term mentions, bound or free.
+fn names(expr: &Expr, names: &mut HashSet<String>) {
+    match expr {
+        Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) => (),
+        Expr::Var(Var { value, .. }) => {
+            names.insert(value.clone());
+        }
+        Expr::Abs(Abs { param, body, .. }) => {
+            names.insert(param.clone());
+            self::names(body, names);
+        }
+        Expr::App(App { lambda, argm, .. }) => {
+            self::names(lambda, names);
+            self::names(argm, names);
+        }
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            self::names(cond, names);
+            self::names(then_branch, names);
+            self::names(else_branch, names);
+        }
+        Expr::BinOp(BinOp { left, right, .. }) => {
+            self::names(left, names);
+            self::names(right, names);
+        }
+        Expr::Fix(Fix { value, .. }) => self::names(value, names),
+    }
+}
+
+/// A supply of names that are fresh for a term: a base followed by a number.
+pub(crate) struct Fresh {
+    used: HashSet<String>,
+    next: usize,
+}
+
+impl Fresh {
+    pub(crate) fn new(expr: &Expr) -> Fresh {
+        let mut used = HashSet::new();
+        names(expr, &mut used);
+
+        Fresh { used, next: 0 }
+    }
+
+    pub(crate) fn name(&mut self, base: &str) -> String {
+        loop {
+            let name = format!("{}{}", base, self.next);
+            self.next += 1;
+
+            if self.used.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+}
+
+/// Runs `f` in the context extended with the parameter of an abstraction, restoring the
+/// binding it shadows afterwards.
+pub(crate) fn under<T>(
+    context: &mut TypeContext,
+    param: &str,
+    param_ty: &Type,
+    f: impl FnOnce(&mut TypeContext) -> T,
+) -> T {
+    let shadowed = context.insert(param.to_string(), param_ty.clone());
+    let result = f(context);
+
+    match shadowed {
+        Some(ty) => context.insert(param.to_string(), ty),
+        None => context.remove(param),
+    };
+
+    result
+}
+
+pub(crate) fn var(name: &str, range: Range) -> Expr {
+    Expr::Var(Var { value: name.to_string(), range })
+}
+
+pub(crate) fn abs(param: &str, param_ty: Type, body: Expr, range: Range) -> Expr {
+    Expr::Abs(Abs { param: param.to_string(), param_ty, body: Box::new(body), range })
+}
+
+pub(crate) fn app(lambda: Expr, argm: Expr, range: Range) -> Expr {
+    Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range })
+}
+
+pub(crate) fn arrow(left: Type, right: Type) -> Type {
+    Type::Arrow(Arrow { left: Box::new(left), right: Box::new(right) })
+}
+
+/// The parameter and result types of a function type.
+pub(crate) fn parts(ty: Type) -> Result<(Type, Type), TypeError> {
+    match ty {
+        Type::Arrow(Arrow { left, right }) => Ok((*left, *right)),
+        ty => Err(TypeError::UnexpectedType(ty)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parsetree::Expr;
+    use crate::reduction::{reduce, Norm};
+    use crate::{bytecode, checker};
+
+    // Asserts that a pass kept the type of `expr` and that what it gave evaluates to the normal
+    // form of `expr` under each of the strategies and on the virtual machine.
+    pub(super) fn assert_agrees(expr: &Expr, transformed: &Expr, strategies: &[Norm]) {
+        assert_eq!(checker::type_of(transformed).unwrap(), checker::type_of(expr).unwrap());
+
+        let expected = reduce(Norm::NOR, expr.clone(), None).to_string();
+        for strategy in strategies {
+            let value = reduce(*strategy, transformed.clone(), Some(10_000));
+            assert_eq!(value.to_string(), expected, "{} {:?}", expr, strategy);
+        }
+
+        let range = transformed.range();
+        let value = bytecode::run(transformed, None).unwrap().to_expr(range).unwrap();
+        assert_eq!(value.to_string(), expected, "{}", expr);
+    }
+}
@@ -23,6 +23,8 @@ pub struct Context {
     exprs: HashMap<Symbol, ContextExpr>,
     types: HashMap<Symbol, ContextType>,
     kinds: HashMap<Symbol, Kind>,
+    kind_vars: HashMap<Symbol, T::Kind>,
+    metas: Vec<Option<T::Kind>>,
 }
 
 impl Context {
@@ -31,9 +33,19 @@ impl Context {
             exprs: HashMap::new(),
             types: HashMap::new(),
             kinds: HashMap::new(),
+            kind_vars: HashMap::new(),
+            metas: Vec::new(),
         }
     }
 
+    // Brings the declarations of `other` into scope. Both are top-level contexts of resolved
+    // programs, whose names never collide.
+    pub fn extend(&mut self, other: &Context) {
+        self.exprs.extend(other.exprs.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.types.extend(other.types.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.kinds.extend(other.kinds.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+
     pub fn get_expr(&self, key: &Symbol) -> Result<ContextExpr, TypeError> {
         match self.exprs.get(key) {
             Some(expr) => Ok(expr.clone()),
@@ -74,6 +86,47 @@ impl Context {
             None => Err(TypeError::UndefinedVariable(key.to_string())),
         }
     }
+
+    /// Binds the kind variable `key`, returning its previous binding so that
+    /// it can be restored with `restore_kind_var` at the end of its scope.
+    pub fn add_kind_var(&mut self, key: &Symbol, kind: T::Kind) -> Option<T::Kind> {
+        self.kind_vars.insert(key.clone(), kind)
+    }
+
+    pub fn restore_kind_var(&mut self, key: &Symbol, previous: Option<T::Kind>) {
+        match previous {
+            Some(kind) => self.kind_vars.insert(key.clone(), kind),
+            None => self.kind_vars.remove(key),
+        };
+    }
+
+    pub fn get_kind_var(&self, key: &Symbol) -> Option<T::Kind> {
+        self.kind_vars.get(key).cloned()
+    }
+
+    pub fn fresh_kind(&mut self) -> T::Kind {
+        self.metas.push(None);
+        T::Kind::Meta { id: self.metas.len() - 1 }
+    }
+
+    pub fn solve_kind(&mut self, id: usize, kind: T::Kind) {
+        self.metas[id] = Some(kind);
+    }
+
+    /// Replaces every solved unification variable in `kind` by its solution.
+    pub fn resolve_kind(&self, kind: &T::Kind) -> T::Kind {
+        match kind {
+            T::Kind::Meta { id } => match &self.metas[*id] {
+                Some(solution) => self.resolve_kind(solution),
+                None => kind.clone(),
+            },
+            T::Kind::KindArrow { left, right } => T::Kind::KindArrow {
+                left: Box::new(self.resolve_kind(left)),
+                right: Box::new(self.resolve_kind(right)),
+            },
+            T::Kind::Star | T::Kind::Var { .. } => kind.clone(),
+        }
+    }
 }
 
 impl Default for Context {
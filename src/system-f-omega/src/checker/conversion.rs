@@ -1,40 +1,60 @@
 use crate::checker::errors::TypeError;
 use crate::parser::parsetree::{
-    Abs, Anno, App, Arrow, Expr, Forall, Int, KindAlias, LetAlias, TAbs, TApp, TVar, TyAbs, TyAnno,
-    TyApp, Type, TypeAlias, Var,
+    Abs, Anno, App, Arrow, BinOp, Bool, Case, Exists, Expr, Fold, Forall, Fst, If, Inl, Inr, Int,
+    KindAlias, LetAlias, Pack, Pair, Product, Rec, Snd, Sum, TAbs, TApp, TVar, TyAbs, TyAnno,
+    TyApp, Type, TypeAlias, Unfold, Unit, Unpack, Var,
 };
 use crate::parser::symbol::Symbol;
 
 use std::collections::HashMap;
 
+// Terms and types live in separate namespaces, so `data Foo = Foo` can name both.
 #[derive(Debug)]
 pub struct Names {
     pub names: HashMap<String, Symbol>,
+    pub types: HashMap<String, Symbol>,
     pub count: usize,
 }
 
+impl Default for Names {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Names {
     pub fn new() -> Names {
-        Names { names: HashMap::new(), count: 0 }
+        Names { names: HashMap::new(), types: HashMap::new(), count: 0 }
     }
 
-    pub fn rename(&mut self, name: &Symbol) -> Symbol {
+    fn fresh(&mut self, name: &Symbol) -> Symbol {
         let new_count = self.count;
-        let new_ident = Symbol { id: new_count, ..name.clone() };
-
         self.count += 1;
+
+        Symbol { id: new_count, ..name.clone() }
+    }
+
+    pub fn rename(&mut self, name: &Symbol) -> Symbol {
+        let new_ident = self.fresh(name);
         self.names.insert(name.name.clone(), new_ident.clone());
 
         new_ident
     }
+
+    pub fn rename_type(&mut self, name: &Symbol) -> Symbol {
+        let new_ident = self.fresh(name);
+        self.types.insert(name.name.clone(), new_ident.clone());
+
+        new_ident
+    }
 }
 
 // I don't like de bruijn index.
 pub fn alpha_conversion_type(context: &mut Names, ty: &Type) -> Result<Type, TypeError> {
     match ty {
-        Type::TInt(_) => Ok(ty.clone()),
+        Type::TInt(_) | Type::TBool(_) | Type::TUnit(_) => Ok(ty.clone()),
         Type::TVar(TVar { value }) => {
-            if let Some(n) = context.names.get(&value.name) {
+            if let Some(n) = context.types.get(&value.name) {
                 Ok(Type::TVar(TVar { value: n.to_owned() }))
             } else {
                 Err(TypeError::UndefinedVariable(value.name.clone()))
@@ -49,9 +69,23 @@ pub fn alpha_conversion_type(context: &mut Names, ty: &Type) -> Result<Type, Typ
                 right: Box::new(right),
             }))
         }
+        Type::Product(Product { fst, snd }) => {
+            let fst = alpha_conversion_type(context, fst)?;
+            let snd = alpha_conversion_type(context, snd)?;
+
+            Ok(Type::Product(Product { fst: Box::new(fst), snd: Box::new(snd) }))
+        }
+        Type::Sum(Sum { left, right }) => {
+            let left = alpha_conversion_type(context, left)?;
+            let right = alpha_conversion_type(context, right)?;
+
+            Ok(Type::Sum(Sum { left: Box::new(left), right: Box::new(right) }))
+        }
         Type::Forall(Forall { param, param_ty, body }) => {
-            let param = context.rename(param);
+            let scope = context.types.clone();
+            let param = context.rename_type(param);
             let body = alpha_conversion_type(context, body)?;
+            context.types = scope;
 
             Ok(Type::Forall(Forall {
                 param,
@@ -59,9 +93,35 @@ pub fn alpha_conversion_type(context: &mut Names, ty: &Type) -> Result<Type, Typ
                 body: Box::new(body),
             }))
         }
+        Type::Exists(Exists { param, param_ty, body }) => {
+            let scope = context.types.clone();
+            let param = context.rename_type(param);
+            let body = alpha_conversion_type(context, body)?;
+            context.types = scope;
+
+            Ok(Type::Exists(Exists {
+                param,
+                param_ty: param_ty.clone(),
+                body: Box::new(body),
+            }))
+        }
+        Type::Rec(Rec { param, param_ty, body }) => {
+            let scope = context.types.clone();
+            let param = context.rename_type(param);
+            let body = alpha_conversion_type(context, body)?;
+            context.types = scope;
+
+            Ok(Type::Rec(Rec {
+                param,
+                param_ty: param_ty.clone(),
+                body: Box::new(body),
+            }))
+        }
         Type::TyAbs(TyAbs { param, param_ty, body }) => {
-            let param = context.rename(param);
+            let scope = context.types.clone();
+            let param = context.rename_type(param);
             let body = alpha_conversion_type(context, body)?;
+            context.types = scope;
 
             Ok(Type::TyAbs(TyAbs {
                 param,
@@ -89,12 +149,59 @@ pub fn alpha_conversion_type(context: &mut Names, ty: &Type) -> Result<Type, Typ
     }
 }
 
+// Converts a chain of aliases in a loop, so that long ones like the prelude do not add to the
+// recursion depth. Each alias is rebuilt around the converted body once it is known.
+fn alpha_conversion_aliases(context: &mut Names, mut ex: &Expr) -> Result<Expr, TypeError> {
+    let scope = (context.names.clone(), context.types.clone());
+    let mut aliases: Vec<Box<dyn FnOnce(Expr) -> Expr>> = Vec::new();
+
+    loop {
+        ex = match ex {
+            Expr::LetAlias(LetAlias { name, value, body, range }) => {
+                let name = context.rename(name);
+                let value = Box::new(alpha_conversion_expr(context, value)?);
+                let range = *range;
+
+                aliases.push(Box::new(move |body| {
+                    Expr::LetAlias(LetAlias { name, value, body: Box::new(body), range })
+                }));
+                body
+            }
+            Expr::TypeAlias(TypeAlias { name, value, body, range }) => {
+                let name = context.rename_type(name);
+                let value = alpha_conversion_type(context, value)?;
+                let range = *range;
+
+                aliases.push(Box::new(move |body| {
+                    Expr::TypeAlias(TypeAlias { name, value, body: Box::new(body), range })
+                }));
+                body
+            }
+            // Kinds are never renamed, so the alias keeps the name its uses refer to.
+            Expr::KindAlias(KindAlias { name, value, body, range }) => {
+                let (name, value, range) = (name.clone(), value.clone(), *range);
+
+                aliases.push(Box::new(move |body| {
+                    Expr::KindAlias(KindAlias { name, value, body: Box::new(body), range })
+                }));
+                body
+            }
+            _ => break,
+        }
+    }
+
+    let body = alpha_conversion_expr(context, ex)?;
+    (context.names, context.types) = scope;
+
+    Ok(aliases.into_iter().rev().fold(body, |body, alias| alias(body)))
+}
+
 pub fn alpha_conversion_expr(context: &mut Names, ex: &Expr) -> Result<Expr, TypeError> {
     match ex {
-        Expr::Int(Int { .. }) => Ok(ex.clone()),
+        Expr::Int(Int { .. }) | Expr::Bool(Bool { .. }) | Expr::Unit(Unit { .. }) => Ok(ex.clone()),
         Expr::Var(Var { value, range }) => {
             if let Some(n) = context.names.get(&value.name) {
-                Ok(Expr::Var(Var { value: n.to_owned(), range: range.clone() }))
+                Ok(Expr::Var(Var { value: n.to_owned(), range: *range }))
             } else {
                 Err(TypeError::UndefinedVariable(value.name.clone()))
             }
@@ -106,19 +213,21 @@ pub fn alpha_conversion_expr(context: &mut Names, ex: &Expr) -> Result<Expr, Typ
             Ok(Expr::App(App {
                 lambda: Box::new(lambda),
                 argm: Box::new(argm),
-                range: range.clone(),
+                range: *range,
             }))
         }
         Expr::Abs(Abs { param, body, param_ty, range }) => {
+            let scope = context.names.clone();
             let param = context.rename(param);
             let param_ty = alpha_conversion_type(context, param_ty)?;
-            let body = alpha_conversion_expr(context, &body)?;
+            let body = alpha_conversion_expr(context, body)?;
+            context.names = scope;
 
             Ok(Expr::Abs(Abs {
                 param,
                 param_ty,
                 body: Box::new(body),
-                range: range.clone(),
+                range: *range,
             }))
         }
         Expr::TApp(TApp { lambda, argm, range }) => {
@@ -128,63 +237,137 @@ pub fn alpha_conversion_expr(context: &mut Names, ex: &Expr) -> Result<Expr, Typ
             Ok(Expr::TApp(TApp {
                 lambda: Box::new(lambda),
                 argm,
-                range: range.clone(),
+                range: *range,
             }))
         }
         Expr::TAbs(TAbs { param, param_ty, body, range }) => {
-            let param = context.rename(param);
-            let body = alpha_conversion_expr(context, &body)?;
+            let scope = context.types.clone();
+            let param = context.rename_type(param);
+            let body = alpha_conversion_expr(context, body)?;
+            context.types = scope;
 
             Ok(Expr::TAbs(TAbs {
                 param,
                 param_ty: param_ty.clone(),
                 body: Box::new(body),
-                range: range.clone(),
+                range: *range,
             }))
         }
-        Expr::LetAlias(LetAlias { name, value, body, range }) => {
-            let name = context.rename(name);
-            let value = alpha_conversion_expr(context, value)?;
-            let body = alpha_conversion_expr(context, body)?;
+        Expr::LetAlias(_) | Expr::TypeAlias(_) | Expr::KindAlias(_) => {
+            alpha_conversion_aliases(context, ex)
+        }
+        Expr::Anno(Anno { expr, anno, range }) => {
+            let expr = alpha_conversion_expr(context, expr)?;
+            let anno = alpha_conversion_type(context, anno)?;
 
-            Ok(Expr::LetAlias(LetAlias {
-                name,
-                value: Box::new(value),
-                body: Box::new(body),
-                range: range.clone(),
+            Ok(Expr::Anno(Anno {
+                expr: Box::new(expr),
+                anno: anno.clone(),
+                range: *range,
             }))
         }
-        Expr::TypeAlias(TypeAlias { name, value, body, range }) => {
-            let name = context.rename(name);
-            let value = alpha_conversion_type(context, value)?;
-            let body = alpha_conversion_expr(context, body)?;
+        Expr::If(If { cond, then_branch, else_branch, range }) => {
+            let cond = alpha_conversion_expr(context, cond)?;
+            let then_branch = alpha_conversion_expr(context, then_branch)?;
+            let else_branch = alpha_conversion_expr(context, else_branch)?;
 
-            Ok(Expr::TypeAlias(TypeAlias {
-                name,
-                value: value,
-                body: Box::new(body),
-                range: range.clone(),
+            Ok(Expr::If(If {
+                cond: Box::new(cond),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                range: *range,
+            }))
+        }
+        Expr::BinOp(BinOp { op, left, right, range }) => {
+            let left = alpha_conversion_expr(context, left)?;
+            let right = alpha_conversion_expr(context, right)?;
+
+            Ok(Expr::BinOp(BinOp {
+                op: *op,
+                left: Box::new(left),
+                right: Box::new(right),
+                range: *range,
             }))
         }
-        Expr::KindAlias(KindAlias { name, value, body, range }) => {
-            let name = context.rename(name);
+        Expr::Fold(Fold { ty, value, range }) => {
+            let ty = alpha_conversion_type(context, ty)?;
+            let value = alpha_conversion_expr(context, value)?;
+
+            Ok(Expr::Fold(Fold { ty, value: Box::new(value), range: *range }))
+        }
+        Expr::Unfold(Unfold { value, range }) => {
+            let value = alpha_conversion_expr(context, value)?;
+
+            Ok(Expr::Unfold(Unfold { value: Box::new(value), range: *range }))
+        }
+        Expr::Pack(Pack { witness, value, ty, range }) => {
+            let witness = alpha_conversion_type(context, witness)?;
+            let value = alpha_conversion_expr(context, value)?;
+            let ty = alpha_conversion_type(context, ty)?;
+
+            Ok(Expr::Pack(Pack { witness, value: Box::new(value), ty, range: *range }))
+        }
+        Expr::Unpack(Unpack { package, ty_param, param, body, range }) => {
+            let package = alpha_conversion_expr(context, package)?;
+            let scope = (context.names.clone(), context.types.clone());
+            let ty_param = context.rename_type(ty_param);
+            let param = context.rename(param);
             let body = alpha_conversion_expr(context, body)?;
+            (context.names, context.types) = scope;
 
-            Ok(Expr::KindAlias(KindAlias {
-                name,
-                value: value.clone(),
+            Ok(Expr::Unpack(Unpack {
+                package: Box::new(package),
+                ty_param,
+                param,
                 body: Box::new(body),
-                range: range.clone(),
+                range: *range,
             }))
         }
-        Expr::Anno(Anno { expr, anno, range }) => {
-            let expr = alpha_conversion_expr(context, expr)?;
-            let anno = alpha_conversion_type(context, anno)?;
+        Expr::Pair(Pair { fst, snd, range }) => {
+            let fst = alpha_conversion_expr(context, fst)?;
+            let snd = alpha_conversion_expr(context, snd)?;
 
-            Ok(Expr::Anno(Anno {
-                expr: Box::new(expr),
-                anno: anno.clone(),
-                range: range.clone(),
+            Ok(Expr::Pair(Pair { fst: Box::new(fst), snd: Box::new(snd), range: *range }))
+        }
+        Expr::Fst(Fst { pair, range }) => {
+            let pair = alpha_conversion_expr(context, pair)?;
+
+            Ok(Expr::Fst(Fst { pair: Box::new(pair), range: *range }))
+        }
+        Expr::Snd(Snd { pair, range }) => {
+            let pair = alpha_conversion_expr(context, pair)?;
+
+            Ok(Expr::Snd(Snd { pair: Box::new(pair), range: *range }))
+        }
+        Expr::Inl(Inl { value, right_ty, range }) => {
+            let value = alpha_conversion_expr(context, value)?;
+            let right_ty = alpha_conversion_type(context, right_ty)?;
+
+            Ok(Expr::Inl(Inl { value: Box::new(value), right_ty, range: *range }))
+        }
+        Expr::Inr(Inr { value, left_ty, range }) => {
+            let value = alpha_conversion_expr(context, value)?;
+            let left_ty = alpha_conversion_type(context, left_ty)?;
+
+            Ok(Expr::Inr(Inr { value: Box::new(value), left_ty, range: *range }))
+        }
+        Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, range }) => {
+            let scrutinee = alpha_conversion_expr(context, scrutinee)?;
+            let scope = context.names.clone();
+            let left_param = context.rename(left_param);
+            let left_body = alpha_conversion_expr(context, left_body)?;
+            context.names = scope.clone();
+            let right_param = context.rename(right_param);
+            let right_body = alpha_conversion_expr(context, right_body)?;
+            context.names = scope;
+
+            Ok(Expr::Case(Case {
+                scrutinee: Box::new(scrutinee),
+                left_param,
+                left_body: Box::new(left_body),
+                right_param,
+                right_body: Box::new(right_body),
+                range: *range,
             }))
         }
     }
@@ -1,3 +1,4 @@
+use super::context::Context;
 use super::errors::TypeError;
 use super::substitution;
 use super::typedtree as T;
@@ -5,6 +6,8 @@ use super::typedtree as T;
 pub fn check_type_equiv(received: &T::Annoted, expected: &T::Annoted) -> Result<(), TypeError> {
     match (*received.clone().desc, *expected.clone().desc) {
         (T::Type::Int, T::Type::Int) => Ok(()),
+        (T::Type::Bool, T::Type::Bool) => Ok(()),
+        (T::Type::Unit, T::Type::Unit) => Ok(()),
         (T::Type::Var { value: re_value, .. }, T::Type::Var { value: ex_value, .. })
             if re_value == ex_value =>
         {
@@ -18,6 +21,27 @@ pub fn check_type_equiv(received: &T::Annoted, expected: &T::Annoted) -> Result<
             check_type_equiv(&re_left, &ex_left)?;
             check_type_equiv(&re_right, &ex_right)
         }
+        (
+            T::Type::Product { fst: re_fst, snd: re_snd },
+            T::Type::Product { fst: ex_fst, snd: ex_snd },
+        ) => {
+            check_type_equiv(&re_fst, &ex_fst)?;
+            check_type_equiv(&re_snd, &ex_snd)
+        }
+        (
+            T::Type::Sum { left: re_left, right: re_right },
+            T::Type::Sum { left: ex_left, right: ex_right },
+        ) => {
+            check_type_equiv(&re_left, &ex_left)?;
+            check_type_equiv(&re_right, &ex_right)
+        }
+        (
+            T::Type::TyApp { lambda: re_lambda, argm: re_argm },
+            T::Type::TyApp { lambda: ex_lambda, argm: ex_argm },
+        ) => {
+            check_type_equiv(&re_lambda, &ex_lambda)?;
+            check_type_equiv(&re_argm, &ex_argm)
+        }
         (
             T::Type::Forall { param: re_param, param_ty: re_kind, body: re_body },
             T::Type::Forall { param: ex_param, param_ty: ex_kind, body: ex_body },
@@ -28,6 +52,36 @@ pub fn check_type_equiv(received: &T::Annoted, expected: &T::Annoted) -> Result<
             check_kind_equiv(&re_kind, &ex_kind)?;
             check_type_equiv(&substituted, &ex_body)
         }
+        (
+            T::Type::Exists { param: re_param, param_ty: re_kind, body: re_body },
+            T::Type::Exists { param: ex_param, param_ty: ex_kind, body: ex_body },
+        ) => {
+            let to = T::Type::Var { value: ex_param };
+            let substituted = substitution(re_body, re_param, to);
+
+            check_kind_equiv(&re_kind, &ex_kind)?;
+            check_type_equiv(&substituted, &ex_body)
+        }
+        (
+            T::Type::TyAbs { param: re_param, param_ty: re_kind, body: re_body },
+            T::Type::TyAbs { param: ex_param, param_ty: ex_kind, body: ex_body },
+        ) => {
+            let to = T::Type::Var { value: ex_param };
+            let substituted = substitution(re_body, re_param, to);
+
+            check_kind_equiv(&re_kind, &ex_kind)?;
+            check_type_equiv(&substituted, &ex_body)
+        }
+        (
+            T::Type::Rec { param: re_param, param_ty: re_kind, body: re_body },
+            T::Type::Rec { param: ex_param, param_ty: ex_kind, body: ex_body },
+        ) => {
+            let to = T::Type::Var { value: ex_param };
+            let substituted = substitution(re_body, re_param, to);
+
+            check_kind_equiv(&re_kind, &ex_kind)?;
+            check_type_equiv(&substituted, &ex_body)
+        }
         (_, _) => Err(TypeError::TypeClash),
     }
 }
@@ -42,6 +96,56 @@ pub fn check_kind_equiv(received: &T::Kind, expected: &T::Kind) -> Result<(), Ty
             check_kind_equiv(received_left, expected_left)?;
             check_kind_equiv(received_right, expected_right)
         }
+        (T::Kind::Var { value: received }, T::Kind::Var { value: expected })
+            if received == expected =>
+        {
+            Ok(())
+        }
+        (T::Kind::Meta { id: received }, T::Kind::Meta { id: expected })
+            if received == expected =>
+        {
+            Ok(())
+        }
         (_, _) => Err(TypeError::TypeClash),
     }
 }
+
+fn occurs_kind(id: usize, kind: &T::Kind) -> bool {
+    match kind {
+        T::Kind::Meta { id: other } => id == *other,
+        T::Kind::KindArrow { left, right } => occurs_kind(id, left) || occurs_kind(id, right),
+        T::Kind::Star | T::Kind::Var { .. } => false,
+    }
+}
+
+/// First-order unification of kinds, solving the unification variables of
+/// both sides in the context.
+pub fn unify_kind(
+    context: &mut Context,
+    received: &T::Kind,
+    expected: &T::Kind,
+) -> Result<(), TypeError> {
+    let received = context.resolve_kind(received);
+    let expected = context.resolve_kind(expected);
+
+    match (&received, &expected) {
+        (T::Kind::Meta { id: re_id }, T::Kind::Meta { id: ex_id }) if re_id == ex_id => Ok(()),
+        (T::Kind::Meta { id }, kind) | (kind, T::Kind::Meta { id }) => {
+            if occurs_kind(*id, kind) {
+                Err(TypeError::InfiniteKind(kind.clone()))
+            } else {
+                context.solve_kind(*id, kind.clone());
+                Ok(())
+            }
+        }
+        (
+            T::Kind::KindArrow { left: re_left, right: re_right },
+            T::Kind::KindArrow { left: ex_left, right: ex_right },
+        ) => {
+            unify_kind(context, re_left, ex_left)?;
+            unify_kind(context, re_right, ex_right)
+        }
+        (_, _) => check_kind_equiv(&received, &expected)
+            .map_err(|_| TypeError::KindMismatch(expected.clone(), received.clone())),
+    }
+}
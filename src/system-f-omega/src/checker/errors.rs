@@ -14,6 +14,14 @@ pub enum TypeError {
     TypeClash,
     TypeNotAForall(T::Type),
     TypeNotAArrow(T::Type),
+    TypeNotAProduct(T::Type),
+    TypeNotASum(T::Type),
+    TypeNotARec(T::Type),
+    TypeNotAnExists(T::Type),
+    EscapingType(String),
+    KindMismatch(T::Kind, T::Kind),
+    InfiniteKind(T::Kind),
+    UnexpectedKindScheme,
 }
 
 impl Display for TypeError {
@@ -26,6 +34,16 @@ impl Display for TypeError {
             TypeError::TypeClash => write!(f, "type clash"),
             TypeError::TypeNotAForall(t) => write!(f, "TypeNotAForall {}", t),
             TypeError::TypeNotAArrow(t) => write!(f, "TypeNotAArrow {}", t),
+            TypeError::TypeNotAProduct(t) => write!(f, "TypeNotAProduct {}", t),
+            TypeError::TypeNotASum(t) => write!(f, "TypeNotASum {}", t),
+            TypeError::TypeNotARec(t) => write!(f, "TypeNotARec {}", t),
+            TypeError::TypeNotAnExists(t) => write!(f, "TypeNotAnExists {}", t),
+            TypeError::EscapingType(t) => write!(f, "type {} escapes its scope", t),
+            TypeError::KindMismatch(e, r) => write!(f, "expect kind {} but got {}", e, r),
+            TypeError::InfiniteKind(k) => write!(f, "infinite kind {}", k),
+            TypeError::UnexpectedKindScheme => {
+                write!(f, "kind quantifier outside of a type definition")
+            }
         }
     }
 }
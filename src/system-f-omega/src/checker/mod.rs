@@ -6,35 +6,78 @@ pub mod normalize;
 pub mod typedtree;
 
 use self::context::{Context, ContextExpr, ContextType};
-use self::equivalence::{check_kind_equiv, check_type_equiv};
+use self::equivalence::{check_type_equiv, unify_kind};
 use self::errors::TypeError;
 use self::normalize::normalize;
 use self::typedtree as T;
 
 use crate::parser::parsetree::{
-    Abs, Anno, App, Arrow, Expr, Forall, Int, Kind, KindAlias, KindArrow, KindVar, LetAlias, Star,
-    TAbs, TApp, TInt, TVar, TyAbs, TyAnno, TyApp, Type, TypeAlias, Var,
+    Abs, Anno, App, Arrow, BinOp, Bool, Case, Exists, Expr, Fold, Forall, Fst, If, Inl, Inr, Int,
+    Kind, KindAlias, KindArrow, KindForall, KindVar, LetAlias, Operator, Pack, Pair, Product, Rec,
+    Snd, Star, Sum, TAbs, TApp, TBool, TInt, TUnit, TVar, TyAbs, TyAnno, TyApp, Type, TypeAlias,
+    Unfold, Unit, Unpack, Var,
 };
 use crate::parser::symbol::Symbol;
 
-pub fn transl_kind(context: &mut Context, kind: &Kind) -> T::Kind {
+pub fn transl_kind(context: &mut Context, kind: &Kind) -> Result<T::Kind, TypeError> {
     match kind {
-        Kind::Star(Star {}) => T::Kind::Star,
-        Kind::KindVar(KindVar { value }) => match context.get_kind(&value) {
-            Ok(kind) => transl_kind(context, &kind),
-            Err(err) => panic!("{}", err),
+        Kind::Star(Star {}) => Ok(T::Kind::Star),
+        Kind::KindVar(KindVar { value }) => match context.get_kind_var(value) {
+            Some(kind) => Ok(kind),
+            None => {
+                let kind = context.get_kind(value)?;
+                transl_kind(context, &kind)
+            }
         },
         Kind::KindArrow(KindArrow { left, right }) => {
-            let left = transl_kind(context, left);
-            let right = transl_kind(context, right);
-            T::Kind::KindArrow { left: Box::new(left), right: Box::new(right) }
+            let left = transl_kind(context, left)?;
+            let right = transl_kind(context, right)?;
+            Ok(T::Kind::KindArrow { left: Box::new(left), right: Box::new(right) })
+        }
+        Kind::KindForall(_) => Err(TypeError::UnexpectedKindScheme),
+    }
+}
+
+/// Checks `ty` against an annotation that may quantify over kinds. Each
+/// variable of `∀κ. K` is bound to `instance(κ)` while `ty` is checked: a
+/// rigid variable when a definition is declared, a fresh unification
+/// variable each time it is used.
+pub fn check_scheme(
+    context: &mut Context,
+    ty: Type,
+    anno: &Kind,
+    instance: fn(&mut Context, &Symbol) -> T::Kind,
+) -> Result<T::Annoted, TypeError> {
+    match anno {
+        Kind::KindForall(KindForall { param, body }) => {
+            let kind = instance(context, param);
+            let previous = context.add_kind_var(param, kind);
+            let checked = check_scheme(context, ty, body, instance);
+
+            context.restore_kind_var(param, previous);
+            checked
+        }
+        anno => {
+            let anno = transl_kind(context, anno)?;
+            check_type(context, ty, anno)
         }
     }
 }
 
+pub fn operator_type(op: Operator) -> T::Annoted {
+    let desc = match op {
+        Operator::Add | Operator::Sub | Operator::Mul | Operator::Div => T::Type::Int,
+        Operator::Eq | Operator::Lt => T::Type::Bool,
+    };
+
+    T::Annoted { desc: Box::new(desc), kind: T::Kind::Star }
+}
+
 pub fn substitution(ty: T::Annoted, from: Symbol, to: T::Type) -> T::Annoted {
     let desc = match *ty.desc {
         T::Type::Int => T::Type::Int,
+        T::Type::Bool => T::Type::Bool,
+        T::Type::Unit => T::Type::Unit,
         T::Type::Var { value } if value == from => to,
         T::Type::Var { value } => T::Type::Var { value },
         T::Type::Arrow { left, right } => {
@@ -43,6 +86,32 @@ pub fn substitution(ty: T::Annoted, from: Symbol, to: T::Type) -> T::Annoted {
 
             T::Type::Arrow { left, right }
         }
+        T::Type::Exists { param, param_ty, body } if param == from => {
+            T::Type::Exists { param, param_ty, body }
+        }
+        T::Type::Exists { param, param_ty, body } => {
+            let body = substitution(body, from, to);
+            T::Type::Exists { param, param_ty, body }
+        }
+        T::Type::Rec { param, param_ty, body } if param == from => {
+            T::Type::Rec { param, param_ty, body }
+        }
+        T::Type::Rec { param, param_ty, body } => {
+            let body = substitution(body, from, to);
+            T::Type::Rec { param, param_ty, body }
+        }
+        T::Type::Product { fst, snd } => {
+            let fst = substitution(fst, from.clone(), to.clone());
+            let snd = substitution(snd, from, to);
+
+            T::Type::Product { fst, snd }
+        }
+        T::Type::Sum { left, right } => {
+            let left = substitution(left, from.clone(), to.clone());
+            let right = substitution(right, from, to);
+
+            T::Type::Sum { left, right }
+        }
         T::Type::Forall { param, param_ty, body } if param == from => {
             T::Type::Forall { param, param_ty, body }
         }
@@ -51,11 +120,11 @@ pub fn substitution(ty: T::Annoted, from: Symbol, to: T::Type) -> T::Annoted {
             T::Type::Forall { param, param_ty, body }
         }
         T::Type::TyAbs { param, param_ty, body } if param == from => {
-            T::Type::Forall { param, param_ty, body }
+            T::Type::TyAbs { param, param_ty, body }
         }
         T::Type::TyAbs { param, param_ty, body } => {
             let body = substitution(body, from, to);
-            T::Type::Forall { param, param_ty, body }
+            T::Type::TyAbs { param, param_ty, body }
         }
         T::Type::TyApp { lambda, argm } => {
             let lambda = substitution(lambda, from.clone(), to.clone());
@@ -68,12 +137,53 @@ pub fn substitution(ty: T::Annoted, from: Symbol, to: T::Type) -> T::Annoted {
     T::Annoted { desc: Box::new(desc), kind: ty.kind }
 }
 
+/// Whether the type variable `var` occurs free in `ty`.
+pub fn occurs(ty: &T::Annoted, var: &Symbol) -> bool {
+    match &*ty.desc {
+        T::Type::Int | T::Type::Bool | T::Type::Unit => false,
+        T::Type::Var { value } => value == var,
+        T::Type::Arrow { left: fst, right: snd }
+        | T::Type::Product { fst, snd }
+        | T::Type::Sum { left: fst, right: snd }
+        | T::Type::TyApp { lambda: fst, argm: snd } => occurs(fst, var) || occurs(snd, var),
+        T::Type::Forall { param, body, .. }
+        | T::Type::Exists { param, body, .. }
+        | T::Type::Rec { param, body, .. }
+        | T::Type::TyAbs { param, body, .. } => param != var && occurs(body, var),
+    }
+}
+
+/// Unrolls one level of a recursive type, looking through the type operators
+/// it is applied to: `(μF: K. T) A` becomes `T[F := μF: K. T] A`.
+pub fn unroll(context: &mut Context, ty: T::Annoted) -> Option<T::Annoted> {
+    let ty = normalize(context, ty);
+
+    match *ty.desc.clone() {
+        T::Type::Rec { param, body, .. } => {
+            let unrolled = substitution(body, param, *ty.desc);
+            Some(normalize(context, unrolled))
+        }
+        T::Type::TyApp { lambda, argm } => {
+            let lambda = unroll(context, lambda)?;
+            let desc = T::Type::TyApp { lambda, argm };
+
+            Some(normalize(context, T::Annoted { desc: Box::new(desc), kind: ty.kind }))
+        }
+        _ => None,
+    }
+}
+
 pub fn infer_type(context: &mut Context, ty: Type) -> Result<T::Annoted, TypeError> {
     match ty {
         Type::TInt(TInt {}) => Ok(T::Annoted { desc: Box::new(T::Type::Int), kind: T::Kind::Star }),
+        Type::TBool(TBool {}) => {
+            Ok(T::Annoted { desc: Box::new(T::Type::Bool), kind: T::Kind::Star })
+        }
+        Type::TUnit(TUnit {}) => {
+            Ok(T::Annoted { desc: Box::new(T::Type::Unit), kind: T::Kind::Star })
+        }
         Type::TyAnno(TyAnno { ty, anno, .. }) => {
-            let annotation = transl_kind(context, &anno.clone());
-            check_type(context, *ty, annotation)
+            check_scheme(context, *ty, &anno, |context, _| context.fresh_kind())
         }
         Type::TVar(TVar { value }) => {
             let expr = context.get_type(&value);
@@ -82,12 +192,12 @@ pub fn infer_type(context: &mut Context, ty: Type) -> Result<T::Annoted, TypeErr
                 Err(error) => Err(error),
                 Ok(ContextType::Alias(alias)) => infer_type(context, alias),
                 Ok(ContextType::Value(kind)) => {
-                    Ok(T::Annoted { desc: Box::new(T::Type::Var { value }), kind: kind })
+                    Ok(T::Annoted { desc: Box::new(T::Type::Var { value }), kind })
                 }
             }
         }
         Type::Forall(Forall { param, param_ty, body }) => {
-            let param_ty = transl_kind(context, &param_ty);
+            let param_ty = transl_kind(context, &param_ty)?;
             context.add_type(&param, param_ty.clone());
             let body = check_type(context, *body, T::Kind::Star)?;
 
@@ -105,11 +215,49 @@ pub fn infer_type(context: &mut Context, ty: Type) -> Result<T::Annoted, TypeErr
                 kind: T::Kind::Star,
             })
         }
-        Type::TyAbs(TyAbs { param, param_ty, body }) => {
-            let param_ty = transl_kind(context, &param_ty);
+        Type::Exists(Exists { param, param_ty, body }) => {
+            let param_ty = transl_kind(context, &param_ty)?;
             context.add_type(&param, param_ty.clone());
             let body = check_type(context, *body, T::Kind::Star)?;
 
+            Ok(T::Annoted {
+                desc: Box::new(T::Type::Exists { param, param_ty, body }),
+                kind: T::Kind::Star,
+            })
+        }
+        Type::Rec(Rec { param, param_ty, body }) => {
+            let param_ty = transl_kind(context, &param_ty)?;
+            context.add_type(&param, param_ty.clone());
+            let body = check_type(context, *body, param_ty.clone())?;
+
+            Ok(T::Annoted {
+                desc: Box::new(T::Type::Rec { param, param_ty: param_ty.clone(), body }),
+                kind: param_ty,
+            })
+        }
+        Type::Product(Product { fst, snd }) => {
+            let fst = check_type(context, *fst, T::Kind::Star)?;
+            let snd = check_type(context, *snd, T::Kind::Star)?;
+
+            Ok(T::Annoted {
+                desc: Box::new(T::Type::Product { fst, snd }),
+                kind: T::Kind::Star,
+            })
+        }
+        Type::Sum(Sum { left, right }) => {
+            let left = check_type(context, *left, T::Kind::Star)?;
+            let right = check_type(context, *right, T::Kind::Star)?;
+
+            Ok(T::Annoted {
+                desc: Box::new(T::Type::Sum { left, right }),
+                kind: T::Kind::Star,
+            })
+        }
+        Type::TyAbs(TyAbs { param, param_ty, body }) => {
+            let param_ty = transl_kind(context, &param_ty)?;
+            context.add_type(&param, param_ty.clone());
+            let body = infer_type(context, *body)?;
+
             Ok(T::Annoted {
                 desc: Box::new(T::Type::TyAbs {
                     param,
@@ -125,17 +273,59 @@ pub fn infer_type(context: &mut Context, ty: Type) -> Result<T::Annoted, TypeErr
         Type::TyApp(TyApp { lambda, argm }) => {
             let lambda = infer_type(context, *lambda)?;
 
-            match lambda.clone().kind {
-                T::Kind::Star => Err(TypeError::TypeClash),
-                T::Kind::KindArrow { left, right } => {
-                    let argm = check_type(context, *argm, *left)?;
+            let (left, right) = match context.resolve_kind(&lambda.kind) {
+                T::Kind::KindArrow { left, right } => (*left, *right),
+                T::Kind::Meta { .. } => {
+                    let left = context.fresh_kind();
+                    let right = context.fresh_kind();
+                    let arrow = T::Kind::KindArrow {
+                        left: Box::new(left.clone()),
+                        right: Box::new(right.clone()),
+                    };
+
+                    unify_kind(context, &lambda.kind, &arrow)?;
+                    (left, right)
+                }
+                _ => return Err(TypeError::TypeClash),
+            };
+
+            let argm = check_type(context, *argm, left)?;
+
+            Ok(T::Annoted {
+                desc: Box::new(T::Type::TyApp { lambda, argm }),
+                kind: right,
+            })
+        }
+    }
+}
 
-                    Ok(T::Annoted {
-                        desc: Box::new(T::Type::TyApp { lambda, argm }),
-                        kind: *right,
-                    })
+// Adds a chain of aliases to the context and returns the expression they scope over. Chains are
+// walked in a loop, so that long ones like the prelude do not add to the recursion depth.
+fn check_aliases<'a>(context: &mut Context, mut ex: &'a Expr) -> Result<&'a Expr, TypeError> {
+    loop {
+        ex = match ex {
+            // The value is checked where it is defined even when nothing uses it, so an error
+            // is reported in the module that made it.
+            Expr::LetAlias(LetAlias { name, value, body, .. }) => {
+                infer_expr(context, value)?;
+                context.add_expr_alias(name, *value.clone());
+                body
+            }
+            Expr::TypeAlias(TypeAlias { name, value, body, .. }) => {
+                if let Type::TyAnno(TyAnno { ty, anno }) = value {
+                    let rigid =
+                        |_: &mut Context, param: &Symbol| T::Kind::Var { value: param.clone() };
+                    check_scheme(context, *ty.clone(), anno, rigid)?;
                 }
+
+                context.add_type_alias(name, value.clone());
+                body
+            }
+            Expr::KindAlias(KindAlias { name, value, body, .. }) => {
+                context.add_kind_alias(name, value.clone());
+                body
             }
+            _ => return Ok(ex),
         }
     }
 }
@@ -148,21 +338,154 @@ pub fn infer_expr(context: &mut Context, ex: &Expr) -> Result<T::Annoted, TypeEr
             check_expr(context, *expr.clone(), annotation.clone())?;
             Ok(annotation)
         }
-        Expr::LetAlias(LetAlias { name, value, body, .. }) => {
-            context.add_expr_alias(&name, *value);
-            infer_expr(context, &body.clone())
-        }
-        Expr::TypeAlias(TypeAlias { name, value, body, .. }) => {
-            context.add_type_alias(&name, value.clone());
-            infer_expr(context, &body.clone())
-        }
-        Expr::KindAlias(KindAlias { name, value, body, .. }) => {
-            context.add_kind_alias(&name, value);
-            infer_expr(context, &body)
+        Expr::LetAlias(_) | Expr::TypeAlias(_) | Expr::KindAlias(_) => {
+            let body = check_aliases(context, ex)?;
+            infer_expr(context, body)
         }
         Expr::Int(Int { .. }) => {
             Ok(T::Annoted { desc: Box::new(T::Type::Int), kind: T::Kind::Star })
         }
+        Expr::Bool(Bool { .. }) => {
+            Ok(T::Annoted { desc: Box::new(T::Type::Bool), kind: T::Kind::Star })
+        }
+        Expr::Unit(Unit { .. }) => {
+            Ok(T::Annoted { desc: Box::new(T::Type::Unit), kind: T::Kind::Star })
+        }
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            let bool_ty = T::Annoted { desc: Box::new(T::Type::Bool), kind: T::Kind::Star };
+            check_expr(context, *cond, bool_ty)?;
+
+            let then_ty = infer_expr(context, &then_branch)?;
+            check_expr(context, *else_branch, then_ty.clone())?;
+
+            Ok(then_ty)
+        }
+        Expr::BinOp(BinOp { op, left, right, .. }) => {
+            let int_ty = T::Annoted { desc: Box::new(T::Type::Int), kind: T::Kind::Star };
+            check_expr(context, *left, int_ty.clone())?;
+            check_expr(context, *right, int_ty)?;
+
+            Ok(operator_type(op))
+        }
+        Expr::Pair(Pair { fst, snd, .. }) => {
+            let fst = infer_expr(context, &fst)?;
+            let snd = infer_expr(context, &snd)?;
+
+            Ok(T::Annoted {
+                desc: Box::new(T::Type::Product { fst, snd }),
+                kind: T::Kind::Star,
+            })
+        }
+        Expr::Fst(Fst { pair, .. }) => {
+            let pair_ty = infer_expr(context, &pair)?;
+            let pair_ty = normalize(context, pair_ty);
+
+            match *pair_ty.desc {
+                T::Type::Product { fst, .. } => Ok(fst),
+                desc => Err(TypeError::TypeNotAProduct(desc)),
+            }
+        }
+        Expr::Snd(Snd { pair, .. }) => {
+            let pair_ty = infer_expr(context, &pair)?;
+            let pair_ty = normalize(context, pair_ty);
+
+            match *pair_ty.desc {
+                T::Type::Product { snd, .. } => Ok(snd),
+                desc => Err(TypeError::TypeNotAProduct(desc)),
+            }
+        }
+        Expr::Inl(Inl { value, right_ty, .. }) => {
+            let left = infer_expr(context, &value)?;
+            let right = check_type(context, right_ty, T::Kind::Star)?;
+
+            Ok(T::Annoted {
+                desc: Box::new(T::Type::Sum { left, right }),
+                kind: T::Kind::Star,
+            })
+        }
+        Expr::Inr(Inr { value, left_ty, .. }) => {
+            let left = check_type(context, left_ty, T::Kind::Star)?;
+            let right = infer_expr(context, &value)?;
+
+            Ok(T::Annoted {
+                desc: Box::new(T::Type::Sum { left, right }),
+                kind: T::Kind::Star,
+            })
+        }
+        Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+            let scrutinee_ty = infer_expr(context, &scrutinee)?;
+            let scrutinee_ty = normalize(context, scrutinee_ty);
+
+            match *scrutinee_ty.desc {
+                T::Type::Sum { left, right } => {
+                    context.add_expr(&left_param, left);
+                    let left_ty = infer_expr(context, &left_body)?;
+
+                    context.add_expr(&right_param, right);
+                    check_expr(context, *right_body, left_ty.clone())?;
+
+                    Ok(left_ty)
+                }
+                desc => Err(TypeError::TypeNotASum(desc)),
+            }
+        }
+        Expr::Fold(Fold { ty, value, .. }) => {
+            let ty = check_type(context, ty, T::Kind::Star)?;
+
+            match unroll(context, ty.clone()) {
+                Some(unrolled) => {
+                    check_expr(context, *value, unrolled)?;
+                    Ok(ty)
+                }
+                None => Err(TypeError::TypeNotARec(*ty.desc)),
+            }
+        }
+        Expr::Unfold(Unfold { value, .. }) => {
+            let value_ty = infer_expr(context, &value)?;
+
+            match unroll(context, value_ty.clone()) {
+                Some(unrolled) => Ok(unrolled),
+                None => Err(TypeError::TypeNotARec(*value_ty.desc)),
+            }
+        }
+        Expr::Pack(Pack { witness, value, ty, .. }) => {
+            let ty = check_type(context, ty, T::Kind::Star)?;
+            let ty = normalize(context, ty);
+
+            match *ty.desc.clone() {
+                T::Type::Exists { param, param_ty, body } => {
+                    let witness = check_type(context, witness, param_ty)?;
+                    let expected = substitution(body, param, *witness.desc);
+
+                    check_expr(context, *value, expected)?;
+                    Ok(ty)
+                }
+                desc => Err(TypeError::TypeNotAnExists(desc)),
+            }
+        }
+        Expr::Unpack(Unpack { package, ty_param, param, body, .. }) => {
+            let package_ty = infer_expr(context, &package)?;
+            let package_ty = normalize(context, package_ty);
+
+            match *package_ty.desc {
+                T::Type::Exists { param: ex_param, param_ty, body: ex_body } => {
+                    let to = T::Type::Var { value: ty_param.clone() };
+
+                    context.add_type(&ty_param, param_ty);
+                    context.add_expr(&param, substitution(ex_body, ex_param, to));
+
+                    let body_ty = infer_expr(context, &body)?;
+                    let body_ty = normalize(context, body_ty);
+
+                    if occurs(&body_ty, &ty_param) {
+                        Err(TypeError::EscapingType(ty_param.to_string()))
+                    } else {
+                        Ok(body_ty)
+                    }
+                }
+                desc => Err(TypeError::TypeNotAnExists(desc)),
+            }
+        }
         Expr::Var(Var { value, .. }) => {
             let expr = context.get_expr(&value);
 
@@ -195,7 +518,7 @@ pub fn infer_expr(context: &mut Context, ex: &Expr) -> Result<T::Annoted, TypeEr
             }
         }
         Expr::TAbs(TAbs { param, param_ty, body, .. }) => {
-            let kind = transl_kind(context, &param_ty);
+            let kind = transl_kind(context, &param_ty)?;
             context.add_type(&param, kind.clone());
             let body = infer_expr(context, &body)?;
 
@@ -225,7 +548,7 @@ pub fn check_type(
     expected: T::Kind,
 ) -> Result<T::Annoted, TypeError> {
     let received = infer_type(context, ty)?;
-    check_kind_equiv(&received.kind, &expected)?;
+    unify_kind(context, &received.kind, &expected)?;
 
     Ok(received)
 }
@@ -238,14 +561,15 @@ pub fn check_expr(context: &mut Context, ex: Expr, expected: T::Annoted) -> Resu
             Expr::TAbs(TAbs { param: rp, param_ty: rt, body: rb, .. }),
             T::Type::Forall { param: ep, param_ty: ek, body: bk },
         ) => {
-            let rt = transl_kind(context, &rt);
-            check_kind_equiv(&rt, &ek)?;
+            let rt = transl_kind(context, &rt)?;
+            unify_kind(context, &rt, &ek)?;
             let ret = substitution(bk, ep, T::Type::Var { value: rp.clone() });
             context.add_type(&rp, ek);
             check_expr(context, *rb, ret)
         }
         (Expr::Abs(Abs { param, param_ty, body, .. }), T::Type::Arrow { left, right }) => {
             let received_param = check_type(context, param_ty, T::Kind::Star)?;
+            let received_param = normalize(context, received_param);
             check_type_equiv(&received_param, &left)?;
             context.add_expr(&param, left);
 
@@ -253,11 +577,22 @@ pub fn check_expr(context: &mut Context, ex: Expr, expected: T::Annoted) -> Resu
         }
         (expr, _) => {
             let received = infer_expr(context, &expr)?;
+            let received = normalize(context, received);
+            let expected = normalize(context, expected);
             check_type_equiv(&received, &expected)
         }
     }
 }
 
+/// Checks a module, leaving its declarations in `context` so that the modules importing it
+/// are checked in their scope without checking it again.
+pub fn check_module(context: &mut Context, ex: &Expr) -> Result<(), TypeError> {
+    let body = check_aliases(context, ex)?;
+    infer_expr(context, body)?;
+
+    Ok(())
+}
+
 pub fn type_of(ex: Expr) -> Result<T::Annoted, TypeError> {
     let mut context = Context::default();
     let typed_terms = infer_expr(&mut context, &ex)?;
@@ -265,3 +600,88 @@ pub fn type_of(ex: Expr) -> Result<T::Annoted, TypeError> {
 
     Ok(norml_terms)
 }
+
+#[cfg(test)]
+mod test {
+    use super::conversion::{alpha_conversion_expr, Names};
+    use super::type_of;
+    use crate::parser;
+
+    #[test]
+    fn test_check_case_branches() {
+        let input = r"λs: Int + Bool. case s of inl n => n | inr b => b";
+        let expr = parser::from_string(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_escaping_type() {
+        let input = r"
+        let counter: ∃C: *. C * (C -> Int) = pack [Int, {0, λc: Int. c}] as ∃C: *. C * (C -> Int) in
+        unpack counter as [C, c] in fst c
+        ";
+        let expr = parser::from_string(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_rigid_kind_variable() {
+        let input = r"
+        type Bad: ∀κ. κ -> * = λA: κ. A in
+        0
+        ";
+        let expr = parser::from_string(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_kind_instance_mismatch() {
+        let input = r"
+        type Const: ∀κ. * -> κ -> * = λA: *. λB: κ. A in
+        let x: Const Int Bool Int = 0 in
+        x
+        ";
+        let expr = parser::from_string(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_program_scope() {
+        let inputs = [
+            "def one: Int = two - 1 def two: Int = 2 main = one",
+            "def id: Int -> Int = λx: Int. x main = x",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            assert!(alpha_conversion_expr(&mut Names::new(), &expr).is_err())
+        }
+    }
+
+    #[test]
+    fn test_check_shadowed_parameter() {
+        // The inner `x` goes out of scope after its abstraction.
+        let input = r"(λx: Int. if (λx: Bool. x) true then x else 0) 1";
+        let expr = parser::from_string(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+
+        assert_eq!(type_of(expr).unwrap().to_string(), "Int")
+    }
+
+    #[test]
+    fn test_check_unused_let() {
+        let input = "let x: Int = 1 + true in 0";
+        let expr = parser::from_string(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+}
@@ -2,11 +2,15 @@ use super::context::Context;
 use super::substitution;
 use super::typedtree as T;
 
+/// Beta-normalizes a type and replaces the solved kind variables it carries.
 pub fn normalize(context: &mut Context, ty: T::Annoted) -> T::Annoted {
     let desc = match *ty.desc {
         T::Type::Int => T::Type::Int,
+        T::Type::Bool => T::Type::Bool,
+        T::Type::Unit => T::Type::Unit,
         T::Type::Var { value } => T::Type::Var { value },
         T::Type::Forall { param, param_ty, body } => {
+            let param_ty = context.resolve_kind(&param_ty);
             let body = normalize(context, body);
 
             T::Type::Forall { param, param_ty, body }
@@ -17,17 +21,41 @@ pub fn normalize(context: &mut Context, ty: T::Annoted) -> T::Annoted {
 
             T::Type::Arrow { left, right }
         }
+        T::Type::Exists { param, param_ty, body } => {
+            let param_ty = context.resolve_kind(&param_ty);
+            let body = normalize(context, body);
+
+            T::Type::Exists { param, param_ty, body }
+        }
+        T::Type::Rec { param, param_ty, body } => {
+            let param_ty = context.resolve_kind(&param_ty);
+            let body = normalize(context, body);
+
+            T::Type::Rec { param, param_ty, body }
+        }
+        T::Type::Product { fst, snd } => {
+            let fst = normalize(context, fst);
+            let snd = normalize(context, snd);
+
+            T::Type::Product { fst, snd }
+        }
+        T::Type::Sum { left, right } => {
+            let left = normalize(context, left);
+            let right = normalize(context, right);
+
+            T::Type::Sum { left, right }
+        }
         T::Type::TyAbs { param, param_ty, body } => {
+            let param_ty = context.resolve_kind(&param_ty);
             let body = normalize(context, body);
 
             T::Type::TyAbs { param, param_ty, body }
         }
         T::Type::TyApp { lambda, argm } => {
             let lambda = normalize(context, lambda);
-            let argm = normalize(context, argm.clone());
-            let desc = normalize(context, argm.clone());
+            let argm = normalize(context, argm);
 
-            match *desc.desc {
+            match *lambda.desc.clone() {
                 T::Type::TyAbs { param, param_ty: _, body } => {
                     let argm = argm.desc;
                     let sub = substitution(body.clone(), param, *argm);
@@ -39,5 +67,5 @@ pub fn normalize(context: &mut Context, ty: T::Annoted) -> T::Annoted {
         }
     };
 
-    T::Annoted { desc: Box::new(desc), kind: ty.kind }
+    T::Annoted { desc: Box::new(desc), kind: context.resolve_kind(&ty.kind) }
 }
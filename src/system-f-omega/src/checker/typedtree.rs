@@ -12,11 +12,17 @@ pub struct Annoted {
 pub enum Kind {
     Star,
     KindArrow { left: Box<Kind>, right: Box<Kind> },
+    /// A kind variable held rigid while a kind-polymorphic definition is checked.
+    Var { value: Symbol },
+    /// A kind unification variable, solved in the context.
+    Meta { id: usize },
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     Int,
+    Bool,
+    Unit,
     Var {
         value: Symbol,
     },
@@ -29,6 +35,24 @@ pub enum Type {
         param_ty: Kind,
         body: Annoted,
     },
+    Exists {
+        param: Symbol,
+        param_ty: Kind,
+        body: Annoted,
+    },
+    Rec {
+        param: Symbol,
+        param_ty: Kind,
+        body: Annoted,
+    },
+    Product {
+        fst: Annoted,
+        snd: Annoted,
+    },
+    Sum {
+        left: Annoted,
+        right: Annoted,
+    },
     TyAbs {
         param: Symbol,
         param_ty: Kind,
@@ -42,9 +66,7 @@ pub enum Type {
 
 impl fmt::Display for Annoted {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Annoted { desc, .. } => write!(f, "{}", desc),
-        }
+        write!(f, "{}", self.desc)
     }
 }
 
@@ -52,11 +74,21 @@ impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Type::Int => write!(f, "Int"),
+            Type::Bool => write!(f, "Bool"),
+            Type::Unit => write!(f, "Unit"),
             Type::Var { value } => write!(f, "{}", value),
             Type::Arrow { left, right } => write!(f, "({} -> {})", left, right),
             Type::Forall { param, param_ty, body } => {
                 write!(f, "∀{}: {}. {}", param, param_ty, body)
             }
+            Type::Exists { param, param_ty, body } => {
+                write!(f, "(∃{}: {}. {})", param, param_ty, body)
+            }
+            Type::Rec { param, param_ty, body } => {
+                write!(f, "(μ{}: {}. {})", param, param_ty, body)
+            }
+            Type::Product { fst, snd } => write!(f, "({} * {})", fst, snd),
+            Type::Sum { left, right } => write!(f, "({} + {})", left, right),
             Type::TyAbs { param, param_ty, body } => {
                 write!(f, "{}: {}. {}", param, param_ty, body)
             }
@@ -70,6 +102,8 @@ impl fmt::Display for Kind {
         match self {
             Kind::Star => write!(f, "*"),
             Kind::KindArrow { left, right } => write!(f, "({} -> {})", left, right),
+            Kind::Var { value } => write!(f, "{}", value),
+            Kind::Meta { id } => write!(f, "?{}", id),
         }
     }
 }
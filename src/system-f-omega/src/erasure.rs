@@ -0,0 +1,208 @@
+//! Type erasure into the untyped lambda calculus. Type operators and kinds only live in types,
+//! so type and kind aliases leave their bodies, while a `let` of a term stays a `let`, which
+//! the untyped calculus has as well. Symbols keep their names: once the checker has renamed
+//! them apart, each one is bound by a single binder.
+
+use crate::checker::typedtree::{Annoted, Type};
+use crate::parser::location::Range;
+use crate::parser::parsetree::{
+    Abs, Anno, App, BinOp, Bool, Case, Expr, Fold, Fst, If, Inl, Inr, Int, KindAlias, LetAlias,
+    Operator, Pack, Pair, Snd, TAbs, TApp, TypeAlias, Unfold, Unit, Unpack, Var,
+};
+use untyped_lambda_calculus::encodings::{self, Primitive, Sort};
+use untyped_lambda_calculus::parser::{location as untyped_location, parsetree as untyped};
+
+fn range(range: Range) -> untyped_location::Range {
+    let start = untyped_location::Pos::new(range.start.index);
+    let end = untyped_location::Pos::new(range.end.index);
+
+    untyped_location::Range::new(start, end)
+}
+
+fn primitive(op: Operator) -> Primitive {
+    match op {
+        Operator::Add => Primitive::Add,
+        Operator::Sub => Primitive::Sub,
+        Operator::Mul => Primitive::Mul,
+        Operator::Div => Primitive::Div,
+        Operator::Eq => Primitive::Eq,
+        Operator::Lt => Primitive::Lt,
+    }
+}
+
+/// How the erasure of a term of the normal type `ty` reads back.
+pub fn sort(ty: &Annoted) -> Sort {
+    match *ty.desc {
+        Type::Int => Sort::Integer,
+        Type::Bool => Sort::Boolean,
+        _ => Sort::Other,
+    }
+}
+
+/// The untyped term of a well-typed one. Pairs and sums become Church pairs and the two
+/// injections, whose `case` applies one of its arms.
+pub fn erase(expr: &Expr) -> untyped::Expr {
+    let range = range(expr.range());
+
+    match expr {
+        Expr::Int(Int { value, .. }) => encodings::numeral(*value, range),
+        Expr::Bool(Bool { value, .. }) => encodings::boolean(*value),
+        Expr::Unit(Unit { .. }) => encodings::unit(),
+        Expr::Var(Var { value, .. }) => encodings::var(&value.name, range),
+        Expr::Abs(Abs { param, body, .. }) => encodings::abs(&param.name, erase(body), range),
+        Expr::App(App { lambda, argm, .. }) => encodings::app(erase(lambda), erase(argm), range),
+        Expr::TAbs(TAbs { body: value, .. })
+        | Expr::TApp(TApp { lambda: value, .. })
+        | Expr::Anno(Anno { expr: value, .. })
+        | Expr::TypeAlias(TypeAlias { body: value, .. })
+        | Expr::KindAlias(KindAlias { body: value, .. })
+        | Expr::Fold(Fold { value, .. })
+        | Expr::Unfold(Unfold { value, .. })
+        | Expr::Pack(Pack { value, .. }) => erase(value),
+        Expr::LetAlias(LetAlias { name, value, body, .. }) => untyped::Expr::Let(untyped::Let {
+            name: name.name.clone(),
+            value: Box::new(erase(value)),
+            body: Box::new(erase(body)),
+            range,
+        }),
+        Expr::Pair(Pair { fst, snd, .. }) => encodings::pair(erase(fst), erase(snd), range),
+        Expr::Fst(Fst { pair, .. }) => encodings::fst(erase(pair), range),
+        Expr::Snd(Snd { pair, .. }) => encodings::snd(erase(pair), range),
+        Expr::Inl(Inl { value, .. }) => encodings::inl(erase(value), range),
+        Expr::Inr(Inr { value, .. }) => encodings::inr(erase(value), range),
+        Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+            let left = encodings::abs(&left_param.name, erase(left_body), range);
+            let right = encodings::abs(&right_param.name, erase(right_body), range);
+
+            encodings::case(erase(scrutinee), left, right, range)
+        }
+        Expr::Unpack(Unpack { package, param, body, .. }) => {
+            encodings::app(encodings::abs(&param.name, erase(body), range), erase(package), range)
+        }
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            encodings::branch(erase(cond), erase(then_branch), erase(else_branch), range)
+        }
+        Expr::BinOp(BinOp { op, left, right, .. }) => {
+            encodings::primitive(primitive(*op), erase(left), erase(right), range)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{erase, sort};
+    use crate::checker::{self, conversion::alpha_conversion_expr, conversion::Names};
+    use crate::parser::parsetree::Expr;
+    use crate::reduction::{reduce, Norm};
+    use crate::{parser, prelude};
+    use untyped_lambda_calculus::encodings::{evaluate, Sort};
+    use untyped_lambda_calculus::reduction as untyped;
+
+    // A term with its symbols renamed apart, as the checker leaves them, its normal form and
+    // how its erasure reads back.
+    fn rename(expr: Expr) -> (Expr, String, Sort) {
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+        let sort = sort(&checker::type_of(expr.clone()).unwrap());
+
+        let normal = reduce(Norm::NOR, expr.clone(), None).to_string();
+        (expr, normal, sort)
+    }
+
+    #[test]
+    fn test_erase_types() {
+        let input = r"
+        type Id: * = ∀A: *. A -> A in
+        let id: Id = λA: *. λx: A. x in
+        id [Int] 1
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert_eq!(erase(&expr).to_string(), "(let id = (λx. x) in (id (λf. (λx. (f x)))))")
+    }
+
+    #[test]
+    fn test_erase_agrees_with_normal_order() {
+        let inputs = [
+            r"
+            type Op: * = Int -> Int -> Int in
+            let sub: Op = λx: Int. λy: Int. x - y in
+            if sub 5 3 * 2 < 3 then 0 else sub 3 (4 / 2)
+            ",
+            r"
+            type Pair: * -> * -> * = λA: *. λB: *. A * B in
+            let swap: Pair Int Bool -> Pair Bool Int = λp: Pair Int Bool. {snd p, fst p} in
+            let q: Pair Bool Int = swap {4, true} in
+            if fst q then (snd q) + 1 else 0
+            ",
+            r"
+            type Option: * -> * = λA: *. Unit + A in
+            let get: Option Int -> Int = λo: Option Int. case o of inl u => 0 | inr n => n in
+            get (inr [Unit] 7) + get (inl [Int] ())
+            ",
+            r"
+            type List: * -> * = μL: * -> *. λA: *. Unit + A * L A in
+            let nil: List Int = fold [List Int] inl [Int * List Int] () in
+            let cons: Int -> List Int -> List Int =
+                λx: Int. λxs: List Int. fold [List Int] inr [Unit] {x, xs} in
+            let head: List Int -> Int =
+                λl: List Int. case unfold l of inl u => 0 | inr p => fst p in
+            head (cons 4 nil)
+            ",
+            r"
+            type Stack: * -> * = λS: *. S * (Int -> S -> S) * (S -> Int) in
+            let stack: ∃S: *. Stack S =
+                pack [Int, {0, {λx: Int. λs: Int. x + s, λs: Int. s}}] as ∃S: *. Stack S in
+            unpack stack as [S, s] in
+            let empty: S = fst s in
+            let push: Int -> S -> S = fst (snd s) in
+            let top: S -> Int = snd (snd s) in
+            top (push 2 (push 3 empty))
+            ",
+            r"
+            data Nat = Zero | Succ Nat in
+            data Option (A: *) = None | Some A in
+            let pred: Nat -> Option Nat = λn: Nat.
+                foldNat [Option Nat] n (None [Nat])
+                (λo: Option Nat. Some [Nat] (foldOption [Nat] [Nat] o Zero (λm: Nat. Succ m))) in
+            let toInt: Nat -> Int = λn: Nat. foldNat [Int] n 0 (λr: Int. r + 1) in
+            foldOption [Nat] [Int] (pred (Succ (Succ (Succ Zero)))) 0 toInt
+            ",
+            r"(λA: *. λx: A. x) [Bool] false",
+            r"
+            type Pair: * -> * -> * = λA: *. λB: *. A * B in
+            let less: Pair Int Int -> Bool = λp: Pair Int Int. (fst p) < (snd p) in
+            less {4, 3}
+            ",
+        ];
+        let strategies =
+            [untyped::Norm::NOR, untyped::Norm::APP, untyped::Norm::CBN, untyped::Norm::CBV];
+
+        for input in inputs {
+            let (expr, normal, sort) = rename(parser::from_string(input).unwrap());
+
+            for strategy in strategies {
+                let erased = evaluate(strategy, erase(&expr), sort).to_string();
+                assert_eq!(erased, normal, "{:?}", strategy);
+            }
+        }
+    }
+
+    #[test]
+    fn test_erase_prelude() {
+        let input = r"
+        let fact: Int -> Int =
+            fix [Int] [Int] (λf: Int -> Int. λn: Int. if n < 1 then 1 else n * f (n - 1)) in
+        let one: Int = toint (succ zero) in
+        let xs: List Int = map [Int] [Int] fact (Cons [Int] 2 (Cons [Int] one (Nil [Int]))) in
+        let b: Boolean = first [Boolean] [Int] (pair [Boolean] [Int] (iszero zero) 0) in
+        if b [Bool] true false then sum xs else 0
+        ";
+        let (expr, normal, sort) = rename(prelude::load(input).unwrap());
+
+        // Applicative order unfolds the erased `fix` forever.
+        for strategy in [untyped::Norm::NOR, untyped::Norm::CBN, untyped::Norm::CBV] {
+            let erased = evaluate(strategy, erase(&expr), sort).to_string();
+            assert_eq!(erased, normal, "{:?}", strategy);
+        }
+    }
+}
@@ -1,3 +1,6 @@
 pub mod checker;
+pub mod erasure;
+pub mod loader;
 pub mod parser;
-// pub mod reduction;
+pub mod prelude;
+pub mod reduction;
@@ -0,0 +1,290 @@
+use crate::checker::{
+    self,
+    context::Context,
+    conversion::{alpha_conversion_expr, Names},
+    errors::TypeError,
+};
+use crate::parser::{
+    self,
+    error::ParserError,
+    parsetree::{Expr, KindAlias, LetAlias, TypeAlias},
+    symbol::Symbol,
+};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Why a file could not be loaded, naming the file the error comes from.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, io::Error),
+    Parser(PathBuf, ParserError),
+    Type(PathBuf, TypeError),
+    Cycle(Vec<PathBuf>),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            LoadError::Parser(path, error) => write!(f, "{}: {}", path.display(), error),
+            LoadError::Type(path, error) => write!(f, "{}: {}", path.display(), error),
+            LoadError::Cycle(paths) => {
+                let paths: Vec<String> =
+                    paths.iter().map(|path| path.display().to_string()).collect();
+                write!(f, "import cycle: {}", paths.join(" -> "))
+            }
+        }
+    }
+}
+
+struct Module {
+    // The declarations of the file, already resolved against its imports.
+    body: Expr,
+    names: Vec<Symbol>,
+    types: Vec<Symbol>,
+    // The declarations of the file and of everything it depends on, as the checker saw them.
+    context: Context,
+    // Every module the file depends on, dependencies first, ending with the file itself.
+    order: Vec<PathBuf>,
+}
+
+// The terms and types a module binds at the top level, which is what its importers can see.
+// Kind aliases are never renamed, so they need no entry.
+fn exports(body: &Expr, names: &mut Vec<Symbol>, types: &mut Vec<Symbol>) {
+    match body {
+        Expr::LetAlias(LetAlias { name, body, .. }) => {
+            names.push(name.clone());
+            exports(body, names, types)
+        }
+        Expr::TypeAlias(TypeAlias { name, body, .. }) => {
+            types.push(name.clone());
+            exports(body, names, types)
+        }
+        Expr::KindAlias(KindAlias { body, .. }) => exports(body, names, types),
+        _ => (),
+    }
+}
+
+// Puts `rest` in the scope of the declarations of `body`, dropping whatever `body` ends with.
+fn splice(body: &Expr, rest: Expr) -> Expr {
+    match body {
+        Expr::LetAlias(alias) => {
+            let body = Box::new(splice(&alias.body, rest));
+            Expr::LetAlias(LetAlias { body, ..alias.clone() })
+        }
+        Expr::TypeAlias(alias) => {
+            let body = Box::new(splice(&alias.body, rest));
+            Expr::TypeAlias(TypeAlias { body, ..alias.clone() })
+        }
+        Expr::KindAlias(alias) => {
+            let body = Box::new(splice(&alias.body, rest));
+            Expr::KindAlias(KindAlias { body, ..alias.clone() })
+        }
+        _ => rest,
+    }
+}
+
+/// Loads programs split across files. Imports are resolved relative to the importing file and
+/// every module is parsed, resolved and checked once, however many files import it.
+///
+/// Names are resolved while loading: the result of [`Loader::load`] is already alpha converted
+/// and goes straight to the checker.
+#[derive(Default)]
+pub struct Loader {
+    modules: HashMap<PathBuf, Module>,
+    loading: Vec<PathBuf>,
+    count: usize,
+}
+
+impl Loader {
+    pub fn new() -> Loader {
+        Loader::default()
+    }
+
+    /// The program in `path`, in the scope of every module it imports.
+    pub fn load(&mut self, path: &Path) -> Result<Expr, LoadError> {
+        let path = self.load_module(path)?;
+        let module = &self.modules[&path];
+
+        Ok(self.link(&module.order[..module.order.len() - 1], module.body.clone()))
+    }
+
+    fn link(&self, order: &[PathBuf], body: Expr) -> Expr {
+        order.iter().rev().fold(body, |body, path| splice(&self.modules[path].body, body))
+    }
+
+    fn load_module(&mut self, path: &Path) -> Result<PathBuf, LoadError> {
+        let path = fs::canonicalize(path).map_err(|error| LoadError::Io(path.into(), error))?;
+
+        if self.modules.contains_key(&path) {
+            return Ok(path);
+        }
+
+        if let Some(start) = self.loading.iter().position(|loading| *loading == path) {
+            let mut cycle = self.loading[start..].to_vec();
+            cycle.push(path);
+            return Err(LoadError::Cycle(cycle));
+        }
+
+        self.loading.push(path.clone());
+        let module = self.resolve(&path);
+        self.loading.pop();
+
+        self.modules.insert(path.clone(), module?);
+        Ok(path)
+    }
+
+    fn resolve(&mut self, path: &Path) -> Result<Module, LoadError> {
+        let source = fs::read_to_string(path).map_err(|error| LoadError::Io(path.into(), error))?;
+        let module = parser::module_from_string(&source)
+            .map_err(|error| LoadError::Parser(path.into(), error))?;
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut context = Names { count: self.count, ..Names::new() };
+        let mut checked = Context::new();
+        let mut order = Vec::new();
+
+        for import in module.imports {
+            let dependency = self.load_module(&directory.join(&import.path))?;
+            let dependency = &self.modules[&dependency];
+
+            for path in &dependency.order {
+                if !order.contains(path) {
+                    order.push(path.clone());
+                }
+            }
+
+            let qualify = |export: &Symbol| match &import.qualifier {
+                Some(qualifier) => format!("{}.{}", qualifier, export.name),
+                None => export.name.clone(),
+            };
+            for export in &dependency.names {
+                context.names.insert(qualify(export), export.clone());
+            }
+            for export in &dependency.types {
+                context.types.insert(qualify(export), export.clone());
+            }
+            checked.extend(&dependency.context);
+        }
+
+        let body = alpha_conversion_expr(&mut context, &module.body)
+            .map_err(|error| LoadError::Type(path.into(), error))?;
+        self.count = context.count;
+
+        checker::check_module(&mut checked, &body)
+            .map_err(|error| LoadError::Type(path.into(), error))?;
+        order.push(path.into());
+
+        let (mut names, mut types) = (Vec::new(), Vec::new());
+        exports(&body, &mut names, &mut types);
+
+        Ok(Module { body, names, types, context: checked, order })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LoadError, Loader};
+    use crate::checker;
+    use crate::reduction::{reduce, Norm};
+
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn write(directory: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join("system-f-omega").join(directory);
+
+        for (name, source) in files {
+            let path = directory.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+
+        directory
+    }
+
+    fn eval(path: &Path) -> String {
+        let expr = Loader::new().load(path).unwrap();
+        checker::type_of(expr.clone()).unwrap();
+        reduce(Norm::NOR, expr, None).to_string()
+    }
+
+    #[test]
+    fn test_load_sample() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("samples/sum.sfo");
+
+        assert_eq!(eval(&path), "6")
+    }
+
+    #[test]
+    fn test_load_qualified() {
+        let directory = write(
+            "qualified",
+            &[
+                ("Ints.sfo", "def zero: Int = 0 def inc: Int -> Int = λx: Int. x + 1"),
+                ("Bools.sfo", "def zero: Bool = false"),
+                ("lib/twice.sfo", "import \"../Ints.sfo\" def twice: Int = inc (inc zero)"),
+                ("main.sfo", "import Ints as I import Bools import \"lib/twice.sfo\"
+                    main = if zero then 0 else I.inc twice"),
+            ],
+        );
+
+        assert_eq!(eval(&directory.join("main.sfo")), "3")
+    }
+
+    #[test]
+    fn test_load_qualified_type() {
+        let directory = write(
+            "qualified-type",
+            &[
+                ("Nums.sfo", "type Num: * = Int def inc: Num -> Num = λn: Num. n + 1"),
+                ("main.sfo", "import Nums as N def one: N.Num = N.inc 0 main = (λx: N.Num.x) one"),
+            ],
+        );
+
+        assert_eq!(eval(&directory.join("main.sfo")), "1")
+    }
+
+    #[test]
+    fn test_load_hides_qualified_names() {
+        let directory = write(
+            "hidden",
+            &[("Ints.sfo", "def one: Int = 1"), ("main.sfo", "import Ints as I main = one")],
+        );
+        let error = Loader::new().load(&directory.join("main.sfo")).unwrap_err();
+
+        assert!(matches!(error, LoadError::Type(path, _) if path.ends_with("main.sfo")))
+    }
+
+    #[test]
+    fn test_load_cycle() {
+        let directory = write(
+            "cycle",
+            &[
+                ("a.sfo", "import \"b.sfo\" def a: Int = 1"),
+                ("b.sfo", "import \"a.sfo\" def b: Int = 2"),
+            ],
+        );
+        let error = Loader::new().load(&directory.join("a.sfo")).unwrap_err();
+
+        assert!(matches!(error, LoadError::Cycle(paths) if paths.len() == 3))
+    }
+
+    #[test]
+    fn test_load_error_names_file() {
+        let directory = write(
+            "error",
+            &[
+                ("Wrong.sfo", "def one: Int = true"),
+                ("main.sfo", "import Wrong main = one"),
+            ],
+        );
+        let error = Loader::new().load(&directory.join("main.sfo")).unwrap_err();
+
+        assert!(error.to_string().contains("Wrong.sfo"))
+    }
+}
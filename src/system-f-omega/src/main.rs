@@ -1,23 +1,38 @@
 use system_f_omega::checker;
 use system_f_omega::checker::conversion::{alpha_conversion_expr, Names};
-use system_f_omega::parser;
-// use system_f_omega::reduction;
+use system_f_omega::loader::Loader;
+use system_f_omega::parser::parsetree::Expr;
+use system_f_omega::prelude;
+use system_f_omega::reduction;
 
-fn main() {
-    // type Id: * = ∀A: *. (A -> A) in
-    // let id: Id = λA: *. λx: A. x in
-    // id [Int] 69420
+use std::path::Path;
+use std::process;
+
+fn example() -> Expr {
     let input = r"
-        type Fix: * = ∀A: *. ∀B: *. (A -> B) -> A -> B in
-        let fix: Fix = λf: A -> B. λx : A. f x in 
-        fix
+        def fact: Int -> Int =
+            fix [Int] [Int] (λf: Int -> Int. λn: Int. if n < 1 then 1 else n * f (n - 1))
+        def facts: List Int = map [Int] [Int] fact (Cons [Int] 4 (Cons [Int] 5 (Nil [Int])))
+        main = sum facts
     ";
 
-    let expr_parsed = parser::from_string(input).unwrap();
+    let expr_parsed = prelude::load(input).unwrap();
     let mut context = Names::new();
-    let alpha_terms = alpha_conversion_expr(&mut context, &expr_parsed).unwrap();
+    alpha_conversion_expr(&mut context, &expr_parsed).unwrap()
+}
+
+// Runs the program in the file given as argument, or the example above without one.
+fn main() {
+    let alpha_terms = match std::env::args().nth(1) {
+        None => example(),
+        Some(path) => Loader::new().load(Path::new(&path)).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1)
+        }),
+    };
 
     let expr_typed = checker::type_of(alpha_terms.clone()).unwrap();
+    let expr_reduced = reduction::reduce(reduction::Norm::NOR, alpha_terms, None);
 
-    println!("{}", expr_typed)
+    println!("({}) : {}", expr_reduced, expr_typed)
 }
@@ -0,0 +1,450 @@
+use super::{
+    error::ParserError,
+    lexer::tokens::Token,
+    location::Range,
+    macros::{consume, match_token},
+    parsetree::{
+        Abs, Anno, App, Arrow, Exists, Expr, Forall, Fst, Kind, KindArrow, LetAlias, Pair, Product,
+        Rec, Snd, Star, Sum, TAbs, TApp, TVar, TyAbs, TyAnno, TyApp, Type, TypeAlias, Var,
+    },
+    state::Parser,
+    symbol::Symbol,
+};
+
+// `data D (A: K) = C T.. | ..` is sugar for Böhm–Berarducci encodings: D becomes the type
+// operator `λA: K. ∀R: *. (T.. -> R) -> .. -> R`, every constructor C becomes a term that
+// picks its own case and `foldD` exposes the fold over the recursive occurrences of `D A`.
+// `matchD` only looks at the outermost constructor, and hands the cases their fields unfolded.
+struct Constructor {
+    name: Symbol,
+    fields: Vec<Type>,
+    range: Range,
+}
+
+struct Data {
+    name: Symbol,
+    params: Vec<(Symbol, Kind)>,
+    constructors: Vec<Constructor>,
+    result: Symbol,
+    range: Range,
+}
+
+fn star() -> Kind {
+    Kind::Star(Star {})
+}
+
+fn tvar(name: &Symbol) -> Type {
+    Type::TVar(TVar { value: name.clone() })
+}
+
+// Whether the type variable `name` occurs free in `ty`.
+fn mentions(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::TInt(_) | Type::TBool(_) | Type::TUnit(_) => false,
+        Type::TVar(TVar { value }) => value.name == name,
+        Type::Arrow(Arrow { left: fst, right: snd })
+        | Type::Sum(Sum { left: fst, right: snd })
+        | Type::Product(Product { fst, snd })
+        | Type::TyApp(TyApp { lambda: fst, argm: snd }) => {
+            mentions(fst, name) || mentions(snd, name)
+        }
+        Type::Forall(Forall { param, body, .. })
+        | Type::Exists(Exists { param, body, .. })
+        | Type::Rec(Rec { param, body, .. })
+        | Type::TyAbs(TyAbs { param, body, .. }) => param.name != name && mentions(body, name),
+        Type::TyAnno(TyAnno { ty, .. }) => mentions(ty, name),
+    }
+}
+
+fn arrows(params: &[Type], result: Type) -> Type {
+    params.iter().rev().fold(result, |right, left| {
+        Type::Arrow(Arrow { left: Box::new(left.clone()), right: Box::new(right) })
+    })
+}
+
+impl Data {
+    fn var(&self, name: &Symbol) -> Expr {
+        Expr::Var(Var { value: name.clone(), range: self.range })
+    }
+
+    fn app(&self, lambda: Expr, argm: Expr) -> Expr {
+        Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range: self.range })
+    }
+
+    fn abs(&self, param: &Symbol, param_ty: &Type, body: Expr) -> Expr {
+        Expr::Abs(Abs {
+            param: param.clone(),
+            param_ty: param_ty.clone(),
+            body: Box::new(body),
+            range: self.range,
+        })
+    }
+
+    fn tabs(&self, param: &Symbol, param_ty: &Kind, body: Expr) -> Expr {
+        Expr::TAbs(TAbs {
+            param: param.clone(),
+            param_ty: param_ty.clone(),
+            body: Box::new(body),
+            range: self.range,
+        })
+    }
+
+    // `D A..`, the type every constructor builds.
+    fn own_type(&self) -> Type {
+        self.params.iter().fold(tvar(&self.name), |lambda, (param, _)| {
+            Type::TyApp(TyApp { lambda: Box::new(lambda), argm: Box::new(tvar(param)) })
+        })
+    }
+
+    // Constructor names are distinct and capitalized, so the cases cannot clash with each other
+    // or with the fields.
+    fn case_name(constructor: &Constructor) -> Symbol {
+        Symbol::new(format!("on{}", constructor.name.name))
+    }
+
+    fn field_name(index: usize) -> Symbol {
+        Symbol::new(format!("x{}", index))
+    }
+
+    // Recursive fields are already folded, so they show up as R in the case.
+    fn case_type(&self, constructor: &Constructor) -> Type {
+        let own = self.own_type();
+        let fields: Vec<Type> = constructor
+            .fields
+            .iter()
+            .map(|field| if *field == own { tvar(&self.result) } else { field.clone() })
+            .collect();
+
+        arrows(&fields, tvar(&self.result))
+    }
+
+    fn case_types(&self) -> Vec<Type> {
+        self.constructors.iter().map(|constructor| self.case_type(constructor)).collect()
+    }
+
+    fn quantify(&self, body: Type) -> Type {
+        self.params.iter().rev().fold(body, |body, (param, param_ty)| {
+            Type::Forall(Forall {
+                param: param.clone(),
+                param_ty: param_ty.clone(),
+                body: Box::new(body),
+            })
+        })
+    }
+
+    fn generalize(&self, body: Expr) -> Expr {
+        self.params
+            .iter()
+            .rev()
+            .fold(body, |body, (param, param_ty)| self.tabs(param, param_ty, body))
+    }
+
+    fn encoding(&self) -> Type {
+        let body = Type::Forall(Forall {
+            param: self.result.clone(),
+            param_ty: star(),
+            body: Box::new(arrows(&self.case_types(), tvar(&self.result))),
+        });
+        let ty = self.params.iter().rev().fold(body, |body, (param, param_ty)| {
+            Type::TyAbs(TyAbs {
+                param: param.clone(),
+                param_ty: param_ty.clone(),
+                body: Box::new(body),
+            })
+        });
+        let anno = self.params.iter().rev().fold(star(), |right, (_, left)| {
+            Kind::KindArrow(KindArrow { left: Box::new(left.clone()), right: Box::new(right) })
+        });
+
+        Type::TyAnno(TyAnno { ty: Box::new(ty), anno })
+    }
+
+    fn constructor(&self, constructor: &Constructor) -> Expr {
+        let own = self.own_type();
+        let cases: Vec<Symbol> = self.constructors.iter().map(Self::case_name).collect();
+
+        let argms = constructor.fields.iter().enumerate().map(|(index, field)| {
+            let value = self.var(&Self::field_name(index));
+
+            if *field == own {
+                let inst = Expr::TApp(TApp {
+                    lambda: Box::new(value),
+                    argm: tvar(&self.result),
+                    range: self.range,
+                });
+                cases.iter().fold(inst, |fun, case| self.app(fun, self.var(case)))
+            } else {
+                value
+            }
+        });
+        let body = argms.fold(self.var(&Self::case_name(constructor)), |fun, argm| {
+            self.app(fun, argm)
+        });
+
+        let body = cases
+            .iter()
+            .zip(self.case_types())
+            .rev()
+            .fold(body, |body, (case, case_ty)| self.abs(case, &case_ty, body));
+        let body = self.tabs(&self.result, &star(), body);
+        let body = constructor.fields.iter().enumerate().rev().fold(body, |body, (index, ty)| {
+            self.abs(&Self::field_name(index), ty, body)
+        });
+
+        Expr::Anno(Anno {
+            expr: Box::new(self.generalize(body)),
+            anno: self.quantify(arrows(&constructor.fields, own)),
+            range: self.range,
+        })
+    }
+
+    fn eliminator(&self) -> Expr {
+        let own = self.own_type();
+        let value = Symbol::new(String::from("v"));
+
+        let body = Expr::TApp(TApp {
+            lambda: Box::new(self.var(&value)),
+            argm: tvar(&self.result),
+            range: self.range,
+        });
+        let body = self.tabs(&self.result, &star(), self.abs(&value, &own, body));
+
+        let anno = Type::Forall(Forall {
+            param: self.result.clone(),
+            param_ty: star(),
+            body: Box::new(arrows(&[own], arrows(&self.case_types(), tvar(&self.result)))),
+        });
+
+        Expr::Anno(Anno {
+            expr: Box::new(self.generalize(body)),
+            anno: self.quantify(anno),
+            range: self.range,
+        })
+    }
+
+    // The case of `matchD` run by the fold, which pairs the value rebuilt from the unfolded
+    // fields with the result of the case given those fields.
+    fn step(&self, constructor: &Constructor, pair_ty: &Type) -> Expr {
+        let own = self.own_type();
+        let fields: Vec<Expr> = constructor
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let value = self.var(&Self::field_name(index));
+
+                if *field == own {
+                    Expr::Fst(Fst { pair: Box::new(value), range: self.range })
+                } else {
+                    value
+                }
+            })
+            .collect();
+
+        let inst = self.params.iter().fold(self.var(&constructor.name), |lambda, (param, _)| {
+            Expr::TApp(TApp { lambda: Box::new(lambda), argm: tvar(param), range: self.range })
+        });
+        let rebuilt = fields.iter().cloned().fold(inst, |fun, argm| self.app(fun, argm));
+        let result = fields
+            .into_iter()
+            .fold(self.var(&Self::case_name(constructor)), |fun, argm| self.app(fun, argm));
+
+        let body = Expr::Pair(Pair {
+            fst: Box::new(rebuilt),
+            snd: Box::new(result),
+            range: self.range,
+        });
+        constructor.fields.iter().enumerate().rev().fold(body, |body, (index, field)| {
+            let ty = if *field == own { pair_ty } else { field };
+            self.abs(&Self::field_name(index), ty, body)
+        })
+    }
+
+    fn matcher(&self) -> Expr {
+        let own = self.own_type();
+        let value = Symbol::new(String::from("v"));
+        let pair_ty = Type::Product(Product {
+            fst: Box::new(own.clone()),
+            snd: Box::new(tvar(&self.result)),
+        });
+        let case_types: Vec<Type> = self
+            .constructors
+            .iter()
+            .map(|constructor| arrows(&constructor.fields, tvar(&self.result)))
+            .collect();
+
+        let fold = Expr::TApp(TApp {
+            lambda: Box::new(self.var(&value)),
+            argm: pair_ty.clone(),
+            range: self.range,
+        });
+        let fold = self.constructors.iter().fold(fold, |fun, constructor| {
+            self.app(fun, self.step(constructor, &pair_ty))
+        });
+        let body = Expr::Snd(Snd { pair: Box::new(fold), range: self.range });
+
+        let cases = self.constructors.iter().map(Self::case_name).zip(&case_types);
+        let body = cases.rev().fold(body, |body, (case, ty)| self.abs(&case, ty, body));
+        let body = self.tabs(&self.result, &star(), self.abs(&value, &own, body));
+
+        let anno = Type::Forall(Forall {
+            param: self.result.clone(),
+            param_ty: star(),
+            body: Box::new(arrows(&[own], arrows(&case_types, tvar(&self.result)))),
+        });
+
+        Expr::Anno(Anno {
+            expr: Box::new(self.generalize(body)),
+            anno: self.quantify(anno),
+            range: self.range,
+        })
+    }
+
+    fn check(&self) -> Result<(), ParserError> {
+        let own = self.own_type();
+
+        for (index, constructor) in self.constructors.iter().enumerate() {
+            let name = &constructor.name;
+            if self.constructors[..index].iter().any(|other| other.name == *name) {
+                return Err(ParserError::DuplicateConstructor(name.name.clone(), constructor.range));
+            }
+
+            // Only fields that are exactly `D A..` are folded.
+            let nested = |field: &Type| *field != own && mentions(field, &self.name.name);
+            if constructor.fields.iter().any(nested) {
+                return Err(ParserError::NestedRecursion(self.name.name.clone(), constructor.range));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn desugar(self, body: Expr) -> Expr {
+        let matcher = Symbol::new(format!("match{}", self.name.name));
+        let body = Expr::LetAlias(LetAlias {
+            name: matcher,
+            value: Box::new(self.matcher()),
+            body: Box::new(body),
+            range: self.range,
+        });
+
+        let fold = Symbol::new(format!("fold{}", self.name.name));
+        let body = Expr::LetAlias(LetAlias {
+            name: fold,
+            value: Box::new(self.eliminator()),
+            body: Box::new(body),
+            range: self.range,
+        });
+
+        let body = self.constructors.iter().rev().fold(body, |body, constructor| {
+            Expr::LetAlias(LetAlias {
+                name: constructor.name.clone(),
+                value: Box::new(self.constructor(constructor)),
+                body: Box::new(body),
+                range: self.range,
+            })
+        });
+
+        Expr::TypeAlias(TypeAlias {
+            name: self.name.clone(),
+            value: self.encoding(),
+            body: Box::new(body),
+            range: self.range,
+        })
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn parse_data_param(&mut self) -> Result<(Symbol, Kind), ParserError> {
+        consume!(self, Token::LParen)?;
+        let (name, _) = consume!(self, Token::TVar(name) => name.clone())?;
+
+        consume!(self, Token::Colon)?;
+        let kind = self.parse_kind()?;
+        consume!(self, Token::RParen)?;
+
+        Ok((Symbol::new(name), kind))
+    }
+
+    fn parse_constructor(&mut self) -> Result<Constructor, ParserError> {
+        let (name, range) = consume!(self, Token::TVar(name) => name.clone())?;
+        let mut fields = Vec::new();
+
+        while let Some(field) = self.try_single(|state| state.parse_simple_type())? {
+            fields.push(field);
+        }
+
+        Ok(Constructor { name: Symbol::new(name), fields, range })
+    }
+
+    pub fn parse_data(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Data)?;
+        let (name, _) = consume!(self, Token::TVar(name) => name.clone())?;
+        let mut params = Vec::new();
+
+        while let Some(param) = self.try_single(|state| state.parse_data_param())? {
+            params.push(param);
+        }
+
+        consume!(self, Token::Equal)?;
+        let mut constructors = vec![self.parse_constructor()?];
+
+        while let Token::Pipe = self.get() {
+            consume!(self, Token::Pipe)?;
+            constructors.push(self.parse_constructor()?);
+        }
+
+        let body = self.parse_scope(true)?;
+
+        // The result variable of the encoding must not capture a parameter or a type the fields
+        // refer to.
+        let mut result = String::from("R");
+        while params.iter().any(|(param, _)| param.name == result)
+            || constructors.iter().flat_map(|c| &c.fields).any(|field| mentions(field, &result))
+        {
+            result.push('\'');
+        }
+
+        let data = Data {
+            name: Symbol::new(name),
+            params,
+            constructors,
+            result: Symbol::new(result),
+            range: range.mix(body.range()),
+        };
+
+        data.check()?;
+        Ok(data.desugar(body))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::checker::{self, conversion::alpha_conversion_expr, conversion::Names};
+    use crate::parser;
+
+    #[test]
+    fn test_check_data_constructor_field() {
+        let input = r"
+        data List (A: *) = Nil | Cons A (List A) in
+        Cons [Int] true (Nil [Int])
+        ";
+        let expr = parser::from_string(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+
+        assert!(checker::type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_parse_data_nested_recursion() {
+        let inputs = [
+            r"data Rose = Node (Int * Rose) | Leaf in 0",
+            r"data List (A: *) = Nil | Cons A (List Int) in 0",
+            r"data Foo = Bar | Bar in 0",
+        ];
+
+        for input in inputs {
+            assert!(parser::from_string(input).is_err())
+        }
+    }
+}
@@ -7,6 +7,8 @@ use super::location::Range;
 pub enum ParserError {
     UnexpectedToken(Token, Range),
     UnexpectedEOF,
+    DuplicateConstructor(String, Range),
+    NestedRecursion(String, Range),
 }
 
 impl fmt::Display for ParserError {
@@ -16,6 +18,12 @@ impl fmt::Display for ParserError {
                 write!(f, "UnexpectedToken {:?}, todo: error message", token)
             }
             ParserError::UnexpectedEOF => write!(f, "UnexpectedEof, todo: error message"),
+            ParserError::DuplicateConstructor(name, _) => {
+                write!(f, "constructor {} is declared twice", name)
+            }
+            ParserError::NestedRecursion(name, _) => {
+                write!(f, "{} can only be a field itself, it cannot occur inside one", name)
+            }
         }
     }
 }
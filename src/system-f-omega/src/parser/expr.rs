@@ -4,32 +4,70 @@ use super::{
     location::Range,
     macros::{consume, match_token},
     parsetree::{
-        Abs, Anno, App, Arrow, Expr, Forall, Int, Kind, KindAlias, KindArrow, KindVar, LetAlias,
-        Star, TAbs, TApp, TInt, TVar, TyAbs, TyAnno, TyApp, Type, TypeAlias, Var,
+        Abs, Anno, App, Arrow, BinOp, Bool, Case, Exists, Expr, Fold, Forall, Fst, If, Inl, Inr,
+        Int, Kind, KindAlias, KindArrow, KindForall, KindVar, LetAlias, Operator, Pack, Pair,
+        Product, Rec, Snd, Star, Sum, TAbs, TApp, TBool, TInt, TUnit, TVar, TyAbs, TyAnno, TyApp,
+        Type, TypeAlias, Unfold, Unit, Unpack, Var,
     },
     state::Parser,
     symbol::Symbol,
 };
 
+fn make_binop(op: Operator, left: Expr, right: Expr) -> Expr {
+    let range = left.range().mix(right.range());
+
+    Expr::BinOp(BinOp {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+        range,
+    })
+}
+
 impl<'a> Parser<'a> {
     pub fn parse_variable_expr(&mut self) -> Result<Expr, ParserError> {
-        let (token, range) = consume!(self, Token::Variable(var) => var.clone())?;
+        let (token, range) =
+            consume!(self, Token::Variable(var) | Token::TVar(var) => var.clone())?;
+        let (token, range) = self.parse_qualified(token, range)?;
         let symbol = Symbol::new(token);
 
         Ok(Expr::Var(Var { value: symbol, range }))
     }
 
     pub fn parse_number_expr(&mut self) -> Result<Expr, ParserError> {
-        let (token, range) = consume!(self, Token::Number(num) => num.clone())?;
+        let (token, range) = consume!(self, Token::Number(num) => *num)?;
 
         Ok(Expr::Int(Int { value: token, range }))
     }
 
+    pub fn parse_bool_expr(&mut self) -> Result<Expr, ParserError> {
+        let value = matches!(self.get(), Token::True);
+        let (_, range) = consume!(self, Token::True | Token::False)?;
+
+        Ok(Expr::Bool(Bool { value, range }))
+    }
+
+    pub fn parse_pair(&mut self) -> Result<Expr, ParserError> {
+        let (_, range_l) = consume!(self, Token::LBrace)?;
+        let fst = self.parse_expr()?;
+        consume!(self, Token::Comma)?;
+        let snd = self.parse_expr()?;
+        let (_, range_r) = consume!(self, Token::RBrace)?;
+
+        Ok(Expr::Pair(Pair {
+            fst: Box::new(fst),
+            snd: Box::new(snd),
+            range: range_l.mix(range_r),
+        }))
+    }
+
     pub fn parse_atom(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
             Token::LParen => self.parse_parens_expr(),
-            Token::Variable(_) => self.parse_variable_expr(),
+            Token::LBrace => self.parse_pair(),
+            Token::Variable(_) | Token::TVar(_) => self.parse_variable_expr(),
             Token::Number(_) => self.parse_number_expr(),
+            Token::True | Token::False => self.parse_bool_expr(),
             _ => self.fail(),
         }
     }
@@ -129,8 +167,60 @@ impl<'a> Parser<'a> {
         Ok(head)
     }
 
+    pub fn parse_multiplicative(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_application()?;
+
+        loop {
+            let op = match self.get() {
+                Token::Star => Operator::Mul,
+                Token::Slash => Operator::Div,
+                _ => return Ok(left),
+            };
+
+            self.advance()?;
+            let right = self.parse_application()?;
+            left = make_binop(op, left, right);
+        }
+    }
+
+    pub fn parse_additive(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_multiplicative()?;
+
+        loop {
+            let op = match self.get() {
+                Token::Plus => Operator::Add,
+                Token::Minus => Operator::Sub,
+                _ => return Ok(left),
+            };
+
+            self.advance()?;
+            let right = self.parse_multiplicative()?;
+            left = make_binop(op, left, right);
+        }
+    }
+
+    pub fn parse_comparison(&mut self) -> Result<Expr, ParserError> {
+        let left = self.parse_additive()?;
+
+        let op = match self.get() {
+            Token::EqualEqual => Operator::Eq,
+            Token::Less => Operator::Lt,
+            _ => return Ok(left),
+        };
+
+        self.advance()?;
+        let right = self.parse_additive()?;
+
+        Ok(make_binop(op, left, right))
+    }
+
     pub fn parse_parens_expr(&mut self) -> Result<Expr, ParserError> {
-        consume!(self, Token::LParen)?;
+        let (_, range_l) = consume!(self, Token::LParen)?;
+
+        if let Token::RParen = self.get() {
+            let (_, range_r) = consume!(self, Token::RParen)?;
+            return Ok(Expr::Unit(Unit { range: range_l.mix(range_r) }));
+        }
 
         let expr = self.parse_expr()?;
         consume!(self, Token::RParen)?;
@@ -138,8 +228,30 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
 
+    fn at_declaration(&self) -> bool {
+        matches!(self.get(), Token::Def | Token::Type | Token::Kind | Token::Data | Token::Main)
+    }
+
+    // Declarations at the top level of a program scope over the ones after them, down to the
+    // `main = e` closing it, or to the end of a module without one. Everywhere else the body
+    // follows `in`.
+    pub fn parse_scope(&mut self, top_level: bool) -> Result<Expr, ParserError> {
+        if let (Token::Eof, true) = (self.get(), top_level) {
+            let (_, range) = consume!(self, Token::Eof)?;
+            return Ok(Expr::Unit(Unit { range }));
+        }
+
+        if top_level && self.at_declaration() {
+            return self.parse_program();
+        }
+
+        consume!(self, Token::In)?;
+        self.parse_expr()
+    }
+
     pub fn parse_let_alias(&mut self) -> Result<Expr, ParserError> {
-        let (_, range) = consume!(self, Token::Let)?;
+        let declaration = matches!(self.get(), Token::Def);
+        let (_, range) = consume!(self, Token::Let | Token::Def)?;
         let (name, _) = consume!(self, Token::Variable(name) => name.clone())?;
         let symbol = Symbol::new(name);
 
@@ -149,8 +261,10 @@ impl<'a> Parser<'a> {
         consume!(self, Token::Equal)?;
         let value = self.parse_expr()?;
 
-        consume!(self, Token::In)?;
-        let body = self.parse_expr()?;
+        if declaration && !(self.at_declaration() || matches!(self.get(), Token::Eof)) {
+            return self.fail();
+        }
+        let body = self.parse_scope(declaration)?;
 
         Ok(Expr::LetAlias(LetAlias {
             name: symbol,
@@ -174,9 +288,7 @@ impl<'a> Parser<'a> {
 
         consume!(self, Token::Equal)?;
         let value = self.parse_type()?;
-
-        consume!(self, Token::In)?;
-        let body = self.parse_expr()?;
+        let body = self.parse_scope(true)?;
 
         Ok(Expr::TypeAlias(TypeAlias {
             name: symbol,
@@ -193,9 +305,7 @@ impl<'a> Parser<'a> {
 
         consume!(self, Token::Equal)?;
         let value = self.parse_kind()?;
-
-        consume!(self, Token::In)?;
-        let body = self.parse_expr()?;
+        let body = self.parse_scope(true)?;
 
         Ok(Expr::KindAlias(KindAlias {
             name: symbol,
@@ -218,7 +328,7 @@ impl<'a> Parser<'a> {
     pub fn parse_annot_lambda(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
             Token::Lambda => self.parse_abs(),
-            _ => self.parse_application(),
+            _ => self.parse_comparison(),
         }
     }
 
@@ -229,20 +339,198 @@ impl<'a> Parser<'a> {
         }
     }
 
+    pub fn parse_if(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::If)?;
+        let cond = self.parse_expr()?;
+
+        consume!(self, Token::Then)?;
+        let then_branch = self.parse_expr()?;
+
+        consume!(self, Token::Else)?;
+        let else_branch = self.parse_expr()?;
+        let endr = else_branch.range();
+
+        Ok(Expr::If(If {
+            cond: Box::new(cond),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_fst(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Fst)?;
+        let pair = self.parse_expr()?;
+        let endr = pair.range();
+
+        Ok(Expr::Fst(Fst { pair: Box::new(pair), range: range.mix(endr) }))
+    }
+
+    pub fn parse_snd(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Snd)?;
+        let pair = self.parse_expr()?;
+        let endr = pair.range();
+
+        Ok(Expr::Snd(Snd { pair: Box::new(pair), range: range.mix(endr) }))
+    }
+
+    pub fn parse_inl(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Inl)?;
+        let (_, right_ty) = self.parse_type_arg()?;
+        let value = self.parse_expr()?;
+        let endr = value.range();
+
+        Ok(Expr::Inl(Inl {
+            value: Box::new(value),
+            right_ty,
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_inr(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Inr)?;
+        let (_, left_ty) = self.parse_type_arg()?;
+        let value = self.parse_expr()?;
+        let endr = value.range();
+
+        Ok(Expr::Inr(Inr {
+            value: Box::new(value),
+            left_ty,
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_fold(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Fold)?;
+        let (_, ty) = self.parse_type_arg()?;
+        let value = self.parse_expr()?;
+        let endr = value.range();
+
+        Ok(Expr::Fold(Fold { ty, value: Box::new(value), range: range.mix(endr) }))
+    }
+
+    pub fn parse_unfold(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Unfold)?;
+        let value = self.parse_expr()?;
+        let endr = value.range();
+
+        Ok(Expr::Unfold(Unfold { value: Box::new(value), range: range.mix(endr) }))
+    }
+
+    pub fn parse_pack(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Pack)?;
+
+        consume!(self, Token::LBracket)?;
+        let witness = self.parse_type()?;
+
+        consume!(self, Token::Comma)?;
+        let value = self.parse_expr()?;
+
+        consume!(self, Token::RBracket)?;
+        let (_, endr) = consume!(self, Token::As)?;
+        let ty = self.parse_type()?;
+
+        Ok(Expr::Pack(Pack {
+            witness,
+            value: Box::new(value),
+            ty,
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_unpack(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Unpack)?;
+        let package = self.parse_expr()?;
+
+        consume!(self, Token::As)?;
+        consume!(self, Token::LBracket)?;
+        let (ty_param, _) = consume!(self, Token::TVar(var) => var.clone())?;
+
+        consume!(self, Token::Comma)?;
+        let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+
+        consume!(self, Token::RBracket)?;
+        consume!(self, Token::In)?;
+        let body = self.parse_expr()?;
+        let endr = body.range();
+
+        Ok(Expr::Unpack(Unpack {
+            package: Box::new(package),
+            ty_param: Symbol::new(ty_param),
+            param: Symbol::new(param),
+            body: Box::new(body),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_case(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Case)?;
+        let scrutinee = self.parse_expr()?;
+        consume!(self, Token::Of)?;
+
+        consume!(self, Token::Inl)?;
+        let (left_param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+        consume!(self, Token::FatArrow)?;
+        let left_body = self.parse_expr()?;
+
+        consume!(self, Token::Pipe)?;
+        consume!(self, Token::Inr)?;
+        let (right_param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+        consume!(self, Token::FatArrow)?;
+        let right_body = self.parse_expr()?;
+        let endr = right_body.range();
+
+        Ok(Expr::Case(Case {
+            scrutinee: Box::new(scrutinee),
+            left_param: Symbol::new(left_param),
+            left_body: Box::new(left_body),
+            right_param: Symbol::new(right_param),
+            right_body: Box::new(right_body),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_main(&mut self) -> Result<Expr, ParserError> {
+        consume!(self, Token::Main)?;
+        consume!(self, Token::Equal)?;
+
+        self.parse_expr()
+    }
+
+    // A program is an expression, or declarations closed by `main = e`; `main` is accepted
+    // only here, where a declaration could start.
+    pub fn parse_program(&mut self) -> Result<Expr, ParserError> {
+        match self.get() {
+            Token::Main => self.parse_main(),
+            _ => self.parse_expr(),
+        }
+    }
+
     pub fn parse_expr(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
-            Token::Let => self.parse_let_alias(),
+            Token::If => self.parse_if(),
+            Token::Fst => self.parse_fst(),
+            Token::Snd => self.parse_snd(),
+            Token::Inl => self.parse_inl(),
+            Token::Inr => self.parse_inr(),
+            Token::Case => self.parse_case(),
+            Token::Fold => self.parse_fold(),
+            Token::Unfold => self.parse_unfold(),
+            Token::Pack => self.parse_pack(),
+            Token::Unpack => self.parse_unpack(),
+            Token::Let | Token::Def => self.parse_let_alias(),
             Token::Type => self.parse_type_alias(),
             Token::Kind => self.parse_kind_alias(),
+            Token::Data => self.parse_data(),
             _ => self.parse_annot_lambda(),
         }
     }
 
     pub fn parse_type_call(&mut self) -> Result<Type, ParserError> {
-        let func = self.parse_type()?;
+        let func = self.parse_simple_type()?;
         let mut args = Vec::new();
 
-        while let Some(arg) = self.try_single(|state| state.parse_type())? {
+        while let Some(arg) = self.try_single(|state| state.parse_simple_type())? {
             args.push(arg);
         }
 
@@ -278,8 +566,17 @@ impl<'a> Parser<'a> {
                 consume!(self, Token::TInt)?;
                 Ok(Type::TInt(TInt {}))
             }
+            Token::TBool => {
+                consume!(self, Token::TBool)?;
+                Ok(Type::TBool(TBool {}))
+            }
+            Token::TUnit => {
+                consume!(self, Token::TUnit)?;
+                Ok(Type::TUnit(TUnit {}))
+            }
             Token::TVar(_) => {
-                let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
+                let (token, range) = consume!(self, Token::TVar(var) => var.clone())?;
+                let (token, _) = self.parse_qualified(token, range)?;
                 let symbol = Symbol::new(token);
 
                 Ok(Type::TVar(TVar { value: symbol }))
@@ -301,6 +598,40 @@ impl<'a> Parser<'a> {
                     body: Box::new(body),
                 }))
             }
+            Token::Exists => {
+                consume!(self, Token::Exists)?;
+                let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
+                let symbol = Symbol::new(token);
+
+                consume!(self, Token::Colon)?;
+                let param_type = self.parse_kind()?;
+
+                consume!(self, Token::Dot)?;
+                let body = self.parse_type()?;
+
+                Ok(Type::Exists(Exists {
+                    param: symbol,
+                    param_ty: param_type,
+                    body: Box::new(body),
+                }))
+            }
+            Token::Mu => {
+                consume!(self, Token::Mu)?;
+                let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
+                let symbol = Symbol::new(token);
+
+                consume!(self, Token::Colon)?;
+                let param_type = self.parse_kind()?;
+
+                consume!(self, Token::Dot)?;
+                let body = self.parse_type()?;
+
+                Ok(Type::Rec(Rec {
+                    param: symbol,
+                    param_ty: param_type,
+                    body: Box::new(body),
+                }))
+            }
             Token::Lambda => {
                 consume!(self, Token::Lambda)?;
                 let (param, _) = consume!(self, Token::TVar(var) => var.clone())?;
@@ -318,7 +649,33 @@ impl<'a> Parser<'a> {
                     body: Box::new(body),
                 }))
             }
-            _ => self.parse_type_application(),
+            _ => self.fail(),
+        }
+    }
+
+    pub fn parse_product_type(&mut self) -> Result<Type, ParserError> {
+        let head = self.parse_type_application()?;
+
+        if let Token::Star = self.get() {
+            consume!(self, Token::Star)?;
+            let tail = self.parse_product_type()?;
+
+            Ok(Type::Product(Product { fst: Box::new(head), snd: Box::new(tail) }))
+        } else {
+            Ok(head)
+        }
+    }
+
+    pub fn parse_sum_type(&mut self) -> Result<Type, ParserError> {
+        let head = self.parse_product_type()?;
+
+        if let Token::Plus = self.get() {
+            consume!(self, Token::Plus)?;
+            let tail = self.parse_sum_type()?;
+
+            Ok(Type::Sum(Sum { left: Box::new(head), right: Box::new(tail) }))
+        } else {
+            Ok(head)
         }
     }
 
@@ -337,16 +694,8 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_type(&mut self) -> Result<Type, ParserError> {
-        if let Token::LParen = self.get() {
-            consume!(self, Token::LParen)?;
-            let head = self.parse_type()?;
-            consume!(self, Token::RParen)?;
-
-            self.parse_arrow_partial(head)
-        } else {
-            let head = self.parse_simple_type()?;
-            self.parse_arrow_partial(head)
-        }
+        let head = self.parse_sum_type()?;
+        self.parse_arrow_partial(head)
     }
 
     pub fn parse_arrow_kind(&mut self, head: Kind) -> Result<Kind, ParserError> {
@@ -369,12 +718,24 @@ impl<'a> Parser<'a> {
                 consume!(self, Token::Star)?;
                 Ok(Kind::Star(Star {}))
             }
-            Token::TVar(_) => {
-                let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
+            Token::TVar(_) | Token::Variable(_) => {
+                let (token, _) =
+                    consume!(self, Token::TVar(var) | Token::Variable(var) => var.clone())?;
                 let symbol = Symbol::new(token);
 
                 Ok(Kind::KindVar(KindVar { value: symbol }))
             }
+            Token::Forall => {
+                consume!(self, Token::Forall)?;
+                let (token, _) =
+                    consume!(self, Token::TVar(var) | Token::Variable(var) => var.clone())?;
+                let symbol = Symbol::new(token);
+
+                consume!(self, Token::Dot)?;
+                let body = self.parse_kind()?;
+
+                Ok(Kind::KindForall(KindForall { param: symbol, body: Box::new(body) }))
+            }
             _ => self.fail(),
         }
     }
@@ -392,3 +753,17 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::parser;
+
+    #[test]
+    fn test_parse_main_in_expression() {
+        let inputs = [r"(λx: Int. main = x + 1) 1", r"if true then main = 1 else 2"];
+
+        for input in inputs {
+            assert!(parser::from_string(input).is_err())
+        }
+    }
+}
@@ -9,11 +9,15 @@ fn is_whitespace(c: char) -> bool {
 }
 
 fn is_digit(c: char) -> bool {
-    ('0'..='9').contains(&c)
+    c.is_ascii_digit()
 }
 
 fn is_reserved(c: char) -> bool {
-    matches!(c, '(' | ')' | '[' | ']' | '.' | ':' | '*' | '=' | 'λ')
+    matches!(
+        c,
+        '(' | ')' | '[' | ']' | '{' | '}' | '.' | ',' | ':' | '*' | '×' | '=' | 'λ' | 'μ' | '+'
+            | '-' | '/' | '<' | '|' | '"'
+    )
 }
 
 fn is_valid_char(c: char) -> bool {
@@ -30,21 +34,80 @@ impl<'a> Lexer<'a> {
         self.make_token(token, start)
     }
 
+    pub fn double_token(
+        &mut self,
+        second: char,
+        token: Token,
+        single: Token,
+        start: usize,
+    ) -> (Token, Range) {
+        self.next_char();
+
+        match self.peekable.peek() {
+            Some(chr) if *chr == second => self.single_token(token, start),
+            _ => self.make_token(single, start),
+        }
+    }
+
+    pub fn lex_equal(&mut self, start: usize) -> (Token, Range) {
+        self.next_char();
+
+        match self.peekable.peek() {
+            Some('=') => self.single_token(Token::EqualEqual, start),
+            Some('>') => self.single_token(Token::FatArrow, start),
+            _ => self.make_token(Token::Equal, start),
+        }
+    }
+
     fn to_type(buf: &str) -> Token {
         match buf {
             "Int" => Token::TInt,
+            "Bool" => Token::TBool,
+            "Unit" => Token::TUnit,
             _ => Token::TVar(buf.to_string()),
         }
     }
 
+    pub fn lex_string(&mut self, start: usize) -> (Token, Range) {
+        self.next_char();
+        let str = self.accu_while(|c| c != '"' && c != '\n').to_string();
+
+        match self.peekable.peek() {
+            Some('"') => self.single_token(Token::Str(str), start),
+            _ => self.make_token(Token::Error, start),
+        }
+    }
+
     fn to_keyword(buf: &str) -> Token {
         match buf {
             "lambda" => Token::Lambda,
             "forall" => Token::Forall,
+            "exists" => Token::Exists,
+            "mu" => Token::Mu,
+            "fold" => Token::Fold,
+            "unfold" => Token::Unfold,
+            "pack" => Token::Pack,
+            "unpack" => Token::Unpack,
+            "as" => Token::As,
+            "true" => Token::True,
+            "false" => Token::False,
+            "if" => Token::If,
+            "then" => Token::Then,
+            "else" => Token::Else,
             "kind" => Token::Kind,
             "type" => Token::Type,
             "let" => Token::Let,
+            "def" => Token::Def,
+            "data" => Token::Data,
+            "main" => Token::Main,
+            "import" => Token::Import,
             "in" => Token::In,
+            "fst" => Token::Fst,
+            "snd" => Token::Snd,
+            "inl" => Token::Inl,
+            "inr" => Token::Inr,
+            "case" => Token::Case,
+            "of" => Token::Of,
             "->" => Token::Arrow,
             _ => Token::Variable(buf.to_string()),
         }
@@ -66,15 +129,27 @@ impl<'a> Lexer<'a> {
                     self.lex_token()
                 }
                 '*' => self.single_token(Token::Star, start),
+                '×' => self.single_token(Token::Star, start),
                 'λ' => self.single_token(Token::Lambda, start),
                 '∀' => self.single_token(Token::Forall, start),
-                '=' => self.single_token(Token::Equal, start),
+                'μ' => self.single_token(Token::Mu, start),
+                '∃' => self.single_token(Token::Exists, start),
+                '=' => self.lex_equal(start),
+                '-' => self.double_token('>', Token::Arrow, Token::Minus, start),
+                '+' => self.single_token(Token::Plus, start),
+                '/' => self.single_token(Token::Slash, start),
+                '<' => self.single_token(Token::Less, start),
                 '(' => self.single_token(Token::LParen, start),
                 ')' => self.single_token(Token::RParen, start),
                 '[' => self.single_token(Token::LBracket, start),
                 ']' => self.single_token(Token::RBracket, start),
                 ':' => self.single_token(Token::Colon, start),
                 '.' => self.single_token(Token::Dot, start),
+                ',' => self.single_token(Token::Comma, start),
+                '|' => self.single_token(Token::Pipe, start),
+                '{' => self.single_token(Token::LBrace, start),
+                '"' => self.lex_string(start),
+                '}' => self.single_token(Token::RBrace, start),
                 chr if is_digit(*chr) => {
                     let num = self.accu_while(is_digit);
                     let num = num.parse::<usize>().unwrap();
@@ -177,6 +252,133 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_lex_conditional() {
+        let received = "if true then () else false";
+        let expected = vec![
+            Token::If,
+            Token::True,
+            Token::Then,
+            Token::LParen,
+            Token::RParen,
+            Token::Else,
+            Token::False,
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_operators() {
+        let received = "x * 2 + 1 == y -> 3 - 4 / 5 < 6";
+        let expected = vec![
+            Token::Variable(String::from("x")),
+            Token::Star,
+            Token::Number(2),
+            Token::Plus,
+            Token::Number(1),
+            Token::EqualEqual,
+            Token::Variable(String::from("y")),
+            Token::Arrow,
+            Token::Number(3),
+            Token::Minus,
+            Token::Number(4),
+            Token::Slash,
+            Token::Number(5),
+            Token::Less,
+            Token::Number(6),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_pairs_and_sums() {
+        let received = "case {fst p, x} of inl a => a | inr b => 0";
+        let expected = vec![
+            Token::Case,
+            Token::LBrace,
+            Token::Fst,
+            Token::Variable(String::from("p")),
+            Token::Comma,
+            Token::Variable(String::from("x")),
+            Token::RBrace,
+            Token::Of,
+            Token::Inl,
+            Token::Variable(String::from("a")),
+            Token::FatArrow,
+            Token::Variable(String::from("a")),
+            Token::Pipe,
+            Token::Inr,
+            Token::Variable(String::from("b")),
+            Token::FatArrow,
+            Token::Number(0),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_recursive() {
+        let received = "fold [μL: *. L] unfold x";
+        let expected = vec![
+            Token::Fold,
+            Token::LBracket,
+            Token::Mu,
+            Token::TVar(String::from("L")),
+            Token::Colon,
+            Token::Star,
+            Token::Dot,
+            Token::TVar(String::from("L")),
+            Token::RBracket,
+            Token::Unfold,
+            Token::Variable(String::from("x")),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_existential() {
+        let received = "unpack p as [X, x] in pack [Int, 1] as ∃X: *. X";
+        let expected = vec![
+            Token::Unpack,
+            Token::Variable(String::from("p")),
+            Token::As,
+            Token::LBracket,
+            Token::TVar(String::from("X")),
+            Token::Comma,
+            Token::Variable(String::from("x")),
+            Token::RBracket,
+            Token::In,
+            Token::Pack,
+            Token::LBracket,
+            Token::TInt,
+            Token::Comma,
+            Token::Number(1),
+            Token::RBracket,
+            Token::As,
+            Token::Exists,
+            Token::TVar(String::from("X")),
+            Token::Colon,
+            Token::Star,
+            Token::Dot,
+            Token::TVar(String::from("X")),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
     #[test]
     fn test_lex_parens() {
         let received = "(x) y";
@@ -210,4 +412,28 @@ mod test {
             assert_eq!(fst, snd)
         }
     }
+
+    #[test]
+    fn test_lex_import() {
+        let received = "import \"lists.sfo\" as L L.map λx: Int.x";
+        let expected = vec![
+            Token::Import,
+            Token::Str(String::from("lists.sfo")),
+            Token::As,
+            Token::TVar(String::from("L")),
+            Token::TVar(String::from("L")),
+            Token::Dot,
+            Token::Variable(String::from("map")),
+            Token::Lambda,
+            Token::Variable(String::from("x")),
+            Token::Colon,
+            Token::TInt,
+            Token::Dot,
+            Token::Variable(String::from("x")),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
 }
@@ -1,27 +1,62 @@
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Token {
     Number(usize),
+    Str(String),
     Variable(String),
     Lambda,
+    True,
+    False,
+    If,
+    Then,
+    Else,
     Kind,
     Type,
     Let,
+    Def,
+    Data,
+    Main,
+    Import,
     In,
+    Fst,
+    Snd,
+    Inl,
+    Inr,
+    Case,
+    Of,
+    Fold,
+    Unfold,
+    Pack,
+    Unpack,
+    As,
 
     TInt,
+    TBool,
+    TUnit,
     TVar(String),
     Arrow,
     Forall,
+    Exists,
+    Mu,
 
     Star,
 
     Dot,
     Colon,
+    Comma,
     Equal,
+    EqualEqual,
+    FatArrow,
+    Pipe,
+    Less,
+    Plus,
+    Minus,
+    Slash,
     LParen,
     RParen,
     LBracket,
     RBracket,
+    LBrace,
+    RBrace,
 
     Error,
     Eof,
@@ -24,6 +24,6 @@ impl Range {
 
     #[inline]
     pub fn mix(&self, end: Range) -> Range {
-        Range { start: self.start.clone(), end: end.end }
+        Range { start: self.start, end: end.end }
     }
 }
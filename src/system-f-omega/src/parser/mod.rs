@@ -1,8 +1,10 @@
+pub mod data;
 pub mod error;
 pub mod expr;
 pub mod lexer;
 pub mod location;
 pub mod macros;
+pub mod module;
 pub mod parsetree;
 pub mod state;
 pub mod symbol;
@@ -13,5 +15,12 @@ pub fn from_string(str: &str) -> Result<parsetree::Expr, error::ParserError> {
     let mut string = str.to_string();
     let mut parser = state::Parser::init(&mut string)?;
 
-    return parser.parse_expr();
+    parser.parse_program()
+}
+
+pub fn module_from_string(str: &str) -> Result<parsetree::Module, error::ParserError> {
+    let mut string = str.to_string();
+    let mut parser = state::Parser::init(&mut string)?;
+
+    parser.parse_module()
 }
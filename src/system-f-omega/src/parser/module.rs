@@ -0,0 +1,71 @@
+use super::{
+    error::ParserError,
+    lexer::tokens::Token,
+    location::Range,
+    macros::{consume, match_token},
+    parsetree::{Expr, Import, Module, Unit},
+    state::Parser,
+};
+
+impl<'a> Parser<'a> {
+    // `import "path.sfo"` or `import Name`, which stands for `Name.sfo`, optionally followed by
+    // `as Q` to reach the declarations as `Q.name` instead.
+    pub fn parse_import(&mut self) -> Result<Import, ParserError> {
+        let (_, range) = consume!(self, Token::Import)?;
+        let (path, path_range) = match self.get() {
+            Token::TVar(_) => {
+                let (name, range) = consume!(self, Token::TVar(name) => name.clone())?;
+                (format!("{}.sfo", name), range)
+            }
+            _ => consume!(self, Token::Str(path) => path.clone())?,
+        };
+
+        let (qualifier, range) = if let Token::As = self.get() {
+            consume!(self, Token::As)?;
+            let (qualifier, end) = consume!(self, Token::TVar(name) => name.clone())?;
+            (Some(qualifier), range.mix(end))
+        } else {
+            (None, range.mix(path_range))
+        };
+
+        Ok(Import { path, qualifier, range })
+    }
+
+    // Reads the `.name` after `name` when `name` is the qualifier of an import, so that `Q.x`
+    // is a single name while any other `A.x`, like the one in `λx: A.x`, is left to the caller.
+    pub fn parse_qualified(
+        &mut self,
+        name: String,
+        range: Range,
+    ) -> Result<(String, Range), ParserError> {
+        if !(self.qualifiers.contains(&name) && matches!(self.get(), Token::Dot)) {
+            return Ok((name, range));
+        }
+
+        consume!(self, Token::Dot)?;
+        let (member, end) =
+            consume!(self, Token::Variable(var) | Token::TVar(var) => var.clone())?;
+
+        Ok((format!("{}.{}", name, member), range.mix(end)))
+    }
+
+    pub fn parse_module(&mut self) -> Result<Module, ParserError> {
+        let mut imports = Vec::new();
+
+        while let Token::Import = self.get() {
+            let import = self.parse_import()?;
+            self.qualifiers.extend(import.qualifier.clone());
+            imports.push(import);
+        }
+
+        let body = match self.get() {
+            Token::Eof => {
+                let (_, range) = consume!(self, Token::Eof)?;
+                Expr::Unit(Unit { range })
+            }
+            _ => self.parse_program()?,
+        };
+
+        Ok(Module { imports, body })
+    }
+}
@@ -7,6 +7,17 @@ pub struct Int {
     pub range: Range,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bool {
+    pub value: bool,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub range: Range,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Var {
     pub value: Symbol,
@@ -50,9 +61,114 @@ pub struct Anno {
     pub range: Range,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pair {
+    pub fst: Box<Expr>,
+    pub snd: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fst {
+    pub pair: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snd {
+    pub pair: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inl {
+    pub value: Box<Expr>,
+    pub right_ty: Type,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inr {
+    pub value: Box<Expr>,
+    pub left_ty: Type,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    pub scrutinee: Box<Expr>,
+    pub left_param: Symbol,
+    pub left_body: Box<Expr>,
+    pub right_param: Symbol,
+    pub right_body: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pack {
+    pub witness: Type,
+    pub value: Box<Expr>,
+    pub ty: Type,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unpack {
+    pub package: Box<Expr>,
+    pub ty_param: Symbol,
+    pub param: Symbol,
+    pub body: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fold {
+    pub ty: Type,
+    pub value: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unfold {
+    pub value: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct If {
+    pub cond: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Lt,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinOp {
+    pub op: Operator,
+    pub left: Box<Expr>,
+    pub right: Box<Expr>,
+    pub range: Range,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TInt {}
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct TBool {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TUnit {}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TVar {
     pub value: Symbol,
@@ -71,6 +187,32 @@ pub struct Arrow {
     pub right: Box<Type>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exists {
+    pub param: Symbol,
+    pub param_ty: Kind,
+    pub body: Box<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rec {
+    pub param: Symbol,
+    pub param_ty: Kind,
+    pub body: Box<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Product {
+    pub fst: Box<Type>,
+    pub snd: Box<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sum {
+    pub left: Box<Type>,
+    pub right: Box<Type>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TyAbs {
     pub param: Symbol,
@@ -104,6 +246,12 @@ pub struct KindArrow {
     pub right: Box<Kind>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct KindForall {
+    pub param: Symbol,
+    pub body: Box<Kind>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct LetAlias {
     pub name: Symbol,
@@ -133,14 +281,21 @@ pub enum Kind {
     Star(Star),
     KindVar(KindVar),
     KindArrow(KindArrow),
+    KindForall(KindForall),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     TInt(TInt),
+    TBool(TBool),
+    TUnit(TUnit),
     TVar(TVar),
     Arrow(Arrow),
     Forall(Forall),
+    Exists(Exists),
+    Rec(Rec),
+    Product(Product),
+    Sum(Sum),
     TyAbs(TyAbs),
     TyApp(TyApp),
     TyAnno(TyAnno),
@@ -149,6 +304,8 @@ pub enum Type {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Int(Int),
+    Bool(Bool),
+    Unit(Unit),
     Var(Var),
     Abs(Abs),
     App(App),
@@ -158,21 +315,61 @@ pub enum Expr {
     TypeAlias(TypeAlias),
     KindAlias(KindAlias),
     Anno(Anno),
+    If(If),
+    BinOp(BinOp),
+    Pair(Pair),
+    Fst(Fst),
+    Snd(Snd),
+    Inl(Inl),
+    Inr(Inr),
+    Case(Case),
+    Fold(Fold),
+    Unfold(Unfold),
+    Pack(Pack),
+    Unpack(Unpack),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub path: String,
+    pub qualifier: Option<String>,
+    pub range: Range,
+}
+
+/// A source file: the modules it imports, then its declarations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub imports: Vec<Import>,
+    pub body: Expr,
 }
 
 impl Expr {
     pub fn range(&self) -> Range {
         match self {
-            Expr::Anno(Anno { range, .. }) => range.clone(),
-            Expr::Int(Int { range, .. }) => range.clone(),
-            Expr::Var(Var { range, .. }) => range.clone(),
-            Expr::Abs(Abs { range, .. }) => range.clone(),
-            Expr::App(App { range, .. }) => range.clone(),
-            Expr::TAbs(TAbs { range, .. }) => range.clone(),
-            Expr::TApp(TApp { range, .. }) => range.clone(),
-            Expr::LetAlias(LetAlias { range, .. }) => range.clone(),
-            Expr::TypeAlias(TypeAlias { range, .. }) => range.clone(),
-            Expr::KindAlias(KindAlias { range, .. }) => range.clone(),
+            Expr::Anno(Anno { range, .. }) => *range,
+            Expr::Int(Int { range, .. }) => *range,
+            Expr::Bool(Bool { range, .. }) => *range,
+            Expr::Unit(Unit { range }) => *range,
+            Expr::Var(Var { range, .. }) => *range,
+            Expr::Abs(Abs { range, .. }) => *range,
+            Expr::App(App { range, .. }) => *range,
+            Expr::TAbs(TAbs { range, .. }) => *range,
+            Expr::TApp(TApp { range, .. }) => *range,
+            Expr::LetAlias(LetAlias { range, .. }) => *range,
+            Expr::TypeAlias(TypeAlias { range, .. }) => *range,
+            Expr::KindAlias(KindAlias { range, .. }) => *range,
+            Expr::If(If { range, .. }) => *range,
+            Expr::BinOp(BinOp { range, .. }) => *range,
+            Expr::Pair(Pair { range, .. }) => *range,
+            Expr::Fst(Fst { range, .. }) => *range,
+            Expr::Snd(Snd { range, .. }) => *range,
+            Expr::Inl(Inl { range, .. }) => *range,
+            Expr::Inr(Inr { range, .. }) => *range,
+            Expr::Case(Case { range, .. }) => *range,
+            Expr::Fold(Fold { range, .. }) => *range,
+            Expr::Unfold(Unfold { range, .. }) => *range,
+            Expr::Pack(Pack { range, .. }) => *range,
+            Expr::Unpack(Unpack { range, .. }) => *range,
         }
     }
 }
@@ -183,6 +380,18 @@ impl fmt::Display for Int {
     }
 }
 
+impl fmt::Display for Bool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "()")
+    }
+}
+
 impl fmt::Display for Var {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.value)
@@ -213,6 +422,103 @@ impl fmt::Display for TApp {
     }
 }
 
+impl fmt::Display for If {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(if {} then {} else {})",
+            self.cond, self.then_branch, self.else_branch
+        )
+    }
+}
+
+impl fmt::Display for Pair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{}, {}}}", self.fst, self.snd)
+    }
+}
+
+impl fmt::Display for Fst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(fst {})", self.pair)
+    }
+}
+
+impl fmt::Display for Snd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(snd {})", self.pair)
+    }
+}
+
+impl fmt::Display for Inl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(inl [{}] {})", self.right_ty, self.value)
+    }
+}
+
+impl fmt::Display for Inr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(inr [{}] {})", self.left_ty, self.value)
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(case {} of inl {} => {} | inr {} => {})",
+            self.scrutinee, self.left_param, self.left_body, self.right_param, self.right_body
+        )
+    }
+}
+
+impl fmt::Display for Pack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(pack [{}, {}] as {})", self.witness, self.value, self.ty)
+    }
+}
+
+impl fmt::Display for Unpack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(unpack {} as [{}, {}] in {})",
+            self.package, self.ty_param, self.param, self.body
+        )
+    }
+}
+
+impl fmt::Display for Fold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(fold [{}] {})", self.ty, self.value)
+    }
+}
+
+impl fmt::Display for Unfold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(unfold {})", self.value)
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Sub => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+            Operator::Eq => write!(f, "=="),
+            Operator::Lt => write!(f, "<"),
+        }
+    }
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} {} {})", self.left, self.op, self.right)
+    }
+}
+
 impl fmt::Display for Arrow {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self.left {
@@ -234,6 +540,30 @@ impl fmt::Display for Forall {
     }
 }
 
+impl fmt::Display for Exists {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(∃{}: {}. {})", self.param, self.param_ty, self.body)
+    }
+}
+
+impl fmt::Display for Rec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(μ{}: {}. {})", self.param, self.param_ty, self.body)
+    }
+}
+
+impl fmt::Display for Product {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} * {})", self.fst, self.snd)
+    }
+}
+
+impl fmt::Display for Sum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} + {})", self.left, self.right)
+    }
+}
+
 impl fmt::Display for TyAbs {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "(λ{}: {}. {})", self.param, self.param_ty, self.body)
@@ -273,12 +603,19 @@ impl fmt::Display for KindArrow {
     }
 }
 
+impl fmt::Display for KindForall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "∀{}. {}", self.param, self.body)
+    }
+}
+
 impl fmt::Display for Kind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Kind::KindVar(var) => write!(f, "{}", var),
             Kind::Star(star) => write!(f, "{}", star),
             Kind::KindArrow(arrow) => write!(f, "{}", arrow),
+            Kind::KindForall(forall) => write!(f, "{}", forall),
         }
     }
 }
@@ -311,9 +648,15 @@ impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Type::TInt(_) => write!(f, "Int"),
+            Type::TBool(_) => write!(f, "Bool"),
+            Type::TUnit(_) => write!(f, "Unit"),
             Type::TVar(tvar) => write!(f, "{}", tvar),
             Type::Arrow(arrow) => write!(f, "{}", arrow),
             Type::Forall(forall) => write!(f, "{}", forall),
+            Type::Exists(exists) => write!(f, "{}", exists),
+            Type::Rec(rec) => write!(f, "{}", rec),
+            Type::Product(product) => write!(f, "{}", product),
+            Type::Sum(sum) => write!(f, "{}", sum),
             Type::TyAbs(tyabs) => write!(f, "{}", tyabs),
             Type::TyApp(tyapp) => write!(f, "{}", tyapp),
             Type::TyAnno(anno) => write!(f, "{}", anno),
@@ -325,6 +668,8 @@ impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Expr::Int(int) => write!(f, "{}", int),
+            Expr::Bool(bool) => write!(f, "{}", bool),
+            Expr::Unit(unit) => write!(f, "{}", unit),
             Expr::Var(var) => write!(f, "{}", var),
             Expr::Abs(abs) => write!(f, "{}", abs),
             Expr::App(app) => write!(f, "{}", app),
@@ -334,6 +679,18 @@ impl fmt::Display for Expr {
             Expr::TypeAlias(alias) => write!(f, "{}", alias),
             Expr::KindAlias(alias) => write!(f, "{}", alias),
             Expr::Anno(anno) => write!(f, "{}", anno),
+            Expr::If(cond) => write!(f, "{}", cond),
+            Expr::BinOp(binop) => write!(f, "{}", binop),
+            Expr::Pair(pair) => write!(f, "{}", pair),
+            Expr::Fst(fst) => write!(f, "{}", fst),
+            Expr::Snd(snd) => write!(f, "{}", snd),
+            Expr::Inl(inl) => write!(f, "{}", inl),
+            Expr::Inr(inr) => write!(f, "{}", inr),
+            Expr::Case(case) => write!(f, "{}", case),
+            Expr::Fold(fold) => write!(f, "{}", fold),
+            Expr::Unfold(unfold) => write!(f, "{}", unfold),
+            Expr::Pack(pack) => write!(f, "{}", pack),
+            Expr::Unpack(unpack) => write!(f, "{}", unpack),
         }
     }
 }
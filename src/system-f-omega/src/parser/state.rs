@@ -5,6 +5,8 @@ pub struct Parser<'a> {
     current_token: (Token, Range),
     next_token: (Token, Range),
     after: usize,
+    // The names modules are imported as, which are the only ones `Q.name` can start with.
+    pub qualifiers: Vec<String>,
 }
 
 impl<'a> Parser<'a> {
@@ -18,6 +20,7 @@ impl<'a> Parser<'a> {
             current_token,
             next_token,
             after: Default::default(),
+            qualifiers: Vec::new(),
         })
     }
 
@@ -59,7 +62,7 @@ impl<'a> Parser<'a> {
         match expect(self.get()) {
             None => self.fail(),
             Some(res) => {
-                let range = self.current_token.1.clone();
+                let range = self.current_token.1;
                 self.advance()?;
                 Ok((res, range))
             }
@@ -1,4 +1,4 @@
-#[derive(Clone, Debug, Hash, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Symbol {
     pub id: usize,
     pub name: String,
@@ -10,12 +10,6 @@ impl Symbol {
     }
 }
 
-impl PartialEq for Symbol {
-    fn eq(&self, other: &Self) -> bool {
-        self.name == other.name && self.id == other.id
-    }
-}
-
 impl std::fmt::Display for Symbol {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.name)
@@ -0,0 +1,50 @@
+use crate::parser::{
+    self,
+    error::ParserError,
+    parsetree::{Expr, KindAlias, LetAlias, TypeAlias},
+};
+
+/// Definitions every session starts with: a chain of `let`s still waiting for its body.
+pub const PRELUDE: &str = include_str!("../prelude.sfo");
+
+// `input` takes the place of the body the chain of aliases ends with.
+fn splice(prelude: Expr, input: Expr) -> Expr {
+    match prelude {
+        Expr::LetAlias(alias) => {
+            let body = Box::new(splice(*alias.body, input));
+            Expr::LetAlias(LetAlias { body, ..alias })
+        }
+        Expr::TypeAlias(alias) => {
+            let body = Box::new(splice(*alias.body, input));
+            Expr::TypeAlias(TypeAlias { body, ..alias })
+        }
+        Expr::KindAlias(alias) => {
+            let body = Box::new(splice(*alias.body, input));
+            Expr::KindAlias(KindAlias { body, ..alias })
+        }
+        _ => input,
+    }
+}
+
+/// Parses `input` in the scope of the prelude definitions. The two are parsed apart, so the
+/// ranges of `input` are the ones of its own text.
+pub fn load(input: &str) -> Result<Expr, ParserError> {
+    // The `0` holds the place of the input until it is spliced in.
+    let prelude = parser::from_string(&format!("{}0", PRELUDE))?;
+
+    Ok(splice(prelude, parser::from_string(input)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::load;
+    use crate::parser::error::ParserError;
+
+    #[test]
+    fn test_prelude_error_range() {
+        // The input is parsed apart from the prelude, so its ranges start at its own text.
+        let error = load(r"(1 in").unwrap_err();
+
+        assert!(matches!(error, ParserError::UnexpectedToken(_, range) if range.start.index == 3))
+    }
+}
@@ -1,5 +1,6 @@
 use crate::parser::parsetree::Expr;
 
+pub mod primitives;
 pub mod strategies;
 pub mod variables;
 
@@ -14,3 +15,260 @@ pub fn reduce(strategy: Norm, expr: Expr, limit: Option<usize>) -> Expr {
         Norm::NOR => strategies::normal_order(expr, limit),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{reduce, Norm};
+    use crate::checker::{self, conversion::alpha_conversion_expr, conversion::Names};
+    use crate::{parser, prelude};
+
+    fn eval(input: &str) -> String {
+        let expr = parser::from_string(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+        checker::type_of(expr.clone()).unwrap();
+        reduce(Norm::NOR, expr, None).to_string()
+    }
+
+    #[test]
+    fn test_reduce_alias() {
+        let input = r"
+        type Id: * = ∀A: *. (A -> A) in
+        let id: Id = λA: *. λx: A. x in
+        id [Int] 69420
+        ";
+
+        assert_eq!(eval(input), "69420")
+    }
+
+    #[test]
+    fn test_reduce_arithmetic() {
+        let input = r"
+        type Op: * = Int -> Int -> Int in
+        let sub: Op = λx: Int. λy: Int. x - y in
+        if sub 10 3 * 2 < 5 then 0 else sub 7 (8 / 2)
+        ";
+
+        assert_eq!(eval(input), "3")
+    }
+
+    #[test]
+    fn test_reduce_overflow() {
+        let input = r"
+        let big: Int = 4294967296 in
+        big * big + 1
+        ";
+
+        assert_eq!(eval(input), "((4294967296 * 4294967296) + 1)")
+    }
+
+    #[test]
+    fn test_reduce_pair_operator() {
+        let input = r"
+        type Pair: * -> * -> * = λA: *. λB: *. A * B in
+        let swap: Pair Int Bool -> Pair Bool Int = λp: Pair Int Bool. {snd p, fst p} in
+        let q: Pair Bool Int = swap {41, true} in
+        if fst q then (snd q) + 1 else 0
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_case() {
+        let input = r"
+        type Option: * -> * = λA: *. Unit + A in
+        let get: Option Int -> Int = λo: Option Int. case o of inl u => 0 | inr n => n in
+        get (inr [Unit] 7) + get (inl [Int] ())
+        ";
+
+        assert_eq!(eval(input), "7")
+    }
+
+    #[test]
+    fn test_reduce_recursive_operator() {
+        let input = r"
+        type List: * -> * = μL: * -> *. λA: *. Unit + A * L A in
+        let nil: List Int = fold [List Int] inl [Int * List Int] () in
+        let cons: Int -> List Int -> List Int =
+            λx: Int. λxs: List Int. fold [List Int] inr [Unit] {x, xs} in
+        let head: List Int -> Int = λl: List Int. case unfold l of inl u => 0 | inr p => fst p in
+        head (cons 42 nil)
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_recursive_type() {
+        let input = r"
+        type Nat: * = μN: *. Unit + N in
+        let two: Nat = fold [Nat] inr [Unit] fold [Nat] inr [Unit] fold [Nat] inl [Nat] () in
+        case unfold two of inl z => false | inr n => true
+        ";
+
+        assert_eq!(eval(input), "true")
+    }
+
+    #[test]
+    fn test_reduce_abstract_stack() {
+        let input = r"
+        type Stack: * -> * = λS: *. S * (Int -> S -> S) * (S -> Int) in
+        let stack: ∃S: *. Stack S =
+            pack [Int, {0, {λx: Int. λs: Int. x + s * 10, λs: Int. s - s / 10 * 10}}]
+            as ∃S: *. Stack S in
+        unpack stack as [S, s] in
+        let empty: S = fst s in
+        let push: Int -> S -> S = fst (snd s) in
+        let top: S -> Int = snd (snd s) in
+        top (push 7 (push 3 empty))
+        ";
+
+        assert_eq!(eval(input), "7")
+    }
+
+    #[test]
+    fn test_reduce_if() {
+        let input = r"
+        let not: Bool -> Bool = λb: Bool. if b then false else true in
+        if not false then () else ()
+        ";
+
+        assert_eq!(eval(input), "()")
+    }
+
+    #[test]
+    fn test_reduce_kind_polymorphic_const() {
+        let input = r"
+        type Const: ∀κ. * -> κ -> * = λA: *. λB: κ. A in
+        type Id: * -> * = λA: *. A in
+        let x: Const Int Bool = 40 in
+        let y: Const Int Id = 2 in
+        x + y
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_kind_polymorphic_compose() {
+        let input = r"
+        type Compose: ∀α. ∀β. ∀γ. (β -> γ) -> (α -> β) -> α -> γ =
+            λF: β -> γ. λG: α -> β. λX: α. F (G X) in
+        type Twice: ∀κ. (κ -> κ) -> κ -> κ = λF: κ -> κ. λX: κ. F (F X) in
+        type Pair: * -> * = λA: *. A * A in
+        type Fun: * -> * = λA: *. Int -> A in
+        let p: Compose Pair Fun Int = {λn: Int. n, λn: Int. n + 1} in
+        let b: Pair Bool = {true, false} in
+        let q: Compose Twice Twice Pair Bool = {{{b, b}, {b, b}}, {{b, b}, {b, b}}} in
+        if fst (fst (fst (fst q))) then (fst p) 40 + (snd p) 1 else 0
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_prelude() {
+        let input = r"
+        let fact: Int -> Int =
+            fix [Int] [Int] (λf: Int -> Int. λn: Int. if n < 1 then 1 else n * f (n - 1)) in
+        let one: Int = toint (succ zero) in
+        let xs: List Int = map [Int] [Int] fact (Cons [Int] 3 (Cons [Int] one (Nil [Int]))) in
+        let b: Boolean = first [Boolean] [Int] (pair [Boolean] [Int] (iszero zero) 0) in
+        if b [Bool] true false then sum xs else 0
+        ";
+        let expr = prelude::load(input).unwrap();
+        let expr = alpha_conversion_expr(&mut Names::new(), &expr).unwrap();
+        checker::type_of(expr.clone()).unwrap();
+
+        assert_eq!(reduce(Norm::NOR, expr, None).to_string(), "7")
+    }
+
+    #[test]
+    fn test_reduce_data_list() {
+        let input = r"
+        data List (A: *) = Nil | Cons A (List A) in
+        let xs: List Int = Cons [Int] 1 (Cons [Int] 2 (Cons [Int] 3 (Nil [Int]))) in
+        let sum: List Int -> Int =
+            λl: List Int. foldList [Int] [Int] l 0 (λx: Int. λr: Int. x + r) in
+        sum xs
+        ";
+
+        assert_eq!(eval(input), "6")
+    }
+
+    #[test]
+    fn test_reduce_data_match() {
+        let input = r"
+        data Nat = Zero | Succ Nat in
+        data Option (A: *) = None | Some A in
+        let pred: Nat -> Option Nat = λn: Nat.
+            foldNat [Option Nat] n (None [Nat])
+                (λo: Option Nat. Some [Nat] (foldOption [Nat] [Nat] o Zero (λm: Nat. Succ m))) in
+        let toInt: Nat -> Int = λn: Nat. foldNat [Int] n 0 (λr: Int. r + 1) in
+        foldOption [Nat] [Int] (pred (Succ (Succ (Succ Zero)))) 0 toInt
+        ";
+
+        assert_eq!(eval(input), "2")
+    }
+
+    #[test]
+    fn test_reduce_data_hygiene() {
+        let inputs = [
+            (r"type R: * = Int in data Box = B R in foldBox [Int] (B 3) (λx: Int. x)", "3"),
+            (r"data Foo = Bar | BAR in foldFoo [Int] Bar 1 2", "1"),
+            (r"data Foo = Foo in let foo: Foo = Foo in foldFoo [Int] foo 7", "7"),
+        ];
+
+        for (input, expected) in inputs {
+            assert_eq!(eval(input), expected)
+        }
+    }
+
+    #[test]
+    fn test_reduce_data_case() {
+        let input = r"
+        data List (A: *) = Nil | Cons A (List A) in
+        let tail: List Int -> List Int = λl: List Int.
+            matchList [Int] [List Int] l (Nil [Int]) (λx: Int. λxs: List Int. xs) in
+        let head: List Int -> Int = λl: List Int.
+            matchList [Int] [Int] l 0 (λx: Int. λxs: List Int. x) in
+        head (tail (Cons [Int] 1 (Cons [Int] 2 (Nil [Int]))))
+        ";
+
+        assert_eq!(eval(input), "2")
+    }
+
+    #[test]
+    fn test_reduce_program() {
+        let input = r"
+        kind Unary = * -> *
+        type Twice: Unary -> Unary = λF: Unary. λA: *. F (F A)
+        data Option (A: *) = None | Some A
+        def some: ∀A: *. A -> Twice Option A = λA: *. λx: A. Some [Option A] (Some [A] x)
+        def get: Option Int -> Int = λo: Option Int. foldOption [Int] [Int] o 0 (λx: Int. x)
+        def join: Twice Option Int -> Option Int = λo: Twice Option Int.
+            foldOption [Option Int] [Option Int] o (None [Int]) (λx: Option Int. x)
+        main = get (join (some [Int] 42))
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_dot_after_type() {
+        assert_eq!(eval(r"(λx: Int.x) 1"), "1");
+        assert_eq!(eval(r"(λA: *. λx: A.x) [Bool] true"), "true")
+    }
+
+    #[test]
+    fn test_reduce_kind_alias_after_binder() {
+        let input = r"
+        type Id: * = Int in
+        kind Unary = * -> * in
+        type Box: Unary = λA: *. A in
+        let x: Box Id = 1 in x
+        ";
+
+        assert_eq!(eval(input), "1")
+    }
+}
@@ -1,5 +1,9 @@
+use super::primitives::delta;
 use super::variables::{substitution, type_substitution};
-use crate::parser::parsetree::{Abs, App, Expr, TAbs, TApp};
+use crate::parser::parsetree::{
+    Abs, Anno, App, BinOp, Bool, Case, Expr, Fold, Fst, If, Inl, Inr, KindAlias, LetAlias, Pack,
+    Pair, Snd, TAbs, TApp, TyAnno, Type, TypeAlias, Unfold, Unpack,
+};
 
 macro_rules! break_limit {
     ($expr: ident, $limit: ident) => {
@@ -11,63 +15,230 @@ macro_rules! break_limit {
     };
 }
 
-pub fn normal_order(ex: Expr, limit: Option<usize>) -> Expr {
-    break_limit!(ex, limit);
+fn type_alias(value: Type) -> Type {
+    match value {
+        Type::TyAnno(TyAnno { ty, .. }) => *ty,
+        value => value,
+    }
+}
 
-    match ex {
-        Expr::Int(int) => Expr::Int(int),
-        Expr::Var(var) => Expr::Var(var),
-        Expr::Abs(abs) => {
-            let body = normal_order(*abs.body, limit);
-            Expr::Abs(Abs { body: Box::new(body), ..abs })
-        }
-        Expr::App(app) => {
-            let func_expr = normal_order(*app.lambda, limit);
+// Reduces the head of an application spine until it is an abstraction, so that
+// every pending argument is substituted before normal_order goes under binders.
+// The reducts are taken in turn by a loop, so long reductions do not nest calls.
+fn weak_head(mut ex: Expr, mut limit: Option<usize>) -> Expr {
+    loop {
+        break_limit!(ex, limit);
 
-            match func_expr {
-                Expr::Abs(abs) => {
-                    let substituted = substitution(*abs.body, abs.param, *app.argm);
-                    normal_order(substituted, limit.and_then(|l| Some(l - 1)))
+        ex = match ex {
+            Expr::Abs(abs) => return Expr::Abs(abs),
+            Expr::TAbs(tabs) => return Expr::TAbs(tabs),
+            Expr::Fold(fold) => return Expr::Fold(fold),
+            Expr::App(app) => match weak_head(*app.lambda, limit) {
+                Expr::Abs(abs) => substitution(*abs.body, abs.param, *app.argm),
+                expr => return Expr::App(App { lambda: Box::new(expr), ..app }),
+            },
+            Expr::TApp(tapp) => match weak_head(*tapp.lambda, limit) {
+                Expr::TAbs(TAbs { param, body, .. }) => {
+                    type_substitution(&body, &param, &tapp.argm)
                 }
-                expr => {
-                    let func = normal_order(expr, limit);
-                    let argm = normal_order(*app.argm, limit);
-
-                    Expr::App(App {
-                        lambda: Box::new(func),
-                        argm: Box::new(argm),
-                        ..app
-                    })
+                expr => return Expr::TApp(TApp { lambda: Box::new(expr), ..tapp }),
+            },
+            Expr::Unfold(unfold) => match weak_head(*unfold.value, limit) {
+                Expr::Fold(Fold { value, .. }) => *value,
+                value => return Expr::Unfold(Unfold { value: Box::new(value), ..unfold }),
+            },
+            Expr::LetAlias(LetAlias { name, value, body, .. }) => {
+                substitution(*body, name, *value)
+            }
+            Expr::TypeAlias(TypeAlias { name, value, body, .. }) => {
+                type_substitution(&body, &name, &type_alias(value))
+            }
+            Expr::KindAlias(KindAlias { body, .. }) => {
+                ex = *body;
+                continue;
+            }
+            Expr::Anno(Anno { expr, .. }) => {
+                ex = *expr;
+                continue;
+            }
+            expr => return normal_order(expr, limit),
+        };
+        limit = limit.map(|l| l - 1);
+    }
+}
+
+// Like weak_head, the reducts of the terms in tail position are taken in turn by the loop.
+pub fn normal_order(mut ex: Expr, mut limit: Option<usize>) -> Expr {
+    loop {
+        break_limit!(ex, limit);
+
+        ex = match ex {
+            Expr::Int(int) => return Expr::Int(int),
+            Expr::Bool(bool) => return Expr::Bool(bool),
+            Expr::Unit(unit) => return Expr::Unit(unit),
+            Expr::Var(var) => return Expr::Var(var),
+            Expr::Abs(abs) => {
+                let body = normal_order(*abs.body, limit);
+                return Expr::Abs(Abs { body: Box::new(body), ..abs });
+            }
+            Expr::App(app) => {
+                let func_expr = weak_head(*app.lambda, limit);
+
+                match func_expr {
+                    Expr::Abs(abs) => substitution(*abs.body, abs.param, *app.argm),
+                    expr => {
+                        let func = normal_order(expr, limit);
+                        let argm = normal_order(*app.argm, limit);
+
+                        return Expr::App(App {
+                            lambda: Box::new(func),
+                            argm: Box::new(argm),
+                            ..app
+                        });
+                    }
                 }
             }
-        }
-        Expr::TAbs(tabs) => {
-            let body = normal_order(*tabs.body, limit);
-
-            Expr::TAbs(TAbs {
-                param: tabs.param,
-                body: Box::new(body),
-                range: tabs.range,
-            })
-        }
-        Expr::TApp(tapp) => {
-            let func_expr = normal_order(*tapp.lambda, limit);
+            Expr::TAbs(tabs) => {
+                let body = normal_order(*tabs.body, limit);
 
-            match func_expr {
-                Expr::TAbs(TAbs { param, body, .. }) => {
-                    let substituted = type_substitution(&body, &param, &tapp.argm);
-                    normal_order(substituted, limit.and_then(|l| Some(l - 1)))
+                return Expr::TAbs(TAbs { body: Box::new(body), ..tabs });
+            }
+            Expr::TApp(tapp) => {
+                let func_expr = weak_head(*tapp.lambda, limit);
+
+                match func_expr {
+                    Expr::TAbs(TAbs { param, body, .. }) => {
+                        type_substitution(&body, &param, &tapp.argm)
+                    }
+                    expr => {
+                        let func = normal_order(expr, limit);
+
+                        return Expr::TApp(TApp {
+                            lambda: Box::new(func),
+                            argm: tapp.argm,
+                            range: tapp.range,
+                        });
+                    }
                 }
-                expr => {
-                    let func = normal_order(expr, limit);
-
-                    Expr::TApp(TApp {
-                        lambda: Box::new(func),
-                        argm: tapp.argm,
-                        range: tapp.range,
-                    })
+            }
+            Expr::LetAlias(LetAlias { name, value, body, .. }) => {
+                substitution(*body, name, *value)
+            }
+            Expr::TypeAlias(TypeAlias { name, value, body, .. }) => {
+                type_substitution(&body, &name, &type_alias(value))
+            }
+            Expr::KindAlias(KindAlias { body, .. }) => {
+                ex = *body;
+                continue;
+            }
+            Expr::Anno(Anno { expr, .. }) => {
+                ex = *expr;
+                continue;
+            }
+            Expr::If(cond) => {
+                let test = normal_order(*cond.cond, limit);
+
+                match test {
+                    Expr::Bool(Bool { value: true, .. }) => *cond.then_branch,
+                    Expr::Bool(Bool { value: false, .. }) => *cond.else_branch,
+                    test => {
+                        let then_branch = normal_order(*cond.then_branch, limit);
+                        let else_branch = normal_order(*cond.else_branch, limit);
+
+                        return Expr::If(If {
+                            cond: Box::new(test),
+                            then_branch: Box::new(then_branch),
+                            else_branch: Box::new(else_branch),
+                            ..cond
+                        });
+                    }
                 }
             }
-        }
+            Expr::BinOp(binop) => {
+                let left = normal_order(*binop.left, limit);
+                let right = normal_order(*binop.right, limit);
+
+                return delta(BinOp {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    ..binop
+                });
+            }
+            Expr::Pair(pair) => {
+                let fst = normal_order(*pair.fst, limit);
+                let snd = normal_order(*pair.snd, limit);
+
+                return Expr::Pair(Pair { fst: Box::new(fst), snd: Box::new(snd), ..pair });
+            }
+            Expr::Fst(fst) => {
+                return match normal_order(*fst.pair, limit) {
+                    Expr::Pair(Pair { fst, .. }) => *fst,
+                    pair => Expr::Fst(Fst { pair: Box::new(pair), ..fst }),
+                };
+            }
+            Expr::Snd(snd) => {
+                return match normal_order(*snd.pair, limit) {
+                    Expr::Pair(Pair { snd, .. }) => *snd,
+                    pair => Expr::Snd(Snd { pair: Box::new(pair), ..snd }),
+                };
+            }
+            Expr::Fold(fold) => {
+                let value = normal_order(*fold.value, limit);
+                return Expr::Fold(Fold { value: Box::new(value), ..fold });
+            }
+            Expr::Unfold(unfold) => {
+                return match normal_order(*unfold.value, limit) {
+                    Expr::Fold(Fold { value, .. }) => *value,
+                    value => Expr::Unfold(Unfold { value: Box::new(value), ..unfold }),
+                };
+            }
+            Expr::Pack(pack) => {
+                let value = normal_order(*pack.value, limit);
+                return Expr::Pack(Pack { value: Box::new(value), ..pack });
+            }
+            Expr::Unpack(unpack) => match normal_order(*unpack.package, limit) {
+                Expr::Pack(Pack { witness, value, .. }) => {
+                    let body = type_substitution(&unpack.body, &unpack.ty_param, &witness);
+                    substitution(body, unpack.param, *value)
+                }
+                package => {
+                    let body = normal_order(*unpack.body, limit);
+
+                    return Expr::Unpack(Unpack {
+                        package: Box::new(package),
+                        body: Box::new(body),
+                        ..unpack
+                    });
+                }
+            },
+            Expr::Inl(inl) => {
+                let value = normal_order(*inl.value, limit);
+                return Expr::Inl(Inl { value: Box::new(value), ..inl });
+            }
+            Expr::Inr(inr) => {
+                let value = normal_order(*inr.value, limit);
+                return Expr::Inr(Inr { value: Box::new(value), ..inr });
+            }
+            Expr::Case(case) => match normal_order(*case.scrutinee, limit) {
+                Expr::Inl(Inl { value, .. }) => {
+                    substitution(*case.left_body, case.left_param, *value)
+                }
+                Expr::Inr(Inr { value, .. }) => {
+                    substitution(*case.right_body, case.right_param, *value)
+                }
+                scrutinee => {
+                    let left_body = normal_order(*case.left_body, limit);
+                    let right_body = normal_order(*case.right_body, limit);
+
+                    return Expr::Case(Case {
+                        scrutinee: Box::new(scrutinee),
+                        left_body: Box::new(left_body),
+                        right_body: Box::new(right_body),
+                        ..case
+                    });
+                }
+            },
+        };
+        limit = limit.map(|l| l - 1);
     }
 }
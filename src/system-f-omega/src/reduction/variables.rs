@@ -1,11 +1,18 @@
-use crate::parser::parsetree::{Abs, App, Arrow, Expr, Forall, Int, TAbs, TApp, TVar, Type, Var};
+use crate::parser::parsetree::{
+    Abs, Anno, App, Arrow, BinOp, Bool, Case, Exists, Expr, Fold, Forall, Fst, If, Inl, Inr, Int,
+    KindAlias, LetAlias, Pack, Pair, Product, Rec, Snd, Sum, TAbs, TApp, TVar, TyAbs, TyAnno,
+    TyApp, Type, TypeAlias, Unfold, Unit, Unpack, Var,
+};
+use crate::parser::symbol::Symbol;
 use std::collections::HashSet;
 
-pub fn free_variables(expr: Expr) -> HashSet<String> {
-    let mut free: HashSet<String> = HashSet::new();
+pub fn free_variables(expr: Expr) -> HashSet<Symbol> {
+    let mut free: HashSet<Symbol> = HashSet::new();
 
     match expr {
         Expr::Int(Int { .. }) => (),
+        Expr::Bool(Bool { .. }) => (),
+        Expr::Unit(Unit { .. }) => (),
         Expr::Var(Var { value, .. }) => {
             free.insert(value);
         }
@@ -19,16 +26,64 @@ pub fn free_variables(expr: Expr) -> HashSet<String> {
         }
         Expr::TAbs(TAbs { body, .. }) => free.extend(free_variables(*body)),
         Expr::TApp(TApp { lambda, .. }) => free.extend(free_variables(*lambda)),
+        Expr::LetAlias(LetAlias { name, value, body, .. }) => {
+            free.extend(free_variables(*body));
+            free.remove(&name);
+            free.extend(free_variables(*value));
+        }
+        Expr::TypeAlias(TypeAlias { body, .. }) => free.extend(free_variables(*body)),
+        Expr::KindAlias(KindAlias { body, .. }) => free.extend(free_variables(*body)),
+        Expr::Anno(Anno { expr, .. }) => free.extend(free_variables(*expr)),
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            free.extend(free_variables(*cond));
+            free.extend(free_variables(*then_branch));
+            free.extend(free_variables(*else_branch));
+        }
+        Expr::BinOp(BinOp { left, right, .. }) => {
+            free.extend(free_variables(*left));
+            free.extend(free_variables(*right));
+        }
+        Expr::Pair(Pair { fst, snd, .. }) => {
+            free.extend(free_variables(*fst));
+            free.extend(free_variables(*snd));
+        }
+        Expr::Fst(Fst { pair, .. }) => free.extend(free_variables(*pair)),
+        Expr::Snd(Snd { pair, .. }) => free.extend(free_variables(*pair)),
+        Expr::Fold(Fold { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Unfold(Unfold { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Pack(Pack { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Unpack(Unpack { package, param, body, .. }) => {
+            let mut inner = free_variables(*body);
+            inner.remove(&param);
+
+            free.extend(free_variables(*package));
+            free.extend(inner);
+        }
+        Expr::Inl(Inl { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Inr(Inr { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+            let mut left = free_variables(*left_body);
+            left.remove(&left_param);
+
+            let mut right = free_variables(*right_body);
+            right.remove(&right_param);
+
+            free.extend(free_variables(*scrutinee));
+            free.extend(left);
+            free.extend(right);
+        }
     }
 
     free
 }
 
-pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
+pub fn substitution(expr: Expr, from: Symbol, to: Expr) -> Expr {
     match expr.clone() {
         Expr::Var(Var { value, .. }) if value == from => to,
         Expr::Var(Var { .. }) => expr,
         Expr::Int(Int { .. }) => expr,
+        Expr::Bool(Bool { .. }) => expr,
+        Expr::Unit(Unit { .. }) => expr,
         Expr::App(app) => {
             let lambda = substitution(*app.lambda, from.clone(), to.clone());
             let argm = substitution(*app.argm, from, to);
@@ -50,18 +105,140 @@ pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
 
             Expr::Abs(abs)
         }
-        Expr::TAbs(TAbs { param, body, range }) => {
-            let body = substitution(*body, from, to);
-            Expr::TAbs(TAbs { param, body: Box::new(body), range })
+        Expr::TAbs(tabs) => {
+            let body = substitution(*tabs.body, from, to);
+            Expr::TAbs(TAbs { body: Box::new(body), ..tabs })
         }
-        Expr::TApp(TApp { lambda, argm, range }) => {
-            let lambda = substitution(*lambda, from, to);
-            Expr::TApp(TApp { lambda: Box::new(lambda), argm, range })
+        Expr::TApp(tapp) => {
+            let lambda = substitution(*tapp.lambda, from, to);
+            Expr::TApp(TApp { lambda: Box::new(lambda), ..tapp })
+        }
+        Expr::LetAlias(alias) => {
+            let value = substitution(*alias.value, from.clone(), to.clone());
+
+            if alias.name == from {
+                return Expr::LetAlias(LetAlias { value: Box::new(value), ..alias });
+            }
+
+            let body = substitution(*alias.body, from, to);
+
+            Expr::LetAlias(LetAlias {
+                value: Box::new(value),
+                body: Box::new(body),
+                ..alias
+            })
+        }
+        Expr::TypeAlias(alias) => {
+            let body = substitution(*alias.body, from, to);
+            Expr::TypeAlias(TypeAlias { body: Box::new(body), ..alias })
+        }
+        Expr::KindAlias(alias) => {
+            let body = substitution(*alias.body, from, to);
+            Expr::KindAlias(KindAlias { body: Box::new(body), ..alias })
+        }
+        Expr::Anno(anno) => {
+            let expr = substitution(*anno.expr, from, to);
+            Expr::Anno(Anno { expr: Box::new(expr), ..anno })
+        }
+        Expr::If(cond) => {
+            let test = substitution(*cond.cond, from.clone(), to.clone());
+            let then_branch = substitution(*cond.then_branch, from.clone(), to.clone());
+            let else_branch = substitution(*cond.else_branch, from, to);
+
+            Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond
+            })
+        }
+        Expr::BinOp(binop) => {
+            let left = substitution(*binop.left, from.clone(), to.clone());
+            let right = substitution(*binop.right, from, to);
+
+            Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
+        Expr::Pair(pair) => {
+            let fst = substitution(*pair.fst, from.clone(), to.clone());
+            let snd = substitution(*pair.snd, from, to);
+
+            Expr::Pair(Pair { fst: Box::new(fst), snd: Box::new(snd), ..pair })
+        }
+        Expr::Fst(fst) => {
+            let pair = substitution(*fst.pair, from, to);
+            Expr::Fst(Fst { pair: Box::new(pair), ..fst })
+        }
+        Expr::Snd(snd) => {
+            let pair = substitution(*snd.pair, from, to);
+            Expr::Snd(Snd { pair: Box::new(pair), ..snd })
+        }
+        Expr::Fold(fold) => {
+            let value = substitution(*fold.value, from, to);
+            Expr::Fold(Fold { value: Box::new(value), ..fold })
+        }
+        Expr::Unfold(unfold) => {
+            let value = substitution(*unfold.value, from, to);
+            Expr::Unfold(Unfold { value: Box::new(value), ..unfold })
+        }
+        Expr::Pack(pack) => {
+            let value = substitution(*pack.value, from, to);
+            Expr::Pack(Pack { value: Box::new(value), ..pack })
+        }
+        Expr::Unpack(unpack) => {
+            let free = free_variables(to.clone());
+            let package = substitution(*unpack.package, from.clone(), to.clone());
+
+            let body = if from != unpack.param && !free.contains(&unpack.param) {
+                substitution(*unpack.body, from, to)
+            } else {
+                *unpack.body
+            };
+
+            Expr::Unpack(Unpack {
+                package: Box::new(package),
+                body: Box::new(body),
+                ..unpack
+            })
+        }
+        Expr::Inl(inl) => {
+            let value = substitution(*inl.value, from, to);
+            Expr::Inl(Inl { value: Box::new(value), ..inl })
+        }
+        Expr::Inr(inr) => {
+            let value = substitution(*inr.value, from, to);
+            Expr::Inr(Inr { value: Box::new(value), ..inr })
+        }
+        Expr::Case(case) => {
+            let free = free_variables(to.clone());
+            let scrutinee = substitution(*case.scrutinee, from.clone(), to.clone());
+
+            let left_body = if from != case.left_param && !free.contains(&case.left_param) {
+                substitution(*case.left_body, from.clone(), to.clone())
+            } else {
+                *case.left_body
+            };
+
+            let right_body = if from != case.right_param && !free.contains(&case.right_param) {
+                substitution(*case.right_body, from, to)
+            } else {
+                *case.right_body
+            };
+
+            Expr::Case(Case {
+                scrutinee: Box::new(scrutinee),
+                left_body: Box::new(left_body),
+                right_body: Box::new(right_body),
+                ..case
+            })
         }
     }
 }
 
-pub fn type_substitution(expr: &Expr, from: &str, to: &Type) -> Expr {
+pub fn type_substitution(expr: &Expr, from: &Symbol, to: &Type) -> Expr {
     match expr {
         Expr::App(app) => {
             let lambda = type_substitution(&app.lambda, from, to);
@@ -77,38 +254,209 @@ pub fn type_substitution(expr: &Expr, from: &str, to: &Type) -> Expr {
             let param_ty = type_type_substitute(&abs.param_ty, from, to);
             let body = type_substitution(&abs.body, from, to);
 
-            return Expr::Abs(Abs {
-                param_ty: param_ty,
+            Expr::Abs(Abs {
+                param_ty,
                 body: Box::new(body),
                 ..abs.clone()
-            });
+            })
+        }
+        Expr::TAbs(tabs) if tabs.param == *from => expr.clone(),
+        Expr::TAbs(tabs) => {
+            let body = type_substitution(&tabs.body, from, to);
+            Expr::TAbs(TAbs { body: Box::new(body), ..tabs.clone() })
+        }
+        Expr::TApp(tapp) => {
+            let lambda = type_substitution(&tapp.lambda, from, to);
+            let argm = type_type_substitute(&tapp.argm, from, to);
+
+            Expr::TApp(TApp {
+                lambda: Box::new(lambda),
+                argm,
+                ..tapp.clone()
+            })
         }
-        Expr::TAbs(tabs) if tabs.param == from => expr.clone(),
-        Expr::TAbs(TAbs { param, body, range }) => {
-            let body = type_substitution(&body, from, to);
+        Expr::LetAlias(alias) => {
+            let value = type_substitution(&alias.value, from, to);
+            let body = type_substitution(&alias.body, from, to);
 
-            Expr::TAbs(TAbs {
-                param: param.clone(),
+            Expr::LetAlias(LetAlias {
+                value: Box::new(value),
                 body: Box::new(body),
-                range: range.clone(),
+                ..alias.clone()
             })
         }
-        _ => expr.clone(),
+        Expr::TypeAlias(alias) => {
+            let value = type_type_substitute(&alias.value, from, to);
+
+            if alias.name == *from {
+                return Expr::TypeAlias(TypeAlias { value, ..alias.clone() });
+            }
+
+            let body = type_substitution(&alias.body, from, to);
+
+            Expr::TypeAlias(TypeAlias {
+                value,
+                body: Box::new(body),
+                ..alias.clone()
+            })
+        }
+        Expr::KindAlias(alias) => {
+            let body = type_substitution(&alias.body, from, to);
+            Expr::KindAlias(KindAlias { body: Box::new(body), ..alias.clone() })
+        }
+        Expr::Anno(anno) => {
+            let expr = type_substitution(&anno.expr, from, to);
+            let ty = type_type_substitute(&anno.anno, from, to);
+
+            Expr::Anno(Anno {
+                expr: Box::new(expr),
+                anno: ty,
+                ..anno.clone()
+            })
+        }
+        Expr::If(cond) => {
+            let test = type_substitution(&cond.cond, from, to);
+            let then_branch = type_substitution(&cond.then_branch, from, to);
+            let else_branch = type_substitution(&cond.else_branch, from, to);
+
+            Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond.clone()
+            })
+        }
+        Expr::BinOp(binop) => {
+            let left = type_substitution(&binop.left, from, to);
+            let right = type_substitution(&binop.right, from, to);
+
+            Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop.clone()
+            })
+        }
+        Expr::Pair(pair) => {
+            let fst = type_substitution(&pair.fst, from, to);
+            let snd = type_substitution(&pair.snd, from, to);
+
+            Expr::Pair(Pair { fst: Box::new(fst), snd: Box::new(snd), ..pair.clone() })
+        }
+        Expr::Fst(fst) => {
+            let pair = type_substitution(&fst.pair, from, to);
+            Expr::Fst(Fst { pair: Box::new(pair), ..fst.clone() })
+        }
+        Expr::Snd(snd) => {
+            let pair = type_substitution(&snd.pair, from, to);
+            Expr::Snd(Snd { pair: Box::new(pair), ..snd.clone() })
+        }
+        Expr::Fold(fold) => {
+            let ty = type_type_substitute(&fold.ty, from, to);
+            let value = type_substitution(&fold.value, from, to);
+
+            Expr::Fold(Fold { ty, value: Box::new(value), ..fold.clone() })
+        }
+        Expr::Unfold(unfold) => {
+            let value = type_substitution(&unfold.value, from, to);
+            Expr::Unfold(Unfold { value: Box::new(value), ..unfold.clone() })
+        }
+        Expr::Pack(pack) => {
+            let witness = type_type_substitute(&pack.witness, from, to);
+            let value = type_substitution(&pack.value, from, to);
+            let ty = type_type_substitute(&pack.ty, from, to);
+
+            Expr::Pack(Pack { witness, value: Box::new(value), ty, ..pack.clone() })
+        }
+        Expr::Unpack(unpack) => {
+            let package = type_substitution(&unpack.package, from, to);
+
+            let body = if unpack.ty_param != *from {
+                type_substitution(&unpack.body, from, to)
+            } else {
+                *unpack.body.clone()
+            };
+
+            Expr::Unpack(Unpack {
+                package: Box::new(package),
+                body: Box::new(body),
+                ..unpack.clone()
+            })
+        }
+        Expr::Inl(inl) => {
+            let value = type_substitution(&inl.value, from, to);
+            let right_ty = type_type_substitute(&inl.right_ty, from, to);
+
+            Expr::Inl(Inl { value: Box::new(value), right_ty, ..inl.clone() })
+        }
+        Expr::Inr(inr) => {
+            let value = type_substitution(&inr.value, from, to);
+            let left_ty = type_type_substitute(&inr.left_ty, from, to);
+
+            Expr::Inr(Inr { value: Box::new(value), left_ty, ..inr.clone() })
+        }
+        Expr::Case(case) => {
+            let scrutinee = type_substitution(&case.scrutinee, from, to);
+            let left_body = type_substitution(&case.left_body, from, to);
+            let right_body = type_substitution(&case.right_body, from, to);
+
+            Expr::Case(Case {
+                scrutinee: Box::new(scrutinee),
+                left_body: Box::new(left_body),
+                right_body: Box::new(right_body),
+                ..case.clone()
+            })
+        }
+        Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => expr.clone(),
     }
 }
 
-pub fn type_type_substitute(ty: &Type, from: &str, to: &Type) -> Type {
+pub fn type_type_substitute(ty: &Type, from: &Symbol, to: &Type) -> Type {
     match ty {
         Type::TVar(TVar { value }) if value == from => to.clone(),
         Type::Arrow(Arrow { left, right }) => Type::Arrow(Arrow {
             left: Box::new(type_type_substitute(left, from, to)),
             right: Box::new(type_type_substitute(right, from, to)),
         }),
+        Type::Product(Product { fst, snd }) => Type::Product(Product {
+            fst: Box::new(type_type_substitute(fst, from, to)),
+            snd: Box::new(type_type_substitute(snd, from, to)),
+        }),
+        Type::Sum(Sum { left, right }) => Type::Sum(Sum {
+            left: Box::new(type_type_substitute(left, from, to)),
+            right: Box::new(type_type_substitute(right, from, to)),
+        }),
         Type::Forall(Forall { param, .. }) if param == from => ty.clone(),
-        Type::Forall(Forall { param, body }) => Type::Forall(Forall {
+        Type::Forall(Forall { param, param_ty, body }) => Type::Forall(Forall {
             param: param.clone(),
+            param_ty: param_ty.clone(),
             body: Box::new(type_type_substitute(body, from, to)),
         }),
+        Type::Exists(Exists { param, .. }) if param == from => ty.clone(),
+        Type::Exists(Exists { param, param_ty, body }) => Type::Exists(Exists {
+            param: param.clone(),
+            param_ty: param_ty.clone(),
+            body: Box::new(type_type_substitute(body, from, to)),
+        }),
+        Type::Rec(Rec { param, .. }) if param == from => ty.clone(),
+        Type::Rec(Rec { param, param_ty, body }) => Type::Rec(Rec {
+            param: param.clone(),
+            param_ty: param_ty.clone(),
+            body: Box::new(type_type_substitute(body, from, to)),
+        }),
+        Type::TyAbs(TyAbs { param, .. }) if param == from => ty.clone(),
+        Type::TyAbs(TyAbs { param, param_ty, body }) => Type::TyAbs(TyAbs {
+            param: param.clone(),
+            param_ty: param_ty.clone(),
+            body: Box::new(type_type_substitute(body, from, to)),
+        }),
+        Type::TyApp(TyApp { lambda, argm }) => Type::TyApp(TyApp {
+            lambda: Box::new(type_type_substitute(lambda, from, to)),
+            argm: Box::new(type_type_substitute(argm, from, to)),
+        }),
+        Type::TyAnno(TyAnno { ty, anno }) => Type::TyAnno(TyAnno {
+            ty: Box::new(type_type_substitute(ty, from, to)),
+            anno: anno.clone(),
+        }),
         _ => ty.clone(),
     }
 }
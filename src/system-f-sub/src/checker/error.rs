@@ -0,0 +1,27 @@
+use core::fmt;
+use std::fmt::Display;
+
+use crate::parser::parsetree::Type;
+
+#[derive(Debug)]
+pub enum TypeError {
+    Mismatch(Type, Type),
+    NotASubtype(Type, Type),
+    UndefinedVariable(String),
+    UnexpectedType(Type),
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TypeError::Mismatch(e, r) => write!(f, "expect {} but got {}", e, r),
+            TypeError::NotASubtype(s, t) => write!(f, "{} is not a subtype of {}", s, t),
+            TypeError::UndefinedVariable(v) => write!(f, "unbounded variable {}", v),
+            TypeError::UnexpectedType(t) => write!(f, "unexpected type {}", t),
+            TypeError::UndefinedLabel(l) => write!(f, "undefined label {}", l),
+            TypeError::DuplicateLabel(l) => write!(f, "duplicate label {}", l),
+        }
+    }
+}
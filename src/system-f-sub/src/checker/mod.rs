@@ -0,0 +1,541 @@
+pub mod error;
+pub mod subtype;
+
+use std::collections::HashMap;
+
+use crate::checker::error::TypeError;
+use crate::checker::subtype::{expose, subtype};
+use crate::parser::parsetree::{
+    Abs, App, BinOp, Bool, Expr, Fst, If, Int, Operator, Pair, Project, Record, Snd, TAbs, TApp,
+    Type, Unit, Var,
+};
+
+#[derive(Debug)]
+pub struct Context {
+    pub types: HashMap<String, Type>,
+    pub bounds: HashMap<String, Type>,
+    pub names: HashMap<String, String>,
+    pub count: usize,
+}
+
+impl Context {
+    pub fn new() -> Context {
+        Context {
+            types: HashMap::new(),
+            bounds: HashMap::new(),
+            names: HashMap::new(),
+            count: 0,
+        }
+    }
+
+    pub fn rename(&mut self, name: &str) -> String {
+        let new_count = self.count;
+        let new_ident = format!("{}{}", name, new_count);
+
+        self.count += 1;
+        self.names.insert(name.to_string(), new_ident.clone());
+
+        new_ident
+    }
+}
+
+impl Default for Context {
+    fn default() -> Context {
+        Context::new()
+    }
+}
+
+pub fn alpha_conversion_type(context: &mut Context, ty: &Type) -> Result<Type, TypeError> {
+    match ty {
+        Type::TInt | Type::TBool | Type::TUnit | Type::Top => Ok(ty.clone()),
+        Type::TVar { value } => {
+            if let Some(n) = context.names.get(value) {
+                Ok(Type::TVar { value: n.clone() })
+            } else {
+                Err(TypeError::UndefinedVariable(value.clone()))
+            }
+        }
+        Type::Product { fst, snd } => {
+            let fst = alpha_conversion_type(context, fst)?;
+            let snd = alpha_conversion_type(context, snd)?;
+
+            Ok(Type::Product {
+                fst: Box::new(fst),
+                snd: Box::new(snd),
+            })
+        }
+        Type::Record { fields } => {
+            let fields = fields
+                .iter()
+                .map(|(label, ty)| Ok((label.clone(), alpha_conversion_type(context, ty)?)))
+                .collect::<Result<Vec<_>, TypeError>>()?;
+
+            Ok(Type::Record { fields })
+        }
+        Type::Arrow { left, right } => {
+            let left = alpha_conversion_type(context, left)?;
+            let right = alpha_conversion_type(context, right)?;
+
+            Ok(Type::Arrow {
+                left: Box::new(left),
+                right: Box::new(right),
+            })
+        }
+        Type::Forall { param, bound, body } => {
+            let bound = alpha_conversion_type(context, bound)?;
+            let scope = context.names.clone();
+            let param = context.rename(param);
+            let body = alpha_conversion_type(context, body)?;
+            context.names = scope;
+
+            Ok(Type::Forall {
+                param,
+                bound: Box::new(bound),
+                body: Box::new(body),
+            })
+        }
+    }
+}
+
+pub fn alpha_conversion_expr(context: &mut Context, ex: &Expr) -> Result<Expr, TypeError> {
+    match ex {
+        Expr::Int(Int { .. }) | Expr::Bool(Bool { .. }) | Expr::Unit(Unit { .. }) => Ok(ex.clone()),
+        Expr::Var(var) => match context.names.get(&var.value) {
+            Some(n) => Ok(Expr::Var(Var {
+                value: n.clone(),
+                ..var.clone()
+            })),
+            None => Err(TypeError::UndefinedVariable(var.value.clone())),
+        },
+        Expr::App(app) => {
+            let lambda = alpha_conversion_expr(context, &app.lambda)?;
+            let argm = alpha_conversion_expr(context, &app.argm)?;
+
+            Ok(Expr::App(App {
+                lambda: Box::new(lambda),
+                argm: Box::new(argm),
+                ..app.clone()
+            }))
+        }
+        Expr::Abs(abs) => {
+            let scope = context.names.clone();
+            let param = context.rename(&abs.param);
+            let param_ty = alpha_conversion_type(context, &abs.param_ty)?;
+            let body = alpha_conversion_expr(context, &abs.body)?;
+            context.names = scope;
+
+            Ok(Expr::Abs(Abs {
+                param,
+                param_ty,
+                body: Box::new(body),
+                ..abs.clone()
+            }))
+        }
+        Expr::TApp(tapp) => {
+            let lambda = alpha_conversion_expr(context, &tapp.lambda)?;
+            let argm = alpha_conversion_type(context, &tapp.argm)?;
+
+            Ok(Expr::TApp(TApp {
+                lambda: Box::new(lambda),
+                argm,
+                ..tapp.clone()
+            }))
+        }
+        Expr::TAbs(tabs) => {
+            let bound = alpha_conversion_type(context, &tabs.bound)?;
+            let scope = context.names.clone();
+            let param = context.rename(&tabs.param);
+            let body = alpha_conversion_expr(context, &tabs.body)?;
+            context.names = scope;
+
+            Ok(Expr::TAbs(TAbs {
+                param,
+                bound,
+                body: Box::new(body),
+                ..tabs.clone()
+            }))
+        }
+        Expr::Pair(pair) => {
+            let fst = alpha_conversion_expr(context, &pair.fst)?;
+            let snd = alpha_conversion_expr(context, &pair.snd)?;
+
+            Ok(Expr::Pair(Pair {
+                fst: Box::new(fst),
+                snd: Box::new(snd),
+                ..pair.clone()
+            }))
+        }
+        Expr::Fst(fst) => {
+            let pair = alpha_conversion_expr(context, &fst.pair)?;
+
+            Ok(Expr::Fst(Fst {
+                pair: Box::new(pair),
+                ..fst.clone()
+            }))
+        }
+        Expr::Snd(snd) => {
+            let pair = alpha_conversion_expr(context, &snd.pair)?;
+
+            Ok(Expr::Snd(Snd {
+                pair: Box::new(pair),
+                ..snd.clone()
+            }))
+        }
+        Expr::Record(record) => {
+            let fields = record
+                .fields
+                .iter()
+                .map(|(label, ex)| Ok((label.clone(), alpha_conversion_expr(context, ex)?)))
+                .collect::<Result<Vec<_>, TypeError>>()?;
+
+            Ok(Expr::Record(Record { fields, ..record.clone() }))
+        }
+        Expr::Project(project) => {
+            let record = alpha_conversion_expr(context, &project.record)?;
+
+            Ok(Expr::Project(Project {
+                record: Box::new(record),
+                ..project.clone()
+            }))
+        }
+        Expr::If(cond) => {
+            let test = alpha_conversion_expr(context, &cond.cond)?;
+            let then_branch = alpha_conversion_expr(context, &cond.then_branch)?;
+            let else_branch = alpha_conversion_expr(context, &cond.else_branch)?;
+
+            Ok(Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond.clone()
+            }))
+        }
+        Expr::BinOp(binop) => {
+            let left = alpha_conversion_expr(context, &binop.left)?;
+            let right = alpha_conversion_expr(context, &binop.right)?;
+
+            Ok(Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop.clone()
+            }))
+        }
+    }
+}
+
+pub fn equal(received: &Type, expected: &Type) -> bool {
+    match (received, expected) {
+        (Type::TInt, Type::TInt) => true,
+        (Type::TBool, Type::TBool) => true,
+        (Type::TUnit, Type::TUnit) => true,
+        (Type::Top, Type::Top) => true,
+        (Type::TVar { value: received, .. }, Type::TVar { value: expected, .. }) => {
+            received == expected
+        }
+        (
+            Type::Arrow {
+                left: received_left,
+                right: received_right,
+            },
+            Type::Arrow {
+                left: expected_left,
+                right: expected_right,
+            },
+        ) => equal(received_left, expected_left) & equal(received_right, expected_right),
+        (
+            Type::Product {
+                fst: received_fst,
+                snd: received_snd,
+            },
+            Type::Product {
+                fst: expected_fst,
+                snd: expected_snd,
+            },
+        ) => equal(received_fst, expected_fst) & equal(received_snd, expected_snd),
+        (Type::Record { fields: received_fields }, Type::Record { fields: expected_fields }) => {
+            received_fields.len() == expected_fields.len()
+                && received_fields.iter().all(|(label, received_ty)| {
+                    match expected_fields.iter().find(|(other, _)| other == label) {
+                        Some((_, expected_ty)) => equal(received_ty, expected_ty),
+                        None => false,
+                    }
+                })
+        }
+        (
+            Type::Forall {
+                param: received_param,
+                bound: received_bound,
+                body: received_body,
+            },
+            Type::Forall {
+                param: expected_param,
+                bound: expected_bound,
+                body: expected_body,
+            },
+        ) => {
+            let to = Type::TVar { value: expected_param.clone() };
+            let received_body = substitution(received_body, received_param, &to);
+            equal(received_bound, expected_bound) && equal(&received_body, expected_body)
+        }
+        (_, _) => false,
+    }
+}
+
+pub fn substitution(ty: &Type, from: &str, to: &Type) -> Type {
+    match ty {
+        Type::TInt | Type::TBool | Type::TUnit | Type::Top => ty.clone(),
+        Type::TVar { value } if value == from => to.clone(),
+        Type::TVar { .. } => ty.clone(),
+        Type::Arrow { left, right } => {
+            let left = substitution(left, from, to);
+            let right = substitution(right, from, to);
+
+            Type::Arrow {
+                left: Box::new(left),
+                right: Box::new(right),
+            }
+        }
+        Type::Forall { param, bound, body } => {
+            let bound = substitution(bound, from, to);
+            let body = if param == from { *body.clone() } else { substitution(body, from, to) };
+
+            Type::Forall {
+                param: param.clone(),
+                bound: Box::new(bound),
+                body: Box::new(body),
+            }
+        }
+        Type::Product { fst, snd } => {
+            let fst = substitution(fst, from, to);
+            let snd = substitution(snd, from, to);
+
+            Type::Product {
+                fst: Box::new(fst),
+                snd: Box::new(snd),
+            }
+        }
+        Type::Record { fields } => {
+            let fields = fields.iter().map(|(l, ty)| (l.clone(), substitution(ty, from, to)));
+            Type::Record { fields: fields.collect() }
+        }
+    }
+}
+
+fn operator_type(op: Operator) -> Type {
+    match op {
+        Operator::Add | Operator::Sub | Operator::Mul | Operator::Div => Type::TInt,
+        Operator::Eq | Operator::Lt => Type::TBool,
+    }
+}
+
+pub fn infer_type(context: &mut Context, ex: &Expr) -> Result<Type, TypeError> {
+    match ex {
+        Expr::Int { .. } => Ok(Type::TInt),
+        Expr::Bool { .. } => Ok(Type::TBool),
+        Expr::Unit { .. } => Ok(Type::TUnit),
+        Expr::Var(var) => match context.types.get(&var.value) {
+            Some(ty) => Ok(ty.clone()),
+            None => Err(TypeError::UndefinedVariable(var.value.clone())),
+        },
+        Expr::Pair(pair) => {
+            let fst = infer_type(context, &pair.fst)?;
+            let snd = infer_type(context, &pair.snd)?;
+
+            Ok(Type::Product {
+                fst: Box::new(fst),
+                snd: Box::new(snd),
+            })
+        }
+        Expr::Fst(fst) => {
+            let pair = infer_type(context, &fst.pair)?;
+
+            match expose(context, &pair) {
+                Type::Product { fst, .. } => Ok(*fst),
+                _ => Err(TypeError::UnexpectedType(pair)),
+            }
+        }
+        Expr::Snd(snd) => {
+            let pair = infer_type(context, &snd.pair)?;
+
+            match expose(context, &pair) {
+                Type::Product { snd, .. } => Ok(*snd),
+                _ => Err(TypeError::UnexpectedType(pair)),
+            }
+        }
+        Expr::Record(record) => {
+            let mut fields: Vec<(String, Type)> = Vec::new();
+
+            for (label, ex) in &record.fields {
+                if fields.iter().any(|(other, _)| other == label) {
+                    return Err(TypeError::DuplicateLabel(label.clone()));
+                }
+
+                fields.push((label.clone(), infer_type(context, ex)?));
+            }
+
+            Ok(Type::Record { fields })
+        }
+        Expr::Project(project) => {
+            let record = infer_type(context, &project.record)?;
+
+            match expose(context, &record) {
+                Type::Record { fields } => fields
+                    .into_iter()
+                    .find(|(label, _)| *label == project.label)
+                    .map(|(_, ty)| ty)
+                    .ok_or_else(|| TypeError::UndefinedLabel(project.label.clone())),
+                _ => Err(TypeError::UnexpectedType(record)),
+            }
+        }
+        Expr::Abs(abs) => {
+            context.types.insert(abs.param.clone(), abs.param_ty.clone());
+
+            let body_ty = infer_type(context, &abs.body)?;
+
+            Ok(Type::Arrow {
+                left: Box::new(abs.param_ty.clone()),
+                right: Box::new(body_ty),
+            })
+        }
+        Expr::App(app) => {
+            let lambda_ty = infer_type(context, &app.lambda)?;
+            let argm_ty = infer_type(context, &app.argm)?;
+
+            match expose(context, &lambda_ty) {
+                Type::Arrow { left, right } if subtype(context, &argm_ty, &left) => Ok(*right),
+                Type::Arrow { left, .. } => Err(TypeError::NotASubtype(argm_ty, *left)),
+                _ => Err(TypeError::Mismatch(lambda_ty, argm_ty)),
+            }
+        }
+        Expr::TAbs(tabs) => {
+            context.bounds.insert(tabs.param.clone(), tabs.bound.clone());
+
+            let body_ty = infer_type(context, &tabs.body)?;
+
+            Ok(Type::Forall {
+                param: tabs.param.clone(),
+                bound: Box::new(tabs.bound.clone()),
+                body: Box::new(body_ty),
+            })
+        }
+        Expr::TApp(tapp) => {
+            let lambda_ty = infer_type(context, &tapp.lambda)?;
+
+            match expose(context, &lambda_ty) {
+                Type::Forall { param, bound, body } if subtype(context, &tapp.argm, &bound) => {
+                    Ok(substitution(&body, &param, &tapp.argm))
+                }
+                Type::Forall { bound, .. } => {
+                    Err(TypeError::NotASubtype(tapp.argm.clone(), *bound))
+                }
+                _ => Err(TypeError::UnexpectedType(lambda_ty)),
+            }
+        }
+        // Without joins, the branches must be related and the larger type is
+        // the type of the conditional.
+        Expr::If(cond) => {
+            let cond_ty = infer_type(context, &cond.cond)?;
+
+            if !subtype(context, &cond_ty, &Type::TBool) {
+                return Err(TypeError::NotASubtype(cond_ty, Type::TBool));
+            }
+
+            let then_ty = infer_type(context, &cond.then_branch)?;
+            let else_ty = infer_type(context, &cond.else_branch)?;
+
+            if subtype(context, &else_ty, &then_ty) {
+                Ok(then_ty)
+            } else if subtype(context, &then_ty, &else_ty) {
+                Ok(else_ty)
+            } else {
+                Err(TypeError::Mismatch(then_ty, else_ty))
+            }
+        }
+        Expr::BinOp(binop) => {
+            for operand in [&binop.left, &binop.right] {
+                let operand_ty = infer_type(context, operand)?;
+
+                if !subtype(context, &operand_ty, &Type::TInt) {
+                    return Err(TypeError::NotASubtype(operand_ty, Type::TInt));
+                }
+            }
+
+            Ok(operator_type(binop.op))
+        }
+    }
+}
+
+pub fn type_of(ex: Expr) -> Result<Type, TypeError> {
+    let mut context = Context::default();
+    let alpha_terms = alpha_conversion_expr(&mut context, &ex)?;
+    let typed_terms = infer_type(&mut context, &alpha_terms)?;
+
+    Ok(typed_terms)
+}
+
+#[cfg(test)]
+mod test {
+    use super::type_of;
+    use crate::parser;
+
+    #[test]
+    fn test_check_bound_violation() {
+        let input = r"
+        let get_x: ∀P <: {x: Int}. P -> Int = λP <: {x: Int}. λp: P. p.x in
+        get_x [{y: Int}]
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_arrow_contravariance() {
+        let input = r"
+        let call: ({x: Int, y: Int} -> Int) -> Int =
+            λf: {x: Int, y: Int} -> Int. f {x = 1, y = 2} in
+        call (λr: {x: Int, y: Int, z: Int}. r.z)
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_kernel_forall() {
+        let input = r"
+        let f: (∀A <: Top. A -> A) -> Int = λg: ∀A <: Top. A -> A. 0 in
+        f (λA <: Int. λa: A. a)
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_unbounded_variable() {
+        let expr = parser::from_string(r"λA. λa: A. a.x").unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_program_scope() {
+        let input = r"
+        def id: ∀A. A -> A = λA. λa: A. a
+        main = λx: A. a
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_shadowed_parameter() {
+        // The inner `x` goes out of scope after its abstraction.
+        let input = r"(λx: Int. if (λx: Bool. x) true then x else 0) 1";
+        let expr = parser::from_string(input).unwrap();
+
+        assert_eq!(type_of(expr).unwrap().to_string(), "Int")
+    }
+}
@@ -0,0 +1,127 @@
+use super::{equal, substitution, Context};
+use crate::parser::parsetree::Type;
+
+/// Promotes a type variable to its bound until the result is not a variable
+/// anymore, so the rules expecting an arrow, a quantifier or a record can see
+/// through `A <: T`. Unbounded variables are left as they are.
+pub fn expose(context: &Context, ty: &Type) -> Type {
+    match ty {
+        Type::TVar { value } => match context.bounds.get(value) {
+            Some(bound) => expose(context, bound),
+            None => ty.clone(),
+        },
+        _ => ty.clone(),
+    }
+}
+
+/// Algorithmic subtyping `sub <: sup` for kernel F-sub: two quantifiers are
+/// only related when their bounds are equal, which keeps the check decidable.
+pub fn subtype(context: &mut Context, sub: &Type, sup: &Type) -> bool {
+    match (sub, sup) {
+        (_, Type::Top) => true,
+        (Type::TInt, Type::TInt) | (Type::TBool, Type::TBool) | (Type::TUnit, Type::TUnit) => {
+            true
+        }
+        (Type::TVar { value: sub }, Type::TVar { value: sup }) if sub == sup => true,
+        (Type::TVar { value }, _) => match context.bounds.get(value).cloned() {
+            Some(bound) => subtype(context, &bound, sup),
+            None => false,
+        },
+        (
+            Type::Arrow { left: sub_left, right: sub_right },
+            Type::Arrow { left: sup_left, right: sup_right },
+        ) => subtype(context, sup_left, sub_left) && subtype(context, sub_right, sup_right),
+        (
+            Type::Product { fst: sub_fst, snd: sub_snd },
+            Type::Product { fst: sup_fst, snd: sup_snd },
+        ) => subtype(context, sub_fst, sup_fst) && subtype(context, sub_snd, sup_snd),
+        (Type::Record { fields: sub_fields }, Type::Record { fields: sup_fields }) => {
+            sup_fields.iter().all(|(label, sup_ty)| {
+                match sub_fields.iter().find(|(other, _)| other == label) {
+                    Some((_, sub_ty)) => subtype(context, sub_ty, sup_ty),
+                    None => false,
+                }
+            })
+        }
+        (
+            Type::Forall { param: sub_param, bound: sub_bound, body: sub_body },
+            Type::Forall { param: sup_param, bound: sup_bound, body: sup_body },
+        ) => {
+            if !equal(sub_bound, sup_bound) {
+                return false;
+            }
+
+            let to = Type::TVar { value: sup_param.clone() };
+            let sub_body = substitution(sub_body, sub_param, &to);
+
+            // The bound only holds under the quantifier, so whatever it shadowed comes back.
+            let shadowed = context.bounds.insert(sup_param.clone(), *sup_bound.clone());
+            let result = subtype(context, &sub_body, sup_body);
+
+            match shadowed {
+                Some(bound) => context.bounds.insert(sup_param.clone(), bound),
+                None => context.bounds.remove(sup_param),
+            };
+
+            result
+        }
+        (_, _) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::subtype;
+    use crate::checker::Context;
+    use crate::parser::parsetree::Type;
+
+    fn record(fields: &[(&str, Type)]) -> Type {
+        let fields = fields.iter().map(|(l, ty)| (l.to_string(), ty.clone())).collect();
+        Type::Record { fields }
+    }
+
+    fn arrow(left: Type, right: Type) -> Type {
+        Type::Arrow { left: Box::new(left), right: Box::new(right) }
+    }
+
+    #[test]
+    fn test_subtype_width_and_depth() {
+        let mut context = Context::new();
+        let point = record(&[("x", Type::TInt), ("y", Type::TBool)]);
+        let nested = record(&[("p", point.clone()), ("z", Type::TUnit)]);
+
+        assert!(subtype(&mut context, &point, &record(&[("y", Type::TBool)])));
+        assert!(subtype(&mut context, &nested, &record(&[("p", record(&[("x", Type::Top)]))])));
+        assert!(!subtype(&mut context, &record(&[("y", Type::TBool)]), &point));
+    }
+
+    #[test]
+    fn test_subtype_arrow_variance() {
+        let mut context = Context::new();
+        let point = record(&[("x", Type::TInt), ("y", Type::TInt)]);
+        let line = record(&[("x", Type::TInt)]);
+
+        let sub = arrow(line.clone(), point.clone());
+        let sup = arrow(point, line);
+
+        assert!(subtype(&mut context, &sub, &sup));
+        assert!(!subtype(&mut context, &sup, &sub));
+    }
+
+    #[test]
+    fn test_subtype_forall_restores_bounds() {
+        let mut context = Context::new();
+        let forall = |param: &str| Type::Forall {
+            param: param.to_string(),
+            bound: Box::new(Type::Top),
+            body: Box::new(Type::TVar { value: param.to_string() }),
+        };
+
+        context.bounds.insert("A".to_string(), Type::TInt);
+
+        assert!(subtype(&mut context, &forall("B"), &forall("A")));
+        assert!(matches!(context.bounds.get("A"), Some(Type::TInt)));
+        assert!(subtype(&mut context, &forall("A"), &forall("B")));
+        assert!(!context.bounds.contains_key("B"));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod checker;
+pub mod parser;
+pub mod prelude;
+pub mod reduction;
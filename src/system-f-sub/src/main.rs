@@ -0,0 +1,17 @@
+use system_f_sub::checker;
+use system_f_sub::prelude;
+use system_f_sub::reduction;
+
+fn main() {
+    let input = r"
+    def norm: ∀P <: {x: Int, y: Int}. P -> Int =
+        λP <: {x: Int, y: Int}. λp: P. p.x * p.x + p.y * p.y
+    main = norm [{x: Int, y: Int, z: Int}] {x = 3, y = 4, z = toint (mul (succ zero) zero)}
+    ";
+
+    let expr_parsed = prelude::load(input).unwrap();
+    let expr_typed = checker::type_of(expr_parsed.clone()).unwrap();
+    let expr_reduced = reduction::reduce(reduction::Norm::NOR, expr_parsed, None);
+
+    println!("({}) : {}", expr_reduced, expr_typed)
+}
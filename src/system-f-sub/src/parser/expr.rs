@@ -0,0 +1,499 @@
+use super::{
+    error::ParserError,
+    lexer::tokens::Token,
+    location::Range,
+    macros::{consume, match_token},
+    parsetree::{
+        Abs, App, BinOp, Bool, Expr, Fst, If, Int, Operator, Pair, Project, Record, Snd, TAbs,
+        TApp, Type, Unit, Var,
+    },
+    state::Parser,
+};
+
+fn make_binop(op: Operator, left: Expr, right: Expr) -> Expr {
+    let range = left.range().mix(right.range());
+
+    Expr::BinOp(BinOp {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+        range,
+    })
+}
+
+impl<'a> Parser<'a> {
+    pub fn parse_variable_expr(&mut self) -> Result<Expr, ParserError> {
+        let (token, range) = consume!(self, Token::Variable(var) => var.clone())?;
+
+        Ok(Expr::Var(Var { value: token, range }))
+    }
+
+    pub fn parse_number_expr(&mut self) -> Result<Expr, ParserError> {
+        let (token, range) = consume!(self, Token::Number(num) => *num)?;
+
+        Ok(Expr::Int(Int { value: token, range }))
+    }
+
+    pub fn parse_bool_expr(&mut self) -> Result<Expr, ParserError> {
+        let value = matches!(self.get(), Token::True);
+        let (_, range) = consume!(self, Token::True | Token::False)?;
+
+        Ok(Expr::Bool(Bool { value, range }))
+    }
+
+    pub fn parse_braces(&mut self) -> Result<Expr, ParserError> {
+        let (_, range_l) = consume!(self, Token::LBrace)?;
+
+        match (self.get(), self.get_next()) {
+            (Token::RBrace, _) | (Token::Variable(_), Token::Equal) => self.parse_record(range_l),
+            _ => self.parse_pair(range_l),
+        }
+    }
+
+    pub fn parse_record(&mut self, range_l: Range) -> Result<Expr, ParserError> {
+        let mut fields = Vec::new();
+
+        while let Token::Variable(_) = self.get() {
+            let (label, _) = consume!(self, Token::Variable(var) => var.clone())?;
+            consume!(self, Token::Equal)?;
+            fields.push((label, self.parse_expr()?));
+
+            if let Token::Comma = self.get() {
+                consume!(self, Token::Comma)?;
+            } else {
+                break;
+            }
+        }
+
+        let (_, range_r) = consume!(self, Token::RBrace)?;
+
+        Ok(Expr::Record(Record { fields, range: range_l.mix(range_r) }))
+    }
+
+    pub fn parse_pair(&mut self, range_l: Range) -> Result<Expr, ParserError> {
+        let fst = self.parse_expr()?;
+        consume!(self, Token::Comma)?;
+        let snd = self.parse_expr()?;
+        let (_, range_r) = consume!(self, Token::RBrace)?;
+
+        Ok(Expr::Pair(Pair {
+            fst: Box::new(fst),
+            snd: Box::new(snd),
+            range: range_l.mix(range_r),
+        }))
+    }
+
+    pub fn parse_atom(&mut self) -> Result<Expr, ParserError> {
+        match self.get() {
+            Token::LParen => self.parse_parens_expr(),
+            Token::Variable(_) => self.parse_variable_expr(),
+            Token::Number(_) => self.parse_number_expr(),
+            Token::LBrace => self.parse_braces(),
+            Token::True | Token::False => self.parse_bool_expr(),
+            _ => self.fail(),
+        }
+    }
+
+    pub fn parse_projection(&mut self) -> Result<Expr, ParserError> {
+        let mut record = self.parse_atom()?;
+
+        while let (Token::Dot, Token::Variable(_)) = (self.get(), self.get_next()) {
+            consume!(self, Token::Dot)?;
+            let (label, range) = consume!(self, Token::Variable(var) => var.clone())?;
+
+            record = Expr::Project(Project {
+                range: record.range().mix(range),
+                record: Box::new(record),
+                label,
+            });
+        }
+
+        Ok(record)
+    }
+
+    pub fn parse_abs_expr(&mut self, range: Range) -> Result<Expr, ParserError> {
+        let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+
+        consume!(self, Token::Colon)?;
+        let param_type = self.parse_type()?;
+
+        consume!(self, Token::Dot)?;
+        let body = self.parse_expr()?;
+        let endr = body.range();
+
+        Ok(Expr::Abs(Abs {
+            param,
+            param_ty: param_type,
+            body: Box::new(body),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_abs_type(&mut self, range: Range) -> Result<Expr, ParserError> {
+        let (param, _) = consume!(self, Token::TVar(var) | Token::Variable(var) => var.clone())?;
+        let bound = self.parse_bound()?;
+
+        consume!(self, Token::Dot)?;
+        let body = self.parse_expr()?;
+        let endr = body.range();
+
+        Ok(Expr::TAbs(TAbs {
+            param,
+            bound,
+            body: Box::new(body),
+            range: range.mix(endr),
+        }))
+    }
+
+    // A type parameter without `<: T` is bounded by `Top`, which gives back plain System F.
+    pub fn parse_bound(&mut self) -> Result<Type, ParserError> {
+        if let Token::Subtype = self.get() {
+            consume!(self, Token::Subtype)?;
+            self.parse_type()
+        } else {
+            Ok(Type::Top)
+        }
+    }
+
+    pub fn parse_abs(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Lambda)?;
+
+        if let (Token::Variable(_), Token::Colon) = (self.get(), self.get_next()) {
+            self.parse_abs_expr(range)
+        } else {
+            self.parse_abs_type(range)
+        }
+    }
+
+    pub fn parse_type_arg(&mut self) -> Result<(Range, Type), ParserError> {
+        let (_, range_l) = consume!(self, Token::LBracket)?;
+        let argument_ty = self.parse_type()?;
+        let (_, range_r) = consume!(self, Token::RBracket)?;
+
+        Ok((range_l.mix(range_r), argument_ty))
+    }
+
+    pub fn parse_call(&mut self) -> Result<Expr, ParserError> {
+        let mut func = self.parse_projection()?;
+        let mut args = Vec::new();
+
+        if let Token::LBracket = self.get() {
+            while let Ok((loc, arg)) = self.parse_type_arg() {
+                func = Expr::TApp(TApp {
+                    lambda: Box::new(func.clone()),
+                    argm: arg,
+                    range: func.range().mix(loc),
+                });
+            }
+        }
+
+        while let Some(arg) = self.try_single(|state| state.parse_projection())? {
+            args.push(arg);
+        }
+
+        if !args.is_empty() {
+            let appl = args.iter().fold(func, |fun, arg| {
+                Expr::App(App {
+                    lambda: Box::new(fun.clone()),
+                    argm: Box::new(arg.clone()),
+                    range: fun.range().mix(arg.range()),
+                })
+            });
+
+            Ok(appl)
+        } else {
+            Ok(func)
+        }
+    }
+
+    pub fn parse_application(&mut self) -> Result<Expr, ParserError> {
+        let head = self.parse_call()?;
+
+        Ok(head)
+    }
+
+    pub fn parse_multiplicative(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_application()?;
+
+        loop {
+            let op = match self.get() {
+                Token::Prod => Operator::Mul,
+                Token::Slash => Operator::Div,
+                _ => return Ok(left),
+            };
+
+            self.advance()?;
+            let right = self.parse_application()?;
+            left = make_binop(op, left, right);
+        }
+    }
+
+    pub fn parse_additive(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_multiplicative()?;
+
+        loop {
+            let op = match self.get() {
+                Token::Plus => Operator::Add,
+                Token::Minus => Operator::Sub,
+                _ => return Ok(left),
+            };
+
+            self.advance()?;
+            let right = self.parse_multiplicative()?;
+            left = make_binop(op, left, right);
+        }
+    }
+
+    pub fn parse_comparison(&mut self) -> Result<Expr, ParserError> {
+        let left = self.parse_additive()?;
+
+        let op = match self.get() {
+            Token::EqualEqual => Operator::Eq,
+            Token::Less => Operator::Lt,
+            _ => return Ok(left),
+        };
+
+        self.advance()?;
+        let right = self.parse_additive()?;
+
+        Ok(make_binop(op, left, right))
+    }
+
+    pub fn parse_parens_expr(&mut self) -> Result<Expr, ParserError> {
+        let (_, range_l) = consume!(self, Token::LParen)?;
+
+        if let Token::RParen = self.get() {
+            let (_, range_r) = consume!(self, Token::RParen)?;
+            return Ok(Expr::Unit(Unit { range: range_l.mix(range_r) }));
+        }
+
+        let expr = self.parse_expr()?;
+        consume!(self, Token::RParen)?;
+
+        Ok(expr)
+    }
+
+    // A top-level `def x: T = e` is a `let` over the declarations after it, down to the
+    // `main = e` closing the program.
+    pub fn parse_let(&mut self) -> Result<Expr, ParserError> {
+        let declaration = matches!(self.get(), Token::Def);
+        let (_, range) = consume!(self, Token::Let | Token::Def)?;
+        let (param, _) = consume!(self, Token::Variable(name) => name.clone())?;
+
+        consume!(self, Token::Colon)?;
+        let param_ty = self.parse_type()?;
+
+        consume!(self, Token::Equal)?;
+        let value = self.parse_expr()?;
+
+        let body = match self.get() {
+            Token::Def | Token::Main if declaration => self.parse_program()?,
+            _ if declaration => self.fail()?,
+            _ => {
+                consume!(self, Token::In)?;
+                self.parse_expr()?
+            }
+        };
+
+        let func = Expr::Abs(Abs {
+            param,
+            param_ty,
+            body: Box::new(body.clone()),
+            range: range.mix(body.range()),
+        });
+
+        Ok(Expr::App(App {
+            lambda: Box::new(func),
+            argm: Box::new(value.clone()),
+            range: range.mix(value.range()),
+        }))
+    }
+
+    pub fn parse_fst(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Fst)?;
+        let pair = self.parse_expr()?;
+
+        Ok(Expr::Fst(Fst {
+            pair: Box::new(pair.clone()),
+            range: range.mix(pair.range()),
+        }))
+    }
+
+    pub fn parse_snd(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Snd)?;
+        let pair = self.parse_expr()?;
+
+        Ok(Expr::Snd(Snd {
+            pair: Box::new(pair.clone()),
+            range: range.mix(pair.range()),
+        }))
+    }
+
+    pub fn parse_if(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::If)?;
+        let cond = self.parse_expr()?;
+
+        consume!(self, Token::Then)?;
+        let then_branch = self.parse_expr()?;
+
+        consume!(self, Token::Else)?;
+        let else_branch = self.parse_expr()?;
+        let endr = else_branch.range();
+
+        Ok(Expr::If(If {
+            cond: Box::new(cond),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_main(&mut self) -> Result<Expr, ParserError> {
+        consume!(self, Token::Main)?;
+        consume!(self, Token::Equal)?;
+
+        self.parse_expr()
+    }
+
+    // A program is an expression, or declarations closed by `main = e`; `main` is accepted
+    // only here, where a declaration could start.
+    pub fn parse_program(&mut self) -> Result<Expr, ParserError> {
+        match self.get() {
+            Token::Main => self.parse_main(),
+            _ => self.parse_expr(),
+        }
+    }
+
+    pub fn parse_expr(&mut self) -> Result<Expr, ParserError> {
+        match self.get() {
+            Token::Lambda => self.parse_abs(),
+            Token::If => self.parse_if(),
+            Token::Let | Token::Def => self.parse_let(),
+            Token::Fst => self.parse_fst(),
+            Token::Snd => self.parse_snd(),
+            _ => self.parse_comparison(),
+        }
+    }
+
+    pub fn parse_simple_type(&mut self) -> Result<Type, ParserError> {
+        match self.get() {
+            Token::LParen => {
+                consume!(self, Token::LParen)?;
+                let ty = self.parse_type()?;
+                consume!(self, Token::RParen)?;
+                Ok(ty)
+            }
+            Token::TInt => {
+                consume!(self, Token::TInt)?;
+                Ok(Type::TInt)
+            }
+            Token::TBool => {
+                consume!(self, Token::TBool)?;
+                Ok(Type::TBool)
+            }
+            Token::TUnit => {
+                consume!(self, Token::TUnit)?;
+                Ok(Type::TUnit)
+            }
+            Token::Top => {
+                consume!(self, Token::Top)?;
+                Ok(Type::Top)
+            }
+            Token::TVar(_) => {
+                let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
+                Ok(Type::TVar { value: token })
+            }
+            Token::LBrace => {
+                consume!(self, Token::LBrace)?;
+                let fields = self.parse_fields()?;
+                consume!(self, Token::RBrace)?;
+
+                Ok(Type::Record { fields })
+            }
+            Token::Forall => {
+                consume!(self, Token::Forall)?;
+                let (token, _) =
+                    consume!(self, Token::TVar(var) | Token::Variable(var) => var.clone())?;
+                let bound = self.parse_bound()?;
+                consume!(self, Token::Dot)?;
+                let body = self.parse_type()?;
+
+                Ok(Type::Forall {
+                    param: token,
+                    bound: Box::new(bound),
+                    body: Box::new(body),
+                })
+            }
+            _ => self.fail(),
+        }
+    }
+
+    pub fn parse_fields(&mut self) -> Result<Vec<(String, Type)>, ParserError> {
+        let mut fields = Vec::new();
+
+        while let Token::Variable(_) = self.get() {
+            let (label, _) = consume!(self, Token::Variable(var) => var.clone())?;
+            consume!(self, Token::Colon)?;
+            fields.push((label, self.parse_type()?));
+
+            if let Token::Comma = self.get() {
+                consume!(self, Token::Comma)?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(fields)
+    }
+
+    pub fn parse_arrow_partial(&mut self, head: Type) -> Result<Type, ParserError> {
+        match self.get() {
+            Token::Arrow => {
+                consume!(self, Token::Arrow)?;
+                let body = self.parse_type()?;
+
+                Ok(Type::Arrow {
+                    left: Box::new(head),
+                    right: Box::new(body),
+                })
+            }
+            Token::Prod => {
+                consume!(self, Token::Prod)?;
+                let body = self.parse_type()?;
+
+                Ok(Type::Product {
+                    fst: Box::new(head),
+                    snd: Box::new(body),
+                })
+            }
+            _ => Ok(head),
+        }
+    }
+
+    pub fn parse_type(&mut self) -> Result<Type, ParserError> {
+        if let Token::LParen = self.get() {
+            consume!(self, Token::LParen)?;
+            let head = self.parse_type()?;
+            consume!(self, Token::RParen)?;
+
+            self.parse_arrow_partial(head)
+        } else {
+            let head = self.parse_simple_type()?;
+            self.parse_arrow_partial(head)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser;
+
+    #[test]
+    fn test_parse_main_in_expression() {
+        let inputs = [r"(λx: Int. main = x + 1) 1", r"if true then main = 1 else 2"];
+
+        for input in inputs {
+            assert!(parser::from_string(input).is_err())
+        }
+    }
+}
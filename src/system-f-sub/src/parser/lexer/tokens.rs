@@ -0,0 +1,46 @@
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Token {
+    Number(usize),
+    Variable(String),
+    Lambda,
+    True,
+    False,
+    If,
+    Then,
+    Else,
+    Let,
+    Def,
+    Main,
+    Fst,
+    Snd,
+    In,
+
+    TInt,
+    TBool,
+    TUnit,
+    Top,
+    TVar(String),
+    Arrow,
+    Forall,
+    Prod,
+    Subtype,
+
+    Dot,
+    Colon,
+    Comma,
+    Equal,
+    EqualEqual,
+    Less,
+    Plus,
+    Minus,
+    Slash,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+
+    Error,
+    Eof,
+}
@@ -0,0 +1,333 @@
+use super::location::Range;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Int {
+    pub value: usize,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bool {
+    pub value: bool,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Var {
+    pub value: String,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Abs {
+    pub param: String,
+    pub param_ty: Type,
+    pub body: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct App {
+    pub lambda: Box<Expr>,
+    pub argm: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct TAbs {
+    pub param: String,
+    pub bound: Type,
+    pub body: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct TApp {
+    pub lambda: Box<Expr>,
+    pub argm: Type,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pair {
+    pub fst: Box<Expr>,
+    pub snd: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fst {
+    pub pair: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Snd {
+    pub pair: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub fields: Vec<(String, Expr)>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub record: Box<Expr>,
+    pub label: String,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct If {
+    pub cond: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Lt,
+}
+
+#[derive(Debug, Clone)]
+pub struct BinOp {
+    pub op: Operator,
+    pub left: Box<Expr>,
+    pub right: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    TInt,
+    TBool,
+    TUnit,
+    TVar { value: String },
+    Arrow { left: Box<Type>, right: Box<Type> },
+    Top,
+    Forall { param: String, bound: Box<Type>, body: Box<Type> },
+    Product { fst: Box<Type>, snd: Box<Type> },
+    Record { fields: Vec<(String, Type)> },
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Int(Int),
+    Bool(Bool),
+    Unit(Unit),
+    Var(Var),
+    Abs(Abs),
+    App(App),
+    Fst(Fst),
+    Snd(Snd),
+    Pair(Pair),
+    Record(Record),
+    Project(Project),
+    TAbs(TAbs),
+    TApp(TApp),
+    If(If),
+    BinOp(BinOp),
+}
+
+impl Expr {
+    pub fn range(&self) -> Range {
+        match self {
+            Expr::Int(Int { range, .. }) => *range,
+            Expr::Bool(Bool { range, .. }) => *range,
+            Expr::Unit(Unit { range }) => *range,
+            Expr::Var(Var { range, .. }) => *range,
+            Expr::Abs(Abs { range, .. }) => *range,
+            Expr::App(App { range, .. }) => *range,
+            Expr::Fst(Fst { range, .. }) => *range,
+            Expr::Snd(Snd { range, .. }) => *range,
+            Expr::Pair(Pair { range, .. }) => *range,
+            Expr::Record(Record { range, .. }) => *range,
+            Expr::Project(Project { range, .. }) => *range,
+            Expr::TAbs(TAbs { range, .. }) => *range,
+            Expr::TApp(TApp { range, .. }) => *range,
+            Expr::If(If { range, .. }) => *range,
+            Expr::BinOp(BinOp { range, .. }) => *range,
+        }
+    }
+}
+
+impl fmt::Display for Int {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for Bool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "()")
+    }
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for Abs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "λ{}: {}. {}", self.param, self.param_ty, self.body)
+    }
+}
+
+impl fmt::Display for App {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} {})", self.lambda, self.argm)
+    }
+}
+
+impl fmt::Display for TAbs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "λ{} <: {}. {}", self.param, self.bound, self.body)
+    }
+}
+
+impl fmt::Display for TApp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} [{}])", self.lambda, self.argm)
+    }
+}
+
+impl fmt::Display for Fst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "fst {}", self.pair)
+    }
+}
+
+impl fmt::Display for Snd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "snd {}", self.pair)
+    }
+}
+
+impl fmt::Display for Pair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{}, {}}}", self.fst, self.snd)
+    }
+}
+
+fn fmt_fields<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    sep: &str,
+    fields: &[(String, T)],
+) -> fmt::Result {
+    for (i, (label, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}{}{}", label, sep, value)?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")?;
+        fmt_fields(f, " = ", &self.fields)?;
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for Project {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.record, self.label)
+    }
+}
+
+impl fmt::Display for If {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(if {} then {} else {})",
+            self.cond, self.then_branch, self.else_branch
+        )
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Sub => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+            Operator::Eq => write!(f, "=="),
+            Operator::Lt => write!(f, "<"),
+        }
+    }
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} {} {})", self.left, self.op, self.right)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::TInt => write!(f, "Int"),
+            Type::TBool => write!(f, "Bool"),
+            Type::TUnit => write!(f, "Unit"),
+            Type::TVar { value } => write!(f, "{}", value),
+            Type::Arrow { left, right } => write!(f, "({} -> {})", left, right),
+            Type::Top => write!(f, "Top"),
+            Type::Forall { param, bound, body } => write!(f, "∀{} <: {}. {}", param, bound, body),
+            Type::Product { fst, snd } => write!(f, "{} × {}", fst, snd),
+            Type::Record { fields } => {
+                write!(f, "{{")?;
+                fmt_fields(f, ": ", fields)?;
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Int(int) => write!(f, "{}", int),
+            Expr::Bool(bool) => write!(f, "{}", bool),
+            Expr::Unit(unit) => write!(f, "{}", unit),
+            Expr::Var(var) => write!(f, "{}", var),
+            Expr::Abs(abs) => write!(f, "{}", abs),
+            Expr::App(app) => write!(f, "{}", app),
+            Expr::Fst(fst) => write!(f, "{}", fst),
+            Expr::Snd(snd) => write!(f, "{}", snd),
+            Expr::Pair(pair) => write!(f, "{}", pair),
+            Expr::Record(record) => write!(f, "{}", record),
+            Expr::Project(project) => write!(f, "{}", project),
+            Expr::TAbs(tabs) => write!(f, "{}", tabs),
+            Expr::TApp(tapp) => write!(f, "{}", tapp),
+            Expr::If(cond) => write!(f, "{}", cond),
+            Expr::BinOp(binop) => write!(f, "{}", binop),
+        }
+    }
+}
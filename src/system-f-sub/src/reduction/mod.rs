@@ -0,0 +1,106 @@
+use crate::parser::parsetree::Expr;
+
+pub mod primitives;
+pub mod strategies;
+pub mod variables;
+
+pub enum Norm {
+    NOR, // normal-order
+}
+
+pub fn reduce(strategy: Norm, expr: Expr, limit: Option<usize>) -> Expr {
+    let limit = Some(limit.unwrap_or(100));
+
+    match strategy {
+        Norm::NOR => strategies::normal_order(expr, limit),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{reduce, Norm};
+    use crate::{checker, parser, prelude};
+
+    fn eval(input: &str) -> String {
+        let expr = parser::from_string(input).unwrap();
+        checker::type_of(expr.clone()).unwrap();
+        reduce(Norm::NOR, expr, None).to_string()
+    }
+
+    #[test]
+    fn test_reduce_if() {
+        let input = r"
+        let pick: ∀A. Bool -> A -> A -> A = λA. λb: Bool. λx: A. λy: A. if b then x else y in
+        pick [Int] true 1 2
+        ";
+
+        assert_eq!(eval(input), "1")
+    }
+
+    #[test]
+    fn test_reduce_overflow() {
+        let input = r"
+        let big: Int = 4294967296 in
+        big * big + 1
+        ";
+
+        assert_eq!(eval(input), "((4294967296 * 4294967296) + 1)")
+    }
+
+    #[test]
+    fn test_reduce_width_subsumption() {
+        let input = r"
+        let get_x: {x: Int} -> Int = λr: {x: Int}. r.x in
+        get_x {x = 40, y = true} + get_x {z = (), x = 2}
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_bounded_quantification() {
+        let input = r"
+        let bump: ∀P <: {x: Int}. P -> Int * P = λP <: {x: Int}. λp: P. {p.x + 1, p} in
+        let point: {x: Int, y: Int} = {x = 1, y = 5} in
+        let res: Int * {x: Int, y: Int} = bump [{x: Int, y: Int}] point in
+        (fst res) + (snd res).y
+        ";
+
+        assert_eq!(eval(input), "7")
+    }
+
+    #[test]
+    fn test_reduce_promoted_function() {
+        let input = r"
+        let apply: ∀F <: {x: Int} -> Int. F -> Int = λF <: {x: Int} -> Int. λf: F. f {x = 3} in
+        apply [Top -> Int] (λt: Top. 4)
+        ";
+
+        assert_eq!(eval(input), "4")
+    }
+
+    #[test]
+    fn test_reduce_program() {
+        let input = r"
+        def base: Int = 0
+        def origin: {x: Int, y: Int} = {x = 0, y = 0}
+        def shift: ∀P <: {x: Int}. P -> Int = λP <: {x: Int}. λp: P. p.x + 1
+        main = shift [{x: Int, y: Int}] origin + shift [{x: Int}] {x = base}
+        ";
+
+        assert_eq!(eval(input), "2")
+    }
+
+    #[test]
+    fn test_reduce_prelude() {
+        let input = r"
+        let two: ∀N. (N -> N) -> N -> N = succ (succ zero) in
+        let xs: ∀R. (Int -> R -> R) -> R -> R = cons [Int] (toint (mul two two)) (nil [Int]) in
+        getx [{x: Int, y: Int}] {x = sum xs, y = 0} + (fst (swap [Bool] [Int] {true, 1}))
+        ";
+        let expr = prelude::load(input).unwrap();
+        checker::type_of(expr.clone()).unwrap();
+
+        assert_eq!(reduce(Norm::NOR, expr, None).to_string(), "5")
+    }
+}
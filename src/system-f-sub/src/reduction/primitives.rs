@@ -0,0 +1,31 @@
+use crate::parser::parsetree::{BinOp, Bool, Expr, Int, Operator};
+
+// Subtraction is truncated at zero since literals are naturals. Division by
+// zero, results that overflow and operands that are not literals leave the
+// operation stuck.
+pub fn delta(binop: BinOp) -> Expr {
+    let (left, right) = match (&*binop.left, &*binop.right) {
+        (Expr::Int(Int { value: left, .. }), Expr::Int(Int { value: right, .. })) => {
+            (*left, *right)
+        }
+        _ => return Expr::BinOp(binop),
+    };
+
+    let range = binop.range;
+
+    match binop.op {
+        Operator::Add => match left.checked_add(right) {
+            Some(value) => Expr::Int(Int { value, range }),
+            None => Expr::BinOp(binop),
+        },
+        Operator::Sub => Expr::Int(Int { value: left.saturating_sub(right), range }),
+        Operator::Mul => match left.checked_mul(right) {
+            Some(value) => Expr::Int(Int { value, range }),
+            None => Expr::BinOp(binop),
+        },
+        Operator::Div if right == 0 => Expr::BinOp(binop),
+        Operator::Div => Expr::Int(Int { value: left / right, range }),
+        Operator::Eq => Expr::Bool(Bool { value: left == right, range }),
+        Operator::Lt => Expr::Bool(Bool { value: left < right, range }),
+    }
+}
@@ -0,0 +1,149 @@
+use super::primitives::delta;
+use super::variables::{substitution, type_substitution};
+use crate::parser::parsetree::{
+    Abs, App, BinOp, Bool, Expr, Fst, If, Pair, Project, Record, Snd, TAbs, TApp,
+};
+
+macro_rules! break_limit {
+    ($expr: ident, $limit: ident) => {
+        if let Some(count) = $limit {
+            if count == 0 {
+                return $expr;
+            }
+        }
+    };
+}
+
+pub fn normal_order(ex: Expr, limit: Option<usize>) -> Expr {
+    break_limit!(ex, limit);
+
+    match ex {
+        Expr::Int(int) => Expr::Int(int),
+        Expr::Bool(bool) => Expr::Bool(bool),
+        Expr::Unit(unit) => Expr::Unit(unit),
+        Expr::Var(var) => Expr::Var(var),
+        Expr::Pair(pair) => {
+            let fst = normal_order(*pair.fst, limit);
+            let snd = normal_order(*pair.snd, limit);
+
+            Expr::Pair(Pair {
+                fst: Box::new(fst),
+                snd: Box::new(snd),
+                ..pair
+            })
+        }
+        Expr::Fst(fst) => {
+            let pair = normal_order(*fst.pair, limit);
+
+            match pair {
+                Expr::Pair(Pair { fst, .. }) => *fst,
+                pair => Expr::Fst(Fst { pair: Box::new(pair), ..fst }),
+            }
+        }
+        Expr::Snd(snd) => {
+            let pair = normal_order(*snd.pair, limit);
+
+            match pair {
+                Expr::Pair(Pair { snd, .. }) => *snd,
+                pair => Expr::Snd(Snd { pair: Box::new(pair), ..snd }),
+            }
+        }
+        Expr::Record(record) => {
+            let fields = record
+                .fields
+                .into_iter()
+                .map(|(label, field)| (label, normal_order(field, limit)))
+                .collect();
+
+            Expr::Record(Record { fields, ..record })
+        }
+        Expr::Project(project) => match normal_order(*project.record, limit) {
+            Expr::Record(Record { fields, .. })
+                if fields.iter().any(|(label, _)| *label == project.label) =>
+            {
+                let (_, field) = fields.into_iter().find(|(l, _)| *l == project.label).unwrap();
+                field
+            }
+            record => Expr::Project(Project { record: Box::new(record), ..project }),
+        },
+        Expr::Abs(abs) => {
+            let body = normal_order(*abs.body, limit);
+            Expr::Abs(Abs { body: Box::new(body), ..abs })
+        }
+        Expr::App(app) => {
+            let func_expr = normal_order(*app.lambda, limit);
+
+            match func_expr {
+                Expr::Abs(abs) => {
+                    let substituted = substitution(*abs.body, abs.param, *app.argm);
+                    normal_order(substituted, limit.map(|l| l - 1))
+                }
+                func => {
+                    let argm = normal_order(*app.argm, limit);
+
+                    Expr::App(App {
+                        lambda: Box::new(func),
+                        argm: Box::new(argm),
+                        ..app
+                    })
+                }
+            }
+        }
+        Expr::TAbs(tabs) => {
+            let body = normal_order(*tabs.body, limit);
+
+            Expr::TAbs(TAbs { body: Box::new(body), ..tabs })
+        }
+        Expr::TApp(tapp) => {
+            let func_expr = normal_order(*tapp.lambda, limit);
+
+            match func_expr {
+                Expr::TAbs(TAbs { param, body, .. }) => {
+                    let substituted = type_substitution(&body, &param, &tapp.argm);
+                    normal_order(substituted, limit.map(|l| l - 1))
+                }
+                func => {
+
+                    Expr::TApp(TApp {
+                        lambda: Box::new(func),
+                        argm: tapp.argm,
+                        range: tapp.range,
+                    })
+                }
+            }
+        }
+        Expr::If(cond) => {
+            let test = normal_order(*cond.cond, limit);
+
+            match test {
+                Expr::Bool(Bool { value: true, .. }) => {
+                    normal_order(*cond.then_branch, limit.map(|l| l - 1))
+                }
+                Expr::Bool(Bool { value: false, .. }) => {
+                    normal_order(*cond.else_branch, limit.map(|l| l - 1))
+                }
+                test => {
+                    let then_branch = normal_order(*cond.then_branch, limit);
+                    let else_branch = normal_order(*cond.else_branch, limit);
+
+                    Expr::If(If {
+                        cond: Box::new(test),
+                        then_branch: Box::new(then_branch),
+                        else_branch: Box::new(else_branch),
+                        ..cond
+                    })
+                }
+            }
+        }
+        Expr::BinOp(binop) => {
+            let left = normal_order(*binop.left, limit);
+            let right = normal_order(*binop.right, limit);
+
+            delta(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use crate::parser::parsetree::{
+    Abs, App, BinOp, Bool, Expr, Fst, If, Int, Pair, Project, Record, Snd, TAbs, TApp, Type, Unit,
+    Var,
+};
+use std::collections::HashSet;
+
+pub fn free_variables(expr: Expr) -> HashSet<String> {
+    let mut free: HashSet<String> = HashSet::new();
+
+    match expr {
+        Expr::Int(Int { .. }) => (),
+        Expr::Bool(Bool { .. }) => (),
+        Expr::Unit(Unit { .. }) => (),
+        Expr::Var(Var { value, .. }) => {
+            free.insert(value);
+        }
+        Expr::Pair(Pair { fst, snd, .. }) => {
+            free.extend(free_variables(*fst));
+            free.extend(free_variables(*snd));
+        }
+        Expr::Fst(Fst { pair, .. }) => {
+            free.extend(free_variables(*pair));
+        }
+        Expr::Snd(Snd { pair, .. }) => {
+            free.extend(free_variables(*pair));
+        }
+        Expr::Record(Record { fields, .. }) => {
+            for (_, field) in fields {
+                free.extend(free_variables(field));
+            }
+        }
+        Expr::Project(Project { record, .. }) => free.extend(free_variables(*record)),
+        Expr::App(App { lambda, argm, .. }) => {
+            free.extend(free_variables(*lambda));
+            free.extend(free_variables(*argm));
+        }
+        Expr::Abs(Abs { param, body, .. }) => {
+            free.extend(free_variables(*body));
+            free.remove(&param);
+        }
+        Expr::TAbs(TAbs { body, .. }) => free.extend(free_variables(*body)),
+        Expr::TApp(TApp { lambda, .. }) => free.extend(free_variables(*lambda)),
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            free.extend(free_variables(*cond));
+            free.extend(free_variables(*then_branch));
+            free.extend(free_variables(*else_branch));
+        }
+        Expr::BinOp(BinOp { left, right, .. }) => {
+            free.extend(free_variables(*left));
+            free.extend(free_variables(*right));
+        }
+    }
+
+    free
+}
+
+pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
+    match expr.clone() {
+        Expr::Var(Var { value, .. }) if value == from => to,
+        Expr::Var(Var { .. }) => expr,
+        Expr::Int(Int { .. }) => expr,
+        Expr::Bool(Bool { .. }) => expr,
+        Expr::Unit(Unit { .. }) => expr,
+        Expr::Pair(pair) => {
+            let fst = substitution(*pair.fst, from.clone(), to.clone());
+            let snd = substitution(*pair.snd, from.clone(), to.clone());
+
+            Expr::Pair(Pair { fst: Box::new(fst), snd: Box::new(snd), ..pair })
+        }
+        Expr::Fst(fst) => {
+            let pair = substitution(*fst.pair, from, to);
+            Expr::Fst(Fst { pair: Box::new(pair), ..fst })
+        }
+        Expr::Snd(snd) => {
+            let pair = substitution(*snd.pair, from, to);
+            Expr::Snd(Snd { pair: Box::new(pair), ..snd })
+        }
+        Expr::Record(record) => {
+            let fields = record
+                .fields
+                .into_iter()
+                .map(|(label, field)| (label, substitution(field, from.clone(), to.clone())))
+                .collect();
+
+            Expr::Record(Record { fields, ..record })
+        }
+        Expr::Project(project) => {
+            let record = substitution(*project.record, from, to);
+            Expr::Project(Project { record: Box::new(record), ..project })
+        }
+        Expr::App(app) => {
+            let lambda = substitution(*app.lambda, from.clone(), to.clone());
+            let argm = substitution(*app.argm, from, to);
+
+            Expr::App(App {
+                lambda: Box::new(lambda),
+                argm: Box::new(argm),
+                ..app
+            })
+        }
+        Expr::Abs(abs) => {
+            let free = free_variables(to.clone());
+            let cond = from != abs.param && !free.contains(&abs.param);
+
+            if cond {
+                let body = substitution(*abs.body, from, to);
+                return Expr::Abs(Abs { body: Box::new(body), ..abs });
+            }
+
+            Expr::Abs(abs)
+        }
+        Expr::TAbs(tabs) => {
+            let body = substitution(*tabs.body, from, to);
+            Expr::TAbs(TAbs { body: Box::new(body), ..tabs })
+        }
+        Expr::TApp(TApp { lambda, argm, range }) => {
+            let lambda = substitution(*lambda, from, to);
+            Expr::TApp(TApp { lambda: Box::new(lambda), argm, range })
+        }
+        Expr::If(cond) => {
+            let test = substitution(*cond.cond, from.clone(), to.clone());
+            let then_branch = substitution(*cond.then_branch, from.clone(), to.clone());
+            let else_branch = substitution(*cond.else_branch, from, to);
+
+            Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond
+            })
+        }
+        Expr::BinOp(binop) => {
+            let left = substitution(*binop.left, from.clone(), to.clone());
+            let right = substitution(*binop.right, from, to);
+
+            Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
+    }
+}
+
+pub fn type_substitution(expr: &Expr, from: &str, to: &Type) -> Expr {
+    match expr {
+        Expr::App(app) => {
+            let lambda = type_substitution(&app.lambda, from, to);
+            let argm = type_substitution(&app.argm, from, to);
+
+            Expr::App(App {
+                lambda: Box::new(lambda),
+                argm: Box::new(argm),
+                ..app.clone()
+            })
+        }
+        Expr::Abs(abs) => {
+            let param_ty = type_type_substitute(&abs.param_ty, from, to);
+            let body = type_substitution(&abs.body, from, to);
+
+            Expr::Abs(Abs {
+                param_ty,
+                body: Box::new(body),
+                ..abs.clone()
+            })
+        }
+        Expr::TAbs(tabs) => {
+            let bound = type_type_substitute(&tabs.bound, from, to);
+
+            let body = if tabs.param != from {
+                type_substitution(&tabs.body, from, to)
+            } else {
+                *tabs.body.clone()
+            };
+
+            Expr::TAbs(TAbs {
+                bound,
+                body: Box::new(body),
+                ..tabs.clone()
+            })
+        }
+        Expr::Record(record) => {
+            let fields = record
+                .fields
+                .iter()
+                .map(|(label, field)| (label.clone(), type_substitution(field, from, to)))
+                .collect();
+
+            Expr::Record(Record { fields, ..record.clone() })
+        }
+        Expr::Project(project) => {
+            let record = type_substitution(&project.record, from, to);
+            Expr::Project(Project { record: Box::new(record), ..project.clone() })
+        }
+        Expr::If(cond) => {
+            let test = type_substitution(&cond.cond, from, to);
+            let then_branch = type_substitution(&cond.then_branch, from, to);
+            let else_branch = type_substitution(&cond.else_branch, from, to);
+
+            Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond.clone()
+            })
+        }
+        Expr::BinOp(binop) => {
+            let left = type_substitution(&binop.left, from, to);
+            let right = type_substitution(&binop.right, from, to);
+
+            Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop.clone()
+            })
+        }
+        _ => expr.clone(),
+    }
+}
+
+pub fn type_type_substitute(ty: &Type, from: &str, to: &Type) -> Type {
+    match ty {
+        Type::TVar { value } if value == from => to.clone(),
+        Type::TVar { .. } => ty.clone(),
+        Type::Arrow { left, right } => Type::Arrow {
+            left: Box::new(type_type_substitute(left, from, to)),
+            right: Box::new(type_type_substitute(right, from, to)),
+        },
+        Type::Forall { param, bound, body } => Type::Forall {
+            param: param.clone(),
+            bound: Box::new(type_type_substitute(bound, from, to)),
+            body: if param == from {
+                body.clone()
+            } else {
+                Box::new(type_type_substitute(body, from, to))
+            },
+        },
+        Type::Product { fst, snd } => Type::Product {
+            fst: Box::new(type_type_substitute(fst, from, to)),
+            snd: Box::new(type_type_substitute(snd, from, to)),
+        },
+        Type::Record { fields } => {
+            let fields = fields
+                .iter()
+                .map(|(l, ty)| (l.clone(), type_type_substitute(ty, from, to)));
+
+            Type::Record { fields: fields.collect() }
+        }
+        _ => ty.clone(),
+    }
+}
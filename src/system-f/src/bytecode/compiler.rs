@@ -0,0 +1,233 @@
+use super::vm::Error;
+use super::{Instr, Program};
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Bool, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Int, Match, Pack, Pair,
+    Project, Record, Snd, TAbs, TApp, Unfold, Unit, Unpack, Var, Variant,
+};
+use crate::reduction::variables::free_variables;
+use std::rc::Rc;
+
+// A function being compiled, with the names of its locals.
+struct Function {
+    code: Vec<Instr>,
+    scope: Vec<String>,
+}
+
+impl Function {
+    fn new(scope: Vec<String>) -> Function {
+        Function { code: Vec::new(), scope }
+    }
+
+    fn slot(&self, name: &str) -> Result<usize, Error> {
+        let slot = self.scope.iter().rposition(|local| local == name);
+        slot.ok_or_else(|| Error::Unbound(name.to_string()))
+    }
+
+    // Emits a jump whose target is set once it is known.
+    fn jump(&mut self, instr: fn(usize) -> Instr) -> usize {
+        self.code.push(instr(0));
+        self.code.len() - 1
+    }
+
+    fn land(&mut self, jump: usize) {
+        let target = self.code.len();
+
+        match &mut self.code[jump] {
+            Instr::Jump(to) | Instr::JumpUnless(to) => *to = target,
+            instr => unreachable!("{} is not a jump", instr),
+        }
+    }
+
+    fn land_arm(&mut self, switch: usize, arm: usize) {
+        let target = self.code.len();
+
+        match &mut self.code[switch] {
+            Instr::Switch(arms) => arms[arm].1 = target,
+            instr => unreachable!("{} is not a switch", instr),
+        }
+    }
+}
+
+// The abstraction becomes a function of the program whose locals are the variables it uses,
+// in the order of their names, then its parameter.
+fn closure(functions: &mut Vec<Vec<Instr>>, outer: &Function, abs: &Abs) -> Result<Instr, Error> {
+    let mut names: Vec<String> = free_variables(Expr::Abs(abs.clone())).into_iter().collect();
+    names.sort();
+
+    let captures = names.iter().map(|name| outer.slot(name)).collect::<Result<_, _>>()?;
+    names.push(abs.param.clone());
+
+    let index = functions.len();
+    functions.push(Vec::new());
+
+    let mut function = Function::new(names);
+    expression(functions, &mut function, &abs.body, true)?;
+    function.code.push(Instr::Return);
+    functions[index] = function.code;
+
+    Ok(Instr::Closure { function: index, captures })
+}
+
+// Compiles the arms of a `case`, each binding what the scrutinee holds to its parameter.
+fn switch(
+    functions: &mut Vec<Vec<Instr>>,
+    function: &mut Function,
+    arms: &[(&str, &str, &Expr)],
+    tail: bool,
+) -> Result<(), Error> {
+    let labels = arms.iter().map(|(label, ..)| (Rc::from(*label), 0)).collect();
+    function.code.push(Instr::Switch(labels));
+
+    let switch = function.code.len() - 1;
+    let mut ends = Vec::new();
+
+    for (index, (_, param, body)) in arms.iter().enumerate() {
+        function.land_arm(switch, index);
+
+        function.code.push(Instr::Store(function.scope.len()));
+        function.scope.push(param.to_string());
+        expression(functions, function, body, tail)?;
+        function.scope.pop();
+
+        ends.push(function.jump(Instr::Jump));
+    }
+
+    for end in ends {
+        function.land(end);
+    }
+
+    Ok(())
+}
+
+// Compiles `expr` so that it leaves its value on the stack. A call in tail position replaces
+// the running function instead.
+fn expression(
+    functions: &mut Vec<Vec<Instr>>,
+    function: &mut Function,
+    expr: &Expr,
+    tail: bool,
+) -> Result<(), Error> {
+    let apply = if tail { Instr::TailApply } else { Instr::Apply };
+
+    match expr {
+        Expr::Int(Int { value, .. }) => function.code.push(Instr::Int(*value)),
+        Expr::Bool(Bool { value, .. }) => function.code.push(Instr::Bool(*value)),
+        Expr::Unit(Unit { .. }) => function.code.push(Instr::Unit),
+        Expr::Var(Var { value, .. }) => {
+            let slot = function.slot(value)?;
+            function.code.push(Instr::Load(slot))
+        }
+        Expr::Abs(abs) => {
+            let closure = closure(functions, function, abs)?;
+            function.code.push(closure)
+        }
+        Expr::App(App { lambda, argm, .. }) => {
+            expression(functions, function, lambda, false)?;
+            expression(functions, function, argm, false)?;
+            function.code.push(apply)
+        }
+        Expr::TAbs(TAbs { body: value, .. })
+        | Expr::TApp(TApp { lambda: value, .. })
+        | Expr::Fold(Fold { value, .. })
+        | Expr::Unfold(Unfold { value, .. })
+        | Expr::Pack(Pack { value, .. }) => expression(functions, function, value, tail)?,
+        Expr::Pair(Pair { fst, snd, .. }) => {
+            expression(functions, function, fst, false)?;
+            expression(functions, function, snd, false)?;
+            function.code.push(Instr::Pair)
+        }
+        Expr::Fst(Fst { pair, .. }) => {
+            expression(functions, function, pair, false)?;
+            function.code.push(Instr::Fst)
+        }
+        Expr::Snd(Snd { pair, .. }) => {
+            expression(functions, function, pair, false)?;
+            function.code.push(Instr::Snd)
+        }
+        Expr::Record(Record { fields, .. }) => {
+            for (_, field) in fields {
+                expression(functions, function, field, false)?;
+            }
+
+            let labels = fields.iter().map(|(label, _)| Rc::from(label.as_str())).collect();
+            function.code.push(Instr::Record(labels))
+        }
+        Expr::Project(Project { record, label, .. }) => {
+            expression(functions, function, record, false)?;
+            function.code.push(Instr::Project(Rc::from(label.as_str())))
+        }
+        Expr::Inl(Inl { value, .. }) => {
+            expression(functions, function, value, false)?;
+            function.code.push(Instr::Inject(Rc::from("inl")))
+        }
+        Expr::Inr(Inr { value, .. }) => {
+            expression(functions, function, value, false)?;
+            function.code.push(Instr::Inject(Rc::from("inr")))
+        }
+        Expr::Variant(Variant { label, value, .. }) => {
+            expression(functions, function, value, false)?;
+            function.code.push(Instr::Inject(Rc::from(label.as_str())))
+        }
+        Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+            expression(functions, function, scrutinee, false)?;
+
+            let arms = [
+                ("inl", left_param.as_str(), left_body.as_ref()),
+                ("inr", right_param.as_str(), right_body.as_ref()),
+            ];
+            switch(functions, function, &arms, tail)?
+        }
+        Expr::Match(Match { scrutinee, arms, .. }) => {
+            expression(functions, function, scrutinee, false)?;
+
+            let arms: Vec<(&str, &str, &Expr)> = arms
+                .iter()
+                .map(|Arm { label, param, body }| (label.as_str(), param.as_str(), body))
+                .collect();
+            switch(functions, function, &arms, tail)?
+        }
+        Expr::Unpack(Unpack { package, param, body, .. }) => {
+            expression(functions, function, package, false)?;
+
+            function.code.push(Instr::Store(function.scope.len()));
+            function.scope.push(param.clone());
+            expression(functions, function, body, tail)?;
+            function.scope.pop();
+        }
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            expression(functions, function, cond, false)?;
+            let otherwise = function.jump(Instr::JumpUnless);
+
+            expression(functions, function, then_branch, tail)?;
+            let end = function.jump(Instr::Jump);
+
+            function.land(otherwise);
+            expression(functions, function, else_branch, tail)?;
+            function.land(end)
+        }
+        Expr::BinOp(BinOp { op, left, right, .. }) => {
+            expression(functions, function, left, false)?;
+            expression(functions, function, right, false)?;
+            function.code.push(Instr::BinOp(*op))
+        }
+        Expr::Fix(Fix { value, .. }) => {
+            expression(functions, function, value, false)?;
+            function.code.push(Instr::Fix);
+            function.code.push(apply)
+        }
+    }
+
+    Ok(())
+}
+
+/// Compiles a well-typed term, erasing its types.
+pub fn compile(expr: &Expr) -> Result<Program, Error> {
+    let mut functions = vec![Vec::new()];
+    let mut main = Function::new(Vec::new());
+
+    expression(&mut functions, &mut main, expr, true)?;
+    main.code.push(Instr::Return);
+    functions[0] = main.code;
+
+    Ok(Program { functions })
+}
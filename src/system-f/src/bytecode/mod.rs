@@ -0,0 +1,273 @@
+//! A compiler from well-typed terms to the bytecode of a stack machine, and the machine that
+//! runs it by call-by-value. Types are erased: type abstractions and applications, `fold`,
+//! `unfold` and `pack` leave their terms as they are, so a type abstraction runs its body right
+//! away. Every abstraction becomes a function of the program whose closures copy the variables
+//! it uses, so nothing is substituted at run time.
+
+use crate::parser::parsetree::{Expr, Operator};
+use std::fmt;
+use std::rc::Rc;
+
+pub mod compiler;
+pub mod vm;
+
+use vm::{Error, Value, Vm};
+
+/// An instruction of the machine, which works on a stack of values and on the locals of the
+/// function it is running: the variables its closure captured, its parameter, then the
+/// variables bound in its body.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instr {
+    Int(usize),
+    Bool(bool),
+    Unit,
+    /// Pushes a local.
+    Load(usize),
+    /// Pops a value into a local, dropping the locals above it.
+    Store(usize),
+    /// Pushes a closure of a function, capturing the given locals.
+    Closure { function: usize, captures: Vec<usize> },
+    /// Pushes the fixed point of the function on top of the stack above it, ready to be
+    /// applied to it.
+    Fix,
+    /// Pops an argument and a function, and calls the function.
+    Apply,
+    /// Calls like `Apply` in place of the running function, which has nothing left to do.
+    TailApply,
+    /// Returns the top of the stack to the caller.
+    Return,
+    Jump(usize),
+    /// Pops a boolean and jumps when it is false.
+    JumpUnless(usize),
+    BinOp(Operator),
+    Pair,
+    Fst,
+    Snd,
+    /// Pops the values of the fields, in order, into a record.
+    Record(Vec<Rc<str>>),
+    Project(Rc<str>),
+    /// Labels the top of the stack. Sums are labelled `inl` and `inr`.
+    Inject(Rc<str>),
+    /// Pops a labelled value, pushes what it holds and jumps to the arm of its label.
+    Switch(Vec<(Rc<str>, usize)>),
+}
+
+/// A compiled program: its functions, the body of the program being the first one.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub functions: Vec<Vec<Instr>>,
+}
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instr::Int(value) => write!(f, "int {}", value),
+            Instr::Bool(value) => write!(f, "bool {}", value),
+            Instr::Unit => write!(f, "unit"),
+            Instr::Load(slot) => write!(f, "load {}", slot),
+            Instr::Store(slot) => write!(f, "store {}", slot),
+            Instr::Closure { function, captures } => {
+                write!(f, "closure {} {:?}", function, captures)
+            }
+            Instr::Fix => write!(f, "fix"),
+            Instr::Apply => write!(f, "apply"),
+            Instr::TailApply => write!(f, "tailapply"),
+            Instr::Return => write!(f, "return"),
+            Instr::Jump(target) => write!(f, "jump {}", target),
+            Instr::JumpUnless(target) => write!(f, "jumpunless {}", target),
+            Instr::BinOp(op) => write!(f, "binop {}", op),
+            Instr::Pair => write!(f, "pair"),
+            Instr::Fst => write!(f, "fst"),
+            Instr::Snd => write!(f, "snd"),
+            Instr::Record(labels) => write!(f, "record [{}]", labels.join(", ")),
+            Instr::Project(label) => write!(f, "project {}", label),
+            Instr::Inject(label) => write!(f, "inject {}", label),
+            Instr::Switch(arms) => {
+                let arms: Vec<String> =
+                    arms.iter().map(|(label, target)| format!("{} {}", label, target)).collect();
+                write!(f, "switch [{}]", arms.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, function) in self.functions.iter().enumerate() {
+            writeln!(f, "{}:", index)?;
+
+            for (pc, instr) in function.iter().enumerate() {
+                writeln!(f, "  {:>3}  {}", pc, instr)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compiles a well-typed term and runs it for at most `limit` instructions.
+pub fn run(expr: &Expr, limit: Option<usize>) -> Result<Value, Error> {
+    let program = compiler::compile(expr)?;
+    Vm::new(&program).run(limit)
+}
+
+#[cfg(test)]
+mod test {
+    use super::vm::{Error, Vm};
+    use super::{compiler::compile, run};
+    use crate::reduction::{reduce, Norm};
+    use crate::{checker, parser, prelude};
+
+    #[test]
+    fn test_compile_case() {
+        let input = r"λs: Int + Bool. case s of inl n => n | inr b => if b then 1 else 0";
+        let expr = parser::from_string(input).unwrap();
+        let program = compile(&expr).unwrap().to_string();
+        let expected = "\
+0:
+    0  closure 1 []
+    1  return
+1:
+    0  load 0
+    1  switch [inl 2, inr 5]
+    2  store 1
+    3  load 1
+    4  jump 12
+    5  store 1
+    6  load 1
+    7  jumpunless 10
+    8  int 1
+    9  jump 11
+   10  int 0
+   11  jump 12
+   12  return
+";
+
+        assert_eq!(program, expected)
+    }
+
+    #[test]
+    fn test_run_agrees_with_normal_order() {
+        let inputs = [
+            r"
+            let pick: ∀A. Bool -> A -> A -> A = λA. λb: Bool. λx: A. λy: A. if b then x else y in
+            pick [Int] true 1 2
+            ",
+            r"
+            let square: Int -> Int = λx: Int. x * x in
+            let pair: Int * Int = {square 3 - 1, 10 / 4} in
+            {(fst pair) == (snd pair) + 6, pair}
+            ",
+            r"
+            let safe_div: Int -> Int -> Unit + Int = λx: Int. λy: Int.
+                if y == 0 then inl [Int] () else inr [Unit] x / y in
+            {safe_div 12 4, safe_div 1 0}
+            ",
+            r"
+            let get_x: ∀ρ. {x: Int | ρ} -> Int = λρ. λr: {x: Int | ρ}. r.x in
+            let point: {x: Int, y: Int} = {x = 20, y = 22} in
+            {sum = get_x [{y: Int}] point + point.y, point = point}
+            ",
+            r"
+            let size: <num: Int, flag: Bool> -> Int = λv: <num: Int, flag: Bool>.
+                case v of <num n> => n | <flag b> => if b then 1 else 0 in
+            size (<num = 41> as <num: Int, flag: Bool>) +
+                size (<flag = true> as <num: Int, flag: Bool>)
+            ",
+            r"
+            let nil: μL. Unit + Int * L =
+                fold [μL. Unit + Int * L] inl [Int * (μL. Unit + Int * L)] () in
+            let cons: Int -> (μL. Unit + Int * L) -> μL. Unit + Int * L =
+                λx: Int. λxs: μL. Unit + Int * L. fold [μL. Unit + Int * L] inr [Unit] {x, xs} in
+            let rec len: (μL. Unit + Int * L) -> Int =
+                λl: μL. Unit + Int * L. case unfold l of inl u => 0 | inr p => 1 + len (snd p) in
+            len (cons 1 (cons 2 nil))
+            ",
+            r"
+            let rec iter: ∀A. (A -> A) -> Int -> A -> A =
+                λA. λf: A -> A. λn: Int. λx: A. if n == 0 then x else iter [A] f (n - 1) (f x) in
+            iter [Int] (λx: Int. x * 2) 5 1
+            ",
+            r"
+            let counter: ∃C. {new: C, inc: C -> C, get: C -> Int} =
+                pack [Int, {new = 0, inc = λc: Int. c + 1, get = λc: Int. c}]
+                as ∃C. {new: C, inc: C -> C, get: C -> Int} in
+            unpack counter as [C, c] in c.get (c.inc (c.inc c.new))
+            ",
+            r"
+            let two: ∀N. (N -> N) -> N -> N = succ (succ zero) in
+            let xs: ∀R. (Int -> R -> R) -> R -> R = cons [Int] (toint (mul two two)) (nil [Int]) in
+            first [Int] [∀B. B -> B -> B] (pair [Int] [∀B. B -> B -> B] (sum xs) (iszero zero))
+            ",
+        ];
+
+        for input in inputs {
+            let expr = prelude::load(input).unwrap();
+            let ty = checker::type_of(expr.clone()).unwrap();
+
+            let value = run(&expr, None).unwrap().to_expr(&ty, expr.range()).unwrap();
+            let normal = reduce(Norm::NOR, expr, None);
+
+            assert_eq!(value.to_string(), normal.to_string(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_run_recursive_value() {
+        let input = r"
+        let cons: Int -> (μL. Unit + Int * L) -> μL. Unit + Int * L =
+            λx: Int. λxs: μL. Unit + Int * L. fold [μL. Unit + Int * L] inr [Unit] {x, xs} in
+        cons 1 (fold [μL. Unit + Int * L] inl [Int * (μL. Unit + Int * L)] ())
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert_eq!(run(&expr, None).unwrap().to_string(), "<inr = {1, <inl = ()>}>")
+    }
+
+    #[test]
+    fn test_run_tail_calls() {
+        let input = r"
+        def rec iter: ∀A. (A -> A) -> Int -> A -> A =
+            λA. λf: A -> A. λn: Int. λx: A. if n == 0 then x else iter [A] f (n - 1) (f x)
+        main = iter [Int] (λx: Int. x + 2) 100000 0
+        ";
+        let expr = parser::from_string(input).unwrap();
+        let program = compile(&expr).unwrap();
+        let mut vm = Vm::new(&program);
+
+        assert_eq!(vm.run(None).unwrap().to_string(), "200000");
+        assert!(vm.depth < 5);
+    }
+
+    #[test]
+    fn test_run_errors() {
+        let expr = parser::from_string(r"1 / 0 == 0").unwrap();
+        assert!(matches!(run(&expr, None), Err(Error::DivisionByZero)));
+
+        let expr = parser::from_string(r"fix (λf: Int -> Int. λn: Int. f n) 0").unwrap();
+        assert!(matches!(run(&expr, Some(1000)), Err(Error::Limit)));
+
+        let expr = parser::from_string(r"100000 * 100000 * 100000 * 100000").unwrap();
+        assert!(matches!(run(&expr, None), Err(Error::Overflow)));
+
+        let expr = parser::from_string(r"λx: Int. x + y").unwrap();
+        assert!(matches!(run(&expr, None), Err(Error::Unbound(name)) if name == "y"));
+    }
+
+    #[test]
+    fn test_run_fixed_point_of_value() {
+        // Well-typed, but only the fixed point of a function can be unfolded.
+        let inputs = [
+            r"fix (λx: Int. x + 1)",
+            r"if fix (λb: Bool. b) then 1 else 2",
+            r"fst (fix (λp: Int * Int. p))",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            checker::type_of(expr.clone()).unwrap();
+
+            assert!(matches!(run(&expr, None), Err(Error::FixedPoint)), "{}", input);
+        }
+    }
+}
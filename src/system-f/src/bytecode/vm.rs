@@ -0,0 +1,322 @@
+use super::{Instr, Program};
+use crate::checker::unroll;
+use crate::parser::location::Range;
+use crate::parser::parsetree::{
+    Bool, Expr, Fold, Inl, Inr, Int, Operator, Pair, Record, Type, Unit, Variant,
+};
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(usize),
+    Bool(bool),
+    Unit,
+    Pair(Rc<(Value, Value)>),
+    Record(Rc<Vec<(Rc<str>, Value)>>),
+    /// A value of a variant, or of a sum labelled `inl` or `inr`.
+    Variant(Rc<str>, Rc<Value>),
+    Closure(Rc<Closure>),
+    /// The fixed point of a function, unfolded when it is applied.
+    Fix(Rc<Value>),
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    function: usize,
+    captured: Vec<Value>,
+}
+
+impl Value {
+    /// The term of a value of type `ty`, whose annotations its type gives back. Functions and
+    /// packages, whose types were erased, have none.
+    pub fn to_expr(&self, ty: &Type, range: Range) -> Option<Expr> {
+        let expr = match (self, ty) {
+            (value, Type::Rec { .. }) => {
+                let value = value.to_expr(&unroll(ty)?, range)?;
+                Expr::Fold(Fold { ty: ty.clone(), value: Box::new(value), range })
+            }
+            (Value::Int(value), _) => Expr::Int(Int { value: *value, range }),
+            (Value::Bool(value), _) => Expr::Bool(Bool { value: *value, range }),
+            (Value::Unit, _) => Expr::Unit(Unit { range }),
+            (Value::Pair(pair), Type::Product { fst, snd }) => Expr::Pair(Pair {
+                fst: Box::new(pair.0.to_expr(fst, range)?),
+                snd: Box::new(pair.1.to_expr(snd, range)?),
+                range,
+            }),
+            (Value::Record(values), Type::Record { fields: types, .. }) => {
+                let mut fields = Vec::new();
+
+                for (label, value) in values.iter() {
+                    let (_, ty) = types.iter().find(|(name, _)| **name == **label)?;
+                    fields.push((label.to_string(), value.to_expr(ty, range)?));
+                }
+
+                Expr::Record(Record { fields, range })
+            }
+            (Value::Variant(label, value), Type::Sum { left, right }) => match &**label {
+                "inl" => {
+                    let value = Box::new(value.to_expr(left, range)?);
+                    Expr::Inl(Inl { value, right_ty: Type::clone(right), range })
+                }
+                _ => {
+                    let value = Box::new(value.to_expr(right, range)?);
+                    Expr::Inr(Inr { value, left_ty: Type::clone(left), range })
+                }
+            },
+            (Value::Variant(label, value), Type::Variant { cases, .. }) => {
+                let (_, case) = cases.iter().find(|(name, _)| **name == **label)?;
+                let value = Box::new(value.to_expr(case, range)?);
+
+                Expr::Variant(Variant { label: label.to_string(), value, ty: ty.clone(), range })
+            }
+            _ => return None,
+        };
+
+        Some(expr)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Unit => write!(f, "()"),
+            Value::Pair(pair) => write!(f, "{{{}, {}}}", pair.0, pair.1),
+            Value::Record(fields) => {
+                let fields: Vec<String> =
+                    fields.iter().map(|(label, value)| format!("{} = {}", label, value)).collect();
+                write!(f, "{{{}}}", fields.join(", "))
+            }
+            Value::Variant(label, value) => write!(f, "<{} = {}>", label, value),
+            Value::Closure(closure) => write!(f, "<closure {}>", closure.function),
+            Value::Fix(value) => write!(f, "(fix {})", value),
+        }
+    }
+}
+
+/// Why a term has no value on the machine.
+#[derive(Debug)]
+pub enum Error {
+    /// The term has a variable no binder binds, so it was not type checked.
+    Unbound(String),
+    DivisionByZero,
+    /// An addition or a multiplication does not fit an integer.
+    Overflow,
+    /// A fixed point was used where a value which is not a function was needed. Like the
+    /// strategies, the machine only unfolds a fixed point by applying it.
+    FixedPoint,
+    /// The program ran for as many instructions as it was allowed to.
+    Limit,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unbound(name) => write!(f, "{} is not bound", name),
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::Overflow => write!(f, "integer overflow"),
+            Error::FixedPoint => write!(f, "the fixed point of a value which is not a function"),
+            Error::Limit => write!(f, "the program ran out of steps"),
+        }
+    }
+}
+
+struct Frame {
+    function: usize,
+    pc: usize,
+    locals: Vec<Value>,
+    // The arguments the value this call returns is applied to next, the first one last.
+    pending: Vec<Value>,
+}
+
+/// The machine, which runs a well-typed program by call-by-value. Like the CBV strategy, it
+/// does not terminate on arguments that do not, even those normal order would drop.
+pub struct Vm<'p> {
+    program: &'p Program,
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    /// The instructions run so far.
+    pub steps: usize,
+    /// The most calls that were running at once.
+    pub depth: usize,
+}
+
+// Subtraction is truncated at zero since literals are naturals.
+fn binop(op: Operator, left: usize, right: usize) -> Result<Value, Error> {
+    let value = match op {
+        Operator::Add => Value::Int(left.checked_add(right).ok_or(Error::Overflow)?),
+        Operator::Sub => Value::Int(left.saturating_sub(right)),
+        Operator::Mul => Value::Int(left.checked_mul(right).ok_or(Error::Overflow)?),
+        Operator::Div if right == 0 => return Err(Error::DivisionByZero),
+        Operator::Div => Value::Int(left / right),
+        Operator::Eq => Value::Bool(left == right),
+        Operator::Lt => Value::Bool(left < right),
+    };
+
+    Ok(value)
+}
+
+// The error of a value which is not the one an instruction needs. In a well-typed program only
+// a fixed point is.
+fn mismatch(value: Value, expected: &str) -> Error {
+    match value {
+        Value::Fix(_) => Error::FixedPoint,
+        value => unreachable!("{} is not {}", value, expected),
+    }
+}
+
+impl<'p> Vm<'p> {
+    pub fn new(program: &'p Program) -> Vm<'p> {
+        let main = Frame { function: 0, pc: 0, locals: Vec::new(), pending: Vec::new() };
+        Vm { program, stack: Vec::new(), frames: vec![main], steps: 0, depth: 1 }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("the stack holds the operands of every instruction")
+    }
+
+    fn pop_int(&mut self) -> Result<usize, Error> {
+        match self.pop() {
+            Value::Int(value) => Ok(value),
+            value => Err(mismatch(value, "an integer")),
+        }
+    }
+
+    fn call(&mut self, lambda: Value, argm: Value, mut pending: Vec<Value>) {
+        match lambda {
+            Value::Closure(closure) => {
+                let mut locals = closure.captured.clone();
+                locals.push(argm);
+
+                let frame = Frame { function: closure.function, pc: 0, locals, pending };
+                self.frames.push(frame);
+                self.depth = self.depth.max(self.frames.len());
+            }
+            Value::Fix(ref functional) => {
+                pending.push(argm);
+                let functional = Value::clone(functional);
+                self.call(functional, lambda, pending)
+            }
+            value => unreachable!("{} is not a function", value),
+        }
+    }
+
+    /// Runs the program for at most `limit` instructions.
+    pub fn run(&mut self, limit: Option<usize>) -> Result<Value, Error> {
+        let program = self.program;
+
+        loop {
+            if limit.is_some_and(|limit| self.steps >= limit) {
+                return Err(Error::Limit);
+            }
+            self.steps += 1;
+
+            let frame = self.frames.last_mut().expect("a program returns from its last call");
+            let instr = &program.functions[frame.function][frame.pc];
+            frame.pc += 1;
+
+            match instr {
+                Instr::Int(value) => self.stack.push(Value::Int(*value)),
+                Instr::Bool(value) => self.stack.push(Value::Bool(*value)),
+                Instr::Unit => self.stack.push(Value::Unit),
+                Instr::Load(slot) => self.stack.push(frame.locals[*slot].clone()),
+                Instr::Store(slot) => {
+                    let value = self.stack.pop().expect("a value is stored once it is computed");
+                    frame.locals.truncate(*slot);
+                    frame.locals.push(value)
+                }
+                Instr::Closure { function, captures } => {
+                    let captured = captures.iter().map(|slot| frame.locals[*slot].clone());
+                    let closure = Closure { function: *function, captured: captured.collect() };
+
+                    self.stack.push(Value::Closure(Rc::new(closure)))
+                }
+                Instr::Fix => {
+                    let functional = self.pop();
+                    self.stack.push(functional.clone());
+                    self.stack.push(Value::Fix(Rc::new(functional)))
+                }
+                Instr::Apply => {
+                    let argm = self.pop();
+                    let lambda = self.pop();
+                    self.call(lambda, argm, Vec::new())
+                }
+                Instr::TailApply => {
+                    let argm = self.pop();
+                    let lambda = self.pop();
+                    let frame = self.frames.pop().expect("the running function has a frame");
+                    self.call(lambda, argm, frame.pending)
+                }
+                Instr::Return => {
+                    let mut frame = self.frames.pop().expect("the running function has a frame");
+                    let value = self.pop();
+
+                    match frame.pending.pop() {
+                        Some(argm) => self.call(value, argm, frame.pending),
+                        None if self.frames.is_empty() => return Ok(value),
+                        None => self.stack.push(value),
+                    }
+                }
+                Instr::Jump(target) => frame.pc = *target,
+                Instr::JumpUnless(target) => match self.pop() {
+                    Value::Bool(true) => (),
+                    Value::Bool(false) => self.jump(*target),
+                    value => return Err(mismatch(value, "a boolean")),
+                },
+                Instr::BinOp(op) => {
+                    let right = self.pop_int()?;
+                    let left = self.pop_int()?;
+                    let value = binop(*op, left, right)?;
+
+                    self.stack.push(value)
+                }
+                Instr::Pair => {
+                    let snd = self.pop();
+                    let fst = self.pop();
+                    self.stack.push(Value::Pair(Rc::new((fst, snd))))
+                }
+                Instr::Fst | Instr::Snd => match self.pop() {
+                    Value::Pair(pair) if *instr == Instr::Fst => self.stack.push(pair.0.clone()),
+                    Value::Pair(pair) => self.stack.push(pair.1.clone()),
+                    value => return Err(mismatch(value, "a pair")),
+                },
+                Instr::Record(labels) => {
+                    let values = self.stack.split_off(self.stack.len() - labels.len());
+                    let fields = labels.iter().cloned().zip(values).collect();
+
+                    self.stack.push(Value::Record(Rc::new(fields)))
+                }
+                Instr::Project(label) => match self.pop() {
+                    Value::Record(fields) => {
+                        let field = fields.iter().find(|(name, _)| name == label);
+                        let (_, value) = field.expect("a well-typed record has the field");
+
+                        self.stack.push(value.clone())
+                    }
+                    value => return Err(mismatch(value, "a record")),
+                },
+                Instr::Inject(label) => {
+                    let value = self.pop();
+                    self.stack.push(Value::Variant(label.clone(), Rc::new(value)))
+                }
+                Instr::Switch(arms) => match self.pop() {
+                    Value::Variant(label, value) => {
+                        let arm = arms.iter().find(|(name, _)| *name == label);
+                        let (_, target) = arm.expect("a well-typed case has an arm for the label");
+
+                        self.stack.push(Value::clone(&value));
+                        self.jump(*target)
+                    }
+                    value => return Err(mismatch(value, "labelled")),
+                },
+            }
+        }
+    }
+
+    fn jump(&mut self, target: usize) {
+        let frame = self.frames.last_mut().expect("the running function has a frame");
+        frame.pc = target;
+    }
+}
@@ -10,6 +10,10 @@ pub enum TypeError {
     UnexpectedType(Type),
     VariableClash,
     TypeClash,
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    MissingLabel(String),
+    EscapingType(String),
 }
 
 impl Display for TypeError {
@@ -20,6 +24,10 @@ impl Display for TypeError {
             TypeError::UnexpectedType(t) => write!(f, "unexpected type {}", t),
             TypeError::VariableClash => write!(f, "variable clash"),
             TypeError::TypeClash => write!(f, "type clash"),
+            TypeError::UndefinedLabel(l) => write!(f, "undefined label {}", l),
+            TypeError::DuplicateLabel(l) => write!(f, "duplicate label {}", l),
+            TypeError::MissingLabel(l) => write!(f, "missing case for label {}", l),
+            TypeError::EscapingType(t) => write!(f, "type {} escapes its scope", t),
         }
     }
 }
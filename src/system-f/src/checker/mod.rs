@@ -3,7 +3,10 @@ pub mod error;
 use std::collections::HashMap;
 
 use crate::checker::error::TypeError;
-use crate::parser::parsetree::{Abs, App, Expr, Fst, Int, Pair, Snd, TAbs, TApp, Type, Var};
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Bool, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Int, Match, Operator,
+    Pack, Pair, Project, Record, Row, Snd, TAbs, TApp, Type, Unfold, Unit, Unpack, Var, Variant,
+};
 
 #[derive(Debug)]
 pub struct Context {
@@ -40,7 +43,7 @@ impl Default for Context {
 
 pub fn alpha_conversion_type(context: &mut Context, ty: &Type) -> Result<Type, TypeError> {
     match ty {
-        Type::TInt => Ok(ty.clone()),
+        Type::TInt | Type::TBool | Type::TUnit => Ok(ty.clone()),
         Type::TVar { value } => {
             if let Some(n) = context.names.get(value) {
                 Ok(Type::TVar { value: n.clone() })
@@ -57,6 +60,23 @@ pub fn alpha_conversion_type(context: &mut Context, ty: &Type) -> Result<Type, T
                 snd: Box::new(snd),
             })
         }
+        Type::Sum { left, right } => {
+            let left = alpha_conversion_type(context, left)?;
+            let right = alpha_conversion_type(context, right)?;
+
+            Ok(Type::Sum {
+                left: Box::new(left),
+                right: Box::new(right),
+            })
+        }
+        Type::Record { fields, rest } => {
+            let (fields, rest) = alpha_conversion_row(context, fields, rest)?;
+            Ok(Type::Record { fields, rest })
+        }
+        Type::Variant { cases, rest } => {
+            let (cases, rest) = alpha_conversion_row(context, cases, rest)?;
+            Ok(Type::Variant { cases, rest })
+        }
         Type::Arrow { left, right } => {
             let left = alpha_conversion_type(context, left)?;
             let right = alpha_conversion_type(context, right)?;
@@ -67,17 +87,92 @@ pub fn alpha_conversion_type(context: &mut Context, ty: &Type) -> Result<Type, T
             })
         }
         Type::Forall { param, body } => {
+            let scope = context.names.clone();
             let param = context.rename(param);
             let body = alpha_conversion_type(context, body)?;
+            context.names = scope;
 
             Ok(Type::Forall { param, body: Box::new(body) })
         }
+        Type::Exists { param, body } => {
+            let scope = context.names.clone();
+            let param = context.rename(param);
+            let body = alpha_conversion_type(context, body)?;
+            context.names = scope;
+
+            Ok(Type::Exists { param, body: Box::new(body) })
+        }
+        Type::Rec { param, body } => {
+            let scope = context.names.clone();
+            let param = context.rename(param);
+            let body = alpha_conversion_type(context, body)?;
+            context.names = scope;
+
+            Ok(Type::Rec { param, body: Box::new(body) })
+        }
     }
 }
 
+pub fn alpha_conversion_row(
+    context: &mut Context,
+    fields: &[(String, Type)],
+    rest: &Option<String>,
+) -> Result<Row, TypeError> {
+    let fields = fields
+        .iter()
+        .map(|(label, ty)| Ok((label.clone(), alpha_conversion_type(context, ty)?)))
+        .collect::<Result<Vec<_>, TypeError>>()?;
+
+    let rest = match rest {
+        Some(rest) => match context.names.get(rest) {
+            Some(n) => Some(n.clone()),
+            None => return Err(TypeError::UndefinedVariable(rest.clone())),
+        },
+        None => None,
+    };
+
+    Ok((fields, rest))
+}
+
+// A `let` is an abstraction applied to the value it binds. Chains of them, like the prelude,
+// are walked in a loop so that their length does not add to the recursion depth.
+pub(crate) fn let_chain(ex: &Expr) -> (Vec<(&App, &Abs)>, &Expr) {
+    let mut lets = Vec::new();
+    let mut body = ex;
+
+    while let Expr::App(app) = body {
+        let Expr::Abs(abs) = &*app.lambda else { break };
+        lets.push((app, abs));
+        body = &abs.body;
+    }
+
+    (lets, body)
+}
+
+fn alpha_conversion_lets(context: &mut Context, ex: &Expr) -> Result<Expr, TypeError> {
+    let (lets, body) = let_chain(ex);
+    let scope = context.names.clone();
+    let mut bindings = Vec::new();
+
+    for (app, abs) in &lets {
+        let argm = alpha_conversion_expr(context, &app.argm)?;
+        let param = context.rename(&abs.param);
+        let param_ty = alpha_conversion_type(context, &abs.param_ty)?;
+        bindings.push((argm, param, param_ty));
+    }
+    let body = alpha_conversion_expr(context, body)?;
+    context.names = scope;
+
+    let lets = lets.into_iter().zip(bindings).rev();
+    Ok(lets.fold(body, |body, ((app, abs), (argm, param, param_ty))| {
+        let lambda = Expr::Abs(Abs { param, param_ty, body: Box::new(body), range: abs.range });
+        Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range: app.range })
+    }))
+}
+
 pub fn alpha_conversion_expr(context: &mut Context, ex: &Expr) -> Result<Expr, TypeError> {
     match ex {
-        Expr::Int(Int { .. }) => Ok(ex.clone()),
+        Expr::Int(Int { .. }) | Expr::Bool(Bool { .. }) | Expr::Unit(Unit { .. }) => Ok(ex.clone()),
         Expr::Var(var) => match context.names.get(&var.value) {
             Some(n) => Ok(Expr::Var(Var {
                 value: n.clone(),
@@ -85,6 +180,7 @@ pub fn alpha_conversion_expr(context: &mut Context, ex: &Expr) -> Result<Expr, T
             })),
             None => Err(TypeError::UndefinedVariable(var.value.clone())),
         },
+        Expr::App(app) if matches!(*app.lambda, Expr::Abs(_)) => alpha_conversion_lets(context, ex),
         Expr::App(app) => {
             let lambda = alpha_conversion_expr(context, &app.lambda)?;
             let argm = alpha_conversion_expr(context, &app.argm)?;
@@ -96,9 +192,11 @@ pub fn alpha_conversion_expr(context: &mut Context, ex: &Expr) -> Result<Expr, T
             }))
         }
         Expr::Abs(abs) => {
+            let scope = context.names.clone();
             let param = context.rename(&abs.param);
             let param_ty = alpha_conversion_type(context, &abs.param_ty)?;
             let body = alpha_conversion_expr(context, &abs.body)?;
+            context.names = scope;
 
             Ok(Expr::Abs(Abs {
                 param,
@@ -118,8 +216,10 @@ pub fn alpha_conversion_expr(context: &mut Context, ex: &Expr) -> Result<Expr, T
             }))
         }
         Expr::TAbs(tabs) => {
+            let scope = context.names.clone();
             let param = context.rename(&tabs.param);
             let body = alpha_conversion_expr(context, &tabs.body)?;
+            context.names = scope;
 
             Ok(Expr::TAbs(TAbs {
                 param,
@@ -153,12 +253,171 @@ pub fn alpha_conversion_expr(context: &mut Context, ex: &Expr) -> Result<Expr, T
                 ..snd.clone()
             }))
         }
+        Expr::Inl(inl) => {
+            let value = alpha_conversion_expr(context, &inl.value)?;
+            let right_ty = alpha_conversion_type(context, &inl.right_ty)?;
+
+            Ok(Expr::Inl(Inl {
+                value: Box::new(value),
+                right_ty,
+                ..inl.clone()
+            }))
+        }
+        Expr::Inr(inr) => {
+            let value = alpha_conversion_expr(context, &inr.value)?;
+            let left_ty = alpha_conversion_type(context, &inr.left_ty)?;
+
+            Ok(Expr::Inr(Inr {
+                value: Box::new(value),
+                left_ty,
+                ..inr.clone()
+            }))
+        }
+        Expr::Case(case) => {
+            let scrutinee = alpha_conversion_expr(context, &case.scrutinee)?;
+            let scope = context.names.clone();
+            let left_param = context.rename(&case.left_param);
+            let left_body = alpha_conversion_expr(context, &case.left_body)?;
+            context.names = scope.clone();
+            let right_param = context.rename(&case.right_param);
+            let right_body = alpha_conversion_expr(context, &case.right_body)?;
+            context.names = scope;
+
+            Ok(Expr::Case(Case {
+                scrutinee: Box::new(scrutinee),
+                left_param,
+                left_body: Box::new(left_body),
+                right_param,
+                right_body: Box::new(right_body),
+                ..case.clone()
+            }))
+        }
+        Expr::Record(record) => {
+            let fields = record
+                .fields
+                .iter()
+                .map(|(label, ex)| Ok((label.clone(), alpha_conversion_expr(context, ex)?)))
+                .collect::<Result<Vec<_>, TypeError>>()?;
+
+            Ok(Expr::Record(Record { fields, ..record.clone() }))
+        }
+        Expr::Project(project) => {
+            let record = alpha_conversion_expr(context, &project.record)?;
+
+            Ok(Expr::Project(Project {
+                record: Box::new(record),
+                ..project.clone()
+            }))
+        }
+        Expr::Variant(variant) => {
+            let value = alpha_conversion_expr(context, &variant.value)?;
+            let ty = alpha_conversion_type(context, &variant.ty)?;
+
+            Ok(Expr::Variant(Variant {
+                value: Box::new(value),
+                ty,
+                ..variant.clone()
+            }))
+        }
+        Expr::Match(matching) => {
+            let scrutinee = alpha_conversion_expr(context, &matching.scrutinee)?;
+            let mut arms = Vec::new();
+
+            for arm in &matching.arms {
+                let scope = context.names.clone();
+                let param = context.rename(&arm.param);
+                let body = alpha_conversion_expr(context, &arm.body)?;
+                context.names = scope;
+                arms.push(Arm { param, body, ..arm.clone() });
+            }
+
+            Ok(Expr::Match(Match {
+                scrutinee: Box::new(scrutinee),
+                arms,
+                ..matching.clone()
+            }))
+        }
+        Expr::Fold(fold) => {
+            let ty = alpha_conversion_type(context, &fold.ty)?;
+            let value = alpha_conversion_expr(context, &fold.value)?;
+
+            Ok(Expr::Fold(Fold {
+                ty,
+                value: Box::new(value),
+                ..fold.clone()
+            }))
+        }
+        Expr::Unfold(unfold) => {
+            let value = alpha_conversion_expr(context, &unfold.value)?;
+
+            Ok(Expr::Unfold(Unfold {
+                value: Box::new(value),
+                ..unfold.clone()
+            }))
+        }
+        Expr::Fix(fix) => {
+            let value = alpha_conversion_expr(context, &fix.value)?;
+
+            Ok(Expr::Fix(Fix { value: Box::new(value), ..fix.clone() }))
+        }
+        Expr::Pack(pack) => {
+            let witness = alpha_conversion_type(context, &pack.witness)?;
+            let value = alpha_conversion_expr(context, &pack.value)?;
+            let ty = alpha_conversion_type(context, &pack.ty)?;
+
+            Ok(Expr::Pack(Pack {
+                witness,
+                value: Box::new(value),
+                ty,
+                ..pack.clone()
+            }))
+        }
+        Expr::Unpack(unpack) => {
+            let package = alpha_conversion_expr(context, &unpack.package)?;
+            let scope = context.names.clone();
+            let ty_param = context.rename(&unpack.ty_param);
+            let param = context.rename(&unpack.param);
+            let body = alpha_conversion_expr(context, &unpack.body)?;
+            context.names = scope;
+
+            Ok(Expr::Unpack(Unpack {
+                package: Box::new(package),
+                ty_param,
+                param,
+                body: Box::new(body),
+                ..unpack.clone()
+            }))
+        }
+        Expr::If(cond) => {
+            let test = alpha_conversion_expr(context, &cond.cond)?;
+            let then_branch = alpha_conversion_expr(context, &cond.then_branch)?;
+            let else_branch = alpha_conversion_expr(context, &cond.else_branch)?;
+
+            Ok(Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond.clone()
+            }))
+        }
+        Expr::BinOp(binop) => {
+            let left = alpha_conversion_expr(context, &binop.left)?;
+            let right = alpha_conversion_expr(context, &binop.right)?;
+
+            Ok(Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop.clone()
+            }))
+        }
     }
 }
 
 pub fn equal(received: &Type, expected: &Type) -> bool {
     match (received, expected) {
         (Type::TInt, Type::TInt) => true,
+        (Type::TBool, Type::TBool) => true,
+        (Type::TUnit, Type::TUnit) => true,
         (Type::TVar { value: received, .. }, Type::TVar { value: expected, .. }) => {
             received == expected
         }
@@ -181,7 +440,27 @@ pub fn equal(received: &Type, expected: &Type) -> bool {
                 fst: expected_fst,
                 snd: expected_snd,
             },
-        ) => equal(&received_fst, &expected_fst) & equal(&received_snd, &expected_snd),
+        ) => equal(received_fst, expected_fst) & equal(received_snd, expected_snd),
+        (
+            Type::Sum {
+                left: received_left,
+                right: received_right,
+            },
+            Type::Sum {
+                left: expected_left,
+                right: expected_right,
+            },
+        ) => equal(received_left, expected_left) & equal(received_right, expected_right),
+        (
+            Type::Record { fields: received_fields, rest: received_rest },
+            Type::Record { fields: expected_fields, rest: expected_rest },
+        ) => {
+            received_rest == expected_rest && equal_row(received_fields, expected_fields)
+        }
+        (
+            Type::Variant { cases: received_cases, rest: received_rest },
+            Type::Variant { cases: expected_cases, rest: expected_rest },
+        ) => received_rest == expected_rest && equal_row(received_cases, expected_cases),
         (
             Type::Forall {
                 param: received_param,
@@ -196,13 +475,73 @@ pub fn equal(received: &Type, expected: &Type) -> bool {
             let received_body = substitution(received_body, received_param, &to);
             equal(&received_body, expected_body)
         }
+        (
+            Type::Exists {
+                param: received_param,
+                body: received_body,
+            },
+            Type::Exists {
+                param: expected_param,
+                body: expected_body,
+            },
+        ) => {
+            let to = Type::TVar { value: expected_param.clone() };
+            let received_body = substitution(received_body, received_param, &to);
+            equal(&received_body, expected_body)
+        }
+        (
+            Type::Rec {
+                param: received_param,
+                body: received_body,
+            },
+            Type::Rec {
+                param: expected_param,
+                body: expected_body,
+            },
+        ) => {
+            let to = Type::TVar { value: expected_param.clone() };
+            let received_body = substitution(received_body, received_param, &to);
+            equal(&received_body, expected_body)
+        }
         (_, _) => false,
     }
 }
 
+// Rows are compared up to reordering of their labels.
+fn equal_row(received: &[(String, Type)], expected: &[(String, Type)]) -> bool {
+    received.len() == expected.len()
+        && received.iter().all(|(label, received_ty)| {
+            match expected.iter().find(|(other, _)| other == label) {
+                Some((_, expected_ty)) => equal(received_ty, expected_ty),
+                None => false,
+            }
+        })
+}
+
+/// Instantiates the row variable `from` in a row. A record or variant type
+/// given as `to` is spliced into the row, a type variable just renames it.
+pub fn extend_row(
+    mut fields: Vec<(String, Type)>,
+    rest: &Option<String>,
+    from: &str,
+    to: &Type,
+) -> Row {
+    match (rest, to) {
+        (Some(rest), Type::Record { fields: extra, rest: tail })
+        | (Some(rest), Type::Variant { cases: extra, rest: tail })
+            if rest == from =>
+        {
+            fields.extend(extra.iter().cloned());
+            (fields, tail.clone())
+        }
+        (Some(rest), Type::TVar { value }) if rest == from => (fields, Some(value.clone())),
+        _ => (fields, rest.clone()),
+    }
+}
+
 pub fn substitution(ty: &Type, from: &str, to: &Type) -> Type {
     match ty {
-        Type::TInt => ty.clone(),
+        Type::TInt | Type::TBool | Type::TUnit => ty.clone(),
         Type::TVar { value } if value == from => to.clone(),
         Type::TVar { .. } => ty.clone(),
         Type::Arrow { left, right } => {
@@ -223,6 +562,24 @@ pub fn substitution(ty: &Type, from: &str, to: &Type) -> Type {
                 body: Box::new(body),
             }
         }
+        Type::Exists { param, .. } if param == from => ty.clone(),
+        Type::Exists { param, body } => {
+            let body = substitution(body, from, to);
+
+            Type::Exists {
+                param: param.clone(),
+                body: Box::new(body),
+            }
+        }
+        Type::Rec { param, .. } if param == from => ty.clone(),
+        Type::Rec { param, body } => {
+            let body = substitution(body, from, to);
+
+            Type::Rec {
+                param: param.clone(),
+                body: Box::new(body),
+            }
+        }
         Type::Product { fst, snd } => {
             let fst = substitution(fst, from, to);
             let snd = substitution(snd, from, to);
@@ -232,12 +589,124 @@ pub fn substitution(ty: &Type, from: &str, to: &Type) -> Type {
                 snd: Box::new(snd),
             }
         }
+        Type::Sum { left, right } => {
+            let left = substitution(left, from, to);
+            let right = substitution(right, from, to);
+
+            Type::Sum {
+                left: Box::new(left),
+                right: Box::new(right),
+            }
+        }
+        Type::Record { fields, rest } => {
+            let fields = fields.iter().map(|(l, ty)| (l.clone(), substitution(ty, from, to)));
+            let (fields, rest) = extend_row(fields.collect(), rest, from, to);
+
+            Type::Record { fields, rest }
+        }
+        Type::Variant { cases, rest } => {
+            let cases = cases.iter().map(|(l, ty)| (l.clone(), substitution(ty, from, to)));
+            let (cases, rest) = extend_row(cases.collect(), rest, from, to);
+
+            Type::Variant { cases, rest }
+        }
+    }
+}
+
+/// Unrolls a recursive type one level, `μX. T` becoming `T[X := μX. T]`.
+pub fn unroll(ty: &Type) -> Option<Type> {
+    match ty {
+        Type::Rec { param, body } => Some(substitution(body, param, ty)),
+        _ => None,
+    }
+}
+
+/// Whether the type variable `var` occurs free in `ty`.
+pub fn occurs(ty: &Type, var: &str) -> bool {
+    match ty {
+        Type::TInt | Type::TBool | Type::TUnit => false,
+        Type::TVar { value } => value == var,
+        Type::Arrow { left: fst, right: snd }
+        | Type::Product { fst, snd }
+        | Type::Sum { left: fst, right: snd } => occurs(fst, var) || occurs(snd, var),
+        Type::Forall { param, body } | Type::Exists { param, body } | Type::Rec { param, body } => {
+            param != var && occurs(body, var)
+        }
+        Type::Record { fields, rest } | Type::Variant { cases: fields, rest } => {
+            rest.as_deref() == Some(var) || fields.iter().any(|(_, ty)| occurs(ty, var))
+        }
+    }
+}
+
+/// Checks that `to` can instantiate the type variable `from` wherever `ty`
+/// uses it as the rest of a row. It must be a row of the same sort or a type
+/// variable, and it cannot repeat a label the row already has.
+pub fn check_row_instance(ty: &Type, from: &str, to: &Type) -> Result<(), TypeError> {
+    match ty {
+        Type::TInt | Type::TBool | Type::TUnit | Type::TVar { .. } => Ok(()),
+        Type::Arrow { left: fst, right: snd }
+        | Type::Product { fst, snd }
+        | Type::Sum { left: fst, right: snd } => {
+            check_row_instance(fst, from, to)?;
+            check_row_instance(snd, from, to)
+        }
+        Type::Forall { param, .. } | Type::Exists { param, .. } | Type::Rec { param, .. }
+            if param == from =>
+        {
+            Ok(())
+        }
+        Type::Forall { body, .. } | Type::Exists { body, .. } | Type::Rec { body, .. } => {
+            check_row_instance(body, from, to)
+        }
+        Type::Record { fields, rest } | Type::Variant { cases: fields, rest } => {
+            for (_, field) in fields {
+                check_row_instance(field, from, to)?;
+            }
+            if rest.as_deref() != Some(from) {
+                return Ok(());
+            }
+
+            let extra = match (ty, to) {
+                (Type::Record { .. }, Type::Record { fields: extra, .. })
+                | (Type::Variant { .. }, Type::Variant { cases: extra, .. }) => extra,
+                (_, Type::TVar { .. }) => return Ok(()),
+                _ => return Err(TypeError::UnexpectedType(to.clone())),
+            };
+            match extra.iter().find(|(label, _)| fields.iter().any(|(l, _)| l == label)) {
+                Some((label, _)) => Err(TypeError::DuplicateLabel(label.clone())),
+                None => Ok(()),
+            }
+        }
     }
 }
 
+pub fn operator_type(op: Operator) -> Type {
+    match op {
+        Operator::Add | Operator::Sub | Operator::Mul | Operator::Div => Type::TInt,
+        Operator::Eq | Operator::Lt => Type::TBool,
+    }
+}
+
+fn infer_lets(context: &mut Context, ex: &Expr) -> Result<Type, TypeError> {
+    let (lets, body) = let_chain(ex);
+
+    for (app, abs) in lets {
+        let argm_ty = infer_type(context, &app.argm)?;
+
+        if !equal(&abs.param_ty, &argm_ty) {
+            return Err(TypeError::Mismatch(abs.param_ty.clone(), argm_ty));
+        }
+        context.types.insert(abs.param.clone(), abs.param_ty.clone());
+    }
+
+    infer_type(context, body)
+}
+
 pub fn infer_type(context: &mut Context, ex: &Expr) -> Result<Type, TypeError> {
     match ex {
         Expr::Int { .. } => Ok(Type::TInt),
+        Expr::Bool { .. } => Ok(Type::TBool),
+        Expr::Unit { .. } => Ok(Type::TUnit),
         Expr::Var(var) => match context.types.get(&var.value) {
             Some(ty) => Ok(ty.clone()),
             None => Err(TypeError::UndefinedVariable(var.value.clone())),
@@ -267,6 +736,162 @@ pub fn infer_type(context: &mut Context, ex: &Expr) -> Result<Type, TypeError> {
                 pair => Err(TypeError::UnexpectedType(pair)),
             }
         }
+        Expr::Inl(inl) => {
+            let left = infer_type(context, &inl.value)?;
+
+            Ok(Type::Sum {
+                left: Box::new(left),
+                right: Box::new(inl.right_ty.clone()),
+            })
+        }
+        Expr::Inr(inr) => {
+            let right = infer_type(context, &inr.value)?;
+
+            Ok(Type::Sum {
+                left: Box::new(inr.left_ty.clone()),
+                right: Box::new(right),
+            })
+        }
+        Expr::Case(case) => {
+            let (left, right) = match infer_type(context, &case.scrutinee)? {
+                Type::Sum { left, right } => (left, right),
+                scrutinee => return Err(TypeError::UnexpectedType(scrutinee)),
+            };
+
+            context.types.insert(case.left_param.clone(), *left);
+            let left_ty = infer_type(context, &case.left_body)?;
+
+            context.types.insert(case.right_param.clone(), *right);
+            let right_ty = infer_type(context, &case.right_body)?;
+
+            if equal(&right_ty, &left_ty) {
+                Ok(left_ty)
+            } else {
+                Err(TypeError::Mismatch(left_ty, right_ty))
+            }
+        }
+        Expr::Record(record) => {
+            let mut fields: Vec<(String, Type)> = Vec::new();
+
+            for (label, ex) in &record.fields {
+                if fields.iter().any(|(other, _)| other == label) {
+                    return Err(TypeError::DuplicateLabel(label.clone()));
+                }
+
+                fields.push((label.clone(), infer_type(context, ex)?));
+            }
+
+            Ok(Type::Record { fields, rest: None })
+        }
+        Expr::Project(project) => match infer_type(context, &project.record)? {
+            Type::Record { fields, .. } => fields
+                .into_iter()
+                .find(|(label, _)| *label == project.label)
+                .map(|(_, ty)| ty)
+                .ok_or_else(|| TypeError::UndefinedLabel(project.label.clone())),
+            record => Err(TypeError::UnexpectedType(record)),
+        },
+        Expr::Variant(variant) => {
+            let value_ty = infer_type(context, &variant.value)?;
+
+            let case_ty = match &variant.ty {
+                Type::Variant { cases, .. } => cases.iter().find(|(l, _)| *l == variant.label),
+                ty => return Err(TypeError::UnexpectedType(ty.clone())),
+            };
+
+            match case_ty {
+                Some((_, case_ty)) if equal(&value_ty, case_ty) => Ok(variant.ty.clone()),
+                Some((_, case_ty)) => Err(TypeError::Mismatch(case_ty.clone(), value_ty)),
+                None => Err(TypeError::UndefinedLabel(variant.label.clone())),
+            }
+        }
+        Expr::Match(matching) => {
+            let cases = match infer_type(context, &matching.scrutinee)? {
+                Type::Variant { cases, rest: None } => cases,
+                scrutinee => return Err(TypeError::UnexpectedType(scrutinee)),
+            };
+
+            if let Some((label, _)) = cases
+                .iter()
+                .find(|(label, _)| matching.arms.iter().all(|arm| arm.label != *label))
+            {
+                return Err(TypeError::MissingLabel(label.clone()));
+            }
+
+            let mut result: Option<Type> = None;
+
+            for arm in &matching.arms {
+                let case_ty = match cases.iter().find(|(label, _)| *label == arm.label) {
+                    Some((_, case_ty)) => case_ty.clone(),
+                    None => return Err(TypeError::UndefinedLabel(arm.label.clone())),
+                };
+
+                context.types.insert(arm.param.clone(), case_ty);
+                let body_ty = infer_type(context, &arm.body)?;
+
+                match &result {
+                    Some(expected) if !equal(&body_ty, expected) => {
+                        return Err(TypeError::Mismatch(expected.clone(), body_ty))
+                    }
+                    _ => result = Some(body_ty),
+                }
+            }
+
+            result.ok_or(TypeError::UnexpectedType(Type::Variant { cases, rest: None }))
+        }
+        Expr::Fold(fold) => {
+            let unrolled = unroll(&fold.ty).ok_or(TypeError::UnexpectedType(fold.ty.clone()))?;
+            let value_ty = infer_type(context, &fold.value)?;
+
+            if equal(&value_ty, &unrolled) {
+                Ok(fold.ty.clone())
+            } else {
+                Err(TypeError::Mismatch(unrolled, value_ty))
+            }
+        }
+        Expr::Unfold(unfold) => {
+            let value_ty = infer_type(context, &unfold.value)?;
+            unroll(&value_ty).ok_or(TypeError::UnexpectedType(value_ty))
+        }
+        Expr::Pack(pack) => match &pack.ty {
+            Type::Exists { param, body } => {
+                check_row_instance(body, param, &pack.witness)?;
+                let expected = substitution(body, param, &pack.witness);
+                let value_ty = infer_type(context, &pack.value)?;
+
+                if equal(&value_ty, &expected) {
+                    Ok(pack.ty.clone())
+                } else {
+                    Err(TypeError::Mismatch(expected, value_ty))
+                }
+            }
+            ty => Err(TypeError::UnexpectedType(ty.clone())),
+        },
+        Expr::Unpack(unpack) => match infer_type(context, &unpack.package)? {
+            Type::Exists { param, body } => {
+                let to = Type::TVar { value: unpack.ty_param.clone() };
+                let param_ty = substitution(&body, &param, &to);
+
+                context.types.insert(unpack.param.clone(), param_ty);
+                let body_ty = infer_type(context, &unpack.body)?;
+
+                if occurs(&body_ty, &unpack.ty_param) {
+                    Err(TypeError::EscapingType(unpack.ty_param.clone()))
+                } else {
+                    Ok(body_ty)
+                }
+            }
+            ty => Err(TypeError::UnexpectedType(ty)),
+        },
+        Expr::Fix(fix) => {
+            let value_ty = infer_type(context, &fix.value)?;
+
+            match value_ty {
+                Type::Arrow { left, right } if equal(&right, &left) => Ok(*left),
+                Type::Arrow { left, right } => Err(TypeError::Mismatch(*left, *right)),
+                ty => Err(TypeError::UnexpectedType(ty)),
+            }
+        }
         Expr::Abs(abs) => {
             context.types.insert(abs.param.clone(), abs.param_ty.clone());
 
@@ -277,6 +902,7 @@ pub fn infer_type(context: &mut Context, ex: &Expr) -> Result<Type, TypeError> {
                 right: Box::new(body_ty),
             })
         }
+        Expr::App(app) if matches!(*app.lambda, Expr::Abs(_)) => infer_lets(context, ex),
         Expr::App(app) => {
             let lambda_ty = infer_type(context, &app.lambda)?;
             let argm_ty = infer_type(context, &app.argm)?;
@@ -299,10 +925,40 @@ pub fn infer_type(context: &mut Context, ex: &Expr) -> Result<Type, TypeError> {
             let lambda_ty = infer_type(context, &tapp.lambda)?;
 
             match lambda_ty {
-                Type::Forall { param, body } => Ok(substitution(&body, &param, &tapp.argm)),
+                Type::Forall { param, body } => {
+                    check_row_instance(&body, &param, &tapp.argm)?;
+                    Ok(substitution(&body, &param, &tapp.argm))
+                }
                 func => Err(TypeError::UnexpectedType(func)),
             }
         }
+        Expr::If(cond) => {
+            let cond_ty = infer_type(context, &cond.cond)?;
+
+            if !equal(&cond_ty, &Type::TBool) {
+                return Err(TypeError::Mismatch(Type::TBool, cond_ty));
+            }
+
+            let then_ty = infer_type(context, &cond.then_branch)?;
+            let else_ty = infer_type(context, &cond.else_branch)?;
+
+            if equal(&else_ty, &then_ty) {
+                Ok(then_ty)
+            } else {
+                Err(TypeError::Mismatch(then_ty, else_ty))
+            }
+        }
+        Expr::BinOp(binop) => {
+            for operand in [&binop.left, &binop.right] {
+                let operand_ty = infer_type(context, operand)?;
+
+                if !equal(&operand_ty, &Type::TInt) {
+                    return Err(TypeError::Mismatch(Type::TInt, operand_ty));
+                }
+            }
+
+            Ok(operator_type(binop.op))
+        }
     }
 }
 
@@ -313,3 +969,102 @@ pub fn type_of(ex: Expr) -> Result<Type, TypeError> {
 
     Ok(typed_terms)
 }
+
+#[cfg(test)]
+mod test {
+    use super::type_of;
+    use crate::parser;
+
+    #[test]
+    fn test_check_if_condition() {
+        let expr = parser::from_string(r"if () then true else false").unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_case_branches() {
+        let input = r"λs: Int + Bool. case s of inl n => n | inr b => b";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_row_instance() {
+        let inputs = [
+            r"(λρ. λr: {x: Int | ρ}. r.x) [Int]",
+            r"(λρ. λr: {x: Int | ρ}. r.x) [<y: Int>]",
+            r"(λρ. λr: {x: Int | ρ}. r.x) [{x: Bool}]",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            assert!(type_of(expr).is_err())
+        }
+    }
+
+    #[test]
+    fn test_check_missing_field() {
+        let input = r"(λr: {x: Int}. r.y) {x = 1}";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_non_exhaustive_match() {
+        let input = r"λv: <a: Int, b: Int>. case v of <a n> => n";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_fold_mismatch() {
+        let input = r"fold [μL. Unit + Int * L] 1";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_fix_mismatch() {
+        let expr = parser::from_string(r"fix (λf: Int -> Int. 0)").unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_escaping_type() {
+        let input = r"
+        let counter: ∃C. {new: C, get: C -> Int} =
+            pack [Int, {new = 0, get = λc: Int. c}] as ∃C. {new: C, get: C -> Int} in
+        unpack counter as [C, c] in c.new
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_program_order() {
+        let input = r"
+        def twice: Int -> Int = λn: Int. double (double n)
+        def double: Int -> Int = λn: Int. n + n
+        main = twice 1
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        assert!(type_of(expr).is_err())
+    }
+
+    #[test]
+    fn test_check_shadowed_parameter() {
+        // The inner `x` goes out of scope after its abstraction.
+        let input = r"(λx: Int. if (λx: Bool. x) true then x else 0) 1";
+        let expr = parser::from_string(input).unwrap();
+
+        assert_eq!(type_of(expr).unwrap().to_string(), "Int")
+    }
+}
@@ -0,0 +1,244 @@
+//! Type erasure into the untyped lambda calculus. What System F adds to the simply typed terms
+//! only concerns types, so type abstractions and applications, `fold`, `unfold` and `pack`
+//! leave their terms, and `unpack` binds the package like an application does. The data
+//! becomes its Church encoding, labels being numbered in the order the term first mentions
+//! them: a record is the list of its fields with the numbers of their labels, and a variant
+//! gives the number of its label to the arms of a `case`.
+
+use crate::parser::location::Range;
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Bool, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Int, Match, Operator,
+    Pack, Pair, Project, Record, Snd, TAbs, TApp, Type, Unfold, Unit, Unpack, Var, Variant,
+};
+use untyped_lambda_calculus::encodings::{self, Primitive, Sort};
+use untyped_lambda_calculus::parser::{location as untyped_location, parsetree as untyped};
+
+fn range(range: Range) -> untyped_location::Range {
+    let start = untyped_location::Pos::new(range.start.index);
+    let end = untyped_location::Pos::new(range.end.index);
+
+    untyped_location::Range::new(start, end)
+}
+
+fn primitive(op: Operator) -> Primitive {
+    match op {
+        Operator::Add => Primitive::Add,
+        Operator::Sub => Primitive::Sub,
+        Operator::Mul => Primitive::Mul,
+        Operator::Div => Primitive::Div,
+        Operator::Eq => Primitive::Eq,
+        Operator::Lt => Primitive::Lt,
+    }
+}
+
+/// How the erasure of a term of type `ty` reads back.
+pub fn sort(ty: &Type) -> Sort {
+    match ty {
+        Type::TInt => Sort::Integer,
+        Type::TBool => Sort::Boolean,
+        _ => Sort::Other,
+    }
+}
+
+// The labels met so far, numbered by their position.
+struct Labels {
+    labels: Vec<String>,
+}
+
+impl Labels {
+    fn number(&mut self, label: &str) -> usize {
+        match self.labels.iter().position(|known| known == label) {
+            Some(number) => number,
+            None => {
+                self.labels.push(label.to_string());
+                self.labels.len() - 1
+            }
+        }
+    }
+
+    fn erase(&mut self, expr: &Expr) -> untyped::Expr {
+        let range = range(expr.range());
+
+        match expr {
+            Expr::Int(Int { value, .. }) => encodings::numeral(*value, range),
+            Expr::Bool(Bool { value, .. }) => encodings::boolean(*value),
+            Expr::Unit(Unit { .. }) => encodings::unit(),
+            Expr::Var(Var { value, .. }) => encodings::var(value, range),
+            Expr::Abs(Abs { param, body, .. }) => encodings::abs(param, self.erase(body), range),
+            Expr::App(App { lambda, argm, .. }) => {
+                encodings::app(self.erase(lambda), self.erase(argm), range)
+            }
+            Expr::TAbs(TAbs { body: value, .. })
+            | Expr::TApp(TApp { lambda: value, .. })
+            | Expr::Fold(Fold { value, .. })
+            | Expr::Unfold(Unfold { value, .. })
+            | Expr::Pack(Pack { value, .. }) => self.erase(value),
+            Expr::Pair(Pair { fst, snd, .. }) => {
+                encodings::pair(self.erase(fst), self.erase(snd), range)
+            }
+            Expr::Fst(Fst { pair, .. }) => encodings::fst(self.erase(pair), range),
+            Expr::Snd(Snd { pair, .. }) => encodings::snd(self.erase(pair), range),
+            Expr::Inl(Inl { value, .. }) => encodings::inl(self.erase(value), range),
+            Expr::Inr(Inr { value, .. }) => encodings::inr(self.erase(value), range),
+            Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+                let left = encodings::abs(left_param, self.erase(left_body), range);
+                let right = encodings::abs(right_param, self.erase(right_body), range);
+
+                encodings::case(self.erase(scrutinee), left, right, range)
+            }
+            Expr::Record(Record { fields, .. }) => {
+                let fields = fields
+                    .iter()
+                    .map(|(label, field)| (self.number(label), self.erase(field)))
+                    .collect();
+                encodings::record(fields, range)
+            }
+            Expr::Project(Project { record, label, .. }) => {
+                let label = self.number(label);
+                encodings::project(self.erase(record), label, range)
+            }
+            Expr::Variant(Variant { label, value, .. }) => {
+                let label = self.number(label);
+                encodings::variant(label, self.erase(value), range)
+            }
+            Expr::Match(Match { scrutinee, arms, .. }) => {
+                let arms = arms
+                    .iter()
+                    .map(|Arm { label, param, body }| {
+                        (self.number(label), encodings::abs(param, self.erase(body), range))
+                    })
+                    .collect();
+                encodings::switch(self.erase(scrutinee), arms, range)
+            }
+            Expr::Unpack(Unpack { package, param, body, .. }) => {
+                let body = encodings::abs(param, self.erase(body), range);
+                encodings::app(body, self.erase(package), range)
+            }
+            Expr::If(If { cond, then_branch, else_branch, .. }) => {
+                let then_branch = self.erase(then_branch);
+                let else_branch = self.erase(else_branch);
+
+                encodings::branch(self.erase(cond), then_branch, else_branch, range)
+            }
+            Expr::BinOp(BinOp { op, left, right, .. }) => {
+                encodings::primitive(primitive(*op), self.erase(left), self.erase(right), range)
+            }
+            Expr::Fix(Fix { value, .. }) => encodings::fix(self.erase(value), range),
+        }
+    }
+}
+
+/// The untyped term of a well-typed one. `fix` becomes the fixed point combinator of
+/// call-by-value, which applicative order unfolds forever.
+pub fn erase(expr: &Expr) -> untyped::Expr {
+    Labels { labels: Vec::new() }.erase(expr)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{erase, sort};
+    use crate::parser::parsetree::Expr;
+    use crate::{bytecode, checker, parser, prelude};
+    use untyped_lambda_calculus::encodings::{evaluate, Sort};
+    use untyped_lambda_calculus::reduction::Norm;
+
+    // The value the machine computes for a well-typed term, and how its erasure reads back.
+    fn compute(expr: &Expr) -> (String, Sort) {
+        let ty = checker::type_of(expr.clone()).unwrap();
+        let value = bytecode::run(expr, None).unwrap();
+
+        (value.to_expr(&ty, expr.range()).unwrap().to_string(), sort(&ty))
+    }
+
+    #[test]
+    fn test_erase_types() {
+        let input = r"λA. λx: A. unpack pack [Int, x] as ∃X. A as [X, y] in (λB. λz: B. z) [A] y";
+        let expr = parser::from_string(input).unwrap();
+
+        assert_eq!(erase(&expr).to_string(), "(λx. ((λy. ((λz. z) y)) x))")
+    }
+
+    #[test]
+    fn test_erase_data() {
+        let inputs = [
+            r"
+            let pick: ∀A. Bool -> A -> A -> A = λA. λb: Bool. λx: A. λy: A. if b then x else y in
+            pick [Int] false 1 2
+            ",
+            r"
+            let square: Int -> Int = λx: Int. x * x in
+            let pair: Int * Int = {square 2 - 1, 5 / 2} in
+            if (fst pair) == (snd pair) + 1 then snd pair else 0
+            ",
+            r"
+            let get_x: ∀ρ. {x: Int | ρ} -> Int = λρ. λr: {x: Int | ρ}. r.x in
+            let point: {x: Int, y: Int} = {x = 2, y = 3} in
+            get_x [{y: Int}] point + point.y
+            ",
+            r"
+            let size: <num: Int, flag: Bool> -> Int = λv: <num: Int, flag: Bool>.
+                case v of <num n> => n | <flag b> => if b then 1 else 0 in
+            size (<num = 4> as <num: Int, flag: Bool>) +
+                size (<flag = true> as <num: Int, flag: Bool>)
+            ",
+            r"
+            let counter: ∃C. {new: C, inc: C -> C, get: C -> Int} =
+                pack [Int, {new = 0, inc = λc: Int. c + 1, get = λc: Int. c}]
+                as ∃C. {new: C, inc: C -> C, get: C -> Int} in
+            unpack counter as [C, c] in c.get (c.inc (c.inc c.new))
+            ",
+            r"(λA. λx: A. x) [Bool] false",
+            r"
+            let less: (Int * Int) -> Bool = λp: Int * Int. (fst p) < (snd p) in
+            less {4, 3}
+            ",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            let (value, sort) = compute(&expr);
+
+            for strategy in [Norm::NOR, Norm::APP, Norm::CBN, Norm::CBV] {
+                let erased = evaluate(strategy, erase(&expr), sort).to_string();
+                assert_eq!(erased, value, "{:?}", strategy);
+            }
+        }
+    }
+
+    #[test]
+    fn test_erase_prelude() {
+        // The Church encodings of the prelude are left alone, only their types go.
+        let input = r"
+        let two: ∀N. (N -> N) -> N -> N = succ (succ zero) in
+        let xs: ∀R. (Int -> R -> R) -> R -> R = cons [Int] (toint (mul two two)) (nil [Int]) in
+        first [Int] [∀B. B -> B -> B] (pair [Int] [∀B. B -> B -> B] (sum xs) (iszero zero))
+        ";
+        let expr = prelude::load(input).unwrap();
+        let (value, sort) = compute(&expr);
+
+        for strategy in [Norm::NOR, Norm::APP, Norm::CBN, Norm::CBV] {
+            let erased = evaluate(strategy, erase(&expr), sort).to_string();
+            assert_eq!(erased, value, "{:?}", strategy);
+        }
+    }
+
+    #[test]
+    fn test_erase_recursion() {
+        let input = r"
+        let nil: μL. Unit + Int * L =
+            fold [μL. Unit + Int * L] inl [Int * (μL. Unit + Int * L)] () in
+        let cons: Int -> (μL. Unit + Int * L) -> μL. Unit + Int * L =
+            λx: Int. λxs: μL. Unit + Int * L. fold [μL. Unit + Int * L] inr [Unit] {x, xs} in
+        let rec len: (μL. Unit + Int * L) -> Int =
+            λl: μL. Unit + Int * L. case unfold l of inl u => 0 | inr p => 1 + len (snd p) in
+        len (cons 1 (cons 2 nil))
+        ";
+        let expr = parser::from_string(input).unwrap();
+
+        for strategy in [Norm::NOR, Norm::CBN, Norm::CBV] {
+            let erased = evaluate(strategy, erase(&expr), Sort::Integer).to_string();
+            assert_eq!(erased, "2", "{:?}", strategy);
+        }
+        assert_eq!(compute(&expr), ("2".to_string(), Sort::Integer));
+    }
+}
@@ -1,16 +1,18 @@
 use system_f::checker;
-use system_f::parser;
+use system_f::prelude;
 use system_f::reduction;
+use system_f::reduction::readback::readback;
 
 fn main() {
     let input = r"
-    let swap: ∀A. ∀B. ((A * B) -> (B * A)) = λA. λB. λpair: (A * B). {snd pair, fst pair} in
-    swap [Int] [Int] {1, 2}
+    def swap: ∀A. ∀B. ((A * B) -> (B * A)) = λA. λB. λpair: (A * B). {snd pair, fst pair}
+    def two: ∀N. (N -> N) -> N -> N = add (succ zero) (succ zero)
+    main = swap [Int] [∀N. (N -> N) -> N -> N] {1, two}
     ";
 
-    let expr_parsed = parser::from_string(input).unwrap();
+    let expr_parsed = prelude::load(input).unwrap();
     let expr_typed = checker::type_of(expr_parsed.clone()).unwrap();
     let expr_reduced = reduction::reduce(reduction::Norm::NOR, expr_parsed, None);
 
-    println!("({}) : {}", expr_reduced, expr_typed)
+    println!("({}) : {}", readback(&expr_reduced, &expr_typed), expr_typed)
 }
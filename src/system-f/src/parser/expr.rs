@@ -3,10 +3,25 @@ use super::{
     lexer::tokens::Token,
     location::Range,
     macros::{consume, match_token},
-    parsetree::{Abs, App, Expr, Fst, Int, Pair, Snd, TAbs, TApp, Type, Var},
+    parsetree::{
+        Abs, App, Arm, BinOp, Bool, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Int, Match, Operator,
+        Pack, Pair, Project, Record, Row, Snd, TAbs, TApp, Type, Unfold, Unit, Unpack, Var,
+        Variant,
+    },
     state::Parser,
 };
 
+fn make_binop(op: Operator, left: Expr, right: Expr) -> Expr {
+    let range = left.range().mix(right.range());
+
+    Expr::BinOp(BinOp {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+        range,
+    })
+}
+
 impl<'a> Parser<'a> {
     pub fn parse_variable_expr(&mut self) -> Result<Expr, ParserError> {
         let (token, range) = consume!(self, Token::Variable(var) => var.clone())?;
@@ -15,13 +30,48 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_number_expr(&mut self) -> Result<Expr, ParserError> {
-        let (token, range) = consume!(self, Token::Number(num) => num.clone())?;
+        let (token, range) = consume!(self, Token::Number(num) => *num)?;
 
         Ok(Expr::Int(Int { value: token, range }))
     }
 
-    pub fn parse_pair(&mut self) -> Result<Expr, ParserError> {
+    pub fn parse_bool_expr(&mut self) -> Result<Expr, ParserError> {
+        let value = matches!(self.get(), Token::True);
+        let (_, range) = consume!(self, Token::True | Token::False)?;
+
+        Ok(Expr::Bool(Bool { value, range }))
+    }
+
+    pub fn parse_braces(&mut self) -> Result<Expr, ParserError> {
         let (_, range_l) = consume!(self, Token::LBrace)?;
+
+        match (self.get(), self.get_next()) {
+            (Token::RBrace, _) | (Token::Variable(_), Token::Equal) => self.parse_record(range_l),
+            _ => self.parse_pair(range_l),
+        }
+    }
+
+    pub fn parse_record(&mut self, range_l: Range) -> Result<Expr, ParserError> {
+        let mut fields = Vec::new();
+
+        while let Token::Variable(_) = self.get() {
+            let (label, _) = consume!(self, Token::Variable(var) => var.clone())?;
+            consume!(self, Token::Equal)?;
+            fields.push((label, self.parse_expr()?));
+
+            if let Token::Comma = self.get() {
+                consume!(self, Token::Comma)?;
+            } else {
+                break;
+            }
+        }
+
+        let (_, range_r) = consume!(self, Token::RBrace)?;
+
+        Ok(Expr::Record(Record { fields, range: range_l.mix(range_r) }))
+    }
+
+    pub fn parse_pair(&mut self, range_l: Range) -> Result<Expr, ParserError> {
         let fst = self.parse_expr()?;
         consume!(self, Token::Comma)?;
         let snd = self.parse_expr()?;
@@ -39,11 +89,38 @@ impl<'a> Parser<'a> {
             Token::LParen => self.parse_parens_expr(),
             Token::Variable(_) => self.parse_variable_expr(),
             Token::Number(_) => self.parse_number_expr(),
-            Token::LBrace => self.parse_pair(),
+            Token::LBrace => self.parse_braces(),
+            Token::True | Token::False => self.parse_bool_expr(),
+            Token::Fix => self.parse_fix(),
             _ => self.fail(),
         }
     }
 
+    pub fn parse_fix(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Fix)?;
+        let value = self.parse_atom()?;
+        let endr = value.range();
+
+        Ok(Expr::Fix(Fix { value: Box::new(value), range: range.mix(endr) }))
+    }
+
+    pub fn parse_projection(&mut self) -> Result<Expr, ParserError> {
+        let mut record = self.parse_atom()?;
+
+        while let (Token::Dot, Token::Variable(_)) = (self.get(), self.get_next()) {
+            consume!(self, Token::Dot)?;
+            let (label, range) = consume!(self, Token::Variable(var) => var.clone())?;
+
+            record = Expr::Project(Project {
+                range: record.range().mix(range),
+                record: Box::new(record),
+                label,
+            });
+        }
+
+        Ok(record)
+    }
+
     pub fn parse_abs_expr(&mut self, range: Range) -> Result<Expr, ParserError> {
         let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
 
@@ -63,7 +140,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_abs_type(&mut self, range: Range) -> Result<Expr, ParserError> {
-        let (param, _) = consume!(self, Token::TVar(var) => var.clone())?;
+        let (param, _) = consume!(self, Token::TVar(var) | Token::Variable(var) => var.clone())?;
 
         consume!(self, Token::Dot)?;
         let body = self.parse_expr()?;
@@ -79,7 +156,7 @@ impl<'a> Parser<'a> {
     pub fn parse_abs(&mut self) -> Result<Expr, ParserError> {
         let (_, range) = consume!(self, Token::Lambda)?;
 
-        if let Token::Variable(_) = self.get() {
+        if let (Token::Variable(_), Token::Colon) = (self.get(), self.get_next()) {
             self.parse_abs_expr(range)
         } else {
             self.parse_abs_type(range)
@@ -95,7 +172,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_call(&mut self) -> Result<Expr, ParserError> {
-        let mut func = self.parse_atom()?;
+        let mut func = self.parse_projection()?;
         let mut args = Vec::new();
 
         if let Token::LBracket = self.get() {
@@ -108,7 +185,7 @@ impl<'a> Parser<'a> {
             }
         }
 
-        while let Some(arg) = self.try_single(|state| state.parse_atom())? {
+        while let Some(arg) = self.try_single(|state| state.parse_projection())? {
             args.push(arg);
         }
 
@@ -133,26 +210,106 @@ impl<'a> Parser<'a> {
         Ok(head)
     }
 
+    pub fn parse_multiplicative(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_application()?;
+
+        loop {
+            let op = match self.get() {
+                Token::Prod => Operator::Mul,
+                Token::Slash => Operator::Div,
+                _ => return Ok(left),
+            };
+
+            self.advance()?;
+            let right = self.parse_application()?;
+            left = make_binop(op, left, right);
+        }
+    }
+
+    pub fn parse_additive(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_multiplicative()?;
+
+        loop {
+            let op = match self.get() {
+                Token::Plus => Operator::Add,
+                Token::Minus => Operator::Sub,
+                _ => return Ok(left),
+            };
+
+            self.advance()?;
+            let right = self.parse_multiplicative()?;
+            left = make_binop(op, left, right);
+        }
+    }
+
+    pub fn parse_comparison(&mut self) -> Result<Expr, ParserError> {
+        let left = self.parse_additive()?;
+
+        let op = match self.get() {
+            Token::EqualEqual => Operator::Eq,
+            Token::Less => Operator::Lt,
+            _ => return Ok(left),
+        };
+
+        self.advance()?;
+        let right = self.parse_additive()?;
+
+        Ok(make_binop(op, left, right))
+    }
+
     pub fn parse_parens_expr(&mut self) -> Result<Expr, ParserError> {
-        consume!(self, Token::LParen)?;
+        let (_, range_l) = consume!(self, Token::LParen)?;
+
+        if let Token::RParen = self.get() {
+            let (_, range_r) = consume!(self, Token::RParen)?;
+            return Ok(Expr::Unit(Unit { range: range_l.mix(range_r) }));
+        }
+
         let expr = self.parse_expr()?;
         consume!(self, Token::RParen)?;
 
         Ok(expr)
     }
 
+    // `let rec f: T = e in b` binds `f` to `fix (λf: T. e)`. A top-level `def x: T = e`
+    // binds over the declarations after it, down to the `main = e` closing the program.
     pub fn parse_let(&mut self) -> Result<Expr, ParserError> {
-        let (_, range) = consume!(self, Token::Let)?;
+        let declaration = matches!(self.get(), Token::Def);
+        let (_, range) = consume!(self, Token::Let | Token::Def)?;
+        let recursive = matches!(self.get(), Token::Rec);
+
+        if recursive {
+            consume!(self, Token::Rec)?;
+        }
+
         let (param, _) = consume!(self, Token::Variable(name) => name.clone())?;
 
         consume!(self, Token::Colon)?;
         let param_ty = self.parse_type()?;
 
         consume!(self, Token::Equal)?;
-        let value = self.parse_expr()?;
+        let mut value = self.parse_expr()?;
+
+        if recursive {
+            let value_range = value.range();
+            let functional = Expr::Abs(Abs {
+                param: param.clone(),
+                param_ty: param_ty.clone(),
+                body: Box::new(value),
+                range: value_range,
+            });
 
-        consume!(self, Token::In)?;
-        let body = self.parse_expr()?;
+            value = Expr::Fix(Fix { value: Box::new(functional), range: value_range });
+        }
+
+        let body = match self.get() {
+            Token::Def | Token::Main if declaration => self.parse_program()?,
+            _ if declaration => self.fail()?,
+            _ => {
+                consume!(self, Token::In)?;
+                self.parse_expr()?
+            }
+        };
 
         let func = Expr::Abs(Abs {
             param,
@@ -188,13 +345,220 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    pub fn parse_inl(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Inl)?;
+        let (_, right_ty) = self.parse_type_arg()?;
+        let value = self.parse_expr()?;
+        let endr = value.range();
+
+        Ok(Expr::Inl(Inl {
+            value: Box::new(value),
+            right_ty,
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_inr(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Inr)?;
+        let (_, left_ty) = self.parse_type_arg()?;
+        let value = self.parse_expr()?;
+        let endr = value.range();
+
+        Ok(Expr::Inr(Inr {
+            value: Box::new(value),
+            left_ty,
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_fold(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Fold)?;
+        let (_, ty) = self.parse_type_arg()?;
+        let value = self.parse_expr()?;
+        let endr = value.range();
+
+        Ok(Expr::Fold(Fold { ty, value: Box::new(value), range: range.mix(endr) }))
+    }
+
+    pub fn parse_unfold(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Unfold)?;
+        let value = self.parse_expr()?;
+        let endr = value.range();
+
+        Ok(Expr::Unfold(Unfold { value: Box::new(value), range: range.mix(endr) }))
+    }
+
+    pub fn parse_pack(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Pack)?;
+
+        consume!(self, Token::LBracket)?;
+        let witness = self.parse_type()?;
+
+        consume!(self, Token::Comma)?;
+        let value = self.parse_expr()?;
+
+        consume!(self, Token::RBracket)?;
+        let (_, endr) = consume!(self, Token::As)?;
+        let ty = self.parse_type()?;
+
+        Ok(Expr::Pack(Pack {
+            witness,
+            value: Box::new(value),
+            ty,
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_unpack(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Unpack)?;
+        let package = self.parse_expr()?;
+
+        consume!(self, Token::As)?;
+        consume!(self, Token::LBracket)?;
+        let (ty_param, _) = consume!(self, Token::TVar(var) => var.clone())?;
+
+        consume!(self, Token::Comma)?;
+        let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+
+        consume!(self, Token::RBracket)?;
+        consume!(self, Token::In)?;
+        let body = self.parse_expr()?;
+        let endr = body.range();
+
+        Ok(Expr::Unpack(Unpack {
+            package: Box::new(package),
+            ty_param,
+            param,
+            body: Box::new(body),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_variant(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Less)?;
+        let (label, _) = consume!(self, Token::Variable(var) => var.clone())?;
+
+        consume!(self, Token::Equal)?;
+        let value = self.parse_expr()?;
+        consume!(self, Token::Greater)?;
+
+        consume!(self, Token::As)?;
+        let ty = self.parse_type()?;
+
+        Ok(Expr::Variant(Variant { label, value: Box::new(value), ty, range }))
+    }
+
+    pub fn parse_match(&mut self, scrutinee: Expr, range: Range) -> Result<Expr, ParserError> {
+        let mut arms = Vec::new();
+
+        loop {
+            consume!(self, Token::Less)?;
+            let (label, _) = consume!(self, Token::Variable(var) => var.clone())?;
+            let (param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+            consume!(self, Token::Greater)?;
+
+            consume!(self, Token::FatArrow)?;
+            let body = self.parse_expr()?;
+            arms.push(Arm { label, param, body });
+
+            if let Token::Pipe = self.get() {
+                consume!(self, Token::Pipe)?;
+            } else {
+                break;
+            }
+        }
+
+        let endr = arms.last().map_or(range, |arm| arm.body.range());
+
+        Ok(Expr::Match(Match {
+            scrutinee: Box::new(scrutinee),
+            arms,
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_case(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Case)?;
+        let scrutinee = self.parse_expr()?;
+        consume!(self, Token::Of)?;
+
+        if let Token::Less = self.get() {
+            return self.parse_match(scrutinee, range);
+        }
+
+        consume!(self, Token::Inl)?;
+        let (left_param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+        consume!(self, Token::FatArrow)?;
+        let left_body = self.parse_expr()?;
+
+        consume!(self, Token::Pipe)?;
+        consume!(self, Token::Inr)?;
+        let (right_param, _) = consume!(self, Token::Variable(var) => var.clone())?;
+        consume!(self, Token::FatArrow)?;
+        let right_body = self.parse_expr()?;
+        let endr = right_body.range();
+
+        Ok(Expr::Case(Case {
+            scrutinee: Box::new(scrutinee),
+            left_param,
+            left_body: Box::new(left_body),
+            right_param,
+            right_body: Box::new(right_body),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_if(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::If)?;
+        let cond = self.parse_expr()?;
+
+        consume!(self, Token::Then)?;
+        let then_branch = self.parse_expr()?;
+
+        consume!(self, Token::Else)?;
+        let else_branch = self.parse_expr()?;
+        let endr = else_branch.range();
+
+        Ok(Expr::If(If {
+            cond: Box::new(cond),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+            range: range.mix(endr),
+        }))
+    }
+
+    pub fn parse_main(&mut self) -> Result<Expr, ParserError> {
+        consume!(self, Token::Main)?;
+        consume!(self, Token::Equal)?;
+
+        self.parse_expr()
+    }
+
+    // A program is an expression, or declarations closed by `main = e`; `main` is accepted
+    // only here, where a declaration could start.
+    pub fn parse_program(&mut self) -> Result<Expr, ParserError> {
+        match self.get() {
+            Token::Main => self.parse_main(),
+            _ => self.parse_expr(),
+        }
+    }
+
     pub fn parse_expr(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
             Token::Lambda => self.parse_abs(),
-            Token::Let => self.parse_let(),
+            Token::If => self.parse_if(),
+            Token::Let | Token::Def => self.parse_let(),
             Token::Fst => self.parse_fst(),
             Token::Snd => self.parse_snd(),
-            _ => self.parse_application(),
+            Token::Inl => self.parse_inl(),
+            Token::Inr => self.parse_inr(),
+            Token::Case => self.parse_case(),
+            Token::Less => self.parse_variant(),
+            Token::Fold => self.parse_fold(),
+            Token::Pack => self.parse_pack(),
+            Token::Unpack => self.parse_unpack(),
+            Token::Unfold => self.parse_unfold(),
+            _ => self.parse_comparison(),
         }
     }
 
@@ -210,13 +574,47 @@ impl<'a> Parser<'a> {
                 consume!(self, Token::TInt)?;
                 Ok(Type::TInt)
             }
+            Token::TBool => {
+                consume!(self, Token::TBool)?;
+                Ok(Type::TBool)
+            }
+            Token::TUnit => {
+                consume!(self, Token::TUnit)?;
+                Ok(Type::TUnit)
+            }
             Token::TVar(_) => {
                 let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
                 Ok(Type::TVar { value: token })
             }
+            Token::LBrace => {
+                consume!(self, Token::LBrace)?;
+                let (fields, rest) = self.parse_row()?;
+                consume!(self, Token::RBrace)?;
+
+                Ok(Type::Record { fields, rest })
+            }
+            Token::Less => {
+                consume!(self, Token::Less)?;
+                let (cases, rest) = self.parse_row()?;
+                consume!(self, Token::Greater)?;
+
+                Ok(Type::Variant { cases, rest })
+            }
+            Token::Mu => {
+                consume!(self, Token::Mu)?;
+                let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
+                consume!(self, Token::Dot)?;
+                let body = self.parse_type()?;
+
+                Ok(Type::Rec {
+                    param: token,
+                    body: Box::new(body),
+                })
+            }
             Token::Forall => {
                 consume!(self, Token::Forall)?;
-                let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
+                let (token, _) =
+                    consume!(self, Token::TVar(var) | Token::Variable(var) => var.clone())?;
                 consume!(self, Token::Dot)?;
                 let body = self.parse_type()?;
 
@@ -225,10 +623,45 @@ impl<'a> Parser<'a> {
                     body: Box::new(body),
                 })
             }
+            Token::Exists => {
+                consume!(self, Token::Exists)?;
+                let (token, _) = consume!(self, Token::TVar(var) => var.clone())?;
+                consume!(self, Token::Dot)?;
+                let body = self.parse_type()?;
+
+                Ok(Type::Exists {
+                    param: token,
+                    body: Box::new(body),
+                })
+            }
             _ => self.fail(),
         }
     }
 
+    pub fn parse_row(&mut self) -> Result<Row, ParserError> {
+        let mut fields = Vec::new();
+
+        while let Token::Variable(_) = self.get() {
+            let (label, _) = consume!(self, Token::Variable(var) => var.clone())?;
+            consume!(self, Token::Colon)?;
+            fields.push((label, self.parse_type()?));
+
+            if let Token::Comma = self.get() {
+                consume!(self, Token::Comma)?;
+            } else {
+                break;
+            }
+        }
+
+        if let Token::Pipe = self.get() {
+            consume!(self, Token::Pipe)?;
+            let (rest, _) = consume!(self, Token::Variable(var) => var.clone())?;
+            return Ok((fields, Some(rest)));
+        }
+
+        Ok((fields, None))
+    }
+
     pub fn parse_arrow_partial(&mut self, head: Type) -> Result<Type, ParserError> {
         match self.get() {
             Token::Arrow => {
@@ -249,6 +682,15 @@ impl<'a> Parser<'a> {
                     snd: Box::new(body),
                 })
             }
+            Token::Plus => {
+                consume!(self, Token::Plus)?;
+                let body = self.parse_type()?;
+
+                Ok(Type::Sum {
+                    left: Box::new(head),
+                    right: Box::new(body),
+                })
+            }
             _ => Ok(head),
         }
     }
@@ -266,3 +708,17 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::parser;
+
+    #[test]
+    fn test_parse_main_in_expression() {
+        let inputs = [r"(λx: Int. main = x + 1) 1", r"if true then main = 1 else 2"];
+
+        for input in inputs {
+            assert!(parser::from_string(input).is_err())
+        }
+    }
+}
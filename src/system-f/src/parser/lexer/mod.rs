@@ -9,11 +9,15 @@ fn is_whitespace(c: char) -> bool {
 }
 
 fn is_digit(c: char) -> bool {
-    ('0'..='9').contains(&c)
+    c.is_ascii_digit()
 }
 
 fn is_reserved(c: char) -> bool {
-    matches!(c, '(' | ')' | '[' | ']' | '{' | '}' | '.' | ',' | ':' | '=' | 'λ' | '*' | '×')
+    matches!(
+        c,
+        '(' | ')' | '[' | ']' | '{' | '}' | '.' | ',' | ':' | '=' | 'λ' | 'μ' | '*' | '×' | '+'
+            | '-' | '/' | '<' | '>' | '|'
+    )
 }
 
 fn is_valid_char(c: char) -> bool {
@@ -30,9 +34,36 @@ impl<'a> Lexer<'a> {
         self.make_token(token, start)
     }
 
+    pub fn double_token(
+        &mut self,
+        second: char,
+        token: Token,
+        single: Token,
+        start: usize,
+    ) -> (Token, Range) {
+        self.next_char();
+
+        match self.peekable.peek() {
+            Some(chr) if *chr == second => self.single_token(token, start),
+            _ => self.make_token(single, start),
+        }
+    }
+
+    pub fn lex_equal(&mut self, start: usize) -> (Token, Range) {
+        self.next_char();
+
+        match self.peekable.peek() {
+            Some('=') => self.single_token(Token::EqualEqual, start),
+            Some('>') => self.single_token(Token::FatArrow, start),
+            _ => self.make_token(Token::Equal, start),
+        }
+    }
+
     fn to_type(buf: &str) -> Token {
         match buf {
             "Int" => Token::TInt,
+            "Bool" => Token::TBool,
+            "Unit" => Token::TUnit,
             _ => Token::TVar(buf.to_string()),
         }
     }
@@ -41,9 +72,29 @@ impl<'a> Lexer<'a> {
         match buf {
             "lambda" => Token::Lambda,
             "forall" => Token::Forall,
+            "exists" => Token::Exists,
+            "mu" => Token::Mu,
+            "fold" => Token::Fold,
+            "unfold" => Token::Unfold,
+            "fix" => Token::Fix,
+            "rec" => Token::Rec,
+            "pack" => Token::Pack,
+            "unpack" => Token::Unpack,
+            "true" => Token::True,
+            "false" => Token::False,
+            "if" => Token::If,
+            "then" => Token::Then,
+            "else" => Token::Else,
             "let" => Token::Let,
+            "def" => Token::Def,
+            "main" => Token::Main,
             "fst" => Token::Fst,
             "snd" => Token::Snd,
+            "inl" => Token::Inl,
+            "inr" => Token::Inr,
+            "case" => Token::Case,
+            "of" => Token::Of,
+            "as" => Token::As,
             "in" => Token::In,
             "->" => Token::Arrow,
             _ => Token::Variable(buf.to_string()),
@@ -69,8 +120,15 @@ impl<'a> Lexer<'a> {
                 '×' => self.single_token(Token::Prod, start),
                 'λ' => self.single_token(Token::Lambda, start),
                 '∀' => self.single_token(Token::Forall, start),
+                'μ' => self.single_token(Token::Mu, start),
+                '∃' => self.single_token(Token::Exists, start),
                 'Λ' => self.single_token(Token::Forall, start),
-                '=' => self.single_token(Token::Equal, start),
+                '=' => self.lex_equal(start),
+                '-' => self.double_token('>', Token::Arrow, Token::Minus, start),
+                '+' => self.single_token(Token::Plus, start),
+                '/' => self.single_token(Token::Slash, start),
+                '<' => self.single_token(Token::Less, start),
+                '>' => self.single_token(Token::Greater, start),
                 '(' => self.single_token(Token::LParen, start),
                 ')' => self.single_token(Token::RParen, start),
                 '[' => self.single_token(Token::LBracket, start),
@@ -80,6 +138,7 @@ impl<'a> Lexer<'a> {
                 ':' => self.single_token(Token::Colon, start),
                 '.' => self.single_token(Token::Dot, start),
                 ',' => self.single_token(Token::Comma, start),
+                '|' => self.single_token(Token::Pipe, start),
                 chr if is_digit(*chr) => {
                     let num = self.accu_while(is_digit);
                     let num = num.parse::<usize>().unwrap();
@@ -182,6 +241,165 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_lex_conditional() {
+        let received = "if true then () else false";
+        let expected = vec![
+            Token::If,
+            Token::True,
+            Token::Then,
+            Token::LParen,
+            Token::RParen,
+            Token::Else,
+            Token::False,
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_operators() {
+        let received = "x * 2 + 1 == y -> 3 - 4 / 5 < 6";
+        let expected = vec![
+            Token::Variable(String::from("x")),
+            Token::Prod,
+            Token::Number(2),
+            Token::Plus,
+            Token::Number(1),
+            Token::EqualEqual,
+            Token::Variable(String::from("y")),
+            Token::Arrow,
+            Token::Number(3),
+            Token::Minus,
+            Token::Number(4),
+            Token::Slash,
+            Token::Number(5),
+            Token::Less,
+            Token::Number(6),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_case() {
+        let received = "case inl [Int] x of inl a => a | inr b => 0";
+        let expected = vec![
+            Token::Case,
+            Token::Inl,
+            Token::LBracket,
+            Token::TInt,
+            Token::RBracket,
+            Token::Variable(String::from("x")),
+            Token::Of,
+            Token::Inl,
+            Token::Variable(String::from("a")),
+            Token::FatArrow,
+            Token::Variable(String::from("a")),
+            Token::Pipe,
+            Token::Inr,
+            Token::Variable(String::from("b")),
+            Token::FatArrow,
+            Token::Number(0),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_rows() {
+        let received = "∀ρ. {x: Int | ρ} -> <a = r.x> as <a: Int>";
+        let expected = vec![
+            Token::Forall,
+            Token::Variable(String::from("ρ")),
+            Token::Dot,
+            Token::LBrace,
+            Token::Variable(String::from("x")),
+            Token::Colon,
+            Token::TInt,
+            Token::Pipe,
+            Token::Variable(String::from("ρ")),
+            Token::RBrace,
+            Token::Arrow,
+            Token::Less,
+            Token::Variable(String::from("a")),
+            Token::Equal,
+            Token::Variable(String::from("r")),
+            Token::Dot,
+            Token::Variable(String::from("x")),
+            Token::Greater,
+            Token::As,
+            Token::Less,
+            Token::Variable(String::from("a")),
+            Token::Colon,
+            Token::TInt,
+            Token::Greater,
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_existential() {
+        let received = "unpack pack [Int, 1] as ∃X. X as [X, x] in x";
+        let expected = vec![
+            Token::Unpack,
+            Token::Pack,
+            Token::LBracket,
+            Token::TInt,
+            Token::Comma,
+            Token::Number(1),
+            Token::RBracket,
+            Token::As,
+            Token::Exists,
+            Token::TVar(String::from("X")),
+            Token::Dot,
+            Token::TVar(String::from("X")),
+            Token::As,
+            Token::LBracket,
+            Token::TVar(String::from("X")),
+            Token::Comma,
+            Token::Variable(String::from("x")),
+            Token::RBracket,
+            Token::In,
+            Token::Variable(String::from("x")),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
+    #[test]
+    fn test_lex_recursive() {
+        let received = "unfold fold [μL. Unit + L] x";
+        let expected = vec![
+            Token::Unfold,
+            Token::Fold,
+            Token::LBracket,
+            Token::Mu,
+            Token::TVar(String::from("L")),
+            Token::Dot,
+            Token::TUnit,
+            Token::Plus,
+            Token::TVar(String::from("L")),
+            Token::RBracket,
+            Token::Variable(String::from("x")),
+        ];
+
+        for (fst, snd) in test_lex(received, expected) {
+            assert_eq!(fst, snd)
+        }
+    }
+
     #[test]
     fn test_lex_parens() {
         let received = "(x) y";
@@ -3,21 +3,51 @@ pub enum Token {
     Number(usize),
     Variable(String),
     Lambda,
+    True,
+    False,
+    If,
+    Then,
+    Else,
     Let,
+    Def,
+    Main,
     Fst,
     Snd,
+    Inl,
+    Inr,
+    Case,
+    Of,
+    As,
+    Fold,
+    Unfold,
+    Fix,
+    Pack,
+    Unpack,
+    Rec,
     In,
 
     TInt,
+    TBool,
+    TUnit,
     TVar(String),
     Arrow,
     Forall,
+    Exists,
+    Mu,
     Prod,
 
     Dot,
     Colon,
     Comma,
     Equal,
+    EqualEqual,
+    FatArrow,
+    Pipe,
+    Less,
+    Greater,
+    Plus,
+    Minus,
+    Slash,
     LParen,
     RParen,
     LBracket,
@@ -25,7 +25,7 @@ impl Range {
     #[inline]
     pub fn mix(&self, end: Range) -> Range {
         Range {
-            start: self.start.clone(),
+            start: self.start,
             end: end.end,
         }
     }
@@ -12,5 +12,5 @@ pub fn from_string(str: &str) -> Result<parsetree::Expr, error::ParserError> {
     let mut string = str.to_string();
     let mut parser = state::Parser::init(&mut string)?;
 
-    return parser.parse_expr();
+    parser.parse_program()
 }
@@ -7,6 +7,17 @@ pub struct Int {
     pub range: Range,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bool {
+    pub value: bool,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub range: Range,
+}
+
 #[derive(Debug, Clone)]
 pub struct Var {
     pub value: String,
@@ -61,40 +72,203 @@ pub struct Snd {
     pub range: Range,
 }
 
+#[derive(Debug, Clone)]
+pub struct Inl {
+    pub value: Box<Expr>,
+    pub right_ty: Type,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Inr {
+    pub value: Box<Expr>,
+    pub left_ty: Type,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub scrutinee: Box<Expr>,
+    pub left_param: String,
+    pub left_body: Box<Expr>,
+    pub right_param: String,
+    pub right_body: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub fields: Vec<(String, Expr)>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub record: Box<Expr>,
+    pub label: String,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub label: String,
+    pub value: Box<Expr>,
+    pub ty: Type,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Arm {
+    pub label: String,
+    pub param: String,
+    pub body: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub scrutinee: Box<Expr>,
+    pub arms: Vec<Arm>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fold {
+    pub ty: Type,
+    pub value: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Unfold {
+    pub value: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub witness: Type,
+    pub value: Box<Expr>,
+    pub ty: Type,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Unpack {
+    pub package: Box<Expr>,
+    pub ty_param: String,
+    pub param: String,
+    pub body: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub value: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct If {
+    pub cond: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Lt,
+}
+
+#[derive(Debug, Clone)]
+pub struct BinOp {
+    pub op: Operator,
+    pub left: Box<Expr>,
+    pub right: Box<Expr>,
+    pub range: Range,
+}
+
+/// Labelled fields of a record or variant type, closed by an optional row variable.
+pub type Row = (Vec<(String, Type)>, Option<String>);
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     TInt,
+    TBool,
+    TUnit,
     TVar { value: String },
     Arrow { left: Box<Type>, right: Box<Type> },
     Forall { param: String, body: Box<Type> },
+    Exists { param: String, body: Box<Type> },
+    Rec { param: String, body: Box<Type> },
     Product { fst: Box<Type>, snd: Box<Type> },
+    Sum { left: Box<Type>, right: Box<Type> },
+    Record { fields: Vec<(String, Type)>, rest: Option<String> },
+    Variant { cases: Vec<(String, Type)>, rest: Option<String> },
 }
 
 #[derive(Debug, Clone)]
 pub enum Expr {
     Int(Int),
+    Bool(Bool),
+    Unit(Unit),
     Var(Var),
     Abs(Abs),
     App(App),
     Fst(Fst),
     Snd(Snd),
     Pair(Pair),
+    Inl(Inl),
+    Inr(Inr),
+    Case(Case),
+    Record(Record),
+    Project(Project),
+    Variant(Variant),
+    Match(Match),
+    Fold(Fold),
+    Unfold(Unfold),
+    Fix(Fix),
+    Pack(Pack),
+    Unpack(Unpack),
     TAbs(TAbs),
     TApp(TApp),
+    If(If),
+    BinOp(BinOp),
 }
 
 impl Expr {
     pub fn range(&self) -> Range {
         match self {
-            Expr::Int(Int { range, .. }) => range.clone(),
-            Expr::Var(Var { range, .. }) => range.clone(),
-            Expr::Abs(Abs { range, .. }) => range.clone(),
-            Expr::App(App { range, .. }) => range.clone(),
-            Expr::Fst(Fst { range, .. }) => range.clone(),
-            Expr::Snd(Snd { range, .. }) => range.clone(),
-            Expr::Pair(Pair { range, .. }) => range.clone(),
-            Expr::TAbs(TAbs { range, .. }) => range.clone(),
-            Expr::TApp(TApp { range, .. }) => range.clone(),
+            Expr::Int(Int { range, .. }) => *range,
+            Expr::Bool(Bool { range, .. }) => *range,
+            Expr::Unit(Unit { range }) => *range,
+            Expr::Var(Var { range, .. }) => *range,
+            Expr::Abs(Abs { range, .. }) => *range,
+            Expr::App(App { range, .. }) => *range,
+            Expr::Fst(Fst { range, .. }) => *range,
+            Expr::Snd(Snd { range, .. }) => *range,
+            Expr::Pair(Pair { range, .. }) => *range,
+            Expr::Inl(Inl { range, .. }) => *range,
+            Expr::Inr(Inr { range, .. }) => *range,
+            Expr::Case(Case { range, .. }) => *range,
+            Expr::Record(Record { range, .. }) => *range,
+            Expr::Project(Project { range, .. }) => *range,
+            Expr::Variant(Variant { range, .. }) => *range,
+            Expr::Match(Match { range, .. }) => *range,
+            Expr::Fold(Fold { range, .. }) => *range,
+            Expr::Unfold(Unfold { range, .. }) => *range,
+            Expr::Fix(Fix { range, .. }) => *range,
+            Expr::Pack(Pack { range, .. }) => *range,
+            Expr::Unpack(Unpack { range, .. }) => *range,
+            Expr::TAbs(TAbs { range, .. }) => *range,
+            Expr::TApp(TApp { range, .. }) => *range,
+            Expr::If(If { range, .. }) => *range,
+            Expr::BinOp(BinOp { range, .. }) => *range,
         }
     }
 }
@@ -105,6 +279,18 @@ impl fmt::Display for Int {
     }
 }
 
+impl fmt::Display for Bool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "()")
+    }
+}
+
 impl fmt::Display for Var {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.value)
@@ -153,14 +339,166 @@ impl fmt::Display for Pair {
     }
 }
 
+impl fmt::Display for Inl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "inl [{}] {}", self.right_ty, self.value)
+    }
+}
+
+impl fmt::Display for Inr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "inr [{}] {}", self.left_ty, self.value)
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(case {} of inl {} => {} | inr {} => {})",
+            self.scrutinee, self.left_param, self.left_body, self.right_param, self.right_body
+        )
+    }
+}
+
+fn fmt_row<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    sep: &str,
+    fields: &[(String, T)],
+    rest: &Option<String>,
+) -> fmt::Result {
+    for (i, (label, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}{}{}", label, sep, value)?;
+    }
+
+    match rest {
+        Some(rest) => write!(f, " | {}", rest),
+        None => Ok(()),
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")?;
+        fmt_row(f, " = ", &self.fields, &None)?;
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for Project {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.record, self.label)
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(<{} = {}> as {})", self.label, self.value, self.ty)
+    }
+}
+
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(case {} of", self.scrutinee)?;
+
+        for (i, arm) in self.arms.iter().enumerate() {
+            let sep = if i > 0 { " |" } else { "" };
+            write!(f, "{} <{} {}> => {}", sep, arm.label, arm.param, arm.body)?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for Fold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(fold [{}] {})", self.ty, self.value)
+    }
+}
+
+impl fmt::Display for Unfold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(unfold {})", self.value)
+    }
+}
+
+impl fmt::Display for Pack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(pack [{}, {}] as {})", self.witness, self.value, self.ty)
+    }
+}
+
+impl fmt::Display for Unpack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(unpack {} as [{}, {}] in {})",
+            self.package, self.ty_param, self.param, self.body
+        )
+    }
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(fix {})", self.value)
+    }
+}
+
+impl fmt::Display for If {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(if {} then {} else {})",
+            self.cond, self.then_branch, self.else_branch
+        )
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Sub => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+            Operator::Eq => write!(f, "=="),
+            Operator::Lt => write!(f, "<"),
+        }
+    }
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} {} {})", self.left, self.op, self.right)
+    }
+}
+
 impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Type::TInt => write!(f, "Int"),
+            Type::TBool => write!(f, "Bool"),
+            Type::TUnit => write!(f, "Unit"),
             Type::TVar { value } => write!(f, "{}", value),
             Type::Arrow { left, right } => write!(f, "({} -> {})", left, right),
             Type::Forall { param, body } => write!(f, "∀{}. {}", param, body),
+            Type::Exists { param, body } => write!(f, "(∃{}. {})", param, body),
+            Type::Rec { param, body } => write!(f, "(μ{}. {})", param, body),
             Type::Product { fst, snd } => write!(f, "{} × {}", fst, snd),
+            Type::Sum { left, right } => write!(f, "({} + {})", left, right),
+            Type::Record { fields, rest } => {
+                write!(f, "{{")?;
+                fmt_row(f, ": ", fields, rest)?;
+                write!(f, "}}")
+            }
+            Type::Variant { cases, rest } => {
+                write!(f, "<")?;
+                fmt_row(f, ": ", cases, rest)?;
+                write!(f, ">")
+            }
         }
     }
 }
@@ -169,14 +507,30 @@ impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Expr::Int(int) => write!(f, "{}", int),
+            Expr::Bool(bool) => write!(f, "{}", bool),
+            Expr::Unit(unit) => write!(f, "{}", unit),
             Expr::Var(var) => write!(f, "{}", var),
             Expr::Abs(abs) => write!(f, "{}", abs),
             Expr::App(app) => write!(f, "{}", app),
             Expr::Fst(fst) => write!(f, "{}", fst),
             Expr::Snd(snd) => write!(f, "{}", snd),
             Expr::Pair(pair) => write!(f, "{}", pair),
+            Expr::Inl(inl) => write!(f, "{}", inl),
+            Expr::Inr(inr) => write!(f, "{}", inr),
+            Expr::Case(case) => write!(f, "{}", case),
+            Expr::Record(record) => write!(f, "{}", record),
+            Expr::Project(project) => write!(f, "{}", project),
+            Expr::Variant(variant) => write!(f, "{}", variant),
+            Expr::Match(matching) => write!(f, "{}", matching),
+            Expr::Fold(fold) => write!(f, "{}", fold),
+            Expr::Unfold(unfold) => write!(f, "{}", unfold),
+            Expr::Fix(fix) => write!(f, "{}", fix),
+            Expr::Pack(pack) => write!(f, "{}", pack),
+            Expr::Unpack(unpack) => write!(f, "{}", unpack),
             Expr::TAbs(tabs) => write!(f, "{}", tabs),
             Expr::TApp(tapp) => write!(f, "{}", tapp),
+            Expr::If(cond) => write!(f, "{}", cond),
+            Expr::BinOp(binop) => write!(f, "{}", binop),
         }
     }
 }
@@ -1,16 +1,179 @@
 use crate::parser::parsetree::Expr;
 
+pub mod nbe;
+pub mod primitives;
+pub mod readback;
 pub mod strategies;
 pub mod variables;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Norm {
     NOR, // normal-order
+    NBE, // normal forms by evaluation
 }
 
+/// The reductions evaluation takes when given no limit.
+pub const STEPS: usize = 1_000_000;
+
 pub fn reduce(strategy: Norm, expr: Expr, limit: Option<usize>) -> Expr {
-    let limit = Some(limit.unwrap_or(100));
+    // Normal order bounds how deeply reductions nest and evaluation how many there are. No
+    // limit means a depth of 100 for the one and a million reductions for the other.
+    let depth = Some(limit.unwrap_or(100));
+    let steps = Some(limit.unwrap_or(STEPS));
 
     match strategy {
-        Norm::NOR => strategies::normal_order(expr, limit),
+        Norm::NOR => strategies::normal_order(expr, depth),
+        Norm::NBE => nbe::normalize(&expr, steps),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{reduce, Norm};
+    use crate::{checker, parser, prelude};
+
+    fn eval(input: &str) -> String {
+        let expr = parser::from_string(input).unwrap();
+        checker::type_of(expr.clone()).unwrap();
+        reduce(Norm::NOR, expr, None).to_string()
+    }
+
+    #[test]
+    fn test_reduce_if() {
+        let input = r"
+        let pick: ∀A. Bool -> A -> A -> A = λA. λb: Bool. λx: A. λy: A. if b then x else y in
+        pick [Int] true 1 2
+        ";
+
+        assert_eq!(eval(input), "1")
+    }
+
+    #[test]
+    fn test_reduce_arithmetic() {
+        let input = r"
+        let square: Int -> Int = λx: Int. x * x in
+        let pair: Int * Int = {square 3 - 1, 10 / 4} in
+        (fst pair) == (snd pair) + 6
+        ";
+
+        assert_eq!(eval(input), "true")
+    }
+
+    #[test]
+    fn test_reduce_overflow() {
+        let input = r"
+        let big: Int = 4294967296 in
+        big * big + 1
+        ";
+
+        assert_eq!(eval(input), "((4294967296 * 4294967296) + 1)")
+    }
+
+    #[test]
+    fn test_reduce_case() {
+        let input = r"
+        let safe_div: Int -> Int -> Unit + Int = λx: Int. λy: Int.
+            if y == 0 then inl [Int] () else inr [Unit] x / y in
+        let or_zero: (Unit + Int) -> Int = λr: Unit + Int. case r of inl u => 0 | inr n => n in
+        or_zero (safe_div 12 4) + or_zero (safe_div 1 0)
+        ";
+
+        assert_eq!(eval(input), "3")
+    }
+
+    #[test]
+    fn test_reduce_row_polymorphism() {
+        let input = r"
+        let get_x: ∀ρ. {x: Int | ρ} -> Int = λρ. λr: {x: Int | ρ}. r.x in
+        let point: {x: Int, y: Int} = {x = 20, y = 22} in
+        get_x [{y: Int}] point + point.y
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_variant() {
+        let input = r"
+        let size: <num: Int, flag: Bool> -> Int = λv: <num: Int, flag: Bool>.
+            case v of <num n> => n | <flag b> => if b then 1 else 0 in
+        size (<num = 41> as <num: Int, flag: Bool>) + size (<flag = true> as <num: Int, flag: Bool>)
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_recursive_list() {
+        let input = r"
+        let nil: μL. Unit + Int * L =
+            fold [μL. Unit + Int * L] inl [Int * (μL. Unit + Int * L)] () in
+        let cons: Int -> (μL. Unit + Int * L) -> μL. Unit + Int * L =
+            λx: Int. λxs: μL. Unit + Int * L. fold [μL. Unit + Int * L] inr [Unit] {x, xs} in
+        let head: (μL. Unit + Int * L) -> Int =
+            λl: μL. Unit + Int * L. case unfold l of inl u => 0 | inr p => fst p in
+        head (cons 42 nil)
+        ";
+
+        assert_eq!(eval(input), "42")
+    }
+
+    #[test]
+    fn test_reduce_let_rec() {
+        let input = r"
+        let rec fib: Int -> Int = λn: Int. if n < 2 then n else fib (n - 1) + fib (n - 2) in
+        fib 10
+        ";
+
+        assert_eq!(eval(input), "55")
+    }
+
+    #[test]
+    fn test_reduce_polymorphic_fix() {
+        let input = r"
+        let rec iter: ∀A. (A -> A) -> Int -> A -> A =
+            λA. λf: A -> A. λn: Int. λx: A. if n == 0 then x else iter [A] f (n - 1) (f x) in
+        iter [Int] (λx: Int. x * 2) 5 1
+        ";
+
+        assert_eq!(eval(input), "32")
+    }
+
+    #[test]
+    fn test_reduce_abstract_counter() {
+        let input = r"
+        let counter: ∃C. {new: C, inc: C -> C, get: C -> Int} =
+            pack [Int, {new = 0, inc = λc: Int. c + 1, get = λc: Int. c}]
+            as ∃C. {new: C, inc: C -> C, get: C -> Int} in
+        unpack counter as [C, c] in c.get (c.inc (c.inc c.new))
+        ";
+
+        assert_eq!(eval(input), "2")
+    }
+
+    #[test]
+    fn test_reduce_program() {
+        let input = r"
+        def twice: ∀A. (A -> A) -> A -> A = λA. λf: A -> A. λx: A. f (f x)
+        def rec len: (μL. Unit + Int * L) -> Int =
+            λl: μL. Unit + Int * L. case unfold l of inl u => 0 | inr p => 1 + len (snd p)
+        def nil: μL. Unit + Int * L = fold [μL. Unit + Int * L] inl [Int * (μL. Unit + Int * L)] ()
+        main = twice [Int] (λn: Int. n * 10) (len nil + 1)
+        ";
+
+        assert_eq!(eval(input), "100")
+    }
+
+    #[test]
+    fn test_reduce_prelude() {
+        let input = r"
+        let two: ∀N. (N -> N) -> N -> N = succ (succ zero) in
+        let xs: ∀R. (Int -> R -> R) -> R -> R = cons [Int] (toint (mul two two)) (nil [Int]) in
+        first [Int] [∀B. B -> B -> B] (pair [Int] [∀B. B -> B -> B] (sum xs) (iszero zero))
+        ";
+        let expr = prelude::load(input).unwrap();
+        checker::type_of(expr.clone()).unwrap();
+
+        assert_eq!(reduce(Norm::NOR, expr, None).to_string(), "4")
     }
 }
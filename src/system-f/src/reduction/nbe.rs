@@ -0,0 +1,645 @@
+//! Normalisation by evaluation for System F. Type abstractions are closures too, and the
+//! environments of the untyped evaluator bind type variables next to term variables, so a type
+//! application only extends an environment: the types it binds are substituted in the
+//! annotations of a value when the value is read back.
+//!
+//! Pairs, sums, records, variants, folds and packages are values whose components stay delayed
+//! until an elimination asks for them. An elimination stuck on a variable keeps the environment
+//! of its branches, and a recursive function under one unfolds on every call it reads back, so
+//! its normal form is only found up to a limit.
+
+use super::primitives::delta;
+use super::variables::free_variables;
+use crate::checker::extend_row;
+use crate::parser::location::Range;
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Bool, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Int, Match, Operator,
+    Pack, Pair, Project, Record, Snd, TAbs, TApp, Type, Unfold, Unit, Unpack, Var, Variant,
+};
+use std::collections::HashSet;
+use std::rc::Rc;
+use untyped_lambda_calculus::reduction::nbe::{Env, Thunk};
+use untyped_lambda_calculus::reduction::variables::fresh_name;
+
+#[derive(Clone)]
+enum Value<'a> {
+    Int(usize, Range),
+    Bool(bool, Range),
+    Unit(Range),
+    /// An abstraction with the environment it was evaluated in.
+    Lam(&'a Abs, Scope<'a>),
+    TLam(&'a TAbs, Scope<'a>),
+    /// `fix` of a function, unfolded when it is applied to a term or a type.
+    Fix(Rc<Argument<'a>>, Range),
+    Pair(Rc<Argument<'a>>, Rc<Argument<'a>>, Range),
+    Inl(Rc<Argument<'a>>, Type, Range),
+    Inr(Rc<Argument<'a>>, Type, Range),
+    Record(Vec<(&'a str, Rc<Argument<'a>>)>, Range),
+    Variant(&'a str, Rc<Argument<'a>>, Type, Range),
+    Fold(Type, Rc<Argument<'a>>, Range),
+    Pack(Type, Rc<Argument<'a>>, Type, Range),
+    Neutral(Rc<Neutral<'a>>),
+}
+
+/// A value that cannot be reduced further, headed by a free variable, a stuck elimination, or
+/// a redex left once the limit was reached. Eliminations with binders keep the environment
+/// their branches are read back in.
+enum Neutral<'a> {
+    Var(Var),
+    App(Value<'a>, Rc<Argument<'a>>, Range),
+    TApp(Value<'a>, Type, Range),
+    Fst(Value<'a>, Range),
+    Snd(Value<'a>, Range),
+    Project(Value<'a>, &'a str, Range),
+    Unfold(Value<'a>, Range),
+    Case(Value<'a>, &'a Case, Scope<'a>),
+    Match(Value<'a>, &'a Match, Scope<'a>),
+    Unpack(Value<'a>, &'a Unpack, Scope<'a>),
+    If(Value<'a>, &'a If, Scope<'a>),
+    BinOp(Operator, Value<'a>, Value<'a>, Range),
+}
+
+/// A term with the environment it is evaluated in.
+struct Suspended<'a>(&'a Expr, Scope<'a>);
+
+type Argument<'a> = Thunk<Suspended<'a>, Value<'a>>;
+
+#[derive(Clone)]
+enum Binding<'a> {
+    Term(Rc<Argument<'a>>),
+    Type(Type),
+}
+
+type Scope<'a> = Env<'a, Binding<'a>>;
+
+fn lookup<'e, 'a>(env: &'e Scope<'a>, name: &str) -> Option<&'e Rc<Argument<'a>>> {
+    match env.find(|bound, binding| bound == name && matches!(binding, Binding::Term(_))) {
+        Some(Binding::Term(thunk)) => Some(thunk),
+        _ => None,
+    }
+}
+
+fn lookup_type<'e>(env: &'e Scope, name: &str) -> Option<&'e Type> {
+    match env.find(|bound, binding| bound == name && matches!(binding, Binding::Type(_))) {
+        Some(Binding::Type(ty)) => Some(ty),
+        _ => None,
+    }
+}
+
+// Substitutes the types `env` binds in `ty` at once, but not under the binders of `ty` that
+// are in `shadowed`.
+fn resolve_in(ty: &Type, env: &Scope, shadowed: &mut Vec<String>) -> Type {
+    let bound = |name: &String, shadowed: &[String]| {
+        if shadowed.contains(name) {
+            None
+        } else {
+            lookup_type(env, name).cloned()
+        }
+    };
+
+    let resolve = |ty: &Type, shadowed: &mut Vec<String>| Box::new(resolve_in(ty, env, shadowed));
+
+    let under = |param: &String, body: &Type, shadowed: &mut Vec<String>| {
+        shadowed.push(param.clone());
+        let body = Box::new(resolve_in(body, env, shadowed));
+        shadowed.pop();
+
+        (param.clone(), body)
+    };
+
+    match ty {
+        Type::TInt | Type::TBool | Type::TUnit => ty.clone(),
+        Type::TVar { value } => bound(value, shadowed).unwrap_or_else(|| ty.clone()),
+        Type::Arrow { left, right } => Type::Arrow {
+            left: resolve(left, shadowed),
+            right: resolve(right, shadowed),
+        },
+        Type::Product { fst, snd } => Type::Product {
+            fst: resolve(fst, shadowed),
+            snd: resolve(snd, shadowed),
+        },
+        Type::Sum { left, right } => Type::Sum {
+            left: resolve(left, shadowed),
+            right: resolve(right, shadowed),
+        },
+        Type::Forall { param, body } => {
+            let (param, body) = under(param, body, shadowed);
+            Type::Forall { param, body }
+        }
+        Type::Exists { param, body } => {
+            let (param, body) = under(param, body, shadowed);
+            Type::Exists { param, body }
+        }
+        Type::Rec { param, body } => {
+            let (param, body) = under(param, body, shadowed);
+            Type::Rec { param, body }
+        }
+        Type::Record { fields, rest } | Type::Variant { cases: fields, rest } => {
+            let fields: Vec<(String, Type)> = fields
+                .iter()
+                .map(|(label, ty)| (label.clone(), *resolve(ty, shadowed)))
+                .collect();
+
+            let (fields, rest) = match rest.as_ref().and_then(|rest| bound(rest, shadowed)) {
+                Some(to) => extend_row(fields, rest, rest.as_ref().unwrap(), &to),
+                None => (fields, rest.clone()),
+            };
+
+            match ty {
+                Type::Record { .. } => Type::Record { fields, rest },
+                _ => Type::Variant { cases: fields, rest },
+            }
+        }
+    }
+}
+
+fn resolve(ty: &Type, env: &Scope) -> Type {
+    resolve_in(ty, env, &mut Vec::new())
+}
+
+fn neutral(neutral: Neutral) -> Value {
+    Value::Neutral(Rc::new(neutral))
+}
+
+struct Evaluator {
+    limit: Option<usize>,
+    // The names a binder must not take when read back: the free variables of the term and the
+    // binders it is read back under.
+    used: HashSet<String>,
+}
+
+impl Evaluator {
+    // Counts a reduction, or tells that the limit was reached.
+    fn tick(&mut self) -> bool {
+        match self.limit {
+            Some(0) => false,
+            Some(count) => {
+                self.limit = Some(count - 1);
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn force<'a>(&mut self, thunk: &Argument<'a>) -> Value<'a> {
+        thunk.force(|Suspended(term, env)| self.eval(term, env))
+    }
+
+    // Arguments that are variables share the thunk they are bound to.
+    fn delay<'a>(&self, term: &'a Expr, env: &Scope<'a>) -> Rc<Argument<'a>> {
+        match term {
+            Expr::Var(Var { value, .. }) => match lookup(env, value) {
+                Some(thunk) => thunk.clone(),
+                None => Thunk::new(Suspended(term, env.clone())),
+            },
+            _ => Thunk::new(Suspended(term, env.clone())),
+        }
+    }
+
+    // `fix (λf: T. e)` unfolds to `e` with `f` bound to itself.
+    fn unfold<'a>(&mut self, value: Value<'a>) -> Value<'a> {
+        let Value::Fix(function, _) = &value else {
+            return value;
+        };
+
+        match self.force(function) {
+            Value::Lam(abs, env) if self.tick() => {
+                let itself = Thunk::ready(value.clone());
+                self.eval(&abs.body, env.bind(&abs.param, Binding::Term(itself)))
+            }
+            _ => value,
+        }
+    }
+
+    fn binop<'a>(&mut self, binop: &'a BinOp, env: &Scope<'a>) -> Value<'a> {
+        let left = self.eval(&binop.left, env.clone());
+        let right = self.eval(&binop.right, env.clone());
+
+        // Literals are computed by the same rules the strategies use.
+        if let (Value::Int(left, left_range), Value::Int(right, right_range)) = (&left, &right) {
+            let left = Expr::Int(Int { value: *left, range: *left_range });
+            let right = Expr::Int(Int { value: *right, range: *right_range });
+            let (left, right) = (Box::new(left), Box::new(right));
+            let operation = BinOp { op: binop.op, left, right, range: binop.range };
+
+            match delta(operation) {
+                Expr::Int(Int { value, range }) => return Value::Int(value, range),
+                Expr::Bool(Bool { value, range }) => return Value::Bool(value, range),
+                _ => (),
+            }
+        }
+
+        neutral(Neutral::BinOp(binop.op, left, right, binop.range))
+    }
+
+    // Values are made apart from eval, which keeps the frame of eval small: the laziness of
+    // arguments makes forcing a thunk nest a call to eval for every thunk it depends on.
+    fn construct<'a>(&mut self, term: &'a Expr, env: Scope<'a>) -> Value<'a> {
+        match term {
+            Expr::Int(Int { value, range }) => Value::Int(*value, *range),
+            Expr::Bool(Bool { value, range }) => Value::Bool(*value, *range),
+            Expr::Unit(Unit { range }) => Value::Unit(*range),
+            Expr::Abs(abs) => Value::Lam(abs, env),
+            Expr::TAbs(tabs) => Value::TLam(tabs, env),
+            Expr::Fix(Fix { value, range }) => Value::Fix(self.delay(value, &env), *range),
+            Expr::Pair(Pair { fst, snd, range }) => {
+                Value::Pair(self.delay(fst, &env), self.delay(snd, &env), *range)
+            }
+            Expr::Inl(Inl { value, right_ty, range }) => {
+                let right_ty = resolve(right_ty, &env);
+                Value::Inl(self.delay(value, &env), right_ty, *range)
+            }
+            Expr::Inr(Inr { value, left_ty, range }) => {
+                let left_ty = resolve(left_ty, &env);
+                Value::Inr(self.delay(value, &env), left_ty, *range)
+            }
+            Expr::Record(Record { fields, range }) => {
+                let fields = fields
+                    .iter()
+                    .map(|(label, field)| (label.as_str(), self.delay(field, &env)))
+                    .collect();
+
+                Value::Record(fields, *range)
+            }
+            Expr::Variant(Variant { label, value, ty, range }) => {
+                let ty = resolve(ty, &env);
+                Value::Variant(label, self.delay(value, &env), ty, *range)
+            }
+            Expr::Fold(Fold { ty, value, range }) => {
+                Value::Fold(resolve(ty, &env), self.delay(value, &env), *range)
+            }
+            Expr::Pack(Pack { witness, value, ty, range }) => {
+                let (witness, ty) = (resolve(witness, &env), resolve(ty, &env));
+                Value::Pack(witness, self.delay(value, &env), ty, *range)
+            }
+            _ => self.eval(term, env),
+        }
+    }
+
+    fn project<'a>(&mut self, term: &'a Expr, env: Scope<'a>) -> Value<'a> {
+        match term {
+            Expr::Fst(Fst { pair, range }) => match self.eval(pair, env) {
+                Value::Pair(fst, _, _) => self.force(&fst),
+                pair => neutral(Neutral::Fst(pair, *range)),
+            },
+            Expr::Snd(Snd { pair, range }) => match self.eval(pair, env) {
+                Value::Pair(_, snd, _) => self.force(&snd),
+                pair => neutral(Neutral::Snd(pair, *range)),
+            },
+            Expr::Project(Project { record, label, range }) => {
+                let record = self.eval(record, env);
+
+                if let Value::Record(fields, _) = &record {
+                    if let Some((_, field)) = fields.iter().find(|(l, _)| l == label) {
+                        return self.force(&field.clone());
+                    }
+                }
+
+                neutral(Neutral::Project(record, label, *range))
+            }
+            Expr::Unfold(Unfold { value, range }) => match self.eval(value, env) {
+                Value::Fold(_, value, _) => self.force(&value),
+                value => neutral(Neutral::Unfold(value, *range)),
+            },
+            _ => self.eval(term, env),
+        }
+    }
+
+    // Eliminations in tail position loop instead of recursing, so that a long chain of
+    // reductions does not grow the stack.
+    fn eval<'a>(&mut self, mut term: &'a Expr, mut env: Scope<'a>) -> Value<'a> {
+        loop {
+            match term {
+                Expr::Var(var) => {
+                    return match lookup(&env, &var.value) {
+                        Some(thunk) => self.force(&thunk.clone()),
+                        None => neutral(Neutral::Var(var.clone())),
+                    }
+                }
+                Expr::App(App { lambda, argm, range }) => {
+                    let argm = self.delay(argm, &env);
+                    let lambda = self.eval(lambda, env);
+
+                    match self.unfold(lambda) {
+                        Value::Lam(abs, closure) if self.tick() => {
+                            term = &abs.body;
+                            env = closure.bind(&abs.param, Binding::Term(argm));
+                        }
+                        lambda => return neutral(Neutral::App(lambda, argm, *range)),
+                    }
+                }
+                Expr::TApp(TApp { lambda, argm, range }) => {
+                    let argm = resolve(argm, &env);
+                    let lambda = self.eval(lambda, env);
+
+                    match self.unfold(lambda) {
+                        Value::TLam(tabs, closure) if self.tick() => {
+                            term = &tabs.body;
+                            env = closure.bind(&tabs.param, Binding::Type(argm));
+                        }
+                        lambda => return neutral(Neutral::TApp(lambda, argm, *range)),
+                    }
+                }
+                Expr::Case(case) => match self.eval(&case.scrutinee, env.clone()) {
+                    Value::Inl(value, _, _) if self.tick() => {
+                        term = &case.left_body;
+                        env = env.bind(&case.left_param, Binding::Term(value));
+                    }
+                    Value::Inr(value, _, _) if self.tick() => {
+                        term = &case.right_body;
+                        env = env.bind(&case.right_param, Binding::Term(value));
+                    }
+                    scrutinee => return neutral(Neutral::Case(scrutinee, case, env)),
+                },
+                Expr::Match(matching) => {
+                    let scrutinee = self.eval(&matching.scrutinee, env.clone());
+
+                    let arm = match &scrutinee {
+                        Value::Variant(label, value, _, _) => {
+                            let arm = matching.arms.iter().find(|arm| arm.label == *label);
+                            arm.map(|arm| (arm, value.clone()))
+                        }
+                        _ => None,
+                    };
+
+                    match arm {
+                        Some((arm, value)) if self.tick() => {
+                            term = &arm.body;
+                            env = env.bind(&arm.param, Binding::Term(value));
+                        }
+                        _ => return neutral(Neutral::Match(scrutinee, matching, env)),
+                    }
+                }
+                Expr::Unpack(unpack) => match self.eval(&unpack.package, env.clone()) {
+                    Value::Pack(witness, value, _, _) if self.tick() => {
+                        term = &unpack.body;
+                        env = env.bind(&unpack.ty_param, Binding::Type(witness))
+                            .bind(&unpack.param, Binding::Term(value));
+                    }
+                    package => return neutral(Neutral::Unpack(package, unpack, env)),
+                },
+                Expr::If(cond) => match self.eval(&cond.cond, env.clone()) {
+                    Value::Bool(value, _) if self.tick() => {
+                        term = if value { &cond.then_branch } else { &cond.else_branch };
+                    }
+                    test => return neutral(Neutral::If(test, cond, env)),
+                },
+                Expr::BinOp(binop) => return self.binop(binop, &env),
+                Expr::Fst(_) | Expr::Snd(_) | Expr::Project(_) | Expr::Unfold(_) => {
+                    return self.project(term, env)
+                }
+                _ => return self.construct(term, env),
+            }
+        }
+    }
+
+    // Binds `name` to a fresh variable, which stays used until it is released.
+    fn bind_fresh<'a>(
+        &mut self,
+        env: &Scope<'a>,
+        name: &'a str,
+        range: Range,
+    ) -> (String, Scope<'a>) {
+        let fresh = fresh_name(name, &self.used);
+        let var = Var { value: fresh.clone(), range };
+
+        self.used.insert(fresh.clone());
+        (fresh, env.bind(name, Binding::Term(Thunk::ready(neutral(Neutral::Var(var))))))
+    }
+
+    fn bind_fresh_type<'a>(&mut self, env: &Scope<'a>, name: &'a str) -> (String, Scope<'a>) {
+        let fresh = fresh_name(name, &self.used);
+        let var = Type::TVar { value: fresh.clone() };
+
+        self.used.insert(fresh.clone());
+        (fresh, env.bind(name, Binding::Type(var)))
+    }
+
+    fn release(&mut self, names: &[&String]) {
+        for name in names {
+            self.used.remove(*name);
+        }
+    }
+
+    fn quote_thunk(&mut self, thunk: &Argument) -> Box<Expr> {
+        let value = self.force(thunk);
+        Box::new(self.quote(value))
+    }
+
+    fn quote_in<'a>(&mut self, term: &'a Expr, env: Scope<'a>) -> Box<Expr> {
+        let value = self.eval(term, env);
+        Box::new(self.quote(value))
+    }
+
+    fn quote(&mut self, value: Value) -> Expr {
+        match value {
+            Value::Int(value, range) => Expr::Int(Int { value, range }),
+            Value::Bool(value, range) => Expr::Bool(Bool { value, range }),
+            Value::Unit(range) => Expr::Unit(Unit { range }),
+            Value::Lam(abs, env) => {
+                let param_ty = resolve(&abs.param_ty, &env);
+                let (param, env) = self.bind_fresh(&env, &abs.param, abs.range);
+                let body = self.quote_in(&abs.body, env);
+                self.release(&[&param]);
+
+                Expr::Abs(Abs { param, param_ty, body, range: abs.range })
+            }
+            Value::TLam(tabs, env) => {
+                let (param, env) = self.bind_fresh_type(&env, &tabs.param);
+                let body = self.quote_in(&tabs.body, env);
+                self.release(&[&param]);
+
+                Expr::TAbs(TAbs { param, body, range: tabs.range })
+            }
+            Value::Fix(function, range) => {
+                Expr::Fix(Fix { value: self.quote_thunk(&function), range })
+            }
+            Value::Pair(fst, snd, range) => {
+                let (fst, snd) = (self.quote_thunk(&fst), self.quote_thunk(&snd));
+                Expr::Pair(Pair { fst, snd, range })
+            }
+            Value::Inl(value, right_ty, range) => {
+                Expr::Inl(Inl { value: self.quote_thunk(&value), right_ty, range })
+            }
+            Value::Inr(value, left_ty, range) => {
+                Expr::Inr(Inr { value: self.quote_thunk(&value), left_ty, range })
+            }
+            Value::Record(fields, range) => {
+                let fields = fields
+                    .into_iter()
+                    .map(|(label, field)| (label.to_string(), *self.quote_thunk(&field)))
+                    .collect();
+
+                Expr::Record(Record { fields, range })
+            }
+            Value::Variant(label, value, ty, range) => Expr::Variant(Variant {
+                label: label.to_string(),
+                value: self.quote_thunk(&value),
+                ty,
+                range,
+            }),
+            Value::Fold(ty, value, range) => {
+                Expr::Fold(Fold { ty, value: self.quote_thunk(&value), range })
+            }
+            Value::Pack(witness, value, ty, range) => {
+                Expr::Pack(Pack { witness, value: self.quote_thunk(&value), ty, range })
+            }
+            Value::Neutral(neutral) => self.quote_neutral(&neutral),
+        }
+    }
+
+    fn quote_neutral(&mut self, neutral: &Neutral) -> Expr {
+        match neutral {
+            Neutral::Var(var) => Expr::Var(var.clone()),
+            Neutral::App(lambda, argm, range) => Expr::App(App {
+                lambda: Box::new(self.quote(lambda.clone())),
+                argm: self.quote_thunk(argm),
+                range: *range,
+            }),
+            Neutral::TApp(lambda, argm, range) => Expr::TApp(TApp {
+                lambda: Box::new(self.quote(lambda.clone())),
+                argm: argm.clone(),
+                range: *range,
+            }),
+            Neutral::Fst(pair, range) => {
+                Expr::Fst(Fst { pair: Box::new(self.quote(pair.clone())), range: *range })
+            }
+            Neutral::Snd(pair, range) => {
+                Expr::Snd(Snd { pair: Box::new(self.quote(pair.clone())), range: *range })
+            }
+            Neutral::Project(record, label, range) => Expr::Project(Project {
+                record: Box::new(self.quote(record.clone())),
+                label: label.to_string(),
+                range: *range,
+            }),
+            Neutral::Unfold(value, range) => {
+                Expr::Unfold(Unfold { value: Box::new(self.quote(value.clone())), range: *range })
+            }
+            Neutral::Case(scrutinee, case, env) => {
+                let scrutinee = Box::new(self.quote(scrutinee.clone()));
+
+                let (left_param, left) = self.bind_fresh(env, &case.left_param, case.range);
+                let left_body = self.quote_in(&case.left_body, left);
+                self.release(&[&left_param]);
+
+                let (right_param, right) = self.bind_fresh(env, &case.right_param, case.range);
+                let right_body = self.quote_in(&case.right_body, right);
+                self.release(&[&right_param]);
+
+                Expr::Case(Case {
+                    scrutinee,
+                    left_param,
+                    left_body,
+                    right_param,
+                    right_body,
+                    range: case.range,
+                })
+            }
+            Neutral::Match(scrutinee, matching, env) => {
+                let scrutinee = Box::new(self.quote(scrutinee.clone()));
+
+                let arms = matching
+                    .arms
+                    .iter()
+                    .map(|arm| {
+                        let (param, env) = self.bind_fresh(env, &arm.param, matching.range);
+                        let body = *self.quote_in(&arm.body, env);
+                        self.release(&[&param]);
+
+                        Arm { label: arm.label.clone(), param, body }
+                    })
+                    .collect();
+
+                Expr::Match(Match { scrutinee, arms, range: matching.range })
+            }
+            Neutral::Unpack(package, unpack, env) => {
+                let package = Box::new(self.quote(package.clone()));
+
+                let (ty_param, env) = self.bind_fresh_type(env, &unpack.ty_param);
+                let (param, env) = self.bind_fresh(&env, &unpack.param, unpack.range);
+                let body = self.quote_in(&unpack.body, env);
+                self.release(&[&ty_param, &param]);
+
+                Expr::Unpack(Unpack { package, ty_param, param, body, range: unpack.range })
+            }
+            Neutral::If(test, cond, env) => {
+                let test = Box::new(self.quote(test.clone()));
+                let then_branch = self.quote_in(&cond.then_branch, env.clone());
+                let else_branch = self.quote_in(&cond.else_branch, env.clone());
+
+                Expr::If(If { cond: test, then_branch, else_branch, range: cond.range })
+            }
+            Neutral::BinOp(op, left, right, range) => Expr::BinOp(BinOp {
+                op: *op,
+                left: Box::new(self.quote(left.clone())),
+                right: Box::new(self.quote(right.clone())),
+                range: *range,
+            }),
+        }
+    }
+}
+
+/// The normal form of `expr`, after at most `limit` reductions when there is one. The
+/// redexes left when the limit is reached are read back as they are.
+pub fn normalize(expr: &Expr, limit: Option<usize>) -> Expr {
+    let mut evaluator = Evaluator { limit, used: free_variables(expr.clone()) };
+    let value = evaluator.eval(expr, Env::default());
+
+    evaluator.quote(value)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reduction::{reduce, Norm};
+    use crate::{checker, prelude};
+
+    fn eval(strategy: Norm, input: &str) -> String {
+        let expr = prelude::load(input).unwrap();
+        checker::type_of(expr.clone()).unwrap();
+        reduce(strategy, expr, None).to_string()
+    }
+
+    #[test]
+    fn test_nbe_agrees_with_normal_order() {
+        let inputs = [
+            r"λA. λx: A. (λy: A. y) x",
+            r"λb: Bool. if b then 1 + 1 else 2",
+            r"λp: Int * Bool. {snd {0, fst p}, fst p}",
+            r"λs: Int + Bool. case s of inl n => n + (1 + 1) | inr b => 0",
+            r"case (inr [Int] true) of inl n => false | inr b => b",
+            r"λv: <a: Int, b: Int>. case v of <a n> => n | <b m> => m * (2 * 3)",
+            r"let get_x: ∀ρ. {x: Int | ρ} -> Int = λρ. λr: {x: Int | ρ}. r.x in
+            get_x [{y: Int}] {x = 20, y = 22}",
+            r"unpack (pack [Int, {new = 0, get = λc: Int. c}] as ∃C. {new: C, get: C -> Int})
+            as [C, c] in c.get c.new",
+            r"λp: ∃D. {new: D, get: D -> Int}. unpack p as [D, d] in d.get d.new",
+            r"unfold (fold [μL. Unit + Int * L] inl [Int * (μL. Unit + Int * L)] ())",
+            r"let rec fib: Int -> Int = λn: Int. if n < 2 then n else fib (n - 1) + fib (n - 2) in
+            fib 10",
+            r"λN. λs: N -> N. λz: N. (mul (succ (succ zero)) (succ (succ zero))) [N] s z",
+            r"map [Int] [Int] (λx: Int. x * 10) (cons [Int] 1 (cons [Int] 2 (nil [Int])))",
+        ];
+
+        for input in inputs {
+            assert_eq!(eval(Norm::NBE, input), eval(Norm::NOR, input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_nbe_polymorphic_recursion() {
+        let input = r"
+        def rec iter: ∀A. (A -> A) -> Int -> A -> A =
+            λA. λf: A -> A. λn: Int. λx: A. if n == 0 then x else iter [A] f (n - 1) (f x)
+        main = iter [Int] (λx: Int. x + 3) 100 0
+        ";
+
+        assert_eq!(eval(Norm::NBE, input), "300")
+    }
+
+    #[test]
+    fn test_nbe_stops_without_limit() {
+        let input = r"fix (λf: Int -> Int. λn: Int. f n) 0";
+
+        assert_eq!(eval(Norm::NBE, input), "((fix λf: (Int -> Int). λn: Int. (f n)) 0)")
+    }
+}
@@ -0,0 +1,265 @@
+use super::variables::free_variables;
+use crate::checker::occurs;
+use crate::parser::parsetree::{Abs, App, Expr, Pair, TAbs, Type, Var};
+use std::fmt;
+
+/// A normal form read back through the Church encoding its type describes.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Numeral(usize),
+    Boolean(bool),
+    Pair(Box<Value>, Box<Value>),
+    List(Vec<Value>),
+    Term(Expr),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Numeral(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Pair(fst, snd) => write!(f, "({}, {})", fst, snd),
+            Value::List(values) => {
+                let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                write!(f, "[{}]", values.join(", "))
+            }
+            Value::Term(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+fn arrow(ty: &Type) -> Option<(&Type, &Type)> {
+    match ty {
+        Type::Arrow { left, right } => Some((left, right)),
+        _ => None,
+    }
+}
+
+fn is_tvar(ty: &Type, name: &str) -> bool {
+    matches!(ty, Type::TVar { value } if value == name)
+}
+
+fn is_var(expr: &Expr, name: &str) -> bool {
+    matches!(expr, Expr::Var(Var { value, .. }) if value == name)
+}
+
+fn spine(expr: &Expr) -> (&Expr, Vec<&Expr>) {
+    let mut head = expr;
+    let mut args = Vec::new();
+
+    while let Expr::App(App { lambda, argm, .. }) = head {
+        args.push(argm.as_ref());
+        head = lambda;
+    }
+
+    args.reverse();
+    (head, args)
+}
+
+// ΛR. λa. λb. body, giving back the parameters of the term abstractions.
+fn binders(expr: &Expr, count: usize) -> Option<(Vec<&str>, &Expr)> {
+    let Expr::TAbs(TAbs { body, .. }) = expr else {
+        return None;
+    };
+    let mut body = body.as_ref();
+    let mut params = Vec::new();
+
+    for _ in 0..count {
+        let Expr::Abs(Abs { param, body: inner, .. }) = body else {
+            return None;
+        };
+        params.push(param.as_str());
+        body = inner;
+    }
+
+    Some((params, body))
+}
+
+fn closed_over(expr: &Expr, names: &[&str]) -> bool {
+    let free = free_variables(expr.clone());
+    names.iter().all(|name| !free.contains(*name))
+}
+
+// ∀N. (N -> N) -> N -> N
+fn numeral(expr: &Expr, ty: &Type) -> Option<usize> {
+    let Type::Forall { param, body } = ty else {
+        return None;
+    };
+    let (succ, rest) = arrow(body)?;
+    let (succ_param, succ_result) = arrow(succ)?;
+    let (zero, result) = arrow(rest)?;
+
+    if ![succ_param, succ_result, zero, result].iter().all(|ty| is_tvar(ty, param)) {
+        return None;
+    }
+
+    let (params, mut body) = binders(expr, 2)?;
+    let mut count = 0;
+
+    loop {
+        match body {
+            Expr::Var(_) if is_var(body, params[1]) => return Some(count),
+            Expr::App(App { lambda, argm, .. }) if is_var(lambda, params[0]) => {
+                count += 1;
+                body = argm;
+            }
+            _ => return None,
+        }
+    }
+}
+
+// ∀B. B -> B -> B
+fn boolean(expr: &Expr, ty: &Type) -> Option<bool> {
+    let Type::Forall { param, body } = ty else {
+        return None;
+    };
+    let (truthy, rest) = arrow(body)?;
+    let (falsy, result) = arrow(rest)?;
+
+    if ![truthy, falsy, result].iter().all(|ty| is_tvar(ty, param)) {
+        return None;
+    }
+
+    let (params, body) = binders(expr, 2)?;
+
+    match body {
+        _ if is_var(body, params[0]) => Some(true),
+        _ if is_var(body, params[1]) => Some(false),
+        _ => None,
+    }
+}
+
+// ∀R. (A -> R -> R) -> R -> R
+fn list(expr: &Expr, ty: &Type) -> Option<Vec<Value>> {
+    let Type::Forall { param, body } = ty else {
+        return None;
+    };
+    let (cons, rest) = arrow(body)?;
+    let (elem, cons_rest) = arrow(cons)?;
+    let (cons_tail, cons_result) = arrow(cons_rest)?;
+    let (nil, result) = arrow(rest)?;
+
+    let rigid = [cons_tail, cons_result, nil, result].iter().all(|ty| is_tvar(ty, param));
+    if occurs(elem, param) || !rigid {
+        return None;
+    }
+
+    let (params, mut body) = binders(expr, 2)?;
+    let mut values = Vec::new();
+
+    while !is_var(body, params[1]) {
+        match spine(body) {
+            (head, args) if is_var(head, params[0]) && args.len() == 2 => {
+                if !closed_over(args[0], &params) {
+                    return None;
+                }
+
+                values.push(readback(args[0], elem));
+                body = args[1];
+            }
+            _ => return None,
+        }
+    }
+
+    Some(values)
+}
+
+// ∀R. (A -> B -> R) -> R
+fn pair(expr: &Expr, ty: &Type) -> Option<(Value, Value)> {
+    let Type::Forall { param, body } = ty else {
+        return None;
+    };
+    let (select, result) = arrow(body)?;
+    let (fst, select_rest) = arrow(select)?;
+    let (snd, select_result) = arrow(select_rest)?;
+
+    let rigid = [select_result, result].iter().all(|ty| is_tvar(ty, param));
+    if occurs(fst, param) || occurs(snd, param) || !rigid {
+        return None;
+    }
+
+    let (params, body) = binders(expr, 1)?;
+
+    match spine(body) {
+        (head, args) if is_var(head, params[0]) && args.len() == 2 => {
+            if !closed_over(args[0], &params) || !closed_over(args[1], &params) {
+                return None;
+            }
+
+            Some((readback(args[0], fst), readback(args[1], snd)))
+        }
+        _ => None,
+    }
+}
+
+/// Decodes the normal form `expr` of type `ty`, falling back to the term itself when the type
+/// is not one of the standard encodings or the term is not in the expected shape.
+pub fn readback(expr: &Expr, ty: &Type) -> Value {
+    if let (Expr::Pair(Pair { fst, snd, .. }), Type::Product { fst: fst_ty, snd: snd_ty }) =
+        (expr, ty)
+    {
+        Value::Pair(Box::new(readback(fst, fst_ty)), Box::new(readback(snd, snd_ty)))
+    } else if let Some(value) = numeral(expr, ty) {
+        Value::Numeral(value)
+    } else if let Some(value) = boolean(expr, ty) {
+        Value::Boolean(value)
+    } else if let Some(values) = list(expr, ty) {
+        Value::List(values)
+    } else if let Some((fst, snd)) = pair(expr, ty) {
+        Value::Pair(Box::new(fst), Box::new(snd))
+    } else {
+        Value::Term(expr.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::readback;
+    use crate::reduction::{reduce, Norm};
+    use crate::{checker, parser, prelude};
+
+    fn decode(input: &str) -> String {
+        let expr = parser::from_string(input).unwrap();
+        let ty = checker::type_of(expr.clone()).unwrap();
+        readback(&reduce(Norm::NOR, expr, None), &ty).to_string()
+    }
+
+    #[test]
+    fn test_readback_numeral() {
+        let input = r"
+        let add: (∀N. (N -> N) -> N -> N) -> (∀N. (N -> N) -> N -> N) -> ∀N. (N -> N) -> N -> N =
+            λm: ∀N. (N -> N) -> N -> N. λn: ∀N. (N -> N) -> N -> N.
+            λN. λf: N -> N. λx: N. m [N] f (n [N] f x) in
+        add (λN. λf: N -> N. λx: N. f x) (λN. λf: N -> N. λx: N. f (f x))
+        ";
+
+        assert_eq!(decode(input), "3")
+    }
+
+    #[test]
+    fn test_readback_boolean_and_numeral_pair() {
+        let input = r"{λB. λt: B. λf: B. f, λN. λf: N -> N. λx: N. x}";
+
+        assert_eq!(decode(input), "(false, 0)")
+    }
+
+    #[test]
+    fn test_readback_list_of_pairs() {
+        let input = r"
+        let pair: Int -> Bool -> ∀R. (Int -> Bool -> R) -> R =
+            λa: Int. λb: Bool. λR. λs: Int -> Bool -> R. s a b in
+        λR. λc: (∀P. (Int -> Bool -> P) -> P) -> R -> R. λn: R. c (pair 1 true) (c (pair 2 false) n)
+        ";
+
+        assert_eq!(decode(input), "[(1, true), (2, false)]")
+    }
+
+    #[test]
+    fn test_readback_prelude() {
+        let input = r"map [Int] [Int] (λx: Int. x * 10) (cons [Int] 1 (cons [Int] 2 (nil [Int])))";
+        let expr = prelude::load(input).unwrap();
+        let ty = checker::type_of(expr.clone()).unwrap();
+
+        assert_eq!(readback(&reduce(Norm::NOR, expr, None), &ty).to_string(), "[10, 20]")
+    }
+}
@@ -1,5 +1,9 @@
+use super::primitives::delta;
 use super::variables::{substitution, type_substitution};
-use crate::parser::parsetree::{Abs, App, Expr, Fst, Pair, Snd, TAbs, TApp};
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Bool, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Match, Pack, Pair,
+    Project, Record, Snd, TAbs, TApp, Unfold, Unpack, Variant,
+};
 
 macro_rules! break_limit {
     ($expr: ident, $limit: ident) => {
@@ -11,89 +15,275 @@ macro_rules! break_limit {
     };
 }
 
-pub fn normal_order(ex: Expr, limit: Option<usize>) -> Expr {
-    break_limit!(ex, limit);
-
-    match ex {
-        Expr::Int(int) => Expr::Int(int),
-        Expr::Var(var) => Expr::Var(var),
-        Expr::Pair(pair) => {
-            let fst = normal_order(*pair.fst, limit);
-            let snd = normal_order(*pair.snd, limit);
-
-            Expr::Pair(Pair {
-                fst: Box::new(fst),
-                snd: Box::new(snd),
-                ..pair
-            })
-        }
-        Expr::Fst(fst) => {
-            let pair = normal_order(*fst.pair, limit);
+// `fix (λf: T. e)` is only unfolded to `e[f := fix (λf: T. e)]` when it is
+// applied to a term or a type, otherwise reducing under binders would unroll it
+// forever.
+fn unfold(fix: Fix) -> Expr {
+    match *fix.value.clone() {
+        Expr::Abs(abs) => substitution(*abs.body, abs.param, Expr::Fix(fix)),
+        _ => Expr::Fix(fix),
+    }
+}
+
+// Reduces the head of an application spine until it is an abstraction, so that
+// every pending argument is substituted before normal_order goes under binders.
+// The reducts are taken in turn by a loop, so long reductions do not nest calls.
+fn weak_head(mut ex: Expr, mut limit: Option<usize>) -> Expr {
+    loop {
+        break_limit!(ex, limit);
 
-            match pair {
-                Expr::Pair(Pair { fst, .. }) => *fst,
-                pair => Expr::Fst(Fst { pair: Box::new(pair), ..fst }),
+        ex = match ex {
+            Expr::Abs(abs) => return Expr::Abs(abs),
+            Expr::TAbs(tabs) => return Expr::TAbs(tabs),
+            Expr::Fix(fix) => return Expr::Fix(fix),
+            Expr::App(app) => match weak_head(*app.lambda, limit) {
+                Expr::Abs(abs) => substitution(*abs.body, abs.param, *app.argm),
+                Expr::Fix(fix) => Expr::App(App { lambda: Box::new(unfold(fix)), ..app }),
+                expr => return Expr::App(App { lambda: Box::new(expr), ..app }),
+            },
+            Expr::TApp(tapp) => match weak_head(*tapp.lambda, limit) {
+                Expr::TAbs(TAbs { param, body, .. }) => {
+                    type_substitution(&body, &param, &tapp.argm)
+                }
+                Expr::Fix(fix) => Expr::TApp(TApp { lambda: Box::new(unfold(fix)), ..tapp }),
+                expr => return Expr::TApp(TApp { lambda: Box::new(expr), ..tapp }),
+            },
+            expr => return normal_order(expr, limit),
+        };
+        limit = limit.map(|l| l - 1);
+    }
+}
+
+// Like weak_head, the reducts of the terms in tail position are taken in turn by the loop.
+pub fn normal_order(mut ex: Expr, mut limit: Option<usize>) -> Expr {
+    loop {
+        break_limit!(ex, limit);
+
+        ex = match ex {
+            Expr::Int(int) => return Expr::Int(int),
+            Expr::Bool(bool) => return Expr::Bool(bool),
+            Expr::Unit(unit) => return Expr::Unit(unit),
+            Expr::Var(var) => return Expr::Var(var),
+            Expr::Pair(pair) => {
+                let fst = normal_order(*pair.fst, limit);
+                let snd = normal_order(*pair.snd, limit);
+
+                return Expr::Pair(Pair {
+                    fst: Box::new(fst),
+                    snd: Box::new(snd),
+                    ..pair
+                });
             }
-        }
-        Expr::Snd(snd) => {
-            let pair = normal_order(*snd.pair, limit);
+            Expr::Fst(fst) => {
+                let pair = normal_order(*fst.pair, limit);
 
-            match pair {
-                Expr::Pair(Pair { snd, .. }) => *snd,
-                pair => Expr::Snd(Snd { pair: Box::new(pair), ..snd }),
+                return match pair {
+                    Expr::Pair(Pair { fst, .. }) => *fst,
+                    pair => Expr::Fst(Fst { pair: Box::new(pair), ..fst }),
+                };
             }
-        }
-        Expr::Abs(abs) => {
-            let body = normal_order(*abs.body, limit);
-            Expr::Abs(Abs { body: Box::new(body), ..abs })
-        }
-        Expr::App(app) => {
-            let func_expr = normal_order(*app.lambda, limit);
+            Expr::Snd(snd) => {
+                let pair = normal_order(*snd.pair, limit);
+
+                return match pair {
+                    Expr::Pair(Pair { snd, .. }) => *snd,
+                    pair => Expr::Snd(Snd { pair: Box::new(pair), ..snd }),
+                };
+            }
+            Expr::Inl(inl) => {
+                let value = normal_order(*inl.value, limit);
+                return Expr::Inl(Inl { value: Box::new(value), ..inl });
+            }
+            Expr::Inr(inr) => {
+                let value = normal_order(*inr.value, limit);
+                return Expr::Inr(Inr { value: Box::new(value), ..inr });
+            }
+            Expr::Case(case) => {
+                let scrutinee = normal_order(*case.scrutinee, limit);
+
+                match scrutinee {
+                    Expr::Inl(Inl { value, .. }) => {
+                        substitution(*case.left_body, case.left_param, *value)
+                    }
+                    Expr::Inr(Inr { value, .. }) => {
+                        substitution(*case.right_body, case.right_param, *value)
+                    }
+                    scrutinee => {
+                        let left_body = normal_order(*case.left_body, limit);
+                        let right_body = normal_order(*case.right_body, limit);
 
-            match func_expr {
-                Expr::Abs(abs) => {
-                    let substituted = substitution(*abs.body, abs.param, *app.argm);
-                    normal_order(substituted, limit.and_then(|l| Some(l - 1)))
+                        return Expr::Case(Case {
+                            scrutinee: Box::new(scrutinee),
+                            left_body: Box::new(left_body),
+                            right_body: Box::new(right_body),
+                            ..case
+                        });
+                    }
                 }
-                expr => {
-                    let func = normal_order(expr, limit);
-                    let argm = normal_order(*app.argm, limit);
-
-                    Expr::App(App {
-                        lambda: Box::new(func),
-                        argm: Box::new(argm),
-                        ..app
-                    })
+            }
+            Expr::Record(record) => {
+                let fields = record
+                    .fields
+                    .into_iter()
+                    .map(|(label, field)| (label, normal_order(field, limit)))
+                    .collect();
+
+                return Expr::Record(Record { fields, ..record });
+            }
+            Expr::Project(project) => {
+                return match normal_order(*project.record, limit) {
+                    Expr::Record(Record { fields, .. })
+                        if fields.iter().any(|(label, _)| *label == project.label) =>
+                    {
+                        let (_, field) =
+                            fields.into_iter().find(|(l, _)| *l == project.label).unwrap();
+                        field
+                    }
+                    record => Expr::Project(Project { record: Box::new(record), ..project }),
+                };
+            }
+            Expr::Variant(variant) => {
+                let value = normal_order(*variant.value, limit);
+                return Expr::Variant(Variant { value: Box::new(value), ..variant });
+            }
+            Expr::Match(matching) => match normal_order(*matching.scrutinee, limit) {
+                Expr::Variant(Variant { label, value, .. })
+                    if matching.arms.iter().any(|arm| arm.label == label) =>
+                {
+                    let arm = matching.arms.into_iter().find(|arm| arm.label == label).unwrap();
+                    substitution(arm.body, arm.param, *value)
                 }
+                scrutinee => {
+                    let arms = matching
+                        .arms
+                        .into_iter()
+                        .map(|arm| Arm { body: normal_order(arm.body, limit), ..arm })
+                        .collect();
+
+                    return Expr::Match(Match { scrutinee: Box::new(scrutinee), arms, ..matching });
+                }
+            },
+            Expr::Fold(fold) => {
+                let value = normal_order(*fold.value, limit);
+                return Expr::Fold(Fold { value: Box::new(value), ..fold });
             }
-        }
-        Expr::TAbs(tabs) => {
-            let body = normal_order(*tabs.body, limit);
-
-            Expr::TAbs(TAbs {
-                param: tabs.param,
-                body: Box::new(body),
-                range: tabs.range,
-            })
-        }
-        Expr::TApp(tapp) => {
-            let func_expr = normal_order(*tapp.lambda, limit);
+            Expr::Fix(fix) => {
+                let value = normal_order(*fix.value, limit);
+                return Expr::Fix(Fix { value: Box::new(value), ..fix });
+            }
+            Expr::Pack(pack) => {
+                let value = normal_order(*pack.value, limit);
+                return Expr::Pack(Pack { value: Box::new(value), ..pack });
+            }
+            Expr::Unpack(unpack) => match normal_order(*unpack.package, limit) {
+                Expr::Pack(Pack { witness, value, .. }) => {
+                    let body = type_substitution(&unpack.body, &unpack.ty_param, &witness);
+                    substitution(body, unpack.param, *value)
+                }
+                package => {
+                    let body = normal_order(*unpack.body, limit);
 
-            match func_expr {
-                Expr::TAbs(TAbs { param, body, .. }) => {
-                    let substituted = type_substitution(&body, &param, &tapp.argm);
-                    normal_order(substituted, limit.and_then(|l| Some(l - 1)))
+                    return Expr::Unpack(Unpack {
+                        package: Box::new(package),
+                        body: Box::new(body),
+                        ..unpack
+                    });
                 }
-                expr => {
-                    let func = normal_order(expr, limit);
-
-                    Expr::TApp(TApp {
-                        lambda: Box::new(func),
-                        argm: tapp.argm,
-                        range: tapp.range,
-                    })
+            },
+            Expr::Unfold(unfold) => {
+                return match normal_order(*unfold.value, limit) {
+                    Expr::Fold(Fold { value, .. }) => *value,
+                    value => Expr::Unfold(Unfold { value: Box::new(value), ..unfold }),
+                };
+            }
+            Expr::Abs(abs) => {
+                let body = normal_order(*abs.body, limit);
+                return Expr::Abs(Abs { body: Box::new(body), ..abs });
+            }
+            Expr::App(app) => {
+                let func_expr = weak_head(*app.lambda, limit);
+
+                match func_expr {
+                    Expr::Abs(abs) => substitution(*abs.body, abs.param, *app.argm),
+                    Expr::Fix(fix) => match unfold(fix) {
+                        Expr::Abs(abs) => substitution(*abs.body, abs.param, *app.argm),
+                        expr => Expr::App(App { lambda: Box::new(expr), ..app }),
+                    },
+                    expr => {
+                        let func = normal_order(expr, limit);
+                        let argm = normal_order(*app.argm, limit);
+
+                        return Expr::App(App {
+                            lambda: Box::new(func),
+                            argm: Box::new(argm),
+                            ..app
+                        });
+                    }
                 }
             }
-        }
+            Expr::TAbs(tabs) => {
+                let body = normal_order(*tabs.body, limit);
+
+                return Expr::TAbs(TAbs {
+                    param: tabs.param,
+                    body: Box::new(body),
+                    range: tabs.range,
+                });
+            }
+            Expr::TApp(tapp) => {
+                let func_expr = weak_head(*tapp.lambda, limit);
+
+                match func_expr {
+                    Expr::TAbs(TAbs { param, body, .. }) => {
+                        type_substitution(&body, &param, &tapp.argm)
+                    }
+                    Expr::Fix(fix) => match unfold(fix) {
+                        Expr::TAbs(TAbs { param, body, .. }) => {
+                            type_substitution(&body, &param, &tapp.argm)
+                        }
+                        expr => Expr::TApp(TApp { lambda: Box::new(expr), ..tapp }),
+                    },
+                    expr => {
+                        let func = normal_order(expr, limit);
+
+                        return Expr::TApp(TApp {
+                            lambda: Box::new(func),
+                            argm: tapp.argm,
+                            range: tapp.range,
+                        });
+                    }
+                }
+            }
+            Expr::If(cond) => {
+                let test = normal_order(*cond.cond, limit);
+
+                match test {
+                    Expr::Bool(Bool { value: true, .. }) => *cond.then_branch,
+                    Expr::Bool(Bool { value: false, .. }) => *cond.else_branch,
+                    test => {
+                        let then_branch = normal_order(*cond.then_branch, limit);
+                        let else_branch = normal_order(*cond.else_branch, limit);
+
+                        return Expr::If(If {
+                            cond: Box::new(test),
+                            then_branch: Box::new(then_branch),
+                            else_branch: Box::new(else_branch),
+                            ..cond
+                        });
+                    }
+                }
+            }
+            Expr::BinOp(binop) => {
+                let left = normal_order(*binop.left, limit);
+                let right = normal_order(*binop.right, limit);
+
+                return delta(BinOp {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    ..binop
+                });
+            }
+        };
+        limit = limit.map(|l| l - 1);
     }
 }
@@ -1,4 +1,8 @@
-use crate::parser::parsetree::{Abs, App, Expr, Fst, Int, Pair, Snd, TAbs, TApp, Type, Var};
+use crate::checker::extend_row;
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Bool, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Int, Match, Pack, Pair,
+    Project, Record, Snd, TAbs, TApp, Type, Unfold, Unit, Unpack, Var, Variant,
+};
 use std::collections::HashSet;
 
 pub fn free_variables(expr: Expr) -> HashSet<String> {
@@ -6,6 +10,8 @@ pub fn free_variables(expr: Expr) -> HashSet<String> {
 
     match expr {
         Expr::Int(Int { .. }) => (),
+        Expr::Bool(Bool { .. }) => (),
+        Expr::Unit(Unit { .. }) => (),
         Expr::Var(Var { value, .. }) => {
             free.insert(value);
         }
@@ -19,6 +25,46 @@ pub fn free_variables(expr: Expr) -> HashSet<String> {
         Expr::Snd(Snd { pair, .. }) => {
             free.extend(free_variables(*pair));
         }
+        Expr::Inl(Inl { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Inr(Inr { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+            let mut left = free_variables(*left_body);
+            left.remove(&left_param);
+
+            let mut right = free_variables(*right_body);
+            right.remove(&right_param);
+
+            free.extend(free_variables(*scrutinee));
+            free.extend(left);
+            free.extend(right);
+        }
+        Expr::Record(Record { fields, .. }) => {
+            for (_, field) in fields {
+                free.extend(free_variables(field));
+            }
+        }
+        Expr::Project(Project { record, .. }) => free.extend(free_variables(*record)),
+        Expr::Fold(Fold { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Unfold(Unfold { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Fix(Fix { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Pack(Pack { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Unpack(Unpack { package, param, body, .. }) => {
+            let mut inner = free_variables(*body);
+            inner.remove(&param);
+
+            free.extend(free_variables(*package));
+            free.extend(inner);
+        }
+        Expr::Variant(Variant { value, .. }) => free.extend(free_variables(*value)),
+        Expr::Match(Match { scrutinee, arms, .. }) => {
+            free.extend(free_variables(*scrutinee));
+
+            for Arm { param, body, .. } in arms {
+                let mut body = free_variables(body);
+                body.remove(&param);
+                free.extend(body);
+            }
+        }
         Expr::App(App { lambda, argm, .. }) => {
             free.extend(free_variables(*lambda));
             free.extend(free_variables(*argm));
@@ -29,6 +75,15 @@ pub fn free_variables(expr: Expr) -> HashSet<String> {
         }
         Expr::TAbs(TAbs { body, .. }) => free.extend(free_variables(*body)),
         Expr::TApp(TApp { lambda, .. }) => free.extend(free_variables(*lambda)),
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            free.extend(free_variables(*cond));
+            free.extend(free_variables(*then_branch));
+            free.extend(free_variables(*else_branch));
+        }
+        Expr::BinOp(BinOp { left, right, .. }) => {
+            free.extend(free_variables(*left));
+            free.extend(free_variables(*right));
+        }
     }
 
     free
@@ -39,6 +94,8 @@ pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
         Expr::Var(Var { value, .. }) if value == from => to,
         Expr::Var(Var { .. }) => expr,
         Expr::Int(Int { .. }) => expr,
+        Expr::Bool(Bool { .. }) => expr,
+        Expr::Unit(Unit { .. }) => expr,
         Expr::Pair(pair) => {
             let fst = substitution(*pair.fst, from.clone(), to.clone());
             let snd = substitution(*pair.snd, from.clone(), to.clone());
@@ -53,6 +110,105 @@ pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
             let pair = substitution(*snd.pair, from, to);
             Expr::Snd(Snd { pair: Box::new(pair), ..snd })
         }
+        Expr::Inl(inl) => {
+            let value = substitution(*inl.value, from, to);
+            Expr::Inl(Inl { value: Box::new(value), ..inl })
+        }
+        Expr::Inr(inr) => {
+            let value = substitution(*inr.value, from, to);
+            Expr::Inr(Inr { value: Box::new(value), ..inr })
+        }
+        Expr::Case(case) => {
+            let free = free_variables(to.clone());
+            let scrutinee = substitution(*case.scrutinee, from.clone(), to.clone());
+
+            let left_body = if from != case.left_param && !free.contains(&case.left_param) {
+                substitution(*case.left_body, from.clone(), to.clone())
+            } else {
+                *case.left_body
+            };
+
+            let right_body = if from != case.right_param && !free.contains(&case.right_param) {
+                substitution(*case.right_body, from, to)
+            } else {
+                *case.right_body
+            };
+
+            Expr::Case(Case {
+                scrutinee: Box::new(scrutinee),
+                left_body: Box::new(left_body),
+                right_body: Box::new(right_body),
+                ..case
+            })
+        }
+        Expr::Record(record) => {
+            let fields = record
+                .fields
+                .into_iter()
+                .map(|(label, field)| (label, substitution(field, from.clone(), to.clone())))
+                .collect();
+
+            Expr::Record(Record { fields, ..record })
+        }
+        Expr::Project(project) => {
+            let record = substitution(*project.record, from, to);
+            Expr::Project(Project { record: Box::new(record), ..project })
+        }
+        Expr::Fold(fold) => {
+            let value = substitution(*fold.value, from, to);
+            Expr::Fold(Fold { value: Box::new(value), ..fold })
+        }
+        Expr::Unfold(unfold) => {
+            let value = substitution(*unfold.value, from, to);
+            Expr::Unfold(Unfold { value: Box::new(value), ..unfold })
+        }
+        Expr::Fix(fix) => {
+            let value = substitution(*fix.value, from, to);
+            Expr::Fix(Fix { value: Box::new(value), ..fix })
+        }
+        Expr::Pack(pack) => {
+            let value = substitution(*pack.value, from, to);
+            Expr::Pack(Pack { value: Box::new(value), ..pack })
+        }
+        Expr::Unpack(unpack) => {
+            let free = free_variables(to.clone());
+            let package = substitution(*unpack.package, from.clone(), to.clone());
+
+            let body = if from != unpack.param && !free.contains(&unpack.param) {
+                substitution(*unpack.body, from, to)
+            } else {
+                *unpack.body
+            };
+
+            Expr::Unpack(Unpack {
+                package: Box::new(package),
+                body: Box::new(body),
+                ..unpack
+            })
+        }
+        Expr::Variant(variant) => {
+            let value = substitution(*variant.value, from, to);
+            Expr::Variant(Variant { value: Box::new(value), ..variant })
+        }
+        Expr::Match(matching) => {
+            let free = free_variables(to.clone());
+            let scrutinee = substitution(*matching.scrutinee, from.clone(), to.clone());
+
+            let arms = matching
+                .arms
+                .into_iter()
+                .map(|arm| {
+                    if from != arm.param && !free.contains(&arm.param) {
+                        let body = substitution(arm.body, from.clone(), to.clone());
+                        Arm { body, ..arm }
+                    } else {
+                        arm
+                    }
+                })
+                .collect();
+
+            Expr::Match(Match { scrutinee: Box::new(scrutinee), arms, ..matching })
+        }
         Expr::App(app) => {
             let lambda = substitution(*app.lambda, from.clone(), to.clone());
             let argm = substitution(*app.argm, from, to);
@@ -82,6 +238,28 @@ pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
             let lambda = substitution(*lambda, from, to);
             Expr::TApp(TApp { lambda: Box::new(lambda), argm, range })
         }
+        Expr::If(cond) => {
+            let test = substitution(*cond.cond, from.clone(), to.clone());
+            let then_branch = substitution(*cond.then_branch, from.clone(), to.clone());
+            let else_branch = substitution(*cond.else_branch, from, to);
+
+            Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond
+            })
+        }
+        Expr::BinOp(binop) => {
+            let left = substitution(*binop.left, from.clone(), to.clone());
+            let right = substitution(*binop.right, from, to);
+
+            Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop
+            })
+        }
     }
 }
 
@@ -101,20 +279,156 @@ pub fn type_substitution(expr: &Expr, from: &str, to: &Type) -> Expr {
             let param_ty = type_type_substitute(&abs.param_ty, from, to);
             let body = type_substitution(&abs.body, from, to);
 
-            return Expr::Abs(Abs {
-                param_ty: param_ty,
+            Expr::Abs(Abs {
+                param_ty,
                 body: Box::new(body),
                 ..abs.clone()
-            });
+            })
         }
         Expr::TAbs(tabs) if tabs.param == from => expr.clone(),
         Expr::TAbs(TAbs { param, body, range }) => {
-            let body = type_substitution(&body, from, to);
+            let body = type_substitution(body, from, to);
 
             Expr::TAbs(TAbs {
                 param: param.clone(),
                 body: Box::new(body),
-                range: range.clone(),
+                range: *range,
+            })
+        }
+        Expr::Inl(inl) => {
+            let value = type_substitution(&inl.value, from, to);
+            let right_ty = type_type_substitute(&inl.right_ty, from, to);
+
+            Expr::Inl(Inl {
+                value: Box::new(value),
+                right_ty,
+                ..inl.clone()
+            })
+        }
+        Expr::Inr(inr) => {
+            let value = type_substitution(&inr.value, from, to);
+            let left_ty = type_type_substitute(&inr.left_ty, from, to);
+
+            Expr::Inr(Inr {
+                value: Box::new(value),
+                left_ty,
+                ..inr.clone()
+            })
+        }
+        Expr::Case(case) => {
+            let scrutinee = type_substitution(&case.scrutinee, from, to);
+            let left_body = type_substitution(&case.left_body, from, to);
+            let right_body = type_substitution(&case.right_body, from, to);
+
+            Expr::Case(Case {
+                scrutinee: Box::new(scrutinee),
+                left_body: Box::new(left_body),
+                right_body: Box::new(right_body),
+                ..case.clone()
+            })
+        }
+        Expr::Record(record) => {
+            let fields = record
+                .fields
+                .iter()
+                .map(|(label, field)| (label.clone(), type_substitution(field, from, to)))
+                .collect();
+
+            Expr::Record(Record { fields, ..record.clone() })
+        }
+        Expr::Project(project) => {
+            let record = type_substitution(&project.record, from, to);
+            Expr::Project(Project { record: Box::new(record), ..project.clone() })
+        }
+        Expr::Fold(fold) => {
+            let ty = type_type_substitute(&fold.ty, from, to);
+            let value = type_substitution(&fold.value, from, to);
+
+            Expr::Fold(Fold {
+                ty,
+                value: Box::new(value),
+                ..fold.clone()
+            })
+        }
+        Expr::Unfold(unfold) => {
+            let value = type_substitution(&unfold.value, from, to);
+            Expr::Unfold(Unfold { value: Box::new(value), ..unfold.clone() })
+        }
+        Expr::Fix(fix) => {
+            let value = type_substitution(&fix.value, from, to);
+            Expr::Fix(Fix { value: Box::new(value), ..fix.clone() })
+        }
+        Expr::Pack(pack) => {
+            let witness = type_type_substitute(&pack.witness, from, to);
+            let value = type_substitution(&pack.value, from, to);
+            let ty = type_type_substitute(&pack.ty, from, to);
+
+            Expr::Pack(Pack {
+                witness,
+                value: Box::new(value),
+                ty,
+                ..pack.clone()
+            })
+        }
+        Expr::Unpack(unpack) => {
+            let package = type_substitution(&unpack.package, from, to);
+
+            let body = if unpack.ty_param != from {
+                type_substitution(&unpack.body, from, to)
+            } else {
+                *unpack.body.clone()
+            };
+
+            Expr::Unpack(Unpack {
+                package: Box::new(package),
+                body: Box::new(body),
+                ..unpack.clone()
+            })
+        }
+        Expr::Variant(variant) => {
+            let value = type_substitution(&variant.value, from, to);
+            let ty = type_type_substitute(&variant.ty, from, to);
+
+            Expr::Variant(Variant {
+                value: Box::new(value),
+                ty,
+                ..variant.clone()
+            })
+        }
+        Expr::Match(matching) => {
+            let scrutinee = type_substitution(&matching.scrutinee, from, to);
+            let arms = matching
+                .arms
+                .iter()
+                .map(|arm| Arm { body: type_substitution(&arm.body, from, to), ..arm.clone() })
+                .collect();
+
+            Expr::Match(Match {
+                scrutinee: Box::new(scrutinee),
+                arms,
+                ..matching.clone()
+            })
+        }
+        Expr::If(cond) => {
+            let test = type_substitution(&cond.cond, from, to);
+            let then_branch = type_substitution(&cond.then_branch, from, to);
+            let else_branch = type_substitution(&cond.else_branch, from, to);
+
+            Expr::If(If {
+                cond: Box::new(test),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+                ..cond.clone()
+            })
+        }
+        Expr::BinOp(binop) => {
+            let left = type_substitution(&binop.left, from, to);
+            let right = type_substitution(&binop.right, from, to);
+
+            Expr::BinOp(BinOp {
+                left: Box::new(left),
+                right: Box::new(right),
+                ..binop.clone()
             })
         }
         _ => expr.clone(),
@@ -134,6 +448,40 @@ pub fn type_type_substitute(ty: &Type, from: &str, to: &Type) -> Type {
             param: param.clone(),
             body: Box::new(type_type_substitute(body, from, to)),
         },
+        Type::Exists { param, .. } if param == from => ty.clone(),
+        Type::Exists { param, body } => Type::Exists {
+            param: param.clone(),
+            body: Box::new(type_type_substitute(body, from, to)),
+        },
+        Type::Rec { param, .. } if param == from => ty.clone(),
+        Type::Rec { param, body } => Type::Rec {
+            param: param.clone(),
+            body: Box::new(type_type_substitute(body, from, to)),
+        },
+        Type::Product { fst, snd } => Type::Product {
+            fst: Box::new(type_type_substitute(fst, from, to)),
+            snd: Box::new(type_type_substitute(snd, from, to)),
+        },
+        Type::Sum { left, right } => Type::Sum {
+            left: Box::new(type_type_substitute(left, from, to)),
+            right: Box::new(type_type_substitute(right, from, to)),
+        },
+        Type::Record { fields, rest } => {
+            let fields = fields
+                .iter()
+                .map(|(l, ty)| (l.clone(), type_type_substitute(ty, from, to)));
+            let (fields, rest) = extend_row(fields.collect(), rest, from, to);
+
+            Type::Record { fields, rest }
+        }
+        Type::Variant { cases, rest } => {
+            let cases = cases
+                .iter()
+                .map(|(l, ty)| (l.clone(), type_type_substitute(ty, from, to)));
+            let (cases, rest) = extend_row(cases.collect(), rest, from, to);
+
+            Type::Variant { cases, rest }
+        }
         _ => ty.clone(),
     }
 }
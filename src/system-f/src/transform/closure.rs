@@ -0,0 +1,543 @@
+//! Typed closure conversion, which makes every abstraction closed so that its code can be
+//! compiled apart from the values it captures. A closure packs its code with the record of the
+//! variables it captures, whose type the existential hides, so that functions capturing
+//! different variables still have the same type: a function type `A -> B` becomes
+//! `∃E. {code: E -> ⟦A⟧ -> ⟦B⟧, env: E}` and an application unpacks the closure to call its code
+//! on its environment. The code `λenv: E. λx: ⟦A⟧. e` is closed in term variables, the
+//! variables of types staying bound by the type abstractions around it.
+//!
+//! The fixed point of a function type is a closure of the function it is the fixed point of,
+//! whose code is a recursive function building the same closure again before every call.
+//!
+//! A `let` is an abstraction applied at once, which needs no closure: it stays as it is, so that
+//! long chains of them like the prelude are still walked in a loop by the passes that follow.
+
+use super::{abs, app, arrow, var, Fresh};
+use crate::checker::{
+    alpha_conversion_expr, error::TypeError, infer_type, let_chain, operator_type, substitution,
+    unroll, Context,
+};
+use crate::parser::location::Range;
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Match, Pack, Pair, Project,
+    Record, Snd, TAbs, TApp, Type, Unfold, Unpack, Var, Variant,
+};
+use crate::reduction::variables::free_variables;
+use std::collections::HashSet;
+
+// The environment of the code being converted: the name of its parameter and the variables
+// it holds.
+#[derive(Default)]
+struct Scope {
+    env: String,
+    captured: HashSet<String>,
+}
+
+fn project(record: Expr, label: &str, range: Range) -> Expr {
+    Expr::Project(Project { record: Box::new(record), label: label.to_string(), range })
+}
+
+fn record(fields: Vec<(String, Expr)>, range: Range) -> Expr {
+    Expr::Record(Record { fields, range })
+}
+
+struct Closure {
+    fresh: Fresh,
+    // The types of all the variables of the term, which are renamed apart.
+    context: Context,
+    // The variable of the existential types of closures.
+    env_ty: String,
+}
+
+impl Closure {
+    fn translate(&self, ty: &Type) -> Type {
+        let translate = |ty: &Type| Box::new(self.translate(ty));
+        let row = |fields: &Vec<(String, Type)>| {
+            fields.iter().map(|(label, ty)| (label.clone(), self.translate(ty))).collect()
+        };
+
+        match ty {
+            Type::TInt | Type::TBool | Type::TUnit | Type::TVar { .. } => ty.clone(),
+            Type::Arrow { left, right } => {
+                let code = arrow(self.env(), arrow(self.translate(left), self.translate(right)));
+                self.closure(code)
+            }
+            Type::Forall { param, body } => {
+                Type::Forall { param: param.clone(), body: translate(body) }
+            }
+            Type::Exists { param, body } => {
+                Type::Exists { param: param.clone(), body: translate(body) }
+            }
+            Type::Rec { param, body } => Type::Rec { param: param.clone(), body: translate(body) },
+            Type::Product { fst, snd } => {
+                Type::Product { fst: translate(fst), snd: translate(snd) }
+            }
+            Type::Sum { left, right } => {
+                Type::Sum { left: translate(left), right: translate(right) }
+            }
+            Type::Record { fields, rest } => {
+                Type::Record { fields: row(fields), rest: rest.clone() }
+            }
+            Type::Variant { cases, rest } => {
+                Type::Variant { cases: row(cases), rest: rest.clone() }
+            }
+        }
+    }
+
+    fn env(&self) -> Type {
+        Type::TVar { value: self.env_ty.clone() }
+    }
+
+    // The type of the closures of a code of type `code`.
+    fn closure(&self, code: Type) -> Type {
+        let fields = vec![("code".to_string(), code), ("env".to_string(), self.env())];
+        let body = Type::Record { fields, rest: None };
+
+        Type::Exists { param: self.env_ty.clone(), body: Box::new(body) }
+    }
+
+    // A closure of a code on an environment of type `env_ty`.
+    fn pack(&self, env_ty: Type, code: Expr, env: Expr, ty: &Type, range: Range) -> Expr {
+        let value = record(vec![("code".to_string(), code), ("env".to_string(), env)], range);
+
+        Expr::Pack(Pack { witness: env_ty, value: Box::new(value), ty: self.translate(ty), range })
+    }
+
+    // Calls a closure on an argument.
+    fn call(&mut self, closure: Expr, argm: Expr, range: Range) -> Expr {
+        self.open(closure, range, |function| app(function, argm, range))
+    }
+
+    // Unpacks a closure for `body`, which is given its code applied to its environment.
+    fn open(&mut self, closure: Expr, range: Range, body: impl FnOnce(Expr) -> Expr) -> Expr {
+        let (ty_param, param) = (self.fresh.name("E"), self.fresh.name("c"));
+
+        let code = project(var(&param, range), "code", range);
+        let env = project(var(&param, range), "env", range);
+        let body = body(app(code, env, range));
+
+        Expr::Unpack(Unpack {
+            package: Box::new(closure),
+            ty_param,
+            param,
+            body: Box::new(body),
+            range,
+        })
+    }
+
+    // The type of a variable of the term, which the checker recorded.
+    fn type_of(&self, name: &str) -> Result<Type, TypeError> {
+        match self.context.types.get(name) {
+            Some(ty) => Ok(ty.clone()),
+            None => Err(TypeError::UndefinedVariable(name.to_string())),
+        }
+    }
+
+    fn boxed(&mut self, scope: &Scope, expr: &Expr) -> Result<(Box<Expr>, Type), TypeError> {
+        let (expr, ty) = self.convert(scope, expr)?;
+        Ok((Box::new(expr), ty))
+    }
+
+    // Converts a term and gives its type, built like the checker does from the types of the
+    // parts, so that no subterm is checked again.
+    fn convert(&mut self, scope: &Scope, expr: &Expr) -> Result<(Expr, Type), TypeError> {
+        let range = expr.range();
+
+        Ok(match expr {
+            Expr::Int(_) => (expr.clone(), Type::TInt),
+            Expr::Bool(_) => (expr.clone(), Type::TBool),
+            Expr::Unit(_) => (expr.clone(), Type::TUnit),
+            Expr::Var(Var { value, .. }) if scope.captured.contains(value) => {
+                (project(var(&scope.env, range), value, range), self.type_of(value)?)
+            }
+            Expr::Var(Var { value, .. }) => (expr.clone(), self.type_of(value)?),
+            Expr::Abs(Abs { param, param_ty, body, .. }) => {
+                let mut free: Vec<String> = free_variables(expr.clone()).into_iter().collect();
+                free.sort();
+
+                let mut fields = Vec::new();
+                let mut values = Vec::new();
+                for name in &free {
+                    let ty = self.type_of(name)?;
+                    fields.push((name.clone(), self.translate(&ty)));
+                    values.push((name.clone(), self.convert(scope, &var(name, range))?.0));
+                }
+                let env_ty = Type::Record { fields, rest: None };
+
+                let env = self.fresh.name("env");
+                let inner = Scope { env, captured: free.into_iter().collect() };
+                let (body, body_ty) = self.convert(&inner, body)?;
+                let code = abs(param, self.translate(param_ty), body, range);
+                let code = abs(&inner.env, env_ty.clone(), code, range);
+
+                let ty = arrow(param_ty.clone(), body_ty);
+                (self.pack(env_ty, code, record(values, range), &ty, range), ty)
+            }
+            Expr::App(App { lambda, .. }) if matches!(**lambda, Expr::Abs(_)) => {
+                self.lets(scope, expr)?
+            }
+            Expr::App(App { lambda, argm, .. }) => {
+                let (lambda, lambda_ty) = self.convert(scope, lambda)?;
+                let (argm, _) = self.convert(scope, argm)?;
+
+                match lambda_ty {
+                    Type::Arrow { right, .. } => (self.call(lambda, argm, range), *right),
+                    ty => return Err(TypeError::UnexpectedType(ty)),
+                }
+            }
+            Expr::Fix(Fix { value, .. }) => {
+                let (value, ty) = match self.convert(scope, value)? {
+                    (value, Type::Arrow { left, .. }) => (value, *left),
+                    (_, ty) => return Err(TypeError::UnexpectedType(ty)),
+                };
+
+                match ty {
+                    Type::Arrow { .. } => (self.fix(value, &ty, range), ty),
+                    // The fixed point of any other type is no closure, so it is taken of the
+                    // function the closure of `value` stands for, its code on its environment.
+                    ty => {
+                        let fixed = self.open(value, range, |function| {
+                            Expr::Fix(Fix { value: Box::new(function), range })
+                        });
+
+                        (fixed, ty)
+                    }
+                }
+            }
+            Expr::TAbs(TAbs { param, body, .. }) => {
+                let (body, body_ty) = self.boxed(scope, body)?;
+                let ty = Type::Forall { param: param.clone(), body: Box::new(body_ty) };
+
+                (Expr::TAbs(TAbs { param: param.clone(), body, range }), ty)
+            }
+            Expr::TApp(TApp { lambda, argm, .. }) => {
+                let (lambda, ty) = match self.boxed(scope, lambda)? {
+                    (lambda, Type::Forall { param, body }) => {
+                        (lambda, substitution(&body, &param, argm))
+                    }
+                    (_, ty) => return Err(TypeError::UnexpectedType(ty)),
+                };
+
+                (Expr::TApp(TApp { lambda, argm: self.translate(argm), range }), ty)
+            }
+            Expr::Pair(Pair { fst, snd, .. }) => {
+                let (fst, fst_ty) = self.boxed(scope, fst)?;
+                let (snd, snd_ty) = self.boxed(scope, snd)?;
+                let ty = Type::Product { fst: Box::new(fst_ty), snd: Box::new(snd_ty) };
+
+                (Expr::Pair(Pair { fst, snd, range }), ty)
+            }
+            Expr::Fst(Fst { pair, .. }) => match self.boxed(scope, pair)? {
+                (pair, Type::Product { fst, .. }) => (Expr::Fst(Fst { pair, range }), *fst),
+                (_, ty) => return Err(TypeError::UnexpectedType(ty)),
+            },
+            Expr::Snd(Snd { pair, .. }) => match self.boxed(scope, pair)? {
+                (pair, Type::Product { snd, .. }) => (Expr::Snd(Snd { pair, range }), *snd),
+                (_, ty) => return Err(TypeError::UnexpectedType(ty)),
+            },
+            Expr::Inl(Inl { value, right_ty, .. }) => {
+                let (value, left) = self.boxed(scope, value)?;
+                let ty = Type::Sum { left: Box::new(left), right: Box::new(right_ty.clone()) };
+
+                (Expr::Inl(Inl { value, right_ty: self.translate(right_ty), range }), ty)
+            }
+            Expr::Inr(Inr { value, left_ty, .. }) => {
+                let (value, right) = self.boxed(scope, value)?;
+                let ty = Type::Sum { left: Box::new(left_ty.clone()), right: Box::new(right) };
+
+                (Expr::Inr(Inr { value, left_ty: self.translate(left_ty), range }), ty)
+            }
+            Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+                let (scrutinee, _) = self.boxed(scope, scrutinee)?;
+                let (left_body, ty) = self.boxed(scope, left_body)?;
+                let (right_body, _) = self.boxed(scope, right_body)?;
+                let case = Case {
+                    scrutinee,
+                    left_param: left_param.clone(),
+                    left_body,
+                    right_param: right_param.clone(),
+                    right_body,
+                    range,
+                };
+
+                (Expr::Case(case), ty)
+            }
+            Expr::Record(Record { fields, .. }) => {
+                let mut values = Vec::new();
+                let mut types = Vec::new();
+                for (label, field) in fields {
+                    let (field, ty) = self.convert(scope, field)?;
+                    values.push((label.clone(), field));
+                    types.push((label.clone(), ty));
+                }
+
+                (record(values, range), Type::Record { fields: types, rest: None })
+            }
+            Expr::Project(Project { record, label, .. }) => {
+                let (record, record_ty) = self.convert(scope, record)?;
+                let ty = match record_ty {
+                    Type::Record { fields, .. } => fields
+                        .into_iter()
+                        .find(|(other, _)| other == label)
+                        .map(|(_, ty)| ty)
+                        .ok_or_else(|| TypeError::UndefinedLabel(label.clone()))?,
+                    ty => return Err(TypeError::UnexpectedType(ty)),
+                };
+
+                (project(record, label, range), ty)
+            }
+            Expr::Variant(Variant { label, value, ty, .. }) => {
+                let (value, _) = self.boxed(scope, value)?;
+                let (label, translated) = (label.clone(), self.translate(ty));
+
+                (Expr::Variant(Variant { label, value, ty: translated, range }), ty.clone())
+            }
+            Expr::Match(Match { scrutinee, arms, .. }) => {
+                let (scrutinee, scrutinee_ty) = self.boxed(scope, scrutinee)?;
+                let mut ty = None;
+                let arms = arms
+                    .iter()
+                    .map(|Arm { label, param, body }| {
+                        let (body, body_ty) = self.convert(scope, body)?;
+                        ty.get_or_insert(body_ty);
+                        Ok(Arm { label: label.clone(), param: param.clone(), body })
+                    })
+                    .collect::<Result<_, TypeError>>()?;
+                let ty = ty.ok_or(TypeError::UnexpectedType(scrutinee_ty))?;
+
+                (Expr::Match(Match { scrutinee, arms, range }), ty)
+            }
+            Expr::Fold(Fold { ty, value, .. }) => {
+                let (value, _) = self.boxed(scope, value)?;
+                (Expr::Fold(Fold { ty: self.translate(ty), value, range }), ty.clone())
+            }
+            Expr::Unfold(Unfold { value, .. }) => {
+                let (value, value_ty) = self.boxed(scope, value)?;
+                let ty = unroll(&value_ty).ok_or(TypeError::UnexpectedType(value_ty))?;
+
+                (Expr::Unfold(Unfold { value, range }), ty)
+            }
+            Expr::Pack(Pack { witness, value, ty, .. }) => {
+                let (value, _) = self.boxed(scope, value)?;
+                let (witness, translated) = (self.translate(witness), self.translate(ty));
+
+                (Expr::Pack(Pack { witness, value, ty: translated, range }), ty.clone())
+            }
+            Expr::Unpack(Unpack { package, ty_param, param, body, .. }) => {
+                let (package, _) = self.boxed(scope, package)?;
+                let (body, ty) = self.boxed(scope, body)?;
+                let unpack = Unpack {
+                    package,
+                    ty_param: ty_param.clone(),
+                    param: param.clone(),
+                    body,
+                    range,
+                };
+
+                (Expr::Unpack(unpack), ty)
+            }
+            Expr::If(If { cond, then_branch, else_branch, .. }) => {
+                let (cond, _) = self.boxed(scope, cond)?;
+                let (then_branch, ty) = self.boxed(scope, then_branch)?;
+                let (else_branch, _) = self.boxed(scope, else_branch)?;
+
+                (Expr::If(If { cond, then_branch, else_branch, range }), ty)
+            }
+            Expr::BinOp(BinOp { op, left, right, .. }) => {
+                let (left, _) = self.boxed(scope, left)?;
+                let (right, _) = self.boxed(scope, right)?;
+
+                (Expr::BinOp(BinOp { op: *op, left, right, range }), operator_type(*op))
+            }
+        })
+    }
+
+    // A chain of `let`s keeps its abstractions, which are applied at once and so need no
+    // closure, and is walked in a loop like the checker does.
+    fn lets(&mut self, scope: &Scope, expr: &Expr) -> Result<(Expr, Type), TypeError> {
+        let (lets, body) = let_chain(expr);
+
+        let mut values = Vec::new();
+        for (App { argm, .. }, _) in &lets {
+            values.push(self.convert(scope, argm)?.0);
+        }
+        let (body, ty) = self.convert(scope, body)?;
+
+        let lets = lets.into_iter().zip(values).rev();
+        let body = lets.fold(body, |body, ((binding, lambda), value)| {
+            let lambda = abs(&lambda.param, self.translate(&lambda.param_ty), body, lambda.range);
+            app(lambda, value, binding.range)
+        });
+
+        Ok((body, ty))
+    }
+
+    // The fixed point of the converted `value`, of the function type `ty`: the closure of the
+    // code `fix (λcode. λenv. λx. (env self) x)` on the closure `value`, where `self` is the
+    // closure of `code` on `env` again.
+    fn fix(&mut self, value: Expr, ty: &Type, range: Range) -> Expr {
+        let (left, right) = match ty {
+            Type::Arrow { left, right } => (self.translate(left), self.translate(right)),
+            _ => unreachable!("the fixed point is a function"),
+        };
+        let env_ty = self.translate(&arrow(ty.clone(), ty.clone()));
+        let code_ty = arrow(env_ty.clone(), arrow(left.clone(), right));
+
+        let (code, env) = (self.fresh.name("code"), self.fresh.name("env"));
+        let param = self.fresh.name("x");
+        let closure = self.pack(env_ty.clone(), var(&code, range), var(&env, range), ty, range);
+        let unfolded = self.call(var(&env, range), closure, range);
+        let body = self.call(unfolded, var(&param, range), range);
+
+        let body = abs(&param, left, body, range);
+        let body = abs(&env, env_ty.clone(), body, range);
+        let recursive = Expr::Fix(Fix { value: Box::new(abs(&code, code_ty, body, range)), range });
+
+        self.pack(env_ty, recursive, value, ty, range)
+    }
+}
+
+/// Converts the abstractions of a closed, well-typed term to closures. The binders of the term
+/// are renamed apart first.
+pub fn transform(expr: &Expr) -> Result<Expr, TypeError> {
+    let mut context = Context::new();
+    let expr = alpha_conversion_expr(&mut context, expr)?;
+    infer_type(&mut context, &expr)?;
+
+    let mut fresh = Fresh::new(&expr);
+    let env_ty = fresh.name("E");
+
+    Ok(Closure { fresh, context, env_ty }.convert(&Scope::default(), &expr)?.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{assert_agrees, children};
+    use super::transform;
+    use crate::checker::{self, equal};
+    use crate::parser::parsetree::{Abs, App, Expr, Type};
+    use crate::reduction::variables::free_variables;
+    use crate::reduction::Norm;
+    use crate::{parser, prelude};
+
+    // Whether every abstraction that is neither bound by a `let` nor the body of another one is
+    // closed.
+    fn is_closed(expr: &Expr) -> bool {
+        match expr {
+            Expr::Abs(Abs { body, .. }) => {
+                free_variables(expr.clone()).is_empty() && is_closed_code(body)
+            }
+            Expr::App(App { lambda, argm, .. }) if matches!(**lambda, Expr::Abs(_)) => {
+                children(lambda).into_iter().chain([&**argm]).all(is_closed)
+            }
+            _ => children(expr).into_iter().all(is_closed),
+        }
+    }
+
+    fn is_closed_code(expr: &Expr) -> bool {
+        match expr {
+            Expr::Abs(Abs { body, .. }) => is_closed_code(body),
+            _ => is_closed(expr),
+        }
+    }
+
+    fn assert_closes(expr: Expr) {
+        let closed = transform(&expr).unwrap();
+        assert!(is_closed(&closed), "{}", closed);
+
+        assert_agrees(&expr, &closed, &[Norm::NOR, Norm::NBE]);
+    }
+
+    #[test]
+    fn test_transform_types() {
+        let expr = parser::from_string(r"λA. λx: A. λy: Int -> A. y").unwrap();
+        let ty = checker::type_of(transform(&expr).unwrap()).unwrap();
+
+        let closure = |left: Type, right: Type| {
+            let code = Type::Arrow {
+                left: Box::new(Type::TVar { value: "E".to_string() }),
+                right: Box::new(Type::Arrow { left: Box::new(left), right: Box::new(right) }),
+            };
+            let fields = vec![
+                ("code".to_string(), code),
+                ("env".to_string(), Type::TVar { value: "E".to_string() }),
+            ];
+            Type::Exists {
+                param: "E".to_string(),
+                body: Box::new(Type::Record { fields, rest: None }),
+            }
+        };
+        let var = Type::TVar { value: "A".to_string() };
+        let function = closure(Type::TInt, var.clone());
+        let expected = Type::Forall {
+            param: "A".to_string(),
+            body: Box::new(closure(var, closure(function.clone(), function))),
+        };
+
+        assert!(equal(&ty, &expected), "{}", ty)
+    }
+
+    #[test]
+    fn test_transform_agrees() {
+        let inputs = [
+            r"
+            let pick: ∀A. Bool -> A -> A -> A = λA. λb: Bool. λx: A. λy: A. if b then x else y in
+            pick [Int] false 1 2
+            ",
+            r"
+            let add: Int -> Int -> Int = λx: Int. λy: Int. x + y in
+            let twice: (Int -> Int) -> Int -> Int = λf: Int -> Int. λx: Int. f (f x) in
+            let pair: Int * (Int -> Int) = {add 2 3, add 4} in
+            twice (snd pair) (fst pair)
+            ",
+            r"
+            let get_x: ∀ρ. {x: Int | ρ} -> Int = λρ. λr: {x: Int | ρ}. r.x in
+            let point: {x: Int, y: Int} = {x = 2, y = 3} in
+            get_x [{y: Int}] point + point.y
+            ",
+            r"
+            let size: <num: Int, flag: Bool> -> Int = λv: <num: Int, flag: Bool>.
+                case v of <num n> => n | <flag b> => if b then 1 else 0 in
+            size (<num = 4> as <num: Int, flag: Bool>) +
+                size (<flag = true> as <num: Int, flag: Bool>)
+            ",
+            r"
+            let counter: ∃C. {new: C, inc: C -> C, get: C -> Int} =
+                pack [Int, {new = 0, inc = λc: Int. c + 1, get = λc: Int. c}]
+                as ∃C. {new: C, inc: C -> C, get: C -> Int} in
+            unpack counter as [C, c] in c.get (c.inc (c.inc c.new))
+            ",
+            r"
+            let step: Int = 2 in
+            let rec down: Int -> Int = λn: Int. if n < 1 then 0 else step + down (n - 1) in
+            down 3
+            ",
+            r"fix (λx: Int. 1)",
+            r"(λk: Int. fix (λx: Int. k)) 3",
+            r"
+            let nil: μL. Unit + Int * L =
+                fold [μL. Unit + Int * L] inl [Int * (μL. Unit + Int * L)] () in
+            let cons: Int -> (μL. Unit + Int * L) -> μL. Unit + Int * L =
+                λx: Int. λxs: μL. Unit + Int * L. fold [μL. Unit + Int * L] inr [Unit] {x, xs} in
+            let rec len: (μL. Unit + Int * L) -> Int =
+                λl: μL. Unit + Int * L. case unfold l of inl u => 0 | inr p => 1 + len (snd p) in
+            len (cons 1 (cons 2 nil))
+            ",
+        ];
+
+        for input in inputs {
+            assert_closes(parser::from_string(input).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_transform_prelude() {
+        let input = r"
+        let two: ∀N. (N -> N) -> N -> N = succ (succ zero) in
+        let xs: ∀R. (Int -> R -> R) -> R -> R = cons [Int] (toint (mul two two)) (nil [Int]) in
+        sum (map [Int] [Int] (λx: Int. x + 1) xs)
+        ";
+
+        assert_closes(prelude::load(input).unwrap())
+    }
+}
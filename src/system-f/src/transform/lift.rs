@@ -0,0 +1,448 @@
+//! Lambda lifting to top-level functions. After closure conversion every code is closed in term
+//! variables, so it is moved out of the term to a function of the program and the term refers to
+//! it by name. A code may still mention the type variables bound around it, so the function
+//! abstracts over them and the term applies it to them: the codes become closed terms of closed
+//! types. The codes inside a code are lifted first, so a function only refers to the ones before
+//! it. The abstractions of the `let`s, which closure conversion left in place, stay too.
+
+use super::{abs, app, closure, var, Fresh};
+use crate::checker::{error::TypeError, infer_type, let_chain, Context};
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Match, Pack, Pair, Project,
+    Record, Snd, TAbs, TApp, Type, Unfold, Unpack, Variant,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// A top-level function, named and typed like a `def`.
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub ty: Type,
+    pub body: Expr,
+}
+
+/// The top-level functions of a program, each in the scope of the ones before it, and the body
+/// of the program in the scope of all of them.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub functions: Vec<Function>,
+    pub main: Expr,
+}
+
+impl Program {
+    /// The program as a term, binding its functions in turn.
+    pub fn to_expr(&self) -> Expr {
+        self.functions.iter().rev().fold(self.main.clone(), |main, function| {
+            let range = function.body.range();
+            app(abs(&function.name, function.ty.clone(), main, range), function.body.clone(), range)
+        })
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for Function { name, ty, body } in &self.functions {
+            writeln!(f, "def {}: {} = {}", name, ty, body)?;
+        }
+
+        write!(f, "main = {}", self.main)
+    }
+}
+
+// The type variables a type mentions that are not in `bound`.
+fn free_type_variables(ty: &Type, bound: &mut Vec<String>, free: &mut BTreeSet<String>) {
+    match ty {
+        Type::TInt | Type::TBool | Type::TUnit => (),
+        Type::TVar { value } => {
+            if !bound.contains(value) {
+                free.insert(value.clone());
+            }
+        }
+        Type::Arrow { left: fst, right: snd }
+        | Type::Product { fst, snd }
+        | Type::Sum { left: fst, right: snd } => {
+            free_type_variables(fst, bound, free);
+            free_type_variables(snd, bound, free);
+        }
+        Type::Forall { param, body } | Type::Exists { param, body } | Type::Rec { param, body } => {
+            bound.push(param.clone());
+            free_type_variables(body, bound, free);
+            bound.pop();
+        }
+        Type::Record { fields, rest } | Type::Variant { cases: fields, rest } => {
+            free.extend(rest.iter().filter(|rest| !bound.contains(rest)).cloned());
+
+            for (_, ty) in fields {
+                free_type_variables(ty, bound, free);
+            }
+        }
+    }
+}
+
+// The type variables a term mentions that are not bound in it nor in `bound`.
+fn free_type_variables_expr(expr: &Expr, bound: &mut Vec<String>, free: &mut BTreeSet<String>) {
+    match expr {
+        Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => (),
+        Expr::Abs(Abs { param_ty, body, .. }) => {
+            free_type_variables(param_ty, bound, free);
+            free_type_variables_expr(body, bound, free);
+        }
+        Expr::TAbs(TAbs { param, body, .. }) => {
+            bound.push(param.clone());
+            free_type_variables_expr(body, bound, free);
+            bound.pop();
+        }
+        Expr::TApp(TApp { lambda, argm, .. }) => {
+            free_type_variables_expr(lambda, bound, free);
+            free_type_variables(argm, bound, free);
+        }
+        Expr::Inl(Inl { value, right_ty: ty, .. })
+        | Expr::Inr(Inr { value, left_ty: ty, .. })
+        | Expr::Variant(Variant { value, ty, .. })
+        | Expr::Fold(Fold { value, ty, .. }) => {
+            free_type_variables_expr(value, bound, free);
+            free_type_variables(ty, bound, free);
+        }
+        Expr::Pack(Pack { witness, value, ty, .. }) => {
+            free_type_variables_expr(value, bound, free);
+            free_type_variables(witness, bound, free);
+            free_type_variables(ty, bound, free);
+        }
+        Expr::Unpack(Unpack { package, ty_param, body, .. }) => {
+            free_type_variables_expr(package, bound, free);
+            bound.push(ty_param.clone());
+            free_type_variables_expr(body, bound, free);
+            bound.pop();
+        }
+        Expr::Case(Case { scrutinee, left_body, right_body, .. }) => {
+            free_type_variables_expr(scrutinee, bound, free);
+            free_type_variables_expr(left_body, bound, free);
+            free_type_variables_expr(right_body, bound, free);
+        }
+        Expr::Match(Match { scrutinee, arms, .. }) => {
+            free_type_variables_expr(scrutinee, bound, free);
+
+            for Arm { body, .. } in arms {
+                free_type_variables_expr(body, bound, free);
+            }
+        }
+        Expr::Record(Record { fields, .. }) => {
+            for (_, field) in fields {
+                free_type_variables_expr(field, bound, free);
+            }
+        }
+        Expr::App(App { lambda: fst, argm: snd, .. })
+        | Expr::Pair(Pair { fst, snd, .. })
+        | Expr::BinOp(BinOp { left: fst, right: snd, .. }) => {
+            free_type_variables_expr(fst, bound, free);
+            free_type_variables_expr(snd, bound, free);
+        }
+        Expr::Fst(Fst { pair: value, .. })
+        | Expr::Snd(Snd { pair: value, .. })
+        | Expr::Project(Project { record: value, .. })
+        | Expr::Unfold(Unfold { value, .. })
+        | Expr::Fix(Fix { value, .. }) => free_type_variables_expr(value, bound, free),
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            free_type_variables_expr(cond, bound, free);
+            free_type_variables_expr(then_branch, bound, free);
+            free_type_variables_expr(else_branch, bound, free);
+        }
+    }
+}
+
+// Whether a term is a code: an abstraction, or the fixed point of a function.
+fn is_code(expr: &Expr) -> bool {
+    match expr {
+        Expr::Abs(_) => true,
+        Expr::Fix(Fix { value, .. }) => {
+            matches!(&**value, Expr::Abs(Abs { body, .. }) if matches!(**body, Expr::Abs(_)))
+        }
+        _ => false,
+    }
+}
+
+struct Lift {
+    fresh: Fresh,
+    // The types of the functions lifted so far.
+    types: HashMap<String, Type>,
+    functions: Vec<Function>,
+}
+
+impl Lift {
+    fn boxed(&mut self, expr: &Expr) -> Result<Box<Expr>, TypeError> {
+        self.lift(expr).map(Box::new)
+    }
+
+    fn lift(&mut self, expr: &Expr) -> Result<Expr, TypeError> {
+        let range = expr.range();
+
+        Ok(match expr {
+            Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => expr.clone(),
+            Expr::Abs(_) => self.function(expr)?,
+            Expr::Fix(_) if is_code(expr) => self.function(expr)?,
+            Expr::Fix(Fix { value, .. }) => Expr::Fix(Fix { value: self.boxed(value)?, range }),
+            Expr::App(App { lambda, .. }) if matches!(**lambda, Expr::Abs(_)) => self.lets(expr)?,
+            Expr::App(App { lambda, argm, .. }) => app(self.lift(lambda)?, self.lift(argm)?, range),
+            Expr::TAbs(TAbs { param, body, .. }) => {
+                Expr::TAbs(TAbs { param: param.clone(), body: self.boxed(body)?, range })
+            }
+            Expr::TApp(TApp { lambda, argm, .. }) => {
+                Expr::TApp(TApp { lambda: self.boxed(lambda)?, argm: argm.clone(), range })
+            }
+            Expr::Pair(Pair { fst, snd, .. }) => {
+                Expr::Pair(Pair { fst: self.boxed(fst)?, snd: self.boxed(snd)?, range })
+            }
+            Expr::Fst(Fst { pair, .. }) => Expr::Fst(Fst { pair: self.boxed(pair)?, range }),
+            Expr::Snd(Snd { pair, .. }) => Expr::Snd(Snd { pair: self.boxed(pair)?, range }),
+            Expr::Inl(Inl { value, right_ty, .. }) => {
+                let right_ty = right_ty.clone();
+                Expr::Inl(Inl { value: self.boxed(value)?, right_ty, range })
+            }
+            Expr::Inr(Inr { value, left_ty, .. }) => {
+                let left_ty = left_ty.clone();
+                Expr::Inr(Inr { value: self.boxed(value)?, left_ty, range })
+            }
+            Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+                Expr::Case(Case {
+                    scrutinee: self.boxed(scrutinee)?,
+                    left_param: left_param.clone(),
+                    left_body: self.boxed(left_body)?,
+                    right_param: right_param.clone(),
+                    right_body: self.boxed(right_body)?,
+                    range,
+                })
+            }
+            Expr::Record(Record { fields, .. }) => {
+                let fields = fields
+                    .iter()
+                    .map(|(label, field)| Ok((label.clone(), self.lift(field)?)))
+                    .collect::<Result<_, TypeError>>()?;
+                Expr::Record(Record { fields, range })
+            }
+            Expr::Project(Project { record, label, .. }) => {
+                Expr::Project(Project { record: self.boxed(record)?, label: label.clone(), range })
+            }
+            Expr::Variant(Variant { label, value, ty, .. }) => {
+                let (label, ty) = (label.clone(), ty.clone());
+                Expr::Variant(Variant { label, value: self.boxed(value)?, ty, range })
+            }
+            Expr::Match(Match { scrutinee, arms, .. }) => {
+                let arms = arms
+                    .iter()
+                    .map(|Arm { label, param, body }| {
+                        let body = self.lift(body)?;
+                        Ok(Arm { label: label.clone(), param: param.clone(), body })
+                    })
+                    .collect::<Result<_, TypeError>>()?;
+                Expr::Match(Match { scrutinee: self.boxed(scrutinee)?, arms, range })
+            }
+            Expr::Fold(Fold { ty, value, .. }) => {
+                Expr::Fold(Fold { ty: ty.clone(), value: self.boxed(value)?, range })
+            }
+            Expr::Unfold(Unfold { value, .. }) => {
+                Expr::Unfold(Unfold { value: self.boxed(value)?, range })
+            }
+            Expr::Pack(Pack { witness, value, ty, .. }) => {
+                let (witness, ty) = (witness.clone(), ty.clone());
+                Expr::Pack(Pack { witness, value: self.boxed(value)?, ty, range })
+            }
+            Expr::Unpack(Unpack { package, ty_param, param, body, .. }) => Expr::Unpack(Unpack {
+                package: self.boxed(package)?,
+                ty_param: ty_param.clone(),
+                param: param.clone(),
+                body: self.boxed(body)?,
+                range,
+            }),
+            Expr::If(If { cond, then_branch, else_branch, .. }) => Expr::If(If {
+                cond: self.boxed(cond)?,
+                then_branch: self.boxed(then_branch)?,
+                else_branch: self.boxed(else_branch)?,
+                range,
+            }),
+            Expr::BinOp(BinOp { op, left, right, .. }) => Expr::BinOp(BinOp {
+                op: *op,
+                left: self.boxed(left)?,
+                right: self.boxed(right)?,
+                range,
+            }),
+        })
+    }
+
+    // A chain of `let`s stays in place, its abstractions being applied at once rather than
+    // codes, and only the values and the body are lifted.
+    fn lets(&mut self, expr: &Expr) -> Result<Expr, TypeError> {
+        let (lets, body) = let_chain(expr);
+
+        let mut values = Vec::new();
+        for (App { argm, .. }, _) in &lets {
+            values.push(self.lift(argm)?);
+        }
+        let body = self.lift(body)?;
+
+        let lets = lets.into_iter().zip(values).rev();
+        Ok(lets.fold(body, |body, ((binding, lambda), value)| {
+            let lambda = abs(&lambda.param, lambda.param_ty.clone(), body, lambda.range);
+            app(lambda, value, binding.range)
+        }))
+    }
+
+    // Lifts a code to a function over the type variables it mentions, after the codes in its
+    // body.
+    fn function(&mut self, code: &Expr) -> Result<Expr, TypeError> {
+        let range = code.range();
+        let body = self.code(code)?;
+
+        let mut free = BTreeSet::new();
+        free_type_variables_expr(&body, &mut Vec::new(), &mut free);
+
+        let mut context = Context { types: self.types.clone(), ..Context::new() };
+        let ty = infer_type(&mut context, &body)?;
+
+        let ty = free.iter().rev().fold(ty, |body, param| Type::Forall {
+            param: param.clone(),
+            body: Box::new(body),
+        });
+        let body = free.iter().rev().fold(body, |body, param| {
+            Expr::TAbs(TAbs { param: param.clone(), body: Box::new(body), range })
+        });
+        let name = self.fresh.name("code");
+
+        self.types.insert(name.clone(), ty.clone());
+        self.functions.push(Function { name: name.clone(), ty, body });
+
+        Ok(free.into_iter().fold(var(&name, range), |lambda, argm| {
+            let argm = Type::TVar { value: argm };
+            Expr::TApp(TApp { lambda: Box::new(lambda), argm, range })
+        }))
+    }
+
+    fn code(&mut self, code: &Expr) -> Result<Expr, TypeError> {
+        match code {
+            Expr::Abs(Abs { param, param_ty, body, range }) => {
+                Ok(abs(param, param_ty.clone(), self.code(body)?, *range))
+            }
+            Expr::Fix(Fix { value, range }) if is_code(code) => {
+                Ok(Expr::Fix(Fix { value: Box::new(self.code(value)?), range: *range }))
+            }
+            body => self.lift(body),
+        }
+    }
+}
+
+/// Converts a closed, well-typed term to closures and lifts their codes to top-level functions.
+pub fn transform(expr: &Expr) -> Result<Program, TypeError> {
+    let converted = closure::transform(expr)?;
+    let mut lift = Lift {
+        fresh: Fresh::new(&converted),
+        types: HashMap::new(),
+        functions: Vec::new(),
+    };
+    let main = lift.lift(&converted)?;
+
+    Ok(Program { functions: lift.functions, main })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{assert_agrees, children};
+    use super::{transform, Function};
+    use crate::parser::parsetree::{Abs, App, Expr, Fix, TAbs};
+    use crate::reduction::variables::free_variables;
+    use crate::reduction::Norm;
+    use crate::{parser, prelude};
+
+    // Whether a term has no abstraction left outside of the parameters of a code and of the
+    // `let`s.
+    fn is_flat(expr: &Expr) -> bool {
+        match expr {
+            Expr::Abs(_) => false,
+            Expr::App(App { lambda, argm, .. }) if matches!(**lambda, Expr::Abs(_)) => {
+                children(lambda).into_iter().chain([&**argm]).all(is_flat)
+            }
+            _ => children(expr).into_iter().all(is_flat),
+        }
+    }
+
+    fn is_flat_code(expr: &Expr) -> bool {
+        match expr {
+            Expr::TAbs(TAbs { body: code, .. })
+            | Expr::Abs(Abs { body: code, .. })
+            | Expr::Fix(Fix { value: code, .. }) => is_flat_code(code),
+            _ => is_flat(expr),
+        }
+    }
+
+    #[test]
+    fn test_transform_program() {
+        let expr = parser::from_string(r"λA. (λx: A. x)").unwrap();
+
+        assert_eq!(
+            transform(&expr).unwrap().to_string(),
+            "def code0: ∀A0. ({} -> (A0 -> A0)) = λA0. λenv1: {}. λx1: A0. x1\n\
+             main = λA0. (pack [{}, {code = (code0 [A0]), env = {}}] as \
+             (∃E0. {code: (E0 -> (A0 -> A0)), env: E0}))"
+        )
+    }
+
+    fn assert_lifts(strategies: &[Norm], expr: Expr) {
+        let program = transform(&expr).unwrap();
+
+        let mut scope = Vec::new();
+        for Function { name, body, .. } in &program.functions {
+            assert!(is_flat_code(body), "{}", body);
+            assert!(free_variables(body.clone()).iter().all(|free| scope.contains(free)));
+            scope.push(name.clone());
+        }
+        assert!(is_flat(&program.main), "{}", program.main);
+
+        // The checker renames every variable bound in the program, so the functions are closed
+        // in type variables too.
+        assert_agrees(&expr, &program.to_expr(), strategies);
+    }
+
+    #[test]
+    fn test_transform_agrees() {
+        let inputs = [
+            r"
+            let pick: ∀A. Bool -> A -> A -> A = λA. λb: Bool. λx: A. λy: A. if b then x else y in
+            pick [Int] false 1 2
+            ",
+            r"
+            let get_x: ∀ρ. {x: Int | ρ} -> Int = λρ. λr: {x: Int | ρ}. r.x in
+            let point: {x: Int, y: Int} = {x = 2, y = 3} in
+            get_x [{y: Int}] point + point.y
+            ",
+            r"
+            let counter: ∃C. {new: C, inc: C -> C, get: C -> Int} =
+                pack [Int, {new = 0, inc = λc: Int. c + 1, get = λc: Int. c}]
+                as ∃C. {new: C, inc: C -> C, get: C -> Int} in
+            unpack counter as [C, c] in
+            let twice: (C -> C) -> C -> C = λf: C -> C. λx: C. f (f x) in
+            c.get (twice c.inc c.new)
+            ",
+            r"
+            let step: Int = 2 in
+            let rec down: Int -> Int = λn: Int. if n < 1 then 0 else step + down (n - 1) in
+            down 3
+            ",
+            r"fix (λx: Int. 1)",
+            r"(λk: Int. fix (λx: Int. k)) 3",
+        ];
+
+        for input in inputs {
+            assert_lifts(&[Norm::NOR, Norm::NBE], parser::from_string(input).unwrap())
+        }
+    }
+
+    // Normal order substitutes every function of the prelude into the rest of the program.
+    #[test]
+    fn test_transform_prelude() {
+        let input = r"
+        let two: ∀N. (N -> N) -> N -> N = succ (succ zero) in
+        toint (mul two two)
+        ";
+
+        assert_lifts(&[Norm::NBE], prelude::load(input).unwrap())
+    }
+}
@@ -0,0 +1,234 @@
+//! Closure conversion and lambda lifting of polymorphic terms, where a code abstracts over the
+//! type variables in scope as well as the term variables it captures, and environments hide
+//! their types in existential packages. The passes work on terms whose binders the checker
+//! renamed apart, and name what they add with names that the term does not mention anywhere.
+
+pub mod closure;
+pub mod lift;
+
+use crate::parser::location::Range;
+use crate::parser::parsetree::{
+    Abs, App, Arm, BinOp, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Match, Pack, Pair, Project,
+    Record, Snd, TAbs, TApp, Type, Unfold, Unpack, Var, Variant,
+};
+use std::collections::HashSet;
+
+// Every name a type mentions, bound or free.
+fn type_names(ty: &Type, names: &mut HashSet<String>) {
+    match ty {
+        Type::TInt | Type::TBool | Type::TUnit => (),
+        Type::TVar { value } => {
+            names.insert(value.clone());
+        }
+        Type::Arrow { left: fst, right: snd }
+        | Type::Product { fst, snd }
+        | Type::Sum { left: fst, right: snd } => {
+            type_names(fst, names);
+            type_names(snd, names);
+        }
+        Type::Forall { param, body } | Type::Exists { param, body } | Type::Rec { param, body } => {
+            names.insert(param.clone());
+            type_names(body, names);
+        }
+        Type::Record { fields, rest } | Type::Variant { cases: fields, rest } => {
+            names.extend(rest.iter().cloned());
+
+            for (_, ty) in fields {
+                type_names(ty, names);
+            }
+        }
+    }
+}
+
+// Every name of a term or type variable a term mentions, bound or free.
+fn names(expr: &Expr, names: &mut HashSet<String>) {
+    match expr {
+        Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) => (),
+        Expr::Var(Var { value, .. }) => {
+            names.insert(value.clone());
+        }
+        Expr::Abs(Abs { param, param_ty, body, .. }) => {
+            names.insert(param.clone());
+            type_names(param_ty, names);
+            self::names(body, names);
+        }
+        Expr::TAbs(TAbs { param, body, .. }) => {
+            names.insert(param.clone());
+            self::names(body, names);
+        }
+        Expr::TApp(TApp { lambda, argm, .. }) => {
+            self::names(lambda, names);
+            type_names(argm, names);
+        }
+        Expr::Inl(Inl { value, right_ty: ty, .. })
+        | Expr::Inr(Inr { value, left_ty: ty, .. })
+        | Expr::Variant(Variant { value, ty, .. })
+        | Expr::Fold(Fold { value, ty, .. }) => {
+            self::names(value, names);
+            type_names(ty, names);
+        }
+        Expr::Pack(Pack { witness, value, ty, .. }) => {
+            self::names(value, names);
+            type_names(witness, names);
+            type_names(ty, names);
+        }
+        Expr::Unpack(Unpack { package, ty_param, param, body, .. }) => {
+            names.insert(ty_param.clone());
+            names.insert(param.clone());
+            self::names(package, names);
+            self::names(body, names);
+        }
+        Expr::Case(Case { scrutinee, left_param, left_body, right_param, right_body, .. }) => {
+            names.insert(left_param.clone());
+            names.insert(right_param.clone());
+            self::names(scrutinee, names);
+            self::names(left_body, names);
+            self::names(right_body, names);
+        }
+        Expr::Match(Match { scrutinee, arms, .. }) => {
+            self::names(scrutinee, names);
+
+            for Arm { param, body, .. } in arms {
+                names.insert(param.clone());
+                self::names(body, names);
+            }
+        }
+        Expr::Record(Record { fields, .. }) => {
+            for (_, field) in fields {
+                self::names(field, names);
+            }
+        }
+        Expr::App(App { lambda: fst, argm: snd, .. })
+        | Expr::Pair(Pair { fst, snd, .. })
+        | Expr::BinOp(BinOp { left: fst, right: snd, .. }) => {
+            self::names(fst, names);
+            self::names(snd, names);
+        }
+        Expr::Fst(Fst { pair: value, .. })
+        | Expr::Snd(Snd { pair: value, .. })
+        | Expr::Project(Project { record: value, .. })
+        | Expr::Unfold(Unfold { value, .. })
+        | Expr::Fix(Fix { value, .. }) => self::names(value, names),
+        Expr::If(If { cond, then_branch, else_branch, .. }) => {
+            self::names(cond, names);
+            self::names(then_branch, names);
+            self::names(else_branch, names);
+        }
+    }
+}
+
+/// A supply of names that are fresh for a term: a base followed by a number.
+pub(crate) struct Fresh {
+    used: HashSet<String>,
+    next: usize,
+}
+
+impl Fresh {
+    pub(crate) fn new(expr: &Expr) -> Fresh {
+        let mut used = HashSet::new();
+        names(expr, &mut used);
+
+        Fresh { used, next: 0 }
+    }
+
+    pub(crate) fn name(&mut self, base: &str) -> String {
+        loop {
+            let name = format!("{}{}", base, self.next);
+            self.next += 1;
+
+            if self.used.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+}
+
+/// The subterms of a term.
+pub(crate) fn var(name: &str, range: Range) -> Expr {
+    Expr::Var(Var { value: name.to_string(), range })
+}
+
+pub(crate) fn abs(param: &str, param_ty: Type, body: Expr, range: Range) -> Expr {
+    Expr::Abs(Abs { param: param.to_string(), param_ty, body: Box::new(body), range })
+}
+
+pub(crate) fn app(lambda: Expr, argm: Expr, range: Range) -> Expr {
+    Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range })
+}
+
+pub(crate) fn arrow(left: Type, right: Type) -> Type {
+    Type::Arrow { left: Box::new(left), right: Box::new(right) }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bytecode;
+    use crate::checker::{self, alpha_conversion_expr, equal, Context};
+    use crate::parser::parsetree::{
+        Abs, App, Arm, BinOp, Case, Expr, Fix, Fold, Fst, If, Inl, Inr, Match, Pack, Pair,
+        Project, Record, Snd, TAbs, TApp, Unfold, Unpack, Variant,
+    };
+    use crate::reduction::{reduce, Norm};
+
+    pub(super) fn children(expr: &Expr) -> Vec<&Expr> {
+        match expr {
+            Expr::Int(_) | Expr::Bool(_) | Expr::Unit(_) | Expr::Var(_) => vec![],
+            Expr::Abs(Abs { body: value, .. })
+            | Expr::TAbs(TAbs { body: value, .. })
+            | Expr::TApp(TApp { lambda: value, .. })
+            | Expr::Fst(Fst { pair: value, .. })
+            | Expr::Snd(Snd { pair: value, .. })
+            | Expr::Inl(Inl { value, .. })
+            | Expr::Inr(Inr { value, .. })
+            | Expr::Project(Project { record: value, .. })
+            | Expr::Variant(Variant { value, .. })
+            | Expr::Fold(Fold { value, .. })
+            | Expr::Unfold(Unfold { value, .. })
+            | Expr::Fix(Fix { value, .. })
+            | Expr::Pack(Pack { value, .. }) => vec![value],
+            Expr::App(App { lambda: fst, argm: snd, .. })
+            | Expr::Pair(Pair { fst, snd, .. })
+            | Expr::BinOp(BinOp { left: fst, right: snd, .. })
+            | Expr::Unpack(Unpack { package: fst, body: snd, .. }) => vec![fst, snd],
+            Expr::Case(Case { scrutinee, left_body, right_body, .. }) => {
+                vec![scrutinee, left_body, right_body]
+            }
+            Expr::If(If { cond, then_branch, else_branch, .. }) => {
+                vec![cond, then_branch, else_branch]
+            }
+            Expr::Record(Record { fields, .. }) => fields.iter().map(|(_, field)| field).collect(),
+            Expr::Match(Match { scrutinee, arms, .. }) => {
+                let mut children = vec![&**scrutinee];
+                children.extend(arms.iter().map(|Arm { body, .. }| body));
+                children
+            }
+        }
+    }
+
+    // Asserts that a pass kept the type of `expr` and that what it gave evaluates to the normal
+    // form of `expr` under each of the strategies and on the virtual machine. Normal order
+    // unrolls a recursive code under a stuck `case` as deep as it may go, so it keeps its
+    // default depth.
+    pub(super) fn assert_agrees(expr: &Expr, transformed: &Expr, strategies: &[Norm]) {
+        let ty = checker::type_of(expr.clone()).unwrap();
+        let transformed_ty = checker::type_of(transformed.clone()).unwrap();
+        assert!(equal(&transformed_ty, &ty), "{}", transformed);
+
+        // The passes rename the binders apart first, which a value may still show.
+        let renamed = alpha_conversion_expr(&mut Context::new(), expr).unwrap();
+        let expected = reduce(Norm::NOR, renamed, None).to_string();
+        for strategy in strategies {
+            let value = reduce(*strategy, transformed.clone(), None);
+            assert_eq!(value.to_string(), expected, "{:?}", strategy);
+        }
+
+        // The virtual machine gives both terms the same value, which is their normal form but
+        // for the fixed points of other types than functions: the machine takes them once
+        // where the reducers leave them.
+        let run = |expr: &Expr| {
+            let value = bytecode::run(expr, None).unwrap();
+            value.to_expr(&ty, expr.range()).unwrap().to_string()
+        };
+        assert_eq!(run(transformed), run(expr));
+    }
+}
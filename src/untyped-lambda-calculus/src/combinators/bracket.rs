@@ -0,0 +1,69 @@
+use super::Term;
+use crate::parser::parsetree::{Abs, App, Expr, Let, Var};
+
+/// The rules used to take a variable out of a term.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Abstraction {
+    /// Only S, K and I: the output grows quickly with the nesting of binders.
+    #[default]
+    Ski,
+    /// Turner's optimisations with B, C and W, which only pass the variable to the side of an
+    /// application that uses it.
+    Turner,
+}
+
+// [x] x = I
+// [x] M = K M                          when x is not in M
+// [x] M x = M                          when x is not in M (Turner)
+// [x] M x = W ([x] M)                  (Turner)
+// [x] M N = B M ([x] N)                when x is not in M (Turner)
+// [x] M N = C ([x] M) N                when x is not in N (Turner)
+// [x] M N = S ([x] M) ([x] N)
+fn abstraction(rules: Abstraction, name: &str, term: Term) -> Term {
+    match term {
+        Term::Var(ref value) if value == name => Term::I,
+        term if !term.occurs(name) => Term::app(Term::K, term),
+        Term::App(lambda, argm) => {
+            let turner = rules == Abstraction::Turner;
+
+            match (*lambda, *argm) {
+                (lambda, Term::Var(value)) if turner && value == name => {
+                    if lambda.occurs(name) {
+                        Term::app(Term::W, abstraction(rules, name, lambda))
+                    } else {
+                        lambda
+                    }
+                }
+                (lambda, argm) if turner && !lambda.occurs(name) => {
+                    Term::app(Term::app(Term::B, lambda), abstraction(rules, name, argm))
+                }
+                (lambda, argm) if turner && !argm.occurs(name) => {
+                    Term::app(Term::app(Term::C, abstraction(rules, name, lambda)), argm)
+                }
+                (lambda, argm) => {
+                    let lambda = abstraction(rules, name, lambda);
+                    let argm = abstraction(rules, name, argm);
+
+                    Term::app(Term::app(Term::S, lambda), argm)
+                }
+            }
+        }
+        term => unreachable!("{} has no application to abstract {} from", term, name),
+    }
+}
+
+/// Compiles a lambda term to combinators by bracket abstraction, innermost binders first.
+/// `let x = v in b` is compiled as `(λx. b) v`.
+pub fn compile(expr: &Expr, rules: Abstraction) -> Term {
+    match expr {
+        Expr::Var(Var { value, .. }) => Term::Var(value.clone()),
+        Expr::App(App { lambda, argm, .. }) => {
+            Term::app(compile(lambda, rules), compile(argm, rules))
+        }
+        Expr::Abs(Abs { param, body, .. }) => abstraction(rules, param, compile(body, rules)),
+        Expr::Let(Let { name, value, body, .. }) => {
+            let lambda = abstraction(rules, name, compile(body, rules));
+            Term::app(lambda, compile(value, rules))
+        }
+    }
+}
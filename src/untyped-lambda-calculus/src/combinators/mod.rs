@@ -0,0 +1,155 @@
+use crate::parser::{
+    location::Range,
+    parsetree::{Abs, App, Expr, Var},
+};
+use std::fmt;
+
+pub mod bracket;
+pub mod reduction;
+
+/// A term of combinatory logic. Variables are the free variables of the lambda term it was
+/// compiled from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    S,
+    K,
+    I,
+    B,
+    C,
+    W,
+    Var(String),
+    App(Box<Term>, Box<Term>),
+}
+
+impl Term {
+    pub fn app(lambda: Term, argm: Term) -> Term {
+        Term::App(Box::new(lambda), Box::new(argm))
+    }
+
+    pub fn occurs(&self, name: &str) -> bool {
+        match self {
+            Term::Var(value) => value == name,
+            Term::App(lambda, argm) => lambda.occurs(name) || argm.occurs(name),
+            _ => false,
+        }
+    }
+
+    /// The number of combinators and variables in the term.
+    pub fn size(&self) -> usize {
+        match self {
+            Term::App(lambda, argm) => lambda.size() + argm.size(),
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Term::S => write!(f, "S"),
+            Term::K => write!(f, "K"),
+            Term::I => write!(f, "I"),
+            Term::B => write!(f, "B"),
+            Term::C => write!(f, "C"),
+            Term::W => write!(f, "W"),
+            Term::Var(value) => write!(f, "{}", value),
+            Term::App(lambda, argm) => match **argm {
+                Term::App(..) => write!(f, "{} ({})", lambda, argm),
+                _ => write!(f, "{} {}", lambda, argm),
+            },
+        }
+    }
+}
+
+fn var(name: &str, range: Range) -> Expr {
+    Expr::Var(Var { value: name.to_string(), range })
+}
+
+fn apply(lambda: Expr, argm: Expr) -> Expr {
+    let range = lambda.range();
+    Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range })
+}
+
+fn abs(params: &[&str], body: Expr) -> Expr {
+    let range = body.range();
+
+    params.iter().rev().fold(body, |body, param| {
+        Expr::Abs(Abs { param: param.to_string(), body: Box::new(body), range })
+    })
+}
+
+/// Reads a combinator term back as a lambda term, each combinator standing for its definition.
+pub fn to_lambda(term: &Term, range: Range) -> Expr {
+    let [x, y, z] = ["x", "y", "z"].map(|name| var(name, range));
+
+    match term {
+        Term::S => abs(&["x", "y", "z"], apply(apply(x, z.clone()), apply(y, z))),
+        Term::K => abs(&["x", "y"], x),
+        Term::I => abs(&["x"], x),
+        Term::B => abs(&["x", "y", "z"], apply(x, apply(y, z))),
+        Term::C => abs(&["x", "y", "z"], apply(apply(x, z), y)),
+        Term::W => abs(&["x", "y"], apply(apply(x, y.clone()), y)),
+        Term::Var(value) => var(value, range),
+        Term::App(lambda, argm) => apply(to_lambda(lambda, range), to_lambda(argm, range)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::bracket::{compile, Abstraction};
+    use super::reduction::{reduce, trace};
+    use super::{to_lambda, Term};
+    use crate::parser;
+    use crate::reduction::{reduce as normalize, variables::alpha_equivalent, Norm};
+
+    fn compile_str(input: &str, rules: Abstraction) -> Term {
+        compile(&parser::from_string(input).unwrap(), rules)
+    }
+
+    #[test]
+    fn test_compile_const() {
+        assert_eq!(compile_str(r"λx. λy. x", Abstraction::Ski).to_string(), "S (K K) I");
+        assert_eq!(compile_str(r"λx. λy. x", Abstraction::Turner).to_string(), "K")
+    }
+
+    #[test]
+    fn test_compile_turner_is_smaller() {
+        let input = r"λf. λg. λx. f (g x) (g (f x))";
+        let ski = compile_str(input, Abstraction::Ski);
+        let turner = compile_str(input, Abstraction::Turner);
+
+        assert!(turner.size() < ski.size())
+    }
+
+    #[test]
+    fn test_reduce_church_addition() {
+        let input = r"(λm. λn. λf. λx. m f (n f x)) 2 3 f x";
+
+        for rules in [Abstraction::Ski, Abstraction::Turner] {
+            let term = reduce(compile_str(input, rules), 1000);
+            assert_eq!(term.to_string(), "f (f (f (f (f x))))")
+        }
+    }
+
+    #[test]
+    fn test_trace_identity() {
+        let term = Term::app(Term::app(Term::S, Term::K), Term::K);
+        let term = Term::app(term, Term::Var(String::from("a")));
+        let steps: Vec<String> = trace(term, 10).iter().map(|term| term.to_string()).collect();
+
+        assert_eq!(steps, ["S K K a", "K a (K a)", "a"])
+    }
+
+    #[test]
+    fn test_to_lambda() {
+        let expr = parser::from_string(r"λx. λy. λz. z (x y) y").unwrap();
+        let range = expr.range();
+
+        for rules in [Abstraction::Ski, Abstraction::Turner] {
+            let back = to_lambda(&compile(&expr, rules), range);
+            let back = normalize(Norm::NOR, back, None);
+
+            assert!(alpha_equivalent(&back, &normalize(Norm::NOR, expr.clone(), None)))
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use super::Term;
+
+// The head of an application spine and its arguments, first argument first.
+fn spine(term: Term) -> (Term, Vec<Term>) {
+    let mut head = term;
+    let mut args = Vec::new();
+
+    while let Term::App(lambda, argm) = head {
+        args.push(*argm);
+        head = *lambda;
+    }
+
+    args.reverse();
+    (head, args)
+}
+
+fn apply(head: Term, args: impl IntoIterator<Item = Term>) -> Term {
+    args.into_iter().fold(head, Term::app)
+}
+
+// Rewrites a combinator applied to enough arguments, giving back the arguments it did not use.
+fn contract(head: &Term, args: &mut Vec<Term>) -> Option<Term> {
+    let arity = match head {
+        Term::I => 1,
+        Term::K | Term::W => 2,
+        Term::S | Term::B | Term::C => 3,
+        _ => return None,
+    };
+
+    if args.len() < arity {
+        return None;
+    }
+
+    // Draining drops the arguments a combinator discards.
+    let mut used = args.drain(..arity);
+    let mut next = || used.next().expect("the combinator has all its arguments");
+
+    let redex = match head {
+        Term::I | Term::K => next(),
+        Term::W => {
+            let (a, b) = (next(), next());
+            apply(a, [b.clone(), b])
+        }
+        Term::S => {
+            let (a, b, c) = (next(), next(), next());
+            apply(a, [c.clone(), Term::app(b, c)])
+        }
+        Term::B => {
+            let (a, b, c) = (next(), next(), next());
+            Term::app(a, Term::app(b, c))
+        }
+        Term::C => {
+            let (a, b, c) = (next(), next(), next());
+            apply(a, [c, b])
+        }
+        _ => unreachable!(),
+    };
+
+    Some(redex)
+}
+
+/// Takes the leftmost outermost step of weak reduction, if the term has a redex.
+pub fn step(term: &Term) -> Option<Term> {
+    let (head, mut args) = spine(term.clone());
+
+    if let Some(redex) = contract(&head, &mut args) {
+        return Some(apply(redex, args));
+    }
+
+    for index in 0..args.len() {
+        if let Some(reduced) = step(&args[index]) {
+            args[index] = reduced;
+            return Some(apply(head, args));
+        }
+    }
+
+    None
+}
+
+/// Every term the reduction goes through, starting with `term`, for at most `limit` steps.
+pub fn trace(term: Term, limit: usize) -> Vec<Term> {
+    let mut terms = vec![term];
+
+    while terms.len() <= limit {
+        match step(&terms[terms.len() - 1]) {
+            Some(next) => terms.push(next),
+            None => break,
+        }
+    }
+
+    terms
+}
+
+/// The normal form of `term`, or the term reached after `limit` steps.
+pub fn reduce(mut term: Term, limit: usize) -> Term {
+    for _ in 0..limit {
+        match step(&term) {
+            Some(next) => term = next,
+            None => break,
+        }
+    }
+
+    term
+}
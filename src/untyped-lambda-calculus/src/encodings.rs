@@ -0,0 +1,261 @@
+//! Church encodings of the data and primitives of the typed calculi, which their erasures into
+//! this calculus are built from. Every encoding is a closed term applied to the erased
+//! subterms, so it cannot capture their variables and the strategies that stop at abstractions
+//! still evaluate the subterms first. Integers are Church numerals and booleans are Church
+//! booleans. False is the same term as zero, so the type of the erased term tells which one
+//! to read back.
+
+use crate::parser::{
+    self,
+    location::Range,
+    numerals::Numerals,
+    parsetree::{Abs, App, Expr, Var},
+};
+use crate::reduction::readback::{self, readback, Value};
+use crate::reduction::variables::{free_variables, fresh_name};
+use crate::reduction::{reduce, Norm};
+
+const ZERO: &str = "λf. λx. x";
+const SUCC: &str = "λn. λf. λx. f (n f x)";
+const MUL: &str = "λm. λn. λf. m (n f)";
+const PRED: &str = "λn. λf. λx. n (λg. λh. h (g f)) (λu. x) (λu. u)";
+const TRUE: &str = "λt. λf. t";
+const FALSE: &str = "λt. λf. f";
+const ISZERO: &str = "λn. n (λx. λt. λf. f) (λt. λf. t)";
+const PAIR: &str = "λa. λb. λs. s a b";
+const FIRST: &str = "λp. p (λa. λb. a)";
+const SECOND: &str = "λp. p (λa. λb. b)";
+
+/// The primitive operators on integers. Subtraction is truncated at zero like it is in the
+/// typed calculi, and the quotient of a division by zero is zero where the typed reducers
+/// leave the division as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Lt,
+}
+
+/// What the typed term an erased one comes from stands for, as far as the read-back goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    Integer,
+    Boolean,
+    /// Anything else, which reads back like an untyped term.
+    Other,
+}
+
+fn sub() -> String {
+    format!("λm. λn. n ({}) m", PRED)
+}
+
+fn leq() -> String {
+    format!("λm. λn. ({}) (({}) m n)", ISZERO, sub())
+}
+
+fn eq() -> String {
+    format!("λm. λn. ({leq}) m n (({leq}) n m) ({})", FALSE, leq = leq())
+}
+
+// Counts the multiples `k * d` of the divisor from `k = 1` that are at most `n`, the counter
+// being threaded through `n` applications so that no intermediate result is duplicated.
+fn div() -> String {
+    let count = format!(
+        "λg. λk. ({leq}) (({mul}) k d) n ({succ}) (λr. r) (g (({succ}) k))",
+        leq = leq(),
+        mul = MUL,
+        succ = SUCC,
+    );
+
+    format!(
+        "λn. λd. ({iszero}) d ({zero}) (n ({count}) (λk. {zero}) (λf. λx. f x))",
+        iszero = ISZERO,
+        zero = ZERO,
+    )
+}
+
+fn term(source: &str) -> Expr {
+    parser::from_string(source).expect("the encodings are well formed")
+}
+
+pub fn var(name: &str, range: Range) -> Expr {
+    Expr::Var(Var { value: name.to_string(), range })
+}
+
+pub fn abs(param: &str, body: Expr, range: Range) -> Expr {
+    Expr::Abs(Abs { param: param.to_string(), body: Box::new(body), range })
+}
+
+pub fn app(lambda: Expr, argm: Expr, range: Range) -> Expr {
+    Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range })
+}
+
+fn apply(lambda: &str, args: impl IntoIterator<Item = Expr>, range: Range) -> Expr {
+    args.into_iter().fold(term(lambda), |lambda, argm| app(lambda, argm, range))
+}
+
+pub fn numeral(value: usize, range: Range) -> Expr {
+    Numerals::Church.encode(value, range)
+}
+
+pub fn boolean(value: bool) -> Expr {
+    term(if value { TRUE } else { FALSE })
+}
+
+/// The unit value, which is the identity.
+pub fn unit() -> Expr {
+    term("λx. x")
+}
+
+pub fn primitive(op: Primitive, left: Expr, right: Expr, range: Range) -> Expr {
+    let lambda = match op {
+        Primitive::Add => "λm. λn. λf. λx. m f (n f x)".to_string(),
+        Primitive::Sub => sub(),
+        Primitive::Mul => MUL.to_string(),
+        Primitive::Div => div(),
+        Primitive::Eq => eq(),
+        Primitive::Lt => format!("λm. λn. ({}) (({}) m) n", leq(), SUCC),
+    };
+
+    apply(&lambda, [left, right], range)
+}
+
+/// The branches are delayed behind an abstraction and the boolean picks the one to run, so
+/// that call-by-value does not run both of them.
+pub fn branch(cond: Expr, then_branch: Expr, else_branch: Expr, range: Range) -> Expr {
+    let mut used = free_variables(then_branch.clone());
+    used.extend(free_variables(else_branch.clone()));
+
+    let param = fresh_name("d", &used);
+    let delayed = [cond, abs(&param, then_branch, range), abs(&param, else_branch, range)];
+
+    apply("λc. λt. λe. c t e (λx. x)", delayed, range)
+}
+
+/// The fixed point of a function by the call-by-value fixed point combinator, which every
+/// strategy but applicative order unfolds only as needed.
+pub fn fix(value: Expr, range: Range) -> Expr {
+    apply("λf. (λx. f (λv. x x v)) (λx. f (λv. x x v))", [value], range)
+}
+
+pub fn pair(fst: Expr, snd: Expr, range: Range) -> Expr {
+    apply(PAIR, [fst, snd], range)
+}
+
+pub fn fst(pair: Expr, range: Range) -> Expr {
+    apply(FIRST, [pair], range)
+}
+
+pub fn snd(pair: Expr, range: Range) -> Expr {
+    apply(SECOND, [pair], range)
+}
+
+pub fn inl(value: Expr, range: Range) -> Expr {
+    apply("λv. λl. λr. l v", [value], range)
+}
+
+pub fn inr(value: Expr, range: Range) -> Expr {
+    apply("λv. λl. λr. r v", [value], range)
+}
+
+/// The sum applied to the functions of its cases.
+pub fn case(scrutinee: Expr, left: Expr, right: Expr, range: Range) -> Expr {
+    app(app(scrutinee, left, range), right, range)
+}
+
+/// A record whose labels are numbered: the list of the pairs of the number of each label and
+/// the value of its field.
+pub fn record(fields: Vec<(usize, Expr)>, range: Range) -> Expr {
+    fields.into_iter().rev().fold(term("λc. λn. n"), |rest, (label, value)| {
+        let field = pair(numeral(label, range), value, range);
+        apply("λh. λt. λc. λn. c h (t c n)", [field, rest], range)
+    })
+}
+
+pub fn project(record: Expr, label: usize, range: Range) -> Expr {
+    let find = format!(
+        "λl. λr. r (λh. λt. ({eq}) (({first}) h) l (({second}) h) t) (λx. x)",
+        eq = eq(),
+        first = FIRST,
+        second = SECOND,
+    );
+
+    apply(&find, [numeral(label, range), record], range)
+}
+
+/// A variant of a numbered label, which gives the number and its value to a function.
+pub fn variant(label: usize, value: Expr, range: Range) -> Expr {
+    apply("λl. λv. λk. k l v", [numeral(label, range), value], range)
+}
+
+/// The variant applied to a function of a number and a value, which compares the number to
+/// the labels of the arms in turn and runs the first arm that matches, or the last one.
+pub fn switch(scrutinee: Expr, arms: Vec<(usize, Expr)>, range: Range) -> Expr {
+    let eq = eq();
+    let params: Vec<String> = (0..arms.len()).map(|index| format!("a{}", index)).collect();
+
+    let (labels, handlers): (Vec<usize>, Vec<Expr>) = arms.into_iter().unzip();
+    let last = var(params.last().expect("a match has arms"), range);
+
+    let pick = labels.iter().zip(&params).rev().skip(1).fold(last, |otherwise, (label, param)| {
+        let test = apply(&eq, [var("l", range), numeral(*label, range)], range);
+        app(app(test, var(param, range), range), otherwise, range)
+    });
+
+    let dispatch = abs("l", abs("v", app(pick, var("v", range), range), range), range);
+    let dispatch = params.iter().rev().fold(dispatch, |body, param| abs(param, body, range));
+    let dispatch = handlers.into_iter().fold(dispatch, |lambda, argm| app(lambda, argm, range));
+
+    app(scrutinee, dispatch, range)
+}
+
+/// The value an erased term of `sort` evaluates to with `strategy`. The weak strategies stop
+/// at the abstractions the encodings of the data are, so the value is normalized before it is
+/// read.
+pub fn evaluate(strategy: Norm, expr: Expr, sort: Sort) -> Value {
+    let value = reduce(strategy, expr, Some(10_000));
+    let normal = reduce(Norm::NOR, value, Some(1_000_000));
+
+    match (sort, readback::boolean(&normal)) {
+        (Sort::Boolean, Some(value)) => Value::Boolean(value),
+        _ => readback(&normal),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{evaluate, numeral, primitive, Primitive, Sort};
+    use crate::parser::location::{Pos, Range};
+    use crate::reduction::Norm;
+
+    #[test]
+    fn test_encode_primitives() {
+        let range = Range::new(Pos::new(0), Pos::new(0));
+        let cases = [
+            (Primitive::Add, 3, 4, "7"),
+            (Primitive::Sub, 3, 4, "0"),
+            (Primitive::Sub, 7, 3, "4"),
+            (Primitive::Mul, 3, 4, "12"),
+            (Primitive::Div, 7, 2, "3"),
+            (Primitive::Div, 5, 0, "0"),
+            (Primitive::Eq, 3, 3, "true"),
+            (Primitive::Eq, 3, 4, "false"),
+            (Primitive::Lt, 4, 3, "false"),
+            (Primitive::Lt, 3, 4, "true"),
+        ];
+
+        for (op, left, right, expected) in cases {
+            let expr = primitive(op, numeral(left, range), numeral(right, range), range);
+            let sort = match op {
+                Primitive::Eq | Primitive::Lt => Sort::Boolean,
+                _ => Sort::Integer,
+            };
+            let value = evaluate(Norm::NOR, expr, sort);
+
+            assert_eq!(value.to_string(), expected, "{:?} {} {}", op, left, right);
+        }
+    }
+}
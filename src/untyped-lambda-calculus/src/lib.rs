@@ -1,2 +1,5 @@
+pub mod combinators;
+pub mod encodings;
 pub mod parser;
+pub mod prelude;
 pub mod reduction;
@@ -1,15 +1,25 @@
-use untyped_lambda_calculus::parser;
+use untyped_lambda_calculus::prelude;
 use untyped_lambda_calculus::reduction;
+use untyped_lambda_calculus::reduction::definitions::Definitions;
+use untyped_lambda_calculus::reduction::readback::{readback, Value};
 
 fn main() {
-    let input = r"(λf. f f) (λx. x)";
-    let parse = parser::from_string(input);
+    let input = r"
+    double = λn. add n n
+    main = sum (map double (map fact (cons 2 (cons 3 nil))))
+    ";
+    let parse = prelude::load(input);
 
     match parse {
         Err(err) => panic!("{}", err),
         Ok(expr) => {
-            let reduce = reduction::reduce(reduction::Norm::NOR, expr, None);
-            println!("{}", reduce)
+            let definitions = Definitions::of_program(&expr);
+            let reduce = reduction::reduce(reduction::Norm::NOR, expr, Some(1000));
+
+            match readback(&reduce) {
+                Value::Term(term) => println!("{}", definitions.fold(term)),
+                value => println!("{}", value),
+            }
         }
     }
 }
@@ -2,7 +2,7 @@ use super::{
     error::ParserError,
     lexer::tokens::Token,
     macros::{consume, match_token},
-    parsetree::{Abs, App, Expr, Var},
+    parsetree::{Abs, App, Expr, Let, Var},
     state::Parser,
 };
 
@@ -16,10 +16,18 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    // Integer literals are elaborated right away, in the encoding the parser was set up with.
+    pub fn parse_number_expr(&mut self) -> Result<Expr, ParserError> {
+        let (value, range) = consume!(self, Token::Number(num) => *num)?;
+
+        Ok(self.numerals.encode(value, range))
+    }
+
     pub fn parse_atom(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
             Token::LParen => self.parse_parens_expr(),
             Token::Variable(_) => self.parse_variable_expr(),
+            Token::Number(_) => self.parse_number_expr(),
             _ => self.fail(),
         }
     }
@@ -45,8 +53,11 @@ impl<'a> Parser<'a> {
 
         let mut args = Vec::new();
 
-        while let Some(arg) = self.try_single(|state| state.parse_atom())? {
-            args.push(arg);
+        while !self.at_definition() {
+            match self.try_single(|state| state.parse_atom())? {
+                Some(arg) => args.push(arg),
+                None => break,
+            }
         }
 
         if args.is_empty() {
@@ -78,9 +89,79 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
 
+    // A top-level definition starts with `def` or with the `x =` that names it, which is also
+    // where the application before it stops.
+    fn at_definition(&self) -> bool {
+        matches!(self.get(), Token::Def | Token::Main)
+            || matches!((self.get(), self.get_next()), (Token::Variable(_), Token::Equal))
+    }
+
+    pub fn parse_let(&mut self) -> Result<Expr, ParserError> {
+        let (_, range) = consume!(self, Token::Let)?;
+        let (name, _) = consume!(self, Token::Variable(var) => var.clone())?;
+
+        consume!(self, Token::Equal)?;
+        let value = self.parse_expr()?;
+
+        consume!(self, Token::In)?;
+        let body = self.parse_expr()?;
+
+        Ok(Expr::Let(Let {
+            name,
+            value: Box::new(value),
+            range: range.mix(body.range()),
+            body: Box::new(body),
+        }))
+    }
+
+    // `x = e` (or `def x = e`) followed by the rest of the program is `let x = e in rest`, so
+    // every definition is visible to the ones after it and to the `main = e` closing the program.
+    pub fn parse_def(&mut self) -> Result<Expr, ParserError> {
+        let start = match self.get() {
+            Token::Def => Some(consume!(self, Token::Def)?.1),
+            _ => None,
+        };
+        let (name, name_range) = consume!(self, Token::Variable(var) => var.clone())?;
+        let range = start.unwrap_or(name_range);
+
+        consume!(self, Token::Equal)?;
+        let value = self.parse_expr()?;
+
+        if !self.at_definition() {
+            return self.fail();
+        }
+        let body = self.parse_program()?;
+
+        Ok(Expr::Let(Let {
+            name,
+            value: Box::new(value),
+            range: range.mix(body.range()),
+            body: Box::new(body),
+        }))
+    }
+
+    pub fn parse_main(&mut self) -> Result<Expr, ParserError> {
+        consume!(self, Token::Main)?;
+        consume!(self, Token::Equal)?;
+
+        self.parse_expr()
+    }
+
+    // A program is an expression, or declarations closed by `main = e`; `main` is accepted
+    // only here, where a declaration could start.
+    pub fn parse_program(&mut self) -> Result<Expr, ParserError> {
+        match self.get() {
+            Token::Main => self.parse_main(),
+            _ => self.parse_expr(),
+        }
+    }
+
     pub fn parse_expr(&mut self) -> Result<Expr, ParserError> {
         match self.get() {
             Token::Lambda => self.parse_abs(),
+            Token::Let => self.parse_let(),
+            Token::Def => self.parse_def(),
+            Token::Variable(_) if matches!(self.get_next(), Token::Equal) => self.parse_def(),
             _ => self.parse_application(),
         }
     }
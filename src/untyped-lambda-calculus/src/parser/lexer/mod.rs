@@ -8,12 +8,16 @@ fn is_whitespace(c: char) -> bool {
     matches!(c, ' ' | '\t' | '\r')
 }
 
+fn is_digit(c: char) -> bool {
+    c.is_ascii_digit()
+}
+
 fn is_reserved(c: char) -> bool {
-    matches!(c, '(' | ')' | '.' | 'λ')
+    matches!(c, '(' | ')' | '.' | '=' | 'λ')
 }
 
 fn is_valid_char(c: char) -> bool {
-    !is_reserved(c) && !is_whitespace(c)
+    !is_reserved(c) && !is_whitespace(c) && c != '\n'
 }
 
 impl<'a> Lexer<'a> {
@@ -25,6 +29,10 @@ impl<'a> Lexer<'a> {
     fn to_keyword(buf: &str) -> Token {
         match buf {
             "lambda" => Token::Lambda,
+            "let" => Token::Let,
+            "in" => Token::In,
+            "def" => Token::Def,
+            "main" => Token::Main,
             _ => Token::Variable(buf.to_string()),
         }
     }
@@ -48,6 +56,13 @@ impl<'a> Lexer<'a> {
                 '(' => self.single_token(Token::LParen, start),
                 ')' => self.single_token(Token::RParen, start),
                 '.' => self.single_token(Token::Dot, start),
+                '=' => self.single_token(Token::Equal, start),
+                chr if is_digit(*chr) => {
+                    let num = self.accu_while(is_digit);
+                    let num = num.parse::<usize>().unwrap();
+                    let tok = Token::Number(num);
+                    self.make_token(tok, start)
+                }
                 chr if is_valid_char(*chr) => {
                     let str = self.accu_while(is_valid_char);
                     let tok = Lexer::to_keyword(str);
@@ -111,6 +126,52 @@ mod test {
         assert!(test_lex(received, expected))
     }
 
+    #[test]
+    fn test_lex_definition() {
+        let received = "def id = λx. x main = id";
+        let expected = vec![
+            Token::Def,
+            Token::Variable(String::from("id")),
+            Token::Equal,
+            Token::Lambda,
+            Token::Variable(String::from("x")),
+            Token::Dot,
+            Token::Variable(String::from("x")),
+            Token::Main,
+            Token::Equal,
+            Token::Variable(String::from("id")),
+        ];
+
+        assert!(test_lex(received, expected))
+    }
+
+    #[test]
+    fn test_lex_let() {
+        let received = "let x = y in x";
+        let expected = vec![
+            Token::Let,
+            Token::Variable(String::from("x")),
+            Token::Equal,
+            Token::Variable(String::from("y")),
+            Token::In,
+            Token::Variable(String::from("x")),
+        ];
+
+        assert!(test_lex(received, expected))
+    }
+
+    #[test]
+    fn test_lex_number() {
+        let received = "add 3 x4";
+        let expected = vec![
+            Token::Variable(String::from("add")),
+            Token::Number(3),
+            Token::Variable(String::from("x4")),
+        ];
+
+        assert!(test_lex(received, expected))
+    }
+
     #[test]
     fn test_lex_nested_parens() {
         let received = "((()()))";
@@ -1,8 +1,14 @@
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Token {
+    Number(usize),
     Variable(String),
     Lambda,
+    Let,
+    In,
+    Def,
+    Main,
     Dot,
+    Equal,
     LParen,
     RParen,
     Error,
@@ -3,14 +3,24 @@ pub mod expr;
 pub mod lexer;
 pub mod location;
 pub mod macros;
+pub mod numerals;
 pub mod parsetree;
 pub mod state;
 
 use lexer::state::*;
 
 pub fn from_string(str: &str) -> Result<parsetree::Expr, error::ParserError> {
+    from_string_with_numerals(str, numerals::Numerals::default())
+}
+
+/// Parses `str` elaborating its integer literals with the given encoding.
+pub fn from_string_with_numerals(
+    str: &str,
+    numerals: numerals::Numerals,
+) -> Result<parsetree::Expr, error::ParserError> {
     let mut string = str.to_string();
     let mut parser = state::Parser::init(&mut string)?;
+    parser.numerals = numerals;
 
-    return parser.parse_expr();
+    parser.parse_program()
 }
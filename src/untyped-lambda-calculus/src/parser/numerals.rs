@@ -0,0 +1,55 @@
+use super::{
+    location::Range,
+    parsetree::{Abs, App, Expr, Var},
+};
+
+/// How integer literals are elaborated into terms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Numerals {
+    /// `n` is `λf. λx. f (... (f x))`, with `n` applications of `f`.
+    #[default]
+    Church,
+    /// `0` is `λs. λz. z` and `n + 1` is `λs. λz. s n`.
+    Scott,
+    /// Bits, least significant first: `0` is `λz. λo. λe. e`, `2n` is `λz. λo. λe. z n` and
+    /// `2n + 1` is `λz. λo. λe. o n`.
+    Binary,
+}
+
+fn var(name: &str, range: Range) -> Expr {
+    Expr::Var(Var { value: name.to_string(), range })
+}
+
+fn app(lambda: Expr, argm: Expr, range: Range) -> Expr {
+    Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range })
+}
+
+fn abs(params: &[&str], body: Expr, range: Range) -> Expr {
+    params.iter().rev().fold(body, |body, param| {
+        Expr::Abs(Abs { param: param.to_string(), body: Box::new(body), range })
+    })
+}
+
+impl Numerals {
+    pub fn encode(self, value: usize, range: Range) -> Expr {
+        match self {
+            Numerals::Church => {
+                let body = (0..value)
+                    .fold(var("x", range), |body, _| app(var("f", range), body, range));
+                abs(&["f", "x"], body, range)
+            }
+            Numerals::Scott => {
+                let zero = abs(&["s", "z"], var("z", range), range);
+                (0..value).fold(zero, |pred, _| {
+                    abs(&["s", "z"], app(var("s", range), pred, range), range)
+                })
+            }
+            Numerals::Binary if value == 0 => abs(&["z", "o", "e"], var("e", range), range),
+            Numerals::Binary => {
+                let bit = if value.is_multiple_of(2) { "z" } else { "o" };
+                let rest = self.encode(value / 2, range);
+                abs(&["z", "o", "e"], app(var(bit, range), rest, range), range)
+            }
+        }
+    }
+}
@@ -21,19 +21,29 @@ pub struct App {
     pub range: Range,
 }
 
+#[derive(Debug, Clone)]
+pub struct Let {
+    pub name: String,
+    pub value: Box<Expr>,
+    pub body: Box<Expr>,
+    pub range: Range,
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
     Var(Var),
     Abs(Abs),
     App(App),
+    Let(Let),
 }
 
 impl Expr {
     pub fn range(&self) -> Range {
         match self {
-            Expr::Var(Var { range, .. }) => range.clone(),
-            Expr::Abs(Abs { range, .. }) => range.clone(),
-            Expr::App(App { range, .. }) => range.clone(),
+            Expr::Var(Var { range, .. }) => *range,
+            Expr::Abs(Abs { range, .. }) => *range,
+            Expr::App(App { range, .. }) => *range,
+            Expr::Let(Let { range, .. }) => *range,
         }
     }
 }
@@ -56,12 +66,19 @@ impl fmt::Display for App {
     }
 }
 
+impl fmt::Display for Let {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(let {} = {} in {})", self.name, self.value, self.body)
+    }
+}
+
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Expr::Var(var) => write!(f, "{}", var),
             Expr::Abs(abs) => write!(f, "{}", abs),
             Expr::App(app) => write!(f, "{}", app),
+            Expr::Let(def) => write!(f, "{}", def),
         }
     }
 }
@@ -1,10 +1,13 @@
-use crate::parser::{error::ParserError, lexer::tokens::Token, location::Range, Lexer};
+use crate::parser::{
+    error::ParserError, lexer::tokens::Token, location::Range, numerals::Numerals, Lexer,
+};
 
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
     current_token: (Token, Range),
     next_token: (Token, Range),
     after: usize,
+    pub numerals: Numerals,
 }
 
 impl<'a> Parser<'a> {
@@ -18,6 +21,7 @@ impl<'a> Parser<'a> {
             current_token,
             next_token,
             after: Default::default(),
+            numerals: Default::default(),
         })
     }
 
@@ -59,7 +63,7 @@ impl<'a> Parser<'a> {
         match expect(self.get()) {
             None => self.fail(),
             Some(res) => {
-                let range = self.current_token.1.clone();
+                let range = self.current_token.1;
                 self.advance()?;
                 Ok((res, range))
             }
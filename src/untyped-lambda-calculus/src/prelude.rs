@@ -0,0 +1,29 @@
+use crate::parser::{
+    self,
+    error::ParserError,
+    parsetree::{Expr, Let},
+};
+
+/// Definitions every session starts with: a program of `name = term` lines still waiting for
+/// its `main`.
+pub const PRELUDE: &str = include_str!("../prelude.lc");
+
+// `input` takes the place of the body of the last definition.
+fn splice(prelude: Expr, input: Expr) -> Expr {
+    match prelude {
+        Expr::Let(Let { name, value, body, range }) => {
+            Expr::Let(Let { name, value, body: Box::new(splice(*body, input)), range })
+        }
+        _ => input,
+    }
+}
+
+/// Parses `input` in the scope of the prelude definitions, the later definitions seeing the
+/// earlier ones. The two are parsed apart, so the ranges of `input` are the ones of its own
+/// text.
+pub fn load(input: &str) -> Result<Expr, ParserError> {
+    // The `main` holds the place of the input until it is spliced in.
+    let prelude = parser::from_string(&format!("{}main = 0", PRELUDE))?;
+
+    Ok(splice(prelude, parser::from_string(input)?))
+}
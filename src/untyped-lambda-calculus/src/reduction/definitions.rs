@@ -0,0 +1,237 @@
+use super::strategies::normal_order;
+use super::variables::{alpha_equivalent, free_variables, fresh_name, substitution};
+use crate::parser::parsetree::{Abs, App, Expr, Let, Var};
+use std::collections::HashSet;
+
+/// The definitions a reduction has gone through. A definition stays a name until it reaches the
+/// head of the term being reduced, where the reducers unfold it (delta reduction).
+///
+/// Definitions are never dropped: every name is defined once, binders that would shadow one are
+/// renamed instead, so a name left in a result still means the same thing. As they outlive
+/// their `let`, a definition is also renamed apart from the names it could capture there: the
+/// free variables of the term and the parameters of the abstractions the reducers went under.
+#[derive(Debug, Clone)]
+pub struct Definitions {
+    scope: Vec<(String, Expr)>,
+    taken: HashSet<String>,
+}
+
+impl Definitions {
+    /// The definitions of a reduction of `expr`, which has none yet.
+    pub fn new(expr: &Expr) -> Definitions {
+        Definitions { scope: Vec::new(), taken: free_variables(expr.clone()) }
+    }
+
+    /// The `let`s a program opens with, such as its top-level definitions.
+    pub fn of_program(expr: &Expr) -> Definitions {
+        let mut definitions = Definitions::new(expr);
+        let mut expr = expr.clone();
+
+        while let Expr::Let(def) = expr {
+            expr = definitions.define(def);
+        }
+
+        definitions
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Expr> {
+        self.scope.iter().rev().find(|(defined, _)| defined == name).map(|(_, value)| value)
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    // A name for the binder `param` of `body` that is none of `clashes`.
+    fn rename(&self, param: String, body: Expr, clashes: bool) -> (String, Expr) {
+        if !clashes {
+            return (param, body);
+        }
+
+        let mut used = free_variables(body.clone());
+        used.extend(self.scope.iter().map(|(name, _)| name.clone()));
+        used.extend(self.taken.iter().cloned());
+
+        let fresh = fresh_name(&param, &used);
+        let renamed = Expr::Var(Var { value: fresh.clone(), range: body.range() });
+
+        (fresh, substitution(body, param, renamed))
+    }
+
+    /// Brings a `let` into scope and gives back its body. A value that refers to variables
+    /// bound outside of the definitions is substituted right away, as it could be captured
+    /// where it would be unfolded.
+    pub fn define(&mut self, def: Let) -> Expr {
+        let free = free_variables(*def.value.clone());
+
+        if !free.iter().all(|name| self.contains(name)) {
+            return substitution(*def.body, def.name, *def.value);
+        }
+
+        let clashes = self.contains(&def.name) || self.taken.contains(&def.name);
+        let (name, body) = self.rename(def.name, *def.body, clashes);
+        self.scope.push((name, *def.value));
+
+        body
+    }
+
+    /// Keeps the parameter of an abstraction the reducers go under from shadowing a definition,
+    /// and the definitions to come from capturing it.
+    pub fn enter(&mut self, abs: Abs) -> Abs {
+        let clashes = self.contains(&abs.param);
+        let (param, body) = self.rename(abs.param, *abs.body, clashes);
+        self.taken.insert(param.clone());
+
+        Abs { param, body: Box::new(body), ..abs }
+    }
+
+    /// Binds again the definitions `expr` still refers to.
+    pub fn close(&self, expr: Expr) -> Expr {
+        self.scope.iter().rev().fold(expr, |body, (name, value)| {
+            if !free_variables(body.clone()).contains(name) {
+                return body;
+            }
+
+            Expr::Let(Let {
+                name: name.clone(),
+                value: Box::new(value.clone()),
+                range: body.range(),
+                body: Box::new(body),
+            })
+        })
+    }
+
+    /// Writes every subterm of `expr` that is the normal form of a definition as the name of
+    /// that definition, the earliest one when several agree. Definitions without a normal form
+    /// within a few steps, like `fix`, are left out.
+    pub fn fold(&self, expr: Expr) -> Expr {
+        let normal_forms: Vec<(&String, Expr)> = self
+            .scope
+            .iter()
+            .map(|(name, value)| (name, normal_order(value.clone(), &mut self.clone(), Some(32))))
+            .filter(|(_, normal)| !matches!(normal, Expr::Var(_)) && self.is_normal(normal))
+            .collect();
+
+        fold_with(&normal_forms, expr)
+    }
+
+    fn is_normal(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Var(Var { value, .. }) => !self.contains(value),
+            Expr::Abs(Abs { body, .. }) => self.is_normal(body),
+            Expr::App(App { lambda, argm, .. }) => {
+                !matches!(**lambda, Expr::Abs(_)) && self.is_normal(lambda) && self.is_normal(argm)
+            }
+            Expr::Let(_) => false,
+        }
+    }
+}
+
+fn fold_with(normal_forms: &[(&String, Expr)], expr: Expr) -> Expr {
+    let found = normal_forms.iter().find(|(_, normal)| alpha_equivalent(normal, &expr));
+
+    if let Some((name, _)) = found {
+        return Expr::Var(Var { value: name.to_string(), range: expr.range() });
+    }
+
+    match expr {
+        Expr::Var(var) => Expr::Var(var),
+        Expr::Abs(abs) => {
+            let body = fold_with(normal_forms, *abs.body);
+            Expr::Abs(Abs { body: Box::new(body), ..abs })
+        }
+        Expr::App(app) => {
+            let lambda = fold_with(normal_forms, *app.lambda);
+            let argm = fold_with(normal_forms, *app.argm);
+
+            Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), ..app })
+        }
+        Expr::Let(def) => {
+            let value = fold_with(normal_forms, *def.value);
+            let body = fold_with(normal_forms, *def.body);
+
+            Expr::Let(Let { value: Box::new(value), body: Box::new(body), ..def })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Definitions;
+    use crate::parser::parsetree::Expr;
+    use crate::reduction::{reduce, Norm};
+    use crate::{parser, prelude};
+
+    fn eval_expr(strategy: Norm, input: &str) -> Expr {
+        let expr = parser::from_string(input).unwrap();
+        reduce(strategy, expr, None)
+    }
+
+    fn eval(strategy: Norm, input: &str) -> String {
+        eval_expr(strategy, input).to_string()
+    }
+
+    #[test]
+    fn test_reduce_let() {
+        let input = r"let id = λx. x in let const = λx. λy. x in const id";
+
+        assert_eq!(eval(Norm::NOR, input), "(λy. (λx. x))");
+        assert_eq!(eval(Norm::CBN, input), "(let id = (λx. x) in (λy. id))")
+    }
+
+    #[test]
+    fn test_reduce_definitions() {
+        let input = r"
+        I = λx. x
+        K = λx. λy. x
+        main = K I (λx. x x) (λx. x x)
+        ";
+
+        assert_eq!(eval(Norm::CBN, input), "(λx. (x x))")
+    }
+
+    #[test]
+    fn test_reduce_shadowed_definition() {
+        let input = r"
+        I = λx. x
+        main = λI. I
+        ";
+
+        assert_eq!(eval(Norm::NOR, input), "(λI'. I')")
+    }
+
+    // The definition outlives its `let`, so it must not capture the free `y` next to it, nor
+    // the parameter `y` of an abstraction around it.
+    #[test]
+    fn test_reduce_let_in_argument() {
+        let strategies = [
+            Norm::NOR,
+            Norm::APP,
+            Norm::CBN,
+            Norm::CBV,
+            Norm::KAM,
+            Norm::CEK,
+            Norm::NBE,
+        ];
+        let inputs = [
+            (r"f (let y = λa. a in y) y", "((f (λa. a)) y)"),
+            (r"λy. f (let y = λa. a in y) y", "(λy. ((f (λa. a)) y))"),
+        ];
+
+        for (input, expected) in inputs {
+            for strategy in strategies {
+                let value = reduce(Norm::NOR, eval_expr(strategy, input), None);
+                assert_eq!(value.to_string(), expected, "{} {:?}", input, strategy);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fold_definitions() {
+        let expr = prelude::load("pair (succ one) (λx. not x)").unwrap();
+        let definitions = Definitions::of_program(&expr);
+        let reduced = reduce(Norm::NOR, expr, Some(1000));
+
+        assert_eq!(definitions.fold(reduced).to_string(), "(λs. ((s two) not))")
+    }
+}
@@ -0,0 +1,143 @@
+use super::{apply, readback, Closure, Env};
+use crate::parser::parsetree::{Abs, App, Expr, Let, Var};
+use crate::reduction::variables::free_variables;
+use std::collections::HashSet;
+use std::fmt;
+
+/// What the machine is doing: evaluating a term, or handing a value to the continuation.
+/// Values are closures of abstractions, or neutral terms.
+#[derive(Debug, Clone)]
+pub enum Control<'a> {
+    Eval(&'a Expr, Env<'a>),
+    Apply(Closure<'a>),
+}
+
+/// What is left to do with a value.
+#[derive(Debug, Clone)]
+pub enum Frame<'a> {
+    /// Evaluate the argument of the function the value is.
+    Arg(&'a Expr, Env<'a>),
+    /// Pass the value to a function already evaluated.
+    Fun(Closure<'a>),
+}
+
+/// The CEK machine, which reduces a term to its weak normal form by call-by-value.
+///
+/// Definitions are bound unevaluated and evaluated where they are used, like a delta
+/// reduction, so that an unused definition which does not terminate is harmless.
+pub struct Cek<'a> {
+    pub control: Control<'a>,
+    /// The continuation, its innermost frame last.
+    pub continuation: Vec<Frame<'a>>,
+    /// The beta reductions taken so far.
+    pub steps: usize,
+    free: HashSet<String>,
+}
+
+impl<'a> Cek<'a> {
+    pub fn new(expr: &'a Expr) -> Cek<'a> {
+        Cek {
+            control: Control::Eval(expr, Env::new()),
+            continuation: Vec::new(),
+            steps: 0,
+            free: free_variables(expr.clone()),
+        }
+    }
+
+    fn eval(&mut self, term: &'a Expr, env: Env<'a>) -> Control<'a> {
+        match term {
+            Expr::Var(var) => match env.lookup(&var.value) {
+                // Abstractions evaluate to themselves, definitions are evaluated now.
+                Some(Closure::Term(term, env)) => Control::Eval(term, env.clone()),
+                Some(neutral) => Control::Apply(neutral.clone()),
+                None => Control::Apply(Closure::neutral(Expr::Var(Var::clone(var)))),
+            },
+            Expr::Abs(_) => Control::Apply(Closure::Term(term, env)),
+            Expr::App(App { lambda, argm, .. }) => {
+                self.continuation.push(Frame::Arg(argm, env.clone()));
+                Control::Eval(lambda, env)
+            }
+            Expr::Let(Let { name, value, body, .. }) => {
+                let value = Closure::Term(value, env.clone());
+                Control::Eval(body, env.bind(name, value))
+            }
+        }
+    }
+
+    fn apply(&mut self, value: Closure<'a>, frame: Frame<'a>) -> Control<'a> {
+        match frame {
+            Frame::Arg(argm, env) => {
+                self.continuation.push(Frame::Fun(value));
+                Control::Eval(argm, env)
+            }
+            Frame::Fun(Closure::Term(Expr::Abs(Abs { param, body, .. }), env)) => {
+                self.steps += 1;
+                Control::Eval(body, env.bind(param, value))
+            }
+            Frame::Fun(neutral) => {
+                let lambda = readback(&neutral, &self.free);
+                let argm = readback(&value, &self.free);
+
+                Control::Apply(Closure::neutral(apply(lambda, argm)))
+            }
+        }
+    }
+
+    /// Takes one transition, or gives back false when the machine has stopped.
+    pub fn step(&mut self) -> bool {
+        self.control = match self.control.clone() {
+            Control::Eval(term, env) => self.eval(term, env),
+            Control::Apply(value) => match self.continuation.pop() {
+                Some(frame) => self.apply(value, frame),
+                None => return false,
+            },
+        };
+
+        true
+    }
+
+    /// Runs until the machine stops or has taken `limit` beta reductions.
+    pub fn run(&mut self, limit: Option<usize>) {
+        while limit.is_none_or(|limit| self.steps < limit) && self.step() {}
+    }
+
+    /// The term the machine is at: the control plugged into the continuation.
+    pub fn readback(&self) -> Expr {
+        let control = match &self.control {
+            Control::Eval(term, env) => readback(&Closure::Term(term, env.clone()), &self.free),
+            Control::Apply(value) => readback(value, &self.free),
+        };
+
+        self.continuation.iter().rev().fold(control, |hole, frame| match frame {
+            Frame::Arg(argm, env) => {
+                apply(hole, readback(&Closure::Term(argm, env.clone()), &self.free))
+            }
+            Frame::Fun(lambda) => apply(readback(lambda, &self.free), hole),
+        })
+    }
+}
+
+impl fmt::Display for Control<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Control::Eval(term, env) => write!(f, "eval {}", Closure::Term(term, env.clone())),
+            Control::Apply(value) => write!(f, "apply {}", value),
+        }
+    }
+}
+
+impl fmt::Display for Frame<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Frame::Arg(argm, env) => write!(f, "□ {}", Closure::Term(argm, env.clone())),
+            Frame::Fun(lambda) => write!(f, "{} □", lambda),
+        }
+    }
+}
+
+impl fmt::Display for Cek<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let frames: Vec<String> = self.continuation.iter().rev().map(|f| f.to_string()).collect();
+        write!(f, "⟨{} | [{}]⟩", self.control, frames.join(", "))
+    }
+}
@@ -0,0 +1,88 @@
+use super::{apply, readback, Closure, Env};
+use crate::parser::parsetree::{Abs, App, Expr, Let, Var};
+use crate::reduction::variables::free_variables;
+use std::collections::HashSet;
+use std::fmt;
+
+/// The Krivine machine, which reduces a term to its weak head normal form by call-by-name.
+/// Arguments wait on the stack as closures and are only evaluated when their variable reaches
+/// the head of the term.
+pub struct Krivine<'a> {
+    /// The term at the head, with its environment.
+    pub closure: Closure<'a>,
+    /// The arguments the head is applied to, the first one on top.
+    pub stack: Vec<Closure<'a>>,
+    /// The beta reductions taken so far.
+    pub steps: usize,
+    free: HashSet<String>,
+}
+
+impl<'a> Krivine<'a> {
+    pub fn new(expr: &'a Expr) -> Krivine<'a> {
+        Krivine {
+            closure: Closure::Term(expr, Env::new()),
+            stack: Vec::new(),
+            steps: 0,
+            free: free_variables(expr.clone()),
+        }
+    }
+
+    /// Takes one transition, or gives back false when the machine has stopped.
+    pub fn step(&mut self) -> bool {
+        let (term, env) = match &self.closure {
+            Closure::Term(term, env) => (*term, env.clone()),
+            Closure::Neutral(_) => return false,
+        };
+
+        self.closure = match term {
+            Expr::App(App { lambda, argm, .. }) => {
+                // A variable is pushed as the closure it is bound to, so that looping terms do
+                // not build up chains of variables bound to variables.
+                let bound = match &**argm {
+                    Expr::Var(Var { value, .. }) => env.lookup(value).cloned(),
+                    _ => None,
+                };
+
+                self.stack.push(bound.unwrap_or_else(|| Closure::Term(argm, env.clone())));
+                Closure::Term(lambda, env)
+            }
+            Expr::Abs(Abs { param, body, .. }) => match self.stack.pop() {
+                Some(argm) => {
+                    self.steps += 1;
+                    Closure::Term(body, env.bind(param, argm))
+                }
+                None => return false,
+            },
+            Expr::Var(Var { value, .. }) => match env.lookup(value) {
+                Some(closure) => closure.clone(),
+                None => return false,
+            },
+            Expr::Let(Let { name, value, body, .. }) => {
+                let value = Closure::Term(value, env.clone());
+                Closure::Term(body, env.bind(name, value))
+            }
+        };
+
+        true
+    }
+
+    /// Runs until the machine stops or has taken `limit` beta reductions.
+    pub fn run(&mut self, limit: Option<usize>) {
+        while limit.is_none_or(|limit| self.steps < limit) && self.step() {}
+    }
+
+    /// The term the machine is at: the head applied to the stack.
+    pub fn readback(&self) -> Expr {
+        let head = readback(&self.closure, &self.free);
+        let args = self.stack.iter().rev().map(|argm| readback(argm, &self.free));
+
+        args.fold(head, apply)
+    }
+}
+
+impl fmt::Display for Krivine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let args: Vec<String> = self.stack.iter().rev().map(|argm| argm.to_string()).collect();
+        write!(f, "⟨{} | [{}]⟩", self.closure, args.join(", "))
+    }
+}
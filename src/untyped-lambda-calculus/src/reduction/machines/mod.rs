@@ -0,0 +1,250 @@
+//! Abstract machines that evaluate a term in place, with environments instead of substitution:
+//! the Krivine machine for call-by-name and the CEK machine for call-by-value.
+//!
+//! Every term a machine handles is a subterm of the one it started with, so closures borrow
+//! it, and environments are linked lists shared between closures. A state is read back as a
+//! term by substituting its environments.
+
+use super::variables::fresh_name;
+use crate::parser::parsetree::{Abs, App, Expr, Let, Var};
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+
+pub mod cek;
+pub mod krivine;
+
+/// What the variables of a closure are bound to, innermost binding first.
+#[derive(Debug, Clone, Default)]
+pub struct Env<'a>(Option<Rc<Binding<'a>>>);
+
+#[derive(Debug)]
+struct Binding<'a> {
+    name: &'a str,
+    value: Closure<'a>,
+    next: Env<'a>,
+}
+
+impl<'a> Env<'a> {
+    pub fn new() -> Env<'a> {
+        Env::default()
+    }
+
+    pub fn bind(&self, name: &'a str, value: Closure<'a>) -> Env<'a> {
+        Env(Some(Rc::new(Binding { name, value, next: self.clone() })))
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&Closure<'a>> {
+        let mut env = self;
+
+        while let Some(binding) = &env.0 {
+            if binding.name == name {
+                return Some(&binding.value);
+            }
+
+            env = &binding.next;
+        }
+
+        None
+    }
+
+    /// The bound names, innermost first, shadowed ones included.
+    pub fn names(&self) -> Vec<&'a str> {
+        let mut names = Vec::new();
+        let mut env = self;
+
+        while let Some(binding) = &env.0 {
+            names.push(binding.name);
+            env = &binding.next;
+        }
+
+        names
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Closure<'a> {
+    /// A subterm of the program with the environment it appeared in.
+    Term(&'a Expr, Env<'a>),
+    /// A term stuck on a free variable, which needs no environment.
+    Neutral(Rc<Expr>),
+}
+
+impl<'a> Closure<'a> {
+    pub fn neutral(expr: Expr) -> Closure<'a> {
+        Closure::Neutral(Rc::new(expr))
+    }
+}
+
+impl fmt::Display for Closure<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Closure::Term(term, env) => {
+                write!(f, "{}", term)?;
+
+                let names = env.names();
+                if !names.is_empty() {
+                    write!(f, " {{{}}}", names.join(", "))?;
+                }
+
+                Ok(())
+            }
+            Closure::Neutral(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+// Substitutes the environment of `term`. Binders named like a free variable of the program are
+// renamed: every other variable a value brings in is bound in the environment it came from.
+fn readback_term(term: &Expr, env: &Env, free: &HashSet<String>) -> Expr {
+    let binder = |name: &String| {
+        let fresh = fresh_name(name, free);
+        let var = Expr::Var(Var { value: fresh.clone(), range: term.range() });
+
+        (fresh, Closure::neutral(var))
+    };
+
+    match term {
+        Expr::Var(var) => match env.lookup(&var.value) {
+            Some(value) => readback(value, free),
+            None => Expr::Var(var.clone()),
+        },
+        Expr::Abs(Abs { param, body, range }) => {
+            let (fresh, var) = binder(param);
+            let body = readback_term(body, &env.bind(param, var), free);
+
+            Expr::Abs(Abs { param: fresh, body: Box::new(body), range: *range })
+        }
+        Expr::App(App { lambda, argm, range }) => Expr::App(App {
+            lambda: Box::new(readback_term(lambda, env, free)),
+            argm: Box::new(readback_term(argm, env, free)),
+            range: *range,
+        }),
+        Expr::Let(Let { name, value, body, range }) => {
+            let value = readback_term(value, env, free);
+            let (fresh, var) = binder(name);
+            let body = readback_term(body, &env.bind(name, var), free);
+
+            Expr::Let(Let {
+                name: fresh,
+                value: Box::new(value),
+                body: Box::new(body),
+                range: *range,
+            })
+        }
+    }
+}
+
+/// The term a closure stands for, given the free variables of the program it comes from.
+/// Definitions it refers to are substituted.
+pub fn readback(closure: &Closure, free: &HashSet<String>) -> Expr {
+    match closure {
+        Closure::Term(term, env) => readback_term(term, env, free),
+        Closure::Neutral(expr) => (**expr).clone(),
+    }
+}
+
+fn apply(lambda: Expr, argm: Expr) -> Expr {
+    let range = lambda.range();
+    Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cek::Cek, krivine::Krivine};
+    use crate::parser;
+    use crate::reduction::variables::alpha_equivalent;
+    use crate::reduction::{reduce, Norm};
+
+    fn eval(strategy: Norm, input: &str) -> String {
+        let expr = parser::from_string(input).unwrap();
+        reduce(strategy, expr, None).to_string()
+    }
+
+    #[test]
+    fn test_machines_agree_with_substitution() {
+        let inputs = [
+            r"(λx. λy. x) a b",
+            r"(λf. f (f a)) (λx. x)",
+            r"x ((λz. z) a)",
+            r"(λx. λy. y x) ((λz. z) a)",
+            r"(λx. λy. x y) y",
+            r"(λx. λa. x) (f a)",
+            r"(λx. λy. y) (λx. x x)",
+            r"(λn. λf. λx. f (n f x)) (λf. λx. f x)",
+        ];
+
+        for input in inputs {
+            assert_eq!(eval(Norm::KAM, input), eval(Norm::CBN, input), "{}", input);
+            assert_eq!(eval(Norm::CEK, input), eval(Norm::CBV, input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_strategies_stop_without_limit() {
+        let omega = parser::from_string(r"(λx. x x) (λx. x x)").unwrap();
+        let strategies =
+            [Norm::NOR, Norm::APP, Norm::CBN, Norm::CBV, Norm::KAM, Norm::CEK, Norm::NBE];
+
+        for strategy in strategies {
+            let expr = reduce(strategy, omega.clone(), None);
+            assert!(alpha_equivalent(&expr, &omega), "{:?} gave {}", strategy, expr);
+        }
+    }
+
+    #[test]
+    fn test_machines_unfold_definitions() {
+        let input = r"
+        I = λx. x
+        K = λx. λy. x
+        omega = (λx. x x) (λx. x x)
+        main = K I K
+        ";
+
+        assert_eq!(eval(Norm::KAM, input), "(λx. x)");
+        assert_eq!(eval(Norm::CEK, input), "(λx. x)");
+    }
+
+    #[test]
+    fn test_krivine_states() {
+        let expr = parser::from_string(r"(λx. x) y").unwrap();
+        let mut machine = Krivine::new(&expr);
+        let mut states = vec![machine.to_string()];
+
+        while machine.step() {
+            states.push(machine.to_string());
+        }
+
+        assert_eq!(states, ["⟨((λx. x) y) | []⟩", "⟨(λx. x) | [y]⟩", "⟨x {x} | []⟩", "⟨y | []⟩"]);
+        assert_eq!(machine.readback().to_string(), "y");
+    }
+
+    #[test]
+    fn test_cek_states() {
+        let expr = parser::from_string(r"f ((λx. x) y)").unwrap();
+        let mut machine = Cek::new(&expr);
+        machine.step();
+        machine.step();
+
+        assert_eq!(machine.to_string(), "⟨apply f | [□ ((λx. x) y)]⟩");
+        machine.run(None);
+        assert_eq!(machine.readback().to_string(), "(f y)");
+    }
+
+    #[test]
+    fn test_machines_scale() {
+        // A thousand times a thousand applications of the identity.
+        let input = r"(λm. λn. λf. m (n f)) 1000 1000 (λx. x) y";
+        let expr = parser::from_string(input).unwrap();
+
+        let mut krivine = Krivine::new(&expr);
+        krivine.run(None);
+        assert_eq!(krivine.readback().to_string(), "y");
+        assert!(krivine.steps > 1_000_000);
+
+        let mut cek = Cek::new(&expr);
+        cek.run(None);
+        assert_eq!(cek.readback().to_string(), "y");
+        assert!(cek.steps > 1_000_000);
+    }
+}
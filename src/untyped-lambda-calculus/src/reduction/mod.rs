@@ -1,22 +1,55 @@
 use crate::parser::parsetree::Expr;
+use definitions::Definitions;
+use machines::{cek::Cek, krivine::Krivine};
 
+pub mod definitions;
+pub mod machines;
+pub mod nbe;
+pub mod optimal;
+pub mod readback;
 pub mod strategies;
 pub mod variables;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Norm {
     NOR, // normal-order
     APP, // applicative-order
     CBN, // call-by-name
     CBV, // call-by-value
+    KAM, // call-by-name on the Krivine machine
+    CEK, // call-by-value on the CEK machine
+    NBE, // normal forms by evaluation
 }
 
+/// The reductions the machines and normalization by evaluation take when given no limit.
+pub const STEPS: usize = 1_000_000;
+
 pub fn reduce(strategy: Norm, expr: Expr, limit: Option<usize>) -> Expr {
-    let limit = Some(limit.unwrap_or(100));
+    // The strategies that substitute bound how deeply reductions nest, the other back-ends
+    // bound how many reductions there are. Without a limit each gets a bound of its own, so
+    // that every strategy stops, even on Ω.
+    let depth = Some(limit.unwrap_or(100));
+    let steps = Some(limit.unwrap_or(STEPS));
+    let mut definitions = Definitions::new(&expr);
+
+    let expr = match strategy {
+        Norm::NOR => strategies::normal_order(expr, &mut definitions, depth),
+        Norm::APP => strategies::applicative_order(expr, &mut definitions, depth),
+        Norm::CBN => strategies::call_by_name(expr, &mut definitions, depth),
+        Norm::CBV => strategies::call_by_value(expr, &mut definitions, depth),
+        Norm::KAM => {
+            let mut machine = Krivine::new(&expr);
+            machine.run(steps);
+            machine.readback()
+        }
+        Norm::CEK => {
+            let mut machine = Cek::new(&expr);
+            machine.run(steps);
+            machine.readback()
+        }
+        Norm::NBE => nbe::normalize(&expr, steps),
+    };
 
-    match strategy {
-        Norm::NOR => strategies::normal_order(expr, limit),
-        Norm::APP => strategies::applicative_order(expr, limit),
-        Norm::CBN => strategies::call_by_name(expr, limit),
-        Norm::CBV => strategies::call_by_value(expr, limit),
-    }
+    // The definitions that were not unfolded are still needed to make sense of the result.
+    definitions.close(expr)
 }
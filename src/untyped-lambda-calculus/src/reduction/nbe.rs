@@ -0,0 +1,255 @@
+//! Normalisation by evaluation: a term is evaluated into values whose abstractions are closures,
+//! and the values are read back into terms, evaluating the bodies of the closures on fresh
+//! variables. Arguments are evaluated lazily and at most once, which leaves a normal form as
+//! easy to reach as normal order does.
+
+use super::variables::{free_variables, fresh_name};
+use crate::parser::location::Range;
+use crate::parser::parsetree::{Abs, App, Expr, Let, Var};
+use std::cell::{Cell, OnceCell};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// A term evaluated the first time it is needed. `T` is what it is evaluated from and `V` the
+/// value it evaluates to, so the typed calculi evaluate their own terms with it as well.
+pub struct Thunk<T, V> {
+    pending: Cell<Option<T>>,
+    value: OnceCell<V>,
+}
+
+impl<T, V: Clone> Thunk<T, V> {
+    pub fn new(pending: T) -> Rc<Thunk<T, V>> {
+        Rc::new(Thunk { pending: Cell::new(Some(pending)), value: OnceCell::new() })
+    }
+
+    pub fn ready(value: V) -> Rc<Thunk<T, V>> {
+        Rc::new(Thunk { pending: Cell::new(None), value: OnceCell::from(value) })
+    }
+
+    /// The value of the thunk, which `eval` computes the first time it is needed.
+    pub fn force(&self, eval: impl FnOnce(T) -> V) -> V {
+        if let Some(value) = self.value.get() {
+            return value.clone();
+        }
+
+        let pending = self.pending.take().expect("a thunk is not forced from itself");
+        let value = eval(pending);
+
+        self.value.get_or_init(|| value).clone()
+    }
+}
+
+/// The bindings of an environment, innermost first, shared by every closure that captures them.
+pub struct Env<'a, B>(Option<Rc<(&'a str, B, Env<'a, B>)>>);
+
+impl<B> Clone for Env<'_, B> {
+    fn clone(&self) -> Self {
+        Env(self.0.clone())
+    }
+}
+
+impl<B> Default for Env<'_, B> {
+    fn default() -> Self {
+        Env(None)
+    }
+}
+
+impl<'a, B> Env<'a, B> {
+    pub fn bind(&self, name: &'a str, binding: B) -> Env<'a, B> {
+        Env(Some(Rc::new((name, binding, self.clone()))))
+    }
+
+    /// The innermost binding `found` accepts.
+    pub fn find(&self, found: impl Fn(&str, &B) -> bool) -> Option<&B> {
+        let mut env = self;
+
+        while let Some(binding) = &env.0 {
+            if found(binding.0, &binding.1) {
+                return Some(&binding.1);
+            }
+
+            env = &binding.2;
+        }
+
+        None
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&B> {
+        self.find(|bound, _| bound == name)
+    }
+}
+
+#[derive(Clone)]
+enum Value<'a> {
+    /// An abstraction with the environment it was evaluated in.
+    Lam(&'a Abs, Scope<'a>),
+    Neutral(Rc<Neutral<'a>>),
+}
+
+/// A value that cannot be reduced further, headed by a free variable or by a redex left once
+/// the limit was reached.
+enum Neutral<'a> {
+    Var(Var),
+    App(Value<'a>, Rc<Argument<'a>>, Range),
+}
+
+/// A term with the environment it is evaluated in.
+struct Suspended<'a>(&'a Expr, Scope<'a>);
+
+type Argument<'a> = Thunk<Suspended<'a>, Value<'a>>;
+
+type Scope<'a> = Env<'a, Rc<Argument<'a>>>;
+
+struct Evaluator {
+    limit: Option<usize>,
+    // The names read back so far that a binder must not take: the free variables of the term
+    // and the binders it is read back under.
+    used: HashSet<String>,
+}
+
+impl Evaluator {
+    // Counts a beta reduction, or tells that the limit was reached.
+    fn tick(&mut self) -> bool {
+        match self.limit {
+            Some(0) => false,
+            Some(count) => {
+                self.limit = Some(count - 1);
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn force<'a>(&mut self, thunk: &Argument<'a>) -> Value<'a> {
+        thunk.force(|Suspended(term, env)| self.eval(term, env))
+    }
+
+    // Arguments that are variables share the thunk they are bound to.
+    fn delay<'a>(&self, term: &'a Expr, env: &Scope<'a>) -> Rc<Argument<'a>> {
+        match term {
+            Expr::Var(Var { value, .. }) => match env.lookup(value) {
+                Some(thunk) => thunk.clone(),
+                None => Thunk::new(Suspended(term, env.clone())),
+            },
+            _ => Thunk::new(Suspended(term, env.clone())),
+        }
+    }
+
+    // Applications in tail position loop instead of recursing, so that a long chain of beta
+    // reductions does not grow the stack.
+    fn eval<'a>(&mut self, mut term: &'a Expr, mut env: Scope<'a>) -> Value<'a> {
+        loop {
+            match term {
+                Expr::Var(var) => {
+                    return match env.lookup(&var.value) {
+                        Some(thunk) => self.force(&thunk.clone()),
+                        None => Value::Neutral(Rc::new(Neutral::Var(var.clone()))),
+                    }
+                }
+                Expr::Abs(abs) => return Value::Lam(abs, env),
+                Expr::App(App { lambda, argm, range }) => {
+                    let argm = self.delay(argm, &env);
+
+                    match self.eval(lambda, env) {
+                        Value::Lam(abs, closure) if self.tick() => {
+                            term = &abs.body;
+                            env = closure.bind(&abs.param, argm);
+                        }
+                        lambda => {
+                            return Value::Neutral(Rc::new(Neutral::App(lambda, argm, *range)))
+                        }
+                    }
+                }
+                Expr::Let(Let { name, value, body, .. }) => {
+                    let value = self.delay(value, &env);
+
+                    term = body;
+                    env = env.bind(name, value);
+                }
+            }
+        }
+    }
+
+    fn quote(&mut self, value: Value) -> Expr {
+        match value {
+            Value::Lam(abs, env) => {
+                let param = fresh_name(&abs.param, &self.used);
+                let var = Var { value: param.clone(), range: abs.range };
+                let var = Thunk::ready(Value::Neutral(Rc::new(Neutral::Var(var))));
+
+                let body = self.eval(&abs.body, env.bind(&abs.param, var));
+                self.used.insert(param.clone());
+                let body = self.quote(body);
+                self.used.remove(&param);
+
+                Expr::Abs(Abs { param, body: Box::new(body), range: abs.range })
+            }
+            Value::Neutral(neutral) => match &*neutral {
+                Neutral::Var(var) => Expr::Var(var.clone()),
+                Neutral::App(lambda, argm, range) => {
+                    let lambda = self.quote(lambda.clone());
+                    let argm = self.force(argm);
+
+                    Expr::App(App {
+                        lambda: Box::new(lambda),
+                        argm: Box::new(self.quote(argm)),
+                        range: *range,
+                    })
+                }
+            },
+        }
+    }
+}
+
+/// The normal form of `expr`, after at most `limit` beta reductions when there is one. The
+/// redexes left when the limit is reached are read back as they are.
+pub fn normalize(expr: &Expr, limit: Option<usize>) -> Expr {
+    let mut evaluator = Evaluator { limit, used: free_variables(expr.clone()) };
+    let value = evaluator.eval(expr, Scope::default());
+
+    evaluator.quote(value)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reduction::readback::readback;
+    use crate::reduction::variables::alpha_equivalent;
+    use crate::reduction::{reduce, Norm};
+    use crate::{parser, prelude};
+
+    #[test]
+    fn test_nbe_agrees_with_normal_order() {
+        let inputs = [
+            r"λx. x",
+            r"(λx. λy. x) y",
+            r"λx. λx. x",
+            r"(λf. λx. f (f x)) (λf. λx. f (f x))",
+            r"(λx. λy. y) ((λx. x x) (λx. x x))",
+            r"let id = λx. x in λy. id y id",
+            r"map fact (cons two (cons (succ two) nil))",
+            r"λp. first (pair p (λx. x))",
+        ];
+
+        for input in inputs {
+            let expr = prelude::load(input).unwrap();
+            let normal = reduce(Norm::NOR, expr.clone(), Some(1000));
+            let evaluated = reduce(Norm::NBE, expr, None);
+
+            assert!(alpha_equivalent(&normal, &evaluated), "{} is not {}", evaluated, normal);
+        }
+    }
+
+    #[test]
+    fn test_nbe_church_arithmetic() {
+        let expr = prelude::load(r"fact 6").unwrap();
+
+        assert_eq!(readback(&reduce(Norm::NBE, expr, None)).to_string(), "720")
+    }
+
+    #[test]
+    fn test_nbe_limit() {
+        let expr = parser::from_string(r"(λx. x x) (λx. x x)").unwrap();
+
+        assert_eq!(reduce(Norm::NBE, expr, Some(10)).to_string(), "((λx. (x x)) (λx. (x x)))")
+    }
+}
@@ -0,0 +1,829 @@
+//! Optimal reduction by Lamping's algorithm: a term is translated to an interaction net, where
+//! a subterm used several times is shared behind duplicators instead of copied, and the net is
+//! rewritten by local interactions until no two nodes face each other.
+//!
+//! Every node sits at a level, the number of arguments it is inside of. The argument of an
+//! application is one level below the application, and the variables it takes from outside
+//! reach their binders through brackets, while each use of a variable goes through a croissant.
+//! A node passing a bracket goes one level down and one passing a croissant one level up, so
+//! that two duplicators annihilate when they meet at the same level and copy each other
+//! otherwise. A bracket whose term goes straight on through a croissant that undoes it is
+//! dropped with the croissant, which keeps chains of them from building up. The read-back
+//! follows paths through the net with the context semantics of Gonthier, Abadi and Lévy, which
+//! tells a path at a duplicator which copy it came in by.
+
+use super::variables::{free_variables, fresh_name};
+use crate::parser::location::Range;
+use crate::parser::parsetree::{Abs, App, Expr, Let, Var};
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// Holds the term the net stands for on its first port.
+    Root,
+    /// An abstraction at a level: its term, its variable and its body.
+    Lam(usize),
+    /// An application at a level: the function, the argument and the result.
+    App(usize),
+    /// A duplicator at a level: the shared term and its two copies.
+    Dup(usize),
+    /// Takes the term on its principal port one level down, into an argument.
+    Bracket(usize),
+    /// Takes the term on its principal port one level up, to a use of a variable.
+    Croissant(usize),
+    /// Erases whatever it faces.
+    Era,
+    /// A free variable, which has no other port than its term.
+    Free,
+}
+
+impl Kind {
+    fn arity(self) -> usize {
+        match self {
+            Kind::Lam(_) | Kind::App(_) | Kind::Dup(_) => 3,
+            Kind::Bracket(_) | Kind::Croissant(_) => 2,
+            Kind::Root | Kind::Era | Kind::Free => 1,
+        }
+    }
+
+    // Duplicators, brackets and croissants only move terms around, and have their principal
+    // port towards the term they move.
+    fn is_control(self) -> bool {
+        matches!(self, Kind::Dup(_) | Kind::Bracket(_) | Kind::Croissant(_))
+    }
+
+    fn level(self) -> usize {
+        match self {
+            Kind::Lam(level)
+            | Kind::App(level)
+            | Kind::Dup(level)
+            | Kind::Bracket(level)
+            | Kind::Croissant(level) => level,
+            Kind::Root | Kind::Era | Kind::Free => 0,
+        }
+    }
+
+    // The node once it went past `control`.
+    fn past(self, control: Kind) -> Kind {
+        let shift = |level: usize| match control {
+            Kind::Bracket(_) => level + 1,
+            Kind::Croissant(_) => level - 1,
+            _ => level,
+        };
+
+        match self {
+            Kind::Lam(level) => Kind::Lam(shift(level)),
+            Kind::App(level) => Kind::App(shift(level)),
+            Kind::Dup(level) => Kind::Dup(shift(level)),
+            Kind::Bracket(level) => Kind::Bracket(shift(level)),
+            Kind::Croissant(level) => Kind::Croissant(shift(level)),
+            kind => kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Port {
+    node: usize,
+    slot: usize,
+}
+
+fn port(node: usize, slot: usize) -> Port {
+    Port { node, slot }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    kind: Kind,
+    ports: [Port; 3],
+    // The name of the binder or free variable the node comes from, kept for the read-back.
+    name: Rc<str>,
+}
+
+/// How many interactions of each kind a reduction took.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Interactions {
+    /// An application meeting an abstraction, the beta reductions of the term.
+    pub beta: usize,
+    /// Two duplicators, brackets or croissants at the same level cancelling each other.
+    pub annihilation: usize,
+    /// A duplicator copying a node, or one at a lower level.
+    pub duplication: usize,
+    /// A bracket or a croissant moving a node to another level, or a bracket dropped with the
+    /// croissant that undoes it.
+    pub bookkeeping: usize,
+    pub erasure: usize,
+}
+
+impl Interactions {
+    pub fn total(&self) -> usize {
+        self.beta + self.annihilation + self.duplication + self.bookkeeping + self.erasure
+    }
+}
+
+/// Why a net has no read-back. The nets of terms always have one, so this only comes of a
+/// reduction cut short in a net that does not stand for a term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadbackError {
+    /// A path comes to a control node at this level by its principal port, and the way it came
+    /// does not tell which port to leave by.
+    Unpaired(usize),
+    /// A path goes through more than `DEPTH` control nodes, going round a cycle.
+    Cycle,
+}
+
+impl fmt::Display for ReadbackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadbackError::Unpaired(level) => {
+                write!(f, "a path leaves a node at level {} by a port it did not enter by", level)
+            }
+            ReadbackError::Cycle => write!(f, "a path goes round a cycle of control nodes"),
+        }
+    }
+}
+
+// How many control nodes a path may go through. Reading the nets that stand for terms takes a
+// few hundred at most.
+const DEPTH: usize = 1000;
+
+pub struct Net {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    interactions: Interactions,
+    range: Range,
+    // The free variables of the term, which no binder read back may take.
+    names: HashSet<String>,
+}
+
+const ROOT: usize = 0;
+
+// The uses of a variable while its binder is translated.
+struct Scope<'a> {
+    name: &'a str,
+    binder: Port,
+    uses: Vec<Port>,
+}
+
+impl Net {
+    fn alloc(&mut self, kind: Kind, name: Rc<str>) -> usize {
+        let node = Node { kind, ports: [port(0, 0); 3], name };
+
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // A released node is linked to itself, and its index taken by the next one allocated.
+    fn release(&mut self, node: usize) {
+        self.nodes[node].ports[0] = port(node, 0);
+        self.free.push(node);
+    }
+
+    fn kind(&self, node: usize) -> Kind {
+        self.nodes[node].kind
+    }
+
+    // The port linked to `port`.
+    fn enter(&self, port: Port) -> Port {
+        self.nodes[port.node].ports[port.slot]
+    }
+
+    fn link(&mut self, a: Port, b: Port) {
+        self.nodes[a.node].ports[a.slot] = b;
+        self.nodes[b.node].ports[b.slot] = a;
+    }
+
+    /// The net of a term. A `let` is translated as the application of an abstraction.
+    pub fn new(expr: &Expr) -> Net {
+        let range = expr.range();
+        let mut net = Net {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            interactions: Interactions::default(),
+            range,
+            names: free_variables(expr.clone()),
+        };
+
+        let root = net.alloc(Kind::Root, "".into());
+        net.encode(expr, port(root, 0), 0, &mut Vec::new());
+
+        net
+    }
+
+    // Builds `expr` at `level` so that its term is linked to `out`.
+    fn encode<'a>(&mut self, expr: &'a Expr, out: Port, level: usize, scope: &mut Vec<Scope<'a>>) {
+        match expr {
+            Expr::Var(Var { value, .. }) => {
+                match scope.iter_mut().rev().find(|bound| bound.name == value) {
+                    Some(bound) => {
+                        let croissant = self.alloc(Kind::Croissant(level), "".into());
+                        self.link(port(croissant, 1), out);
+                        bound.uses.push(port(croissant, 0));
+                    }
+                    None => {
+                        let free = self.alloc(Kind::Free, value.as_str().into());
+                        self.link(port(free, 0), out);
+                    }
+                }
+            }
+            Expr::Abs(Abs { param, body, .. }) => {
+                let lam = self.alloc(Kind::Lam(level), param.as_str().into());
+                self.link(port(lam, 0), out);
+                self.bind(param, port(lam, 1), body, port(lam, 2), level, scope);
+            }
+            Expr::App(App { lambda, argm, .. }) => {
+                let app = self.alloc(Kind::App(level), "".into());
+                self.link(port(app, 2), out);
+                self.encode(lambda, port(app, 0), level, scope);
+                self.argument(argm, port(app, 1), level, scope);
+            }
+            Expr::Let(Let { name, value, body, .. }) => {
+                let app = self.alloc(Kind::App(level), "".into());
+                let lam = self.alloc(Kind::Lam(level), name.as_str().into());
+
+                self.link(port(app, 2), out);
+                self.link(port(lam, 0), port(app, 0));
+                self.bind(name, port(lam, 1), body, port(lam, 2), level, scope);
+                self.argument(value, port(app, 1), level, scope);
+            }
+        }
+    }
+
+    // Builds the argument of an application at `level` one level below it. The uses of each
+    // variable bound outside the argument are shared there, and go up through a bracket.
+    fn argument<'a>(
+        &mut self,
+        argm: &'a Expr,
+        out: Port,
+        level: usize,
+        scope: &mut Vec<Scope<'a>>,
+    ) {
+        let outside: Vec<usize> = scope.iter().map(|bound| bound.uses.len()).collect();
+        self.encode(argm, out, level + 1, scope);
+
+        for (bound, outside) in scope.iter_mut().zip(outside) {
+            let uses = bound.uses.split_off(outside);
+
+            if !uses.is_empty() {
+                let bracket = self.alloc(Kind::Bracket(level), "".into());
+                let shared = self.share(uses, level + 1);
+
+                self.link(port(bracket, 1), shared);
+                bound.uses.push(port(bracket, 0));
+            }
+        }
+    }
+
+    // Builds `body` at `level` with `name` bound to `binder`, which is shared between the uses
+    // of the variable, or erased when there is none.
+    fn bind<'a>(
+        &mut self,
+        name: &'a str,
+        binder: Port,
+        body: &'a Expr,
+        out: Port,
+        level: usize,
+        scope: &mut Vec<Scope<'a>>,
+    ) {
+        scope.push(Scope { name, binder, uses: Vec::new() });
+        self.encode(body, out, level, scope);
+        let Scope { binder, uses, .. } = scope.pop().expect("the binder was pushed");
+
+        if uses.is_empty() {
+            let era = self.alloc(Kind::Era, "".into());
+            return self.link(port(era, 0), binder);
+        }
+
+        let shared = self.share(uses, level);
+        self.link(binder, shared);
+    }
+
+    // A port from which a tree of duplicators at `level` reaches every one of `uses`.
+    fn share(&mut self, mut uses: Vec<Port>, level: usize) -> Port {
+        let mut shared = uses.pop().expect("a variable shared is used");
+
+        for first in uses.into_iter().rev() {
+            let dup = self.alloc(Kind::Dup(level), "".into());
+
+            self.link(port(dup, 1), first);
+            self.link(port(dup, 2), shared);
+            shared = port(dup, 0);
+        }
+
+        shared
+    }
+
+    // Two nodes of the same family cancel out, linking their auxiliary ports pairwise.
+    fn annihilate(&mut self, a: usize, b: usize) {
+        for slot in 1..self.kind(a).arity() {
+            let (left, right) = (self.enter(port(a, slot)), self.enter(port(b, slot)));
+            self.link(left, right);
+        }
+
+        self.release(a);
+        self.release(b);
+    }
+
+    // `control` goes past `node`: each is copied to the other side of the other one, and the
+    // copies of `node` move to the level `control` takes them to.
+    fn commute(&mut self, control: usize, node: usize) {
+        let (control_kind, control_name) = (self.kind(control), self.nodes[control].name.clone());
+        let (node_kind, node_name) = (self.kind(node), self.nodes[node].name.clone());
+        let moved = node_kind.past(control_kind);
+
+        let control_copies: Vec<usize> = (1..node_kind.arity())
+            .map(|_| self.alloc(control_kind, control_name.clone()))
+            .collect();
+        let node_copies: Vec<usize> =
+            (1..control_kind.arity()).map(|_| self.alloc(moved, node_name.clone())).collect();
+
+        for (slot, &copy) in control_copies.iter().enumerate() {
+            self.link(port(copy, 0), self.enter(port(node, slot + 1)));
+        }
+
+        for (slot, &copy) in node_copies.iter().enumerate() {
+            self.link(port(copy, 0), self.enter(port(control, slot + 1)));
+        }
+
+        for (i, &control_copy) in control_copies.iter().enumerate() {
+            for (j, &node_copy) in node_copies.iter().enumerate() {
+                self.link(port(control_copy, j + 1), port(node_copy, i + 1));
+            }
+        }
+
+        self.release(control);
+        self.release(node);
+    }
+
+    // `era` erases `node`, and goes on with what `node` was linked to.
+    fn erase(&mut self, era: usize, node: usize) {
+        for slot in 1..self.kind(node).arity() {
+            let next = self.alloc(Kind::Era, "".into());
+            self.link(port(next, 0), self.enter(port(node, slot)));
+        }
+
+        self.release(era);
+        self.release(node);
+    }
+
+    // A control node takes a free variable to each of its other ports.
+    fn copy_free(&mut self, control: usize, free: usize) {
+        let name = self.nodes[free].name.clone();
+
+        for slot in 1..self.kind(control).arity() {
+            let copy = self.alloc(Kind::Free, name.clone());
+            self.link(port(copy, 0), self.enter(port(control, slot)));
+        }
+
+        self.release(control);
+        self.release(free);
+    }
+
+    // Counts `control` going past a node.
+    fn moved(&mut self, control: Kind) {
+        match control {
+            Kind::Dup(_) => self.interactions.duplication += 1,
+            _ => self.interactions.bookkeeping += 1,
+        }
+    }
+
+    // Rewrites two nodes linked by their principal ports, or a bracket and a croissant.
+    fn interact(&mut self, a: usize, b: usize) {
+        // The only pair that does not face each other is a bracket and a croissant it cancels.
+        if self.enter(port(a, 0)) != port(b, 0) {
+            self.interactions.bookkeeping += 1;
+            return self.cancel(a, b);
+        }
+
+        match (self.kind(a), self.kind(b)) {
+            (Kind::Lam(_), Kind::App(_)) | (Kind::App(_), Kind::Lam(_)) => {
+                self.interactions.beta += 1;
+                self.annihilate(a, b);
+            }
+            (Kind::Era, _) => {
+                self.interactions.erasure += 1;
+                self.erase(a, b);
+            }
+            (_, Kind::Era) => {
+                self.interactions.erasure += 1;
+                self.erase(b, a);
+            }
+            (left, right) if left == right => {
+                self.interactions.annihilation += 1;
+                self.annihilate(a, b);
+            }
+            (control, Kind::Free) => {
+                self.moved(control);
+                self.copy_free(a, b);
+            }
+            (Kind::Free, control) => {
+                self.moved(control);
+                self.copy_free(b, a);
+            }
+            // Of two control nodes, the one at the lower level goes past the other one.
+            (left, right) if left.is_control() && right.is_control() => {
+                if left.level() <= right.level() {
+                    self.moved(left);
+                    self.commute(a, b);
+                } else {
+                    self.moved(right);
+                    self.commute(b, a);
+                }
+            }
+            (control, _) if control.is_control() => {
+                self.moved(control);
+                self.commute(a, b);
+            }
+            (_, control) => {
+                self.moved(control);
+                self.commute(b, a);
+            }
+        }
+    }
+
+    /// Rewrites the net until it is normal, or has taken `limit` interactions.
+    ///
+    /// Only the interactions the read-back needs are taken, outermost first, the way normal
+    /// order goes: each one is the first pair of facing nodes on the path the read-back takes
+    /// from the root. Parts of the net that are erased or never reached, such as unused
+    /// definitions, are left alone.
+    pub fn reduce(&mut self, limit: Option<usize>) -> Result<(), ReadbackError> {
+        while limit.is_none_or(|limit| self.interactions.total() < limit) {
+            let Some((a, b)) = self.redex(port(ROOT, 0), &Context::default())? else {
+                return Ok(());
+            };
+
+            self.interact(a, b);
+        }
+
+        Ok(())
+    }
+
+    // The first pair of facing nodes that reading the term linked to `from` goes through.
+    fn redex(&self, from: Port, context: &Context) -> Result<Redex, ReadbackError> {
+        let Port { node, slot } = self.enter(from);
+
+        if from.slot == 0 && slot == 0 && from.node != ROOT {
+            let (a, b) = (self.kind(from.node), self.kind(node));
+            return Ok(interacts(a, b).then_some((from.node, node)));
+        }
+
+        if let Some(pair) = self.cancels(from, port(node, slot)) {
+            return Ok(Some(pair));
+        }
+
+        match (self.kind(node), slot) {
+            (Kind::Lam(_), 0) => self.redex(port(node, 2), context),
+            (Kind::App(_), 2) => match self.redex(port(node, 0), context)? {
+                Some(redex) => Ok(Some(redex)),
+                None => self.redex(port(node, 1), context),
+            },
+            (kind, slot) if kind.is_control() => {
+                let (context, exit) = context.cross(kind, slot)?;
+                self.redex(port(node, exit), &context)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // A bracket and the croissant its auxiliary port is linked to, at the level of the bracket
+    // or the one below: what goes through both comes out at the level it went in.
+    fn cancels(&self, a: Port, b: Port) -> Redex {
+        let (bracket, croissant) = match (a.slot, b.slot) {
+            (1, 0) => (a.node, b.node),
+            (0, 1) => (b.node, a.node),
+            _ => return None,
+        };
+
+        match (self.kind(bracket), self.kind(croissant)) {
+            (Kind::Bracket(i), Kind::Croissant(j)) if j == i || j == i + 1 => {
+                Some((bracket, croissant))
+            }
+            _ => None,
+        }
+    }
+
+    fn cancel(&mut self, bracket: usize, croissant: usize) {
+        let (above, below) = (self.enter(port(bracket, 0)), self.enter(port(croissant, 1)));
+        self.link(above, below);
+
+        self.release(bracket);
+        self.release(croissant);
+    }
+
+    pub fn interactions(&self) -> Interactions {
+        self.interactions
+    }
+
+    /// The term the net stands for.
+    pub fn readback(&self) -> Result<Expr, ReadbackError> {
+        let used = self.names.clone();
+        let mut reader = Reader { net: self, binders: Vec::new(), used };
+
+        reader.read(port(ROOT, 0), &Context::default())
+    }
+}
+
+type Redex = Option<(usize, usize)>;
+
+// Whether two nodes facing each other have a rule. A free variable applied to something, for
+// one, is stuck.
+fn interacts(a: Kind, b: Kind) -> bool {
+    match (a, b) {
+        (Kind::Lam(_), Kind::App(_)) | (Kind::App(_), Kind::Lam(_)) => true,
+        (Kind::Root, _) | (_, Kind::Root) => false,
+        (Kind::Era, _) | (_, Kind::Era) => true,
+        _ => a.is_control() || b.is_control(),
+    }
+}
+
+// What a path knows of one level: the copies it came in by at the duplicators of the level,
+// the last one on top, a croissant it went up, or the two levels a bracket made one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Level {
+    Empty,
+    Copy(usize, Rc<Level>),
+    Croissant,
+    Bracket(Rc<Level>, Rc<Level>),
+}
+
+// The levels of a path, each one empty past the last. A path coming to a control node by an
+// auxiliary port records it at the node's level, and one coming by the principal port leaves
+// by what is recorded there.
+#[derive(Debug, Clone, Default)]
+struct Context {
+    levels: Vec<Rc<Level>>,
+    depth: usize,
+}
+
+impl Context {
+    fn get(&self, level: usize) -> Rc<Level> {
+        self.levels.get(level).cloned().unwrap_or_else(|| Rc::new(Level::Empty))
+    }
+
+    fn set(&mut self, level: usize, value: Rc<Level>) {
+        self.pad(level + 1);
+        self.levels[level] = value;
+    }
+
+    fn insert(&mut self, level: usize, value: Rc<Level>) {
+        self.pad(level);
+        self.levels.insert(level, value);
+    }
+
+    fn remove(&mut self, level: usize) {
+        if level < self.levels.len() {
+            self.levels.remove(level);
+        }
+    }
+
+    fn pad(&mut self, len: usize) {
+        while self.levels.len() < len {
+            self.levels.push(Rc::new(Level::Empty));
+        }
+    }
+
+    // The levels above `level`, which nothing in the body of an abstraction at `level`
+    // changes: a variable is bound by the abstraction the path entered with the same ones.
+    fn above(&self, level: usize) -> Vec<Rc<Level>> {
+        (0..level).map(|level| self.get(level)).collect()
+    }
+
+    // The context past a control node for a path coming to it by `slot`, and the slot it
+    // leaves by.
+    fn cross(&self, kind: Kind, slot: usize) -> Result<(Context, usize), ReadbackError> {
+        if self.depth == DEPTH {
+            return Err(ReadbackError::Cycle);
+        }
+
+        let mut next = self.clone();
+        next.depth += 1;
+
+        let exit = match (kind, slot) {
+            (Kind::Dup(level), 0) => match &*self.get(level) {
+                Level::Copy(exit, rest) => {
+                    next.set(level, rest.clone());
+                    *exit
+                }
+                _ => return Err(ReadbackError::Unpaired(level)),
+            },
+            (Kind::Dup(level), exit) => {
+                next.set(level, Rc::new(Level::Copy(exit, self.get(level))));
+                0
+            }
+            (Kind::Bracket(level), 0) => {
+                let (this, below) = match &*self.get(level) {
+                    Level::Bracket(this, below) => (this.clone(), below.clone()),
+                    Level::Empty => (Rc::new(Level::Empty), Rc::new(Level::Empty)),
+                    _ => return Err(ReadbackError::Unpaired(level)),
+                };
+
+                next.set(level, this);
+                next.insert(level + 1, below);
+                1
+            }
+            (Kind::Bracket(level), _) => {
+                let (this, below) = (self.get(level), self.get(level + 1));
+                let merged = match (&*this, &*below) {
+                    (Level::Empty, Level::Empty) => this.clone(),
+                    _ => Rc::new(Level::Bracket(this, below)),
+                };
+
+                next.remove(level + 1);
+                next.set(level, merged);
+                0
+            }
+            (Kind::Croissant(level), 0) => match &*self.get(level) {
+                Level::Croissant | Level::Empty => {
+                    next.remove(level);
+                    1
+                }
+                _ => return Err(ReadbackError::Unpaired(level)),
+            },
+            (Kind::Croissant(level), _) => {
+                next.insert(level, Rc::new(Level::Croissant));
+                0
+            }
+            (kind, _) => unreachable!("{:?} is not a control node", kind),
+        };
+
+        Ok((next, exit))
+    }
+}
+
+struct Reader<'n> {
+    net: &'n Net,
+    binders: Vec<(usize, Vec<Rc<Level>>, String)>,
+    used: HashSet<String>,
+}
+
+impl Reader<'_> {
+    fn var(&self, value: String) -> Expr {
+        Expr::Var(Var { value, range: self.net.range })
+    }
+
+    // The term whose port is linked to `from`, for a path that came in `context`.
+    fn read(&mut self, from: Port, context: &Context) -> Result<Expr, ReadbackError> {
+        let Port { node, slot } = self.net.enter(from);
+        let range = self.net.range;
+
+        match (self.net.kind(node), slot) {
+            (Kind::Lam(level), 0) => {
+                let param = fresh_name(&self.net.nodes[node].name, &self.used);
+                self.used.insert(param.clone());
+                self.binders.push((node, context.above(level), param.clone()));
+
+                let body = self.read(port(node, 2), context);
+                self.binders.pop();
+                self.used.remove(&param);
+
+                Ok(Expr::Abs(Abs { param, body: Box::new(body?), range }))
+            }
+            (Kind::Lam(level), _) => {
+                let above = context.above(level);
+                let mut binders = self.binders.iter().rev();
+                let binder = binders
+                    .clone()
+                    .find(|(binder, levels, _)| *binder == node && *levels == above)
+                    .or_else(|| binders.find(|(binder, _, _)| *binder == node));
+                let name = binder.map(|(_, _, name)| name.clone());
+
+                Ok(self.var(name.unwrap_or_else(|| self.net.nodes[node].name.to_string())))
+            }
+            (Kind::App(_), _) => {
+                let lambda = self.read(port(node, 0), context)?;
+                let argm = self.read(port(node, 1), context)?;
+
+                Ok(Expr::App(App { lambda: Box::new(lambda), argm: Box::new(argm), range }))
+            }
+            (kind @ (Kind::Dup(_) | Kind::Bracket(_) | Kind::Croissant(_)), slot) => {
+                let (context, exit) = context.cross(kind, slot)?;
+                self.read(port(node, exit), &context)
+            }
+            (Kind::Free | Kind::Era | Kind::Root, _) => {
+                Ok(self.var(self.net.nodes[node].name.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Net, ReadbackError};
+    use crate::parser::parsetree::Expr;
+    use crate::reduction::machines::krivine::Krivine;
+    use crate::reduction::readback::readback;
+    use crate::reduction::variables::alpha_equivalent;
+    use crate::reduction::{reduce, Norm};
+    use crate::{parser, prelude};
+
+    fn optimal(expr: &Expr) -> Result<Expr, ReadbackError> {
+        let mut net = Net::new(expr);
+        net.reduce(Some(10000))?;
+        net.readback()
+    }
+
+    #[test]
+    fn test_optimal_agrees_with_normal_order() {
+        let inputs = [
+            r"λx. x",
+            r"(λx. λy. x) y",
+            r"(λx. λx. x) a b",
+            r"(λf. λx. f (f x)) (λf. λx. f (f x))",
+            r"λf. (λx. f x x) ((λy. y) f)",
+            r"let three = λf. λx. f (f (f x)) in three two",
+            r"mul (succ two) (add one two)",
+            r"λs. pair (not true) (or false (and true true)) s",
+            r"pred (pred 4)",
+            r"sub 7 3",
+        ];
+
+        for input in inputs {
+            let expr = prelude::load(input).unwrap();
+            let normal = reduce(Norm::NOR, expr.clone(), Some(1000));
+            let optimal = optimal(&expr).unwrap();
+
+            assert!(alpha_equivalent(&normal, &optimal), "{} is not {}", optimal, normal);
+        }
+    }
+
+    #[test]
+    fn test_optimal_duplicates_shared_abstraction() {
+        // The abstraction bound to `f` is shared, and copied by the duplicator at each use.
+        let inputs = [
+            r"(λf. λx. f (f x)) (λy. y)",
+            r"(λf. λs. s (f a) (f b)) (λx. λy. y x)",
+            r"(λf. f (f (λz. z))) (λg. λy. g (g y))",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            let normal = reduce(Norm::NOR, expr.clone(), Some(1000));
+            let optimal = optimal(&expr).unwrap();
+
+            assert!(alpha_equivalent(&normal, &optimal), "{} is not {}", optimal, normal);
+        }
+    }
+
+    #[test]
+    fn test_optimal_self_application() {
+        // The copies of the duplicator sharing `x` meet, and only their levels tell them apart.
+        let expr = parser::from_string(r"(λx. x x) 2").unwrap();
+        assert_eq!(readback(&optimal(&expr).unwrap()).to_string(), "4");
+
+        let expr = parser::from_string(r"(λx. x x x) 2").unwrap();
+        assert_eq!(readback(&optimal(&expr).unwrap()).to_string(), "16");
+
+        let inputs = [
+            r"(λx. x x) (λx. λy. x (x y))",
+            r"(λx. x x) (λx. λk. k x x)",
+            r"(λd. d d) (λx. λy. λz. x (y z))",
+            r"λa. λb. (λx. x x) (λy. λz. a (y b) z)",
+            r"2 (λx. x x) (λz. z)",
+        ];
+
+        for input in inputs {
+            let expr = parser::from_string(input).unwrap();
+            let normal = reduce(Norm::NOR, expr.clone(), Some(1000));
+            let optimal = optimal(&expr).unwrap();
+
+            assert!(alpha_equivalent(&normal, &optimal), "{} is not {}", optimal, normal);
+        }
+    }
+
+    #[test]
+    fn test_optimal_sharing() {
+        // Two to the power of two, four times over: the identity is applied 65536 times.
+        let input = r"2 2 2 2 (λy. y) x";
+        let expr = parser::from_string(input).unwrap();
+
+        let mut net = Net::new(&expr);
+        net.reduce(None).unwrap();
+        assert_eq!(net.readback().unwrap().to_string(), "x");
+
+        let mut machine = Krivine::new(&expr);
+        machine.run(None);
+        assert!(net.interactions().beta * 100 < machine.steps);
+        assert!(net.interactions().total() < 1000);
+    }
+
+    #[test]
+    fn test_optimal_church_arithmetic() {
+        let expr = parser::from_string(r"(λm. λn. λf. m (n f)) 12 12").unwrap();
+        let mut net = Net::new(&expr);
+        net.reduce(None).unwrap();
+
+        assert_eq!(readback(&net.readback().unwrap()).to_string(), "144");
+        assert_eq!(net.interactions().erasure, 0);
+    }
+}
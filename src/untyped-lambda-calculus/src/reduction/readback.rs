@@ -0,0 +1,261 @@
+use super::variables::free_variables;
+use crate::parser::parsetree::{Abs, App, Expr, Var};
+use std::fmt;
+
+// Normal forms of the usual Church encodings, and of the Scott and binary numerals literals can
+// elaborate to. Without types the encodings overlap: `λt. λf. f` is at the same time false,
+// zero and the empty list, and it reads as `0`.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Numeral(usize),
+    Boolean(bool),
+    Pair(Box<Value>, Box<Value>),
+    List(Vec<Value>),
+    Term(Expr),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Numeral(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Pair(fst, snd) => write!(f, "({}, {})", fst, snd),
+            Value::List(values) => {
+                let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                write!(f, "[{}]", values.join(", "))
+            }
+            Value::Term(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+fn spine(expr: &Expr) -> (&Expr, Vec<&Expr>) {
+    let mut head = expr;
+    let mut args = Vec::new();
+
+    while let Expr::App(App { lambda, argm, .. }) = head {
+        args.push(argm.as_ref());
+        head = lambda;
+    }
+
+    args.reverse();
+    (head, args)
+}
+
+fn is_var(expr: &Expr, name: &str) -> bool {
+    matches!(expr, Expr::Var(Var { value, .. }) if value == name)
+}
+
+fn binder(expr: &Expr) -> Option<(&str, &Expr)> {
+    match expr {
+        Expr::Abs(Abs { param, body, .. }) => Some((param, body)),
+        _ => None,
+    }
+}
+
+// λs. ... and λa. λb. ... with two distinct parameters.
+fn binders(expr: &Expr) -> Option<(&str, &str, &Expr)> {
+    let (fst, body) = binder(expr)?;
+    let (snd, body) = binder(body)?;
+
+    (fst != snd).then_some((fst, snd, body))
+}
+
+fn closed_over(expr: &Expr, names: &[&str]) -> bool {
+    let free = free_variables(expr.clone());
+    names.iter().all(|name| !free.contains(*name))
+}
+
+// λf. λx. f (f (... x))
+fn numeral(expr: &Expr) -> Option<usize> {
+    let (succ, zero, mut body) = binders(expr)?;
+    let mut count = 0;
+
+    loop {
+        match body {
+            Expr::Var(_) if is_var(body, zero) => return Some(count),
+            Expr::App(App { lambda, argm, .. }) if is_var(lambda, succ) => {
+                count += 1;
+                body = argm;
+            }
+            _ => return None,
+        }
+    }
+}
+
+// λs. λz. s (λs. λz. s (... (λs. λz. z)))
+fn scott(expr: &Expr) -> Option<usize> {
+    let (succ, zero, body) = binders(expr)?;
+
+    match body {
+        _ if is_var(body, zero) => Some(0),
+        Expr::App(App { lambda, argm, .. }) if is_var(lambda, succ) => Some(scott(argm)? + 1),
+        _ => None,
+    }
+}
+
+// λz. λo. λe. z n for 2n, λz. λo. λe. o n for 2n + 1, and λz. λo. λe. e for 0.
+fn binary(expr: &Expr) -> Option<usize> {
+    let (even, odd, body) = binders(expr)?;
+    let (end, body) = binder(body)?;
+
+    if end == even || end == odd {
+        return None;
+    }
+
+    match body {
+        _ if is_var(body, end) => Some(0),
+        Expr::App(App { lambda, argm, .. }) if is_var(lambda, even) => Some(binary(argm)? * 2),
+        Expr::App(App { lambda, argm, .. }) if is_var(lambda, odd) => Some(binary(argm)? * 2 + 1),
+        _ => None,
+    }
+}
+
+// λt. λf. t and λt. λf. f
+pub(crate) fn boolean(expr: &Expr) -> Option<bool> {
+    let (truthy, falsy, body) = binders(expr)?;
+
+    match body {
+        _ if is_var(body, truthy) => Some(true),
+        _ if is_var(body, falsy) => Some(false),
+        _ => None,
+    }
+}
+
+// λc. λn. c x (c y (... n))
+fn list(expr: &Expr) -> Option<Vec<Value>> {
+    let (cons, nil, mut body) = binders(expr)?;
+    let mut values = Vec::new();
+
+    while !is_var(body, nil) {
+        match spine(body) {
+            (head, args) if is_var(head, cons) && args.len() == 2 => {
+                if !closed_over(args[0], &[cons, nil]) {
+                    return None;
+                }
+
+                values.push(readback(args[0]));
+                body = args[1];
+            }
+            _ => return None,
+        }
+    }
+
+    Some(values)
+}
+
+// λs. s a b
+fn pair(expr: &Expr) -> Option<(Value, Value)> {
+    let (select, body) = binder(expr)?;
+
+    match spine(body) {
+        (head, args) if is_var(head, select) && args.len() == 2 => {
+            if !closed_over(args[0], &[select]) || !closed_over(args[1], &[select]) {
+                return None;
+            }
+
+            Some((readback(args[0]), readback(args[1])))
+        }
+        _ => None,
+    }
+}
+
+pub fn readback(expr: &Expr) -> Value {
+    if let Some(value) = numeral(expr).or_else(|| scott(expr)).or_else(|| binary(expr)) {
+        Value::Numeral(value)
+    } else if let Some(value) = boolean(expr) {
+        Value::Boolean(value)
+    } else if let Some(values) = list(expr) {
+        Value::List(values)
+    } else if let Some((fst, snd)) = pair(expr) {
+        Value::Pair(Box::new(fst), Box::new(snd))
+    } else {
+        Value::Term(expr.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::readback;
+    use crate::parser::numerals::Numerals;
+    use crate::{parser, prelude};
+    use crate::reduction::{reduce, Norm};
+
+    fn eval(input: &str) -> String {
+        let expr = parser::from_string(input).unwrap();
+        readback(&reduce(Norm::NOR, expr, None)).to_string()
+    }
+
+    #[test]
+    fn test_readback_numeral() {
+        let input = r"(λm. λn. λf. λx. m f (n f x)) (λf. λx. f x) (λs. λz. s (s z))";
+
+        assert_eq!(eval(input), "3")
+    }
+
+    #[test]
+    fn test_readback_boolean() {
+        let input = r"(λb. b (λt. λf. f) (λt. λf. t)) (λt. λf. f)";
+
+        assert_eq!(eval(input), "true")
+    }
+
+    #[test]
+    fn test_readback_list_of_pairs() {
+        let input = r"
+        (λpair. λone. λtwo. λc. λn. c (pair one (λt. λf. t)) (c (pair two (λt. λf. t)) n))
+        (λa. λb. λs. s a b) (λf. λx. f x) (λf. λx. f (f x))
+        ";
+
+        assert_eq!(eval(input), "[(1, true), (2, true)]")
+    }
+
+    #[test]
+    fn test_readback_program() {
+        let input = r"
+        def two = λf. λx. f (f x)
+        def mul = λm. λn. λf. m (n f)
+        def square = λn. mul n n
+        main = square (square two)
+        ";
+
+        assert_eq!(eval(input), "16")
+    }
+
+    #[test]
+    fn test_parse_main_in_expression() {
+        let inputs = [r"(λx. main = x) 1", r"def one = (main = 1) main = one"];
+
+        for input in inputs {
+            assert!(parser::from_string(input).is_err())
+        }
+    }
+
+    #[test]
+    fn test_readback_prelude() {
+        let input = r"pair (map fact (cons two (cons (succ two) nil))) (leq one two)";
+        let expr = prelude::load(input).unwrap();
+        let value = readback(&reduce(Norm::NOR, expr, Some(1000)));
+
+        assert_eq!(value.to_string(), "([2, 6], true)")
+    }
+
+    #[test]
+    fn test_readback_literals() {
+        assert_eq!(eval(r"(λm. λn. λf. λx. m f (n f x)) 3 4"), "7");
+
+        for numerals in [Numerals::Church, Numerals::Scott, Numerals::Binary] {
+            for literal in ["0", "6", "11"] {
+                let expr = parser::from_string_with_numerals(literal, numerals).unwrap();
+                assert_eq!(readback(&expr).to_string(), literal)
+            }
+        }
+    }
+
+    #[test]
+    fn test_readback_term() {
+        let input = r"λx. x x";
+
+        assert_eq!(eval(input), "(λx. (x x))")
+    }
+}
@@ -1,5 +1,6 @@
+use super::definitions::Definitions;
 use super::variables::substitution;
-use crate::parser::parsetree::{Abs, App, Expr};
+use crate::parser::parsetree::{Abs, App, Expr, Var};
 
 macro_rules! break_limit {
     ($expr: ident, $limit: ident) => {
@@ -11,27 +12,66 @@ macro_rules! break_limit {
     };
 }
 
-pub fn normal_order(ex: Expr, limit: Option<usize>) -> Expr {
+// A definition reaching the head of the term is unfolded, which takes a step like a beta
+// reduction does.
+fn unfold(var: Var, definitions: &Definitions) -> Result<Expr, Var> {
+    definitions.get(&var.value).cloned().ok_or(var)
+}
+
+// Reduces the head of an application spine until it is an abstraction, so that
+// every pending argument is substituted before normal_order goes under binders.
+fn weak_head(ex: Expr, definitions: &mut Definitions, limit: Option<usize>) -> Expr {
+    break_limit!(ex, limit);
+
+    match ex {
+        Expr::Var(var) => match unfold(var, definitions) {
+            Ok(value) => weak_head(value, definitions, limit.map(|l| l - 1)),
+            Err(var) => Expr::Var(var),
+        },
+        Expr::Let(def) => {
+            let body = definitions.define(def);
+            weak_head(body, definitions, limit)
+        }
+        Expr::App(app) => match weak_head(*app.lambda, definitions, limit) {
+            Expr::Abs(abs) => {
+                let substituted = substitution(*abs.body, abs.param, *app.argm);
+                weak_head(substituted, definitions, limit.map(|l| l - 1))
+            }
+            expr => Expr::App(App { lambda: Box::new(expr), ..app }),
+        },
+        expr => expr,
+    }
+}
+
+pub fn normal_order(ex: Expr, definitions: &mut Definitions, limit: Option<usize>) -> Expr {
     break_limit!(ex, limit);
 
     match ex {
-        Expr::Var(var) => Expr::Var(var),
+        Expr::Var(var) => match unfold(var, definitions) {
+            Ok(value) => normal_order(value, definitions, limit.map(|l| l - 1)),
+            Err(var) => Expr::Var(var),
+        },
+        Expr::Let(def) => {
+            let body = definitions.define(def);
+            normal_order(body, definitions, limit)
+        }
         Expr::Abs(abs) => {
-            let body = normal_order(*abs.body, limit);
+            let abs = definitions.enter(abs);
+            let body = normal_order(*abs.body, definitions, limit);
 
             Expr::Abs(Abs { body: Box::new(body), ..abs })
         }
         Expr::App(app) => {
-            let func_expr = normal_order(*app.lambda, limit);
+            let func_expr = weak_head(*app.lambda, definitions, limit);
 
             match func_expr {
                 Expr::Abs(abs) => {
                     let substituted = substitution(*abs.body, abs.param, *app.argm);
-                    normal_order(substituted, limit.and_then(|l| Some(l - 1)))
+                    normal_order(substituted, definitions, limit.map(|l| l - 1))
                 }
                 expr => {
-                    let func = normal_order(expr, limit);
-                    let argm = normal_order(*app.argm, limit);
+                    let func = normal_order(expr, definitions, limit);
+                    let argm = normal_order(*app.argm, definitions, limit);
 
                     Expr::App(App {
                         lambda: Box::new(func),
@@ -44,23 +84,31 @@ pub fn normal_order(ex: Expr, limit: Option<usize>) -> Expr {
     }
 }
 
-pub fn applicative_order(ex: Expr, limit: Option<usize>) -> Expr {
+pub fn applicative_order(ex: Expr, definitions: &mut Definitions, limit: Option<usize>) -> Expr {
     break_limit!(ex, limit);
 
     match ex {
-        Expr::Var(var) => Expr::Var(var),
+        Expr::Var(var) => match unfold(var, definitions) {
+            Ok(value) => applicative_order(value, definitions, limit.map(|l| l - 1)),
+            Err(var) => Expr::Var(var),
+        },
+        Expr::Let(def) => {
+            let body = definitions.define(def);
+            applicative_order(body, definitions, limit)
+        }
         Expr::Abs(abs) => {
-            let body = applicative_order(*abs.body, limit);
+            let abs = definitions.enter(abs);
+            let body = applicative_order(*abs.body, definitions, limit);
             Expr::Abs(Abs { body: Box::new(body), ..abs })
         }
         Expr::App(App { lambda, argm, range }) => {
-            let func_expr = applicative_order(*lambda, limit);
-            let argm_expr = applicative_order(*argm, limit);
+            let func_expr = applicative_order(*lambda, definitions, limit);
+            let argm_expr = applicative_order(*argm, definitions, limit);
 
             match func_expr {
                 Expr::Abs(abs) => {
                     let substituted = substitution(*abs.body, abs.param, argm_expr);
-                    applicative_order(substituted, limit.and_then(|l| Some(l - 1)))
+                    applicative_order(substituted, definitions, limit.map(|l| l - 1))
                 }
                 expr => Expr::App(App {
                     lambda: Box::new(expr),
@@ -72,19 +120,26 @@ pub fn applicative_order(ex: Expr, limit: Option<usize>) -> Expr {
     }
 }
 
-pub fn call_by_name(ex: Expr, limit: Option<usize>) -> Expr {
+pub fn call_by_name(ex: Expr, definitions: &mut Definitions, limit: Option<usize>) -> Expr {
     break_limit!(ex, limit);
 
     match ex {
-        Expr::Var(var) => Expr::Var(var),
+        Expr::Var(var) => match unfold(var, definitions) {
+            Ok(value) => call_by_name(value, definitions, limit.map(|l| l - 1)),
+            Err(var) => Expr::Var(var),
+        },
+        Expr::Let(def) => {
+            let body = definitions.define(def);
+            call_by_name(body, definitions, limit)
+        }
         Expr::Abs(abs) => Expr::Abs(abs),
         Expr::App(App { lambda, argm, range }) => {
-            let func_expr = call_by_name(*lambda, limit);
+            let func_expr = call_by_name(*lambda, definitions, limit);
 
             match func_expr {
                 Expr::Abs(abs) => {
                     let substituted = substitution(*abs.body, abs.param, *argm);
-                    call_by_name(substituted, limit.and_then(|l| Some(l - 1)))
+                    call_by_name(substituted, definitions, limit.map(|l| l - 1))
                 }
                 expr => Expr::App(App { lambda: Box::new(expr), argm, range }),
             }
@@ -92,23 +147,30 @@ pub fn call_by_name(ex: Expr, limit: Option<usize>) -> Expr {
     }
 }
 
-pub fn call_by_value(ex: Expr, limit: Option<usize>) -> Expr {
+pub fn call_by_value(ex: Expr, definitions: &mut Definitions, limit: Option<usize>) -> Expr {
     break_limit!(ex, limit);
 
     match ex {
-        Expr::Var(var) => Expr::Var(var),
+        Expr::Var(var) => match unfold(var, definitions) {
+            Ok(value) => call_by_value(value, definitions, limit.map(|l| l - 1)),
+            Err(var) => Expr::Var(var),
+        },
+        Expr::Let(def) => {
+            let body = definitions.define(def);
+            call_by_value(body, definitions, limit)
+        }
         Expr::Abs(abs) => Expr::Abs(abs),
         Expr::App(App { lambda, argm, range }) => {
-            let func_expr = call_by_value(*lambda, limit);
+            let func_expr = call_by_value(*lambda, definitions, limit);
 
             match func_expr {
                 Expr::Abs(abs) => {
-                    let argm_expr = call_by_value(*argm, limit);
+                    let argm_expr = call_by_value(*argm, definitions, limit);
                     let substituted = substitution(*abs.body, abs.param, argm_expr);
-                    call_by_name(substituted, limit.and_then(|l| Some(l - 1)))
+                    call_by_value(substituted, definitions, limit.map(|l| l - 1))
                 }
                 expr => {
-                    let argm_expr = call_by_value(*argm, limit);
+                    let argm_expr = call_by_value(*argm, definitions, limit);
 
                     Expr::App(App {
                         lambda: Box::new(expr),
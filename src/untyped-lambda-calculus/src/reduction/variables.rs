@@ -1,4 +1,4 @@
-use crate::parser::parsetree::{Abs, App, Expr, Var};
+use crate::parser::parsetree::{Abs, App, Expr, Let, Var};
 use std::collections::HashSet;
 
 pub fn free_variables(expr: Expr) -> HashSet<String> {
@@ -16,11 +16,52 @@ pub fn free_variables(expr: Expr) -> HashSet<String> {
             free.extend(free_variables(*body));
             free.remove(&param);
         }
+        Expr::Let(Let { name, value, body, .. }) => {
+            free.extend(free_variables(*body));
+            free.remove(&name);
+            free.extend(free_variables(*value));
+        }
     }
 
     free
 }
 
+/// Primes `name` until it is none of `used`.
+pub fn fresh_name(name: &str, used: &HashSet<String>) -> String {
+    let mut fresh = name.to_string();
+
+    while used.contains(&fresh) {
+        fresh.push('\'');
+    }
+
+    fresh
+}
+
+// Substitutes in the scope of the binder `param`, which is renamed first when it would capture
+// a free variable of `to`.
+fn substitution_under(param: String, body: Expr, from: String, to: Expr) -> (String, Expr) {
+    if param == from {
+        return (param, body);
+    }
+
+    let free = free_variables(to.clone());
+
+    if !free.contains(&param) {
+        return (param, substitution(body, from, to));
+    }
+
+    // The fresh name cannot be `from` either, or the substitution would replace it too.
+    let mut used = free_variables(body.clone());
+    used.extend(free);
+    used.insert(from.clone());
+
+    let fresh = fresh_name(&param, &used);
+    let renamed = Expr::Var(Var { value: fresh.clone(), range: body.range() });
+    let body = substitution(body, param, renamed);
+
+    (fresh, substitution(body, from, to))
+}
+
 pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
     match expr.clone() {
         Expr::Var(Var { value, .. }) if value == from => to,
@@ -36,16 +77,75 @@ pub fn substitution(expr: Expr, from: String, to: Expr) -> Expr {
             })
         }
         Expr::Abs(abs) => {
-            let free = free_variables(to.clone());
-            let cond = from != abs.param && !free.contains(&abs.param);
+            let (param, body) = substitution_under(abs.param, *abs.body, from, to);
 
-            if cond {
-                let body = substitution(*abs.body, from, to);
+            Expr::Abs(Abs { param, body: Box::new(body), ..abs })
+        }
+        Expr::Let(def) => {
+            let value = substitution(*def.value, from.clone(), to.clone());
+            let (name, body) = substitution_under(def.name, *def.body, from, to);
 
-                return Expr::Abs(Abs { body: Box::new(body), ..abs });
+            Expr::Let(Let { name, value: Box::new(value), body: Box::new(body), ..def })
+        }
+    }
+}
+
+// Variables are equal when they are bound by the same pair of binders, or both free and named
+// alike. `bound` pairs the binders of both terms, innermost last.
+fn alpha_equivalent_in(left: &Expr, right: &Expr, bound: &mut Vec<(String, String)>) -> bool {
+    match (left, right) {
+        (Expr::Var(left), Expr::Var(right)) => {
+            let left_binder = bound.iter().rposition(|(name, _)| *name == left.value);
+            let right_binder = bound.iter().rposition(|(_, name)| *name == right.value);
+
+            match (left_binder, right_binder) {
+                (None, None) => left.value == right.value,
+                (left_binder, right_binder) => left_binder == right_binder,
             }
+        }
+        (Expr::App(left), Expr::App(right)) => {
+            alpha_equivalent_in(&left.lambda, &right.lambda, bound)
+                && alpha_equivalent_in(&left.argm, &right.argm, bound)
+        }
+        (Expr::Abs(left), Expr::Abs(right)) => {
+            bound.push((left.param.clone(), right.param.clone()));
+            let equivalent = alpha_equivalent_in(&left.body, &right.body, bound);
+            bound.pop();
 
-            Expr::Abs(abs)
+            equivalent
         }
+        (Expr::Let(left), Expr::Let(right)) => {
+            if !alpha_equivalent_in(&left.value, &right.value, bound) {
+                return false;
+            }
+
+            bound.push((left.name.clone(), right.name.clone()));
+            let equivalent = alpha_equivalent_in(&left.body, &right.body, bound);
+            bound.pop();
+
+            equivalent
+        }
+        _ => false,
+    }
+}
+
+/// Whether both terms are the same up to the names of their bound variables.
+pub fn alpha_equivalent(left: &Expr, right: &Expr) -> bool {
+    alpha_equivalent_in(left, right, &mut Vec::new())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{alpha_equivalent, substitution};
+    use crate::parser;
+
+    #[test]
+    fn test_substitution_renames_apart() {
+        // The inner `f` would capture the `f` substituted for `f'`, and it cannot become `f'`.
+        let body = parser::from_string(r"λx. x (λf. λy. f y) f'").unwrap();
+        let to = parser::from_string(r"f").unwrap();
+        let expected = parser::from_string(r"λx. x (λg. λy. g y) f").unwrap();
+
+        assert!(alpha_equivalent(&substitution(body, "f'".to_string(), to), &expected))
     }
 }